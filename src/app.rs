@@ -1,16 +1,18 @@
-use crate::backend::{AudioControl, BacklightControl, BatteryControl, BluetoothControl, MediaControl, NetworkControl, NiriClient, PowerControl};
-use crate::config::Config;
+use crate::backend::system::{AirplaneMode, IdleInhibitor, ScreenCapture, TempSensor};
+use crate::backend::{BatteryControl, BluetoothBackend, BrightnessBackend, MediaControl, NetworkControl, NiriClient, NotificationDaemon, PowerControl, TrayHost, VolumeBackend};
+use crate::config::{AudioConfig, Config};
 use crate::error::BackendStatus;
 use crate::events::EventManager;
 use anyhow::Result;
 use gtk4::glib;
-use std::sync::Arc;
+use notify::RecommendedWatcher;
+use std::sync::{Arc, Mutex, RwLock};
 use tracing::{info, warn};
 
 /// Global application state coordinator
 pub struct AppState {
-    /// Configuration
-    pub config: Config,
+    /// Configuration (replaced when the config file is reloaded)
+    config: RwLock<Config>,
 
     /// Event manager for broadcasting events
     pub events: EventManager,
@@ -22,13 +24,14 @@ pub struct AppState {
     pub niri_client: Option<Arc<NiriClient>>,
 
     /// Audio control (optional - may be None if audio backend unavailable)
-    pub audio_control: Option<Arc<AudioControl>>,
+    pub audio_control: Option<Arc<dyn VolumeBackend>>,
 
-    /// Backlight control (optional - may be None if backlight unavailable)
-    pub backlight_control: Option<Arc<BacklightControl>>,
+    /// Brightness control, of the backlight or else of external monitors over
+    /// DDC/CI (optional - may be None if neither is available)
+    pub backlight_control: Option<Arc<dyn BrightnessBackend>>,
 
     /// Bluetooth control (optional - may be None if bluetooth unavailable)
-    pub bluetooth_control: Option<Arc<BluetoothControl>>,
+    pub bluetooth_control: Option<Arc<dyn BluetoothBackend>>,
 
     /// Network control (optional - may be None if network unavailable)
     pub network_control: Option<Arc<NetworkControl>>,
@@ -41,6 +44,21 @@ pub struct AppState {
 
     /// Power control (optional - may be None if power management unavailable)
     pub power_control: Option<Arc<PowerControl>>,
+
+    /// Notification daemon (optional - may be None if another daemon owns the bus name)
+    pub notification_daemon: Option<Arc<NotificationDaemon>>,
+
+    /// System tray host (optional - may be None if the session bus is unavailable)
+    pub tray_host: Option<Arc<TrayHost>>,
+
+    /// Idle inhibitor (optional - may be None if logind is unavailable)
+    pub idle_inhibitor: Option<Arc<IdleInhibitor>>,
+
+    /// Screenshots and screen recording via grim/slurp/wf-recorder
+    pub screen_capture: Option<Arc<ScreenCapture>>,
+
+    /// Airplane mode, switching WiFi and Bluetooth together
+    pub airplane_mode: Option<Arc<AirplaneMode>>,
 }
 
 impl AppState {
@@ -48,32 +66,43 @@ impl AppState {
     pub fn new(config: Config) -> Self {
         info!("Initializing application state");
 
-        let events = EventManager::default();
+        let events = EventManager::new(config.events.capacity);
 
-        // Try to connect to niri
-        let niri_client = match NiriClient::new() {
+        // Try to connect to niri, and keep looking for it if it isn't running
+        // yet so the workspace features come back once it starts
+        let (niri_client, niri_found) = match NiriClient::new() {
             Ok(client) => {
                 info!("Successfully connected to niri compositor");
-                Some(Arc::new(client))
+                (Arc::new(client), true)
             }
             Err(e) => {
-                warn!("Could not connect to niri: {}. Workspace features will be limited.", e);
-                None
+                warn!("Could not connect to niri: {}. Retrying until it starts.", e);
+                (Arc::new(NiriClient::without_socket()), false)
             }
         };
+        let niri_client = Some(niri_client);
 
         // Initialize audio control
-        let audio_control = Some(crate::backend::system::audio::create_audio_control_sync(
-            events.clone(),
-        ));
-
-        // Initialize backlight control
-        let backlight_control = Some(
-            crate::backend::system::backlight::create_backlight_control_sync(events.clone()),
+        let audio_control: Option<Arc<dyn VolumeBackend>> = Some(
+            crate::backend::system::audio::create_audio_control_sync(events.clone()),
         );
+        if let Some(audio) = &audio_control {
+            apply_audio_config(audio.as_ref(), &config.audio);
+        }
+
+        // Initialize brightness control: the backlight, or else external
+        // monitors over DDC/CI, which are unavailable until a monitor has
+        // been found
+        let backlight =
+            crate::backend::system::backlight::create_backlight_control_sync(events.clone());
+        let backlight_control: Option<Arc<dyn BrightnessBackend>> = if backlight.is_available() {
+            Some(backlight)
+        } else {
+            external_backlight(&events)
+        };
 
         // Initialize bluetooth control
-        let bluetooth_control = Some(
+        let bluetooth_control: Option<Arc<dyn BluetoothBackend>> = Some(
             crate::backend::system::bluetooth::create_bluetooth_control_sync(events.clone()),
         );
 
@@ -91,19 +120,45 @@ impl AppState {
         let battery_control = Some(
             crate::backend::system::battery::create_battery_control_sync(events.clone()),
         );
+        if let Some(battery) = &battery_control {
+            battery.set_critical_percent(config.power.critical_percent);
+        }
 
         // Initialize power control
         let power_control = Some(crate::backend::system::power::create_power_control_sync());
 
+        // Initialize notification daemon
+        let notification_daemon = Some(
+            crate::backend::notifications::create_notification_daemon_sync(events.clone()),
+        );
+
+        // Initialize system tray
+        let tray_host = Some(crate::backend::tray::create_tray_host_sync(events.clone()));
+
+        // Initialize idle inhibitor
+        let idle_inhibitor = Some(
+            crate::backend::system::idle_inhibit::create_idle_inhibitor_sync(events.clone()),
+        );
+
+        // Initialize screen capture (tools are looked up when used)
+        let screen_capture = Some(Arc::new(ScreenCapture::new(events.clone())));
+
+        // Initialize airplane mode over the network and bluetooth controls
+        let airplane_mode = Some(Arc::new(AirplaneMode::new(
+            network_control.clone(),
+            bluetooth_control.clone(),
+            events.clone(),
+        )));
+
         // Check backend availability
-        let backend_status = if niri_client.is_some() {
+        let backend_status = if niri_found {
             BackendStatus::Available
         } else {
             BackendStatus::Unavailable
         };
 
         AppState {
-            config,
+            config: RwLock::new(config),
             events,
             backend_status,
             niri_client,
@@ -114,9 +169,65 @@ impl AppState {
             media_control,
             battery_control,
             power_control,
+            notification_daemon,
+            tray_host,
+            idle_inhibitor,
+            screen_capture,
+            airplane_mode,
         }
     }
 
+    /// Create application state with no backends connected
+    ///
+    /// Only the configuration and event bus are set up; every backend is
+    /// `None`. Used by integration tests and tools that don't need D-Bus or
+    /// niri.
+    pub fn without_backends(config: Config) -> Self {
+        AppState {
+            events: EventManager::new(config.events.capacity),
+            config: RwLock::new(config),
+            backend_status: BackendStatus::Unavailable,
+            niri_client: None,
+            audio_control: None,
+            backlight_control: None,
+            bluetooth_control: None,
+            network_control: None,
+            media_control: None,
+            battery_control: None,
+            power_control: None,
+            notification_daemon: None,
+            tray_host: None,
+            idle_inhibitor: None,
+            screen_capture: None,
+            airplane_mode: None,
+        }
+    }
+
+    /// Get a snapshot of the current configuration
+    pub fn config(&self) -> Config {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the current configuration
+    pub fn set_config(&self, config: Config) {
+        *self.config.write().unwrap() = config;
+    }
+
+    /// Switch to a new configuration: push backend settings, store it and
+    /// let widgets and styling pick it up via `ConfigReloaded`
+    pub fn reload_config(&self, config: Config) {
+        use crate::events::Event;
+
+        if let Some(audio) = &self.audio_control {
+            apply_audio_config(audio.as_ref(), &config.audio);
+        }
+        if let Some(battery) = &self.battery_control {
+            battery.set_critical_percent(config.power.critical_percent);
+        }
+        self.set_config(config);
+        self.events.emit(Event::ConfigReloaded);
+    }
+
     /// Check if system backends are available
     fn check_backend_availability() -> BackendStatus {
         // Deprecated - status is now set during initialization
@@ -129,9 +240,31 @@ impl AppState {
     }
 }
 
+/// Push the `[audio]` settings into the audio backend
+fn apply_audio_config(audio: &dyn VolumeBackend, config: &AudioConfig) {
+    audio.set_unmute_on_volume_up(config.unmute_on_volume_up);
+    audio.set_max_volume(config.max_volume_percent);
+}
+
+/// Brightness control for external monitors, when built with DDC/CI support
+#[cfg(feature = "ddc")]
+fn external_backlight(events: &EventManager) -> Option<Arc<dyn BrightnessBackend>> {
+    Some(crate::backend::system::ddc::create_ddc_backlight_sync(
+        events.clone(),
+    ))
+}
+
+#[cfg(not(feature = "ddc"))]
+fn external_backlight(_events: &EventManager) -> Option<Arc<dyn BrightnessBackend>> {
+    None
+}
+
 /// Application lifecycle manager
 pub struct Application {
     state: Arc<AppState>,
+
+    /// Config file watcher, kept alive for the lifetime of the application
+    config_watcher: Mutex<Option<RecommendedWatcher>>,
 }
 
 impl Application {
@@ -139,7 +272,10 @@ impl Application {
     pub fn new(config: Config) -> Self {
         let state = AppState::new(config).shared();
 
-        Application { state }
+        Application {
+            state,
+            config_watcher: Mutex::new(None),
+        }
     }
 
     /// Get a reference to the application state
@@ -157,66 +293,181 @@ impl Application {
         // Start backend listeners
         self.start_backend_listeners()?;
 
+        // Watch the config file for live reloads
+        self.start_config_watcher();
+
         Ok(())
     }
 
+    /// Reload the config whenever the config file changes
+    fn start_config_watcher(&self) {
+        let state = self.state.clone();
+        match Config::watch(move |config| {
+            info!("Config file changed, reloading");
+            state.reload_config(config);
+        }) {
+            Ok(watcher) => {
+                *self.config_watcher.lock().unwrap() = Some(watcher);
+            }
+            Err(e) => {
+                warn!("Could not watch config file: {}. Live reload disabled.", e);
+            }
+        }
+    }
+
     /// Start system monitoring tasks
     fn start_system_monitors(&self) -> Result<()> {
         use crate::events::Event;
-        use sysinfo::{CpuRefreshKind, RefreshKind, System};
+        use std::time::Instant;
+        use sysinfo::{CpuRefreshKind, Disks, MemoryRefreshKind, Networks, RefreshKind, System};
 
         let events = self.state.events.clone();
 
         // CPU and Memory monitoring
+        // The System is created once and refreshed in place: sysinfo computes CPU
+        // usage from the difference between two refreshes. Creating it takes the
+        // first sample, so the first tick already reports a real percentage.
+        let mut sys = System::new_with_specifics(
+            RefreshKind::new()
+                .with_cpu(CpuRefreshKind::new().with_cpu_usage())
+                .with_memory(MemoryRefreshKind::new().with_ram().with_swap()),
+        );
         glib::timeout_add_seconds_local(2, move || {
-            // Gracefully handle sysinfo errors
-            let result = std::panic::catch_unwind(|| {
-                let mut sys = System::new_with_specifics(
-                    RefreshKind::new()
-                        .with_cpu(CpuRefreshKind::everything())
-                        .with_memory(),
-                );
-
-                sys.refresh_cpu_all();
-                sys.refresh_memory();
-
-                // CPU usage
-                let cpu_usage = sys.global_cpu_usage() as f64;
-                events.emit(Event::CpuUsageChanged { usage: cpu_usage });
-
-                // Memory usage
-                let used = sys.used_memory();
-                let total = sys.total_memory();
+            // Only usage is needed; refresh_cpu_all would also re-read frequencies
+            sys.refresh_cpu_usage();
+            sys.refresh_memory();
+
+            // CPU usage
+            let cpu_usage = sys.global_cpu_usage() as f64;
+            events.emit(Event::CpuUsageChanged { usage: cpu_usage });
+
+            let usages = sys.cpus().iter().map(|cpu| cpu.cpu_usage() as f64).collect();
+            events.emit(Event::CpuPerCoreChanged { usages });
+
+            // Load average
+            let load = System::load_average();
+            events.emit(Event::LoadAverageChanged {
+                one: load.one,
+                five: load.five,
+                fifteen: load.fifteen,
+            });
+
+            // Memory usage
+            let used = sys.used_memory();
+            let total = sys.total_memory();
+            if total > 0 {
                 let percent = (used as f64 / total as f64) * 100.0;
                 events.emit(Event::MemoryUsageChanged {
                     used,
                     total,
                     percent,
+                    swap_used: sys.used_swap(),
+                    swap_total: sys.total_swap(),
                 });
-            });
-
-            if let Err(e) = result {
-                warn!("System monitoring error: {:?}", e);
             }
 
             glib::ControlFlow::Continue
         });
 
         // Temperature monitoring
-        let events = self.state.events.clone();
+        let state = self.state.clone();
+        let mut discovered: Option<TempSensor> = None;
         glib::timeout_add_seconds_local(5, move || {
-            match read_cpu_temp() {
-                Ok(temp) => {
-                    events.emit(Event::TemperatureChanged { celsius: temp });
+            // An explicit sensor from the config wins; otherwise discover once and
+            // retry discovery if the sensor stops working
+            let sensor = match state.config().bar.temperature_sensor {
+                Some(path) => Some(TempSensor::from_path(path)),
+                None => {
+                    if discovered.is_none() {
+                        discovered = TempSensor::discover();
+                        if let Some(sensor) = &discovered {
+                            info!("Using temperature sensor {} ({:?})", sensor.label, sensor.path);
+                        }
+                    }
+                    discovered.clone()
                 }
-                Err(e) => {
+            };
+
+            match sensor.as_ref().map(|s| (s, s.read_celsius())) {
+                Some((sensor, Ok(temp))) => {
+                    state.events.emit(Event::TemperatureChanged {
+                        celsius: temp,
+                        sensor: sensor.label.clone(),
+                    });
+                }
+                Some((sensor, Err(e))) => {
                     // Don't spam logs - temperature read failures are common on some systems
-                    tracing::debug!("Temperature read failed: {}", e);
+                    tracing::debug!("Temperature read from {} failed: {}", sensor.label, e);
+                    discovered = None;
+                }
+                None => {
+                    tracing::debug!("No temperature sensor found");
                 }
             }
             glib::ControlFlow::Continue
         });
 
+        // Disk usage monitoring for the configured mount points
+        let state = self.state.clone();
+        let mut disks = Disks::new_with_refreshed_list();
+        let mut emit_disk_usage = move || {
+            disks.refresh();
+            for mount in &state.config().bar.disk_mounts {
+                let Some(disk) = disks
+                    .list()
+                    .iter()
+                    .find(|d| d.mount_point() == std::path::Path::new(mount))
+                else {
+                    tracing::debug!("Mount point {} not found", mount);
+                    continue;
+                };
+
+                let total = disk.total_space();
+                if total == 0 {
+                    continue;
+                }
+                let used = total.saturating_sub(disk.available_space());
+                state.events.emit(Event::DiskUsageChanged {
+                    mount: mount.clone(),
+                    used,
+                    total,
+                    percent: (used as f64 / total as f64) * 100.0,
+                });
+            }
+        };
+        emit_disk_usage();
+        glib::timeout_add_seconds_local(30, move || {
+            emit_disk_usage();
+            glib::ControlFlow::Continue
+        });
+
+        // Network throughput monitoring
+        let events = self.state.events.clone();
+        let mut networks = Networks::new_with_refreshed_list();
+        let mut last_sample = Instant::now();
+        glib::timeout_add_seconds_local(2, move || {
+            // refresh_list also picks up interfaces that appeared since the last tick
+            networks.refresh_list();
+            let elapsed = last_sample.elapsed().as_secs_f64();
+            last_sample = Instant::now();
+
+            let (rx, tx) = networks
+                .list()
+                .iter()
+                .filter(|(name, _)| name.as_str() != "lo")
+                .fold((0, 0), |(rx, tx), (_, data)| {
+                    (rx + data.received(), tx + data.transmitted())
+                });
+
+            if elapsed > 0.0 {
+                events.emit(Event::NetworkThroughputChanged {
+                    rx_bytes_per_sec: rx as f64 / elapsed,
+                    tx_bytes_per_sec: tx as f64 / elapsed,
+                });
+            }
+            glib::ControlFlow::Continue
+        });
+
         // Battery monitoring
         if let Some(battery) = &self.state.battery_control {
             let battery_clone = battery.clone();
@@ -257,23 +508,16 @@ impl Application {
     pub fn shutdown(&self) {
         info!("Shutting down application");
         // Cleanup resources, close connections, etc.
-    }
-}
 
-/// Read CPU temperature from sysfs
-fn read_cpu_temp() -> Result<i32> {
-    let thermal_paths = [
-        "/sys/class/thermal/thermal_zone0/temp",
-        "/sys/class/thermal/thermal_zone1/temp",
-    ];
-
-    for path in &thermal_paths {
-        if let Ok(content) = std::fs::read_to_string(path) {
-            if let Ok(temp) = content.trim().parse::<i32>() {
-                return Ok(temp / 1000); // Convert from millidegrees
-            }
+        if let Some(inhibitor) = &self.state.idle_inhibitor {
+            inhibitor.release();
         }
-    }
 
-    anyhow::bail!("No thermal zone found")
+        if let Some(niri) = &self.state.niri_client {
+            niri.disconnect();
+        }
+
+        // Stop the config watcher
+        self.config_watcher.lock().unwrap().take();
+    }
 }
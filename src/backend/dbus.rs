@@ -0,0 +1,153 @@
+use crate::error::{AmiyaError, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use zbus::Connection;
+
+/// D-Bus error names meaning the service or object a backend found earlier
+/// is gone, usually because the service restarted
+const STALE_ERRORS: &[&str] = &[
+    "org.freedesktop.DBus.Error.ServiceUnknown",
+    "org.freedesktop.DBus.Error.NameHasNoOwner",
+    "org.freedesktop.DBus.Error.UnknownObject",
+    "org.freedesktop.DBus.Error.Disconnected",
+];
+
+/// Which message bus a backend talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bus {
+    Session,
+    System,
+}
+
+impl Bus {
+    fn name(self) -> &'static str {
+        match self {
+            Bus::Session => "session",
+            Bus::System => "system",
+        }
+    }
+}
+
+/// Whether `err` means the connection, or the objects a backend found
+/// through it, can't be used any more
+///
+/// That is the case when the bus itself went away, and when the service
+/// behind a name restarted (bluetoothd, NetworkManager) or a player quit.
+pub fn is_stale(err: &zbus::Error) -> bool {
+    match err {
+        zbus::Error::InputOutput(_) => true,
+        zbus::Error::MethodError(name, _, _) => STALE_ERRORS.contains(&name.as_str()),
+        zbus::Error::FDO(e) => matches!(
+            **e,
+            zbus::fdo::Error::ServiceUnknown(_)
+                | zbus::fdo::Error::NameHasNoOwner(_)
+                | zbus::fdo::Error::UnknownObject(_)
+                | zbus::fdo::Error::Disconnected(_)
+                | zbus::fdo::Error::IOError(_)
+        ),
+        _ => false,
+    }
+}
+
+/// Bus connection shared by a backend's calls
+///
+/// A call that fails with a stale error (see `is_stale`) drops the
+/// connection through `error`. The backend then sees `cached` return `None`
+/// and connects again on its next operation, finding its adapter, device or
+/// players afresh, instead of failing for the rest of the session.
+pub struct DbusConnection {
+    bus: Bus,
+    connection: RwLock<Option<Connection>>,
+    /// Whether `connection` holds a usable connection, readable without
+    /// waiting on the lock
+    connected: AtomicBool,
+}
+
+impl DbusConnection {
+    pub fn new(bus: Bus) -> Self {
+        DbusConnection {
+            bus,
+            connection: RwLock::new(None),
+            connected: AtomicBool::new(false),
+        }
+    }
+
+    /// Open a new connection, replacing the cached one
+    pub async fn open(&self) -> Result<Connection> {
+        let result = match self.bus {
+            Bus::Session => Connection::session().await,
+            Bus::System => Connection::system().await,
+        };
+        let conn = result.map_err(|e| {
+            warn!("Could not connect to D-Bus {} bus: {}", self.bus.name(), e);
+            AmiyaError::Backend(format!("Failed to connect to D-Bus: {}", e))
+        })?;
+
+        *self.connection.write().await = Some(conn.clone());
+        self.connected.store(true, Ordering::Relaxed);
+        Ok(conn)
+    }
+
+    /// The cached connection, unless it was never opened or has gone stale
+    pub async fn cached(&self) -> Option<Connection> {
+        if !self.is_connected() {
+            return None;
+        }
+        self.connection.read().await.clone()
+    }
+
+    /// Whether a usable connection is cached
+    pub fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    /// Turn a failed call into a backend error, dropping the connection if
+    /// the error shows it's stale
+    pub fn error(&self, context: &str, err: zbus::Error) -> AmiyaError {
+        if is_stale(&err) && self.connected.swap(false, Ordering::Relaxed) {
+            info!(
+                "D-Bus {} bus connection is stale ({}), reconnecting on next use",
+                self.bus.name(),
+                err
+            );
+        }
+        AmiyaError::Backend(format!("{}: {}", context, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_is_stale() {
+        let io_error = io::Error::new(io::ErrorKind::BrokenPipe, "socket closed");
+        assert!(is_stale(&zbus::Error::InputOutput(Arc::new(io_error))));
+
+        let service_unknown =
+            zbus::fdo::Error::ServiceUnknown("org.bluez was not provided".to_string());
+        assert!(is_stale(&service_unknown.into()));
+
+        let denied = zbus::fdo::Error::AccessDenied("not allowed".to_string());
+        assert!(!is_stale(&denied.into()));
+        assert!(!is_stale(&zbus::Error::Failure("busy".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_stale_error_drops_connection() {
+        let bus = DbusConnection::new(Bus::Session);
+        bus.connected.store(true, Ordering::Relaxed);
+
+        let error = bus.error(
+            "Failed to list devices",
+            zbus::fdo::Error::UnknownObject("/org/bluez/hci0".to_string()).into(),
+        );
+        assert!(error
+            .to_string()
+            .starts_with("Backend error: Failed to list devices: "));
+        assert!(!bus.is_connected());
+    }
+}
@@ -0,0 +1,336 @@
+//! In-memory backends for tests
+//!
+//! These implement the traits in `backend::traits` without touching D-Bus and
+//! emit the same events as the real backends, so UI and IPC code can be
+//! exercised end to end.
+
+use crate::backend::system::bluetooth::BluetoothDevice;
+use crate::backend::traits::{BluetoothBackend, BrightnessBackend, VolumeBackend};
+use crate::error::{AmiyaError, Result};
+use crate::events::{Event, EventManager};
+use futures::future::BoxFuture;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+
+/// Volume backend that keeps its state in memory
+pub struct FakeVolume {
+    state: Mutex<(f64, bool)>,
+    unmute_on_volume_up: AtomicBool,
+    max_volume: AtomicU32,
+    events: Option<EventManager>,
+}
+
+impl FakeVolume {
+    /// Create a fake at `volume`, unmuted
+    pub fn new(volume: f64) -> Self {
+        FakeVolume {
+            state: Mutex::new((volume, false)),
+            unmute_on_volume_up: AtomicBool::new(true),
+            max_volume: AtomicU32::new(100),
+            events: None,
+        }
+    }
+
+    /// Create a fake that emits `VolumeChanged` events
+    pub fn with_events(volume: f64, events: EventManager) -> Self {
+        FakeVolume {
+            state: Mutex::new((volume, false)),
+            unmute_on_volume_up: AtomicBool::new(true),
+            max_volume: AtomicU32::new(100),
+            events: Some(events),
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut (f64, bool))) -> Result<()> {
+        let (level, muted) = {
+            let mut state = self.state.lock().unwrap();
+            f(&mut state);
+            *state
+        };
+        if let Some(events) = &self.events {
+            events.emit(Event::VolumeChanged { level, muted });
+        }
+        Ok(())
+    }
+}
+
+impl VolumeBackend for FakeVolume {
+    fn get_volume(&self) -> BoxFuture<'_, Result<f64>> {
+        let volume = self.state.lock().unwrap().0;
+        Box::pin(async move { Ok(volume) })
+    }
+
+    fn set_volume(&self, volume: f64) -> BoxFuture<'_, Result<()>> {
+        let max = self.max_volume.load(Ordering::Relaxed) as f64;
+        let result = self.update(|state| state.0 = volume.clamp(0.0, max));
+        Box::pin(async move { result })
+    }
+
+    fn get_mute(&self) -> BoxFuture<'_, Result<bool>> {
+        let muted = self.state.lock().unwrap().1;
+        Box::pin(async move { Ok(muted) })
+    }
+
+    fn set_mute(&self, muted: bool) -> BoxFuture<'_, Result<()>> {
+        let result = self.update(|state| state.1 = muted);
+        Box::pin(async move { result })
+    }
+
+    fn set_unmute_on_volume_up(&self, enabled: bool) {
+        self.unmute_on_volume_up.store(enabled, Ordering::Relaxed);
+    }
+
+    fn set_max_volume(&self, percent: u32) {
+        self.max_volume.store(percent, Ordering::Relaxed);
+    }
+
+    fn increase_volume(&self, step: f64) -> BoxFuture<'_, Result<()>> {
+        let unmute = self.unmute_on_volume_up.load(Ordering::Relaxed);
+        let max = self.max_volume.load(Ordering::Relaxed) as f64;
+        let result = self.update(|state| {
+            state.0 = (state.0 + step).min(max);
+            state.1 &= !unmute;
+        });
+        Box::pin(async move { result })
+    }
+}
+
+/// Brightness backend that keeps its level in memory
+pub struct FakeBrightness {
+    level: Mutex<f64>,
+    available: bool,
+}
+
+impl FakeBrightness {
+    /// Create a fake at `level`
+    pub fn new(level: f64) -> Self {
+        FakeBrightness {
+            level: Mutex::new(level),
+            available: true,
+        }
+    }
+
+    /// Create a fake that hasn't found a device, like DDC/CI before a
+    /// monitor has answered
+    pub fn unavailable() -> Self {
+        FakeBrightness {
+            available: false,
+            ..Self::new(0.0)
+        }
+    }
+}
+
+impl BrightnessBackend for FakeBrightness {
+    fn is_available(&self) -> bool {
+        self.available
+    }
+
+    fn get_brightness(&self) -> BoxFuture<'_, Result<f64>> {
+        let level = *self.level.lock().unwrap();
+        Box::pin(async move { Ok(level) })
+    }
+
+    fn set_brightness(&self, brightness: f64) -> BoxFuture<'_, Result<()>> {
+        *self.level.lock().unwrap() = brightness.clamp(0.0, 100.0);
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Bluetooth backend with a fixed set of devices
+pub struct FakeBluetooth {
+    powered: Mutex<bool>,
+    scanning: Mutex<bool>,
+    devices: Mutex<Vec<BluetoothDevice>>,
+    events: Option<EventManager>,
+}
+
+impl FakeBluetooth {
+    /// Create a powered-on adapter that knows about `devices`
+    pub fn new(devices: Vec<BluetoothDevice>) -> Self {
+        FakeBluetooth {
+            powered: Mutex::new(true),
+            scanning: Mutex::new(false),
+            devices: Mutex::new(devices),
+            events: None,
+        }
+    }
+
+    /// Create a fake that emits Bluetooth events
+    pub fn with_events(devices: Vec<BluetoothDevice>, events: EventManager) -> Self {
+        FakeBluetooth {
+            events: Some(events),
+            ..Self::new(devices)
+        }
+    }
+
+    /// Whether discovery is running
+    pub fn is_scanning(&self) -> bool {
+        *self.scanning.lock().unwrap()
+    }
+
+    /// Apply `f` to the device with `address`
+    fn update_device(&self, address: &str, f: impl FnOnce(&mut BluetoothDevice)) -> Result<()> {
+        let devices = {
+            let mut devices = self.devices.lock().unwrap();
+            let device = devices
+                .iter_mut()
+                .find(|d| d.address == address)
+                .ok_or_else(|| AmiyaError::Backend(format!("Unknown device: {}", address)))?;
+            f(device);
+            devices.clone()
+        };
+        if let Some(events) = &self.events {
+            events.emit(Event::BluetoothDevicesUpdated { devices });
+        }
+        Ok(())
+    }
+}
+
+impl BluetoothBackend for FakeBluetooth {
+    fn is_powered(&self) -> BoxFuture<'_, Result<bool>> {
+        let powered = *self.powered.lock().unwrap();
+        Box::pin(async move { Ok(powered) })
+    }
+
+    fn set_powered(&self, enabled: bool) -> BoxFuture<'_, Result<()>> {
+        *self.powered.lock().unwrap() = enabled;
+        if let Some(events) = &self.events {
+            events.emit(Event::BluetoothStateChanged { enabled });
+        }
+        Box::pin(async { Ok(()) })
+    }
+
+    fn start_scan(&self) -> BoxFuture<'_, Result<()>> {
+        *self.scanning.lock().unwrap() = true;
+        Box::pin(async { Ok(()) })
+    }
+
+    fn stop_scan(&self) -> BoxFuture<'_, Result<()>> {
+        *self.scanning.lock().unwrap() = false;
+        Box::pin(async { Ok(()) })
+    }
+
+    fn get_devices(&self) -> BoxFuture<'_, Result<Vec<BluetoothDevice>>> {
+        let devices = self.devices.lock().unwrap().clone();
+        Box::pin(async move { Ok(devices) })
+    }
+
+    fn connect_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        let mut name = String::new();
+        let result = self.update_device(address, |d| {
+            d.connected = true;
+            name = d.name.clone();
+        });
+        if let (Ok(()), Some(events)) = (&result, &self.events) {
+            events.emit(Event::BluetoothDeviceConnected {
+                address: address.to_string(),
+                name,
+            });
+        }
+        Box::pin(async move { result })
+    }
+
+    fn disconnect_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        let result = self.update_device(address, |d| d.connected = false);
+        if let (Ok(()), Some(events)) = (&result, &self.events) {
+            events.emit(Event::BluetoothDeviceDisconnected {
+                address: address.to_string(),
+            });
+        }
+        Box::pin(async move { result })
+    }
+
+    fn pair_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        let result = self.update_device(address, |d| d.paired = true);
+        Box::pin(async move { result })
+    }
+
+    fn remove_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        let mut devices = self.devices.lock().unwrap();
+        let before = devices.len();
+        devices.retain(|d| d.address != address);
+        let result = if devices.len() < before {
+            Ok(())
+        } else {
+            Err(AmiyaError::Backend(format!("Unknown device: {}", address)))
+        };
+        Box::pin(async move { result })
+    }
+
+    fn set_trusted<'a>(&'a self, address: &'a str, trusted: bool) -> BoxFuture<'a, Result<()>> {
+        let result = self.update_device(address, |d| d.trusted = trusted);
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(address: &str) -> BluetoothDevice {
+        BluetoothDevice {
+            address: address.to_string(),
+            name: "Headphones".to_string(),
+            connected: false,
+            paired: true,
+            trusted: false,
+            icon: None,
+            codec: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fake_volume_steps() {
+        let volume = FakeVolume::new(98.0);
+        volume.increase_volume(5.0).await.unwrap();
+        assert_eq!(volume.get_volume().await.unwrap(), 100.0);
+
+        volume.toggle_mute().await.unwrap();
+        assert!(volume.get_mute().await.unwrap());
+
+        volume.increase_volume(5.0).await.unwrap();
+        assert!(!volume.get_mute().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_fake_bluetooth_connect() {
+        let events = EventManager::default();
+        let mut receiver = events.subscribe();
+        let bt = FakeBluetooth::with_events(vec![device("AA:BB")], events);
+
+        bt.connect_device("AA:BB").await.unwrap();
+        assert!(bt.get_devices().await.unwrap()[0].connected);
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Event::BluetoothDevicesUpdated { .. }
+        ));
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Event::BluetoothDeviceConnected { address, name }
+                if address == "AA:BB" && name == "Headphones"
+        ));
+
+        bt.disconnect_device("AA:BB").await.unwrap();
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Event::BluetoothDevicesUpdated { .. }
+        ));
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Event::BluetoothDeviceDisconnected { address } if address == "AA:BB"
+        ));
+
+        assert!(bt.connect_device("CC:DD").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fake_bluetooth_trust() {
+        let bt = FakeBluetooth::new(vec![device("AA:BB")]);
+
+        bt.set_trusted("AA:BB", true).await.unwrap();
+        assert!(bt.get_devices().await.unwrap()[0].trusted);
+
+        bt.set_trusted("AA:BB", false).await.unwrap();
+        assert!(!bt.get_devices().await.unwrap()[0].trusted);
+    }
+}
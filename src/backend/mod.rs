@@ -1,5 +1,14 @@
+pub mod dbus;
+pub mod fake;
 pub mod niri;
+pub mod notifications;
+pub mod retry;
 pub mod system;
+pub mod traits;
+pub mod tray;
 
 pub use niri::NiriClient;
+pub use notifications::NotificationDaemon;
 pub use system::{AudioControl, BacklightControl, BluetoothControl, MediaControl, NetworkControl};
+pub use traits::{BluetoothBackend, BrightnessBackend, VolumeBackend};
+pub use tray::TrayHost;
@@ -1,18 +1,33 @@
 use super::protocol::{
-    commands, JsonRpcRequest, JsonRpcResponse, NiriAction, NiriEvent, NiriWorkspace,
-    NiriWorkspacesResponse, WorkspaceReference,
+    commands, JsonRpcRequest, JsonRpcResponse, LayoutSwitchTarget, NiriAction, NiriEvent,
+    NiriKeyboardLayouts, NiriOutput, NiriOutputsResponse, NiriWindow, NiriWindowsResponse,
+    NiriWorkspace, NiriWorkspacesResponse, OutputAction, OutputTransform, WorkspaceReference,
+    OUTPUT_WAS_MISSING,
 };
 use crate::error::{AmiyaError, Result};
 use std::io::{BufRead, BufReader, Write};
+use std::ops::RangeInclusive;
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tracing::{debug, info, warn};
 
+/// Output scales niri accepts
+pub const OUTPUT_SCALE_RANGE: RangeInclusive<f64> = 0.1..=10.0;
+
+/// How a request/response exchange with niri failed
+enum Exchange {
+    /// The request wasn't written, so niri never saw it
+    NotSent(std::io::Error),
+    /// The request was written, but no response came back
+    NoResponse(std::io::Error),
+}
+
 /// Niri IPC client for communicating with the compositor
 pub struct NiriClient {
-    socket_path: PathBuf,
+    /// `None` until niri's socket has been found
+    socket_path: Mutex<Option<PathBuf>>,
     stream: Arc<Mutex<Option<UnixStream>>>,
     request_id: AtomicU64,
 }
@@ -24,12 +39,23 @@ impl NiriClient {
         info!("Found niri socket at: {:?}", socket_path);
 
         Ok(NiriClient {
-            socket_path,
+            socket_path: Mutex::new(Some(socket_path)),
             stream: Arc::new(Mutex::new(None)),
             request_id: AtomicU64::new(1),
         })
     }
 
+    /// Create a client for a niri that isn't running yet
+    ///
+    /// Requests fail until niri's socket appears; each one looks for it again.
+    pub fn without_socket() -> Self {
+        NiriClient {
+            socket_path: Mutex::new(None),
+            stream: Arc::new(Mutex::new(None)),
+            request_id: AtomicU64::new(1),
+        }
+    }
+
     /// Find the niri socket path
     fn find_socket() -> Result<PathBuf> {
         // Niri socket is typically at $XDG_RUNTIME_DIR/niri/niri-$WAYLAND_DISPLAY.sock
@@ -69,7 +95,19 @@ impl NiriClient {
 
     /// Connect to the niri socket
     fn connect(&self) -> Result<UnixStream> {
-        let stream = UnixStream::connect(&self.socket_path)
+        let socket_path = {
+            let mut socket_path = self.socket_path.lock().unwrap();
+            match &*socket_path {
+                Some(path) => path.clone(),
+                None => {
+                    let path = Self::find_socket()?;
+                    info!("Found niri socket at: {:?}", path);
+                    socket_path.insert(path).clone()
+                }
+            }
+        };
+
+        let stream = UnixStream::connect(&socket_path)
             .map_err(|e| AmiyaError::Ipc(format!("Failed to connect to niri socket: {}", e)))?;
 
         // Set non-blocking mode for async operations
@@ -91,34 +129,24 @@ impl NiriClient {
     }
 
     /// Send a JSON-RPC request and receive response
+    ///
+    /// If the cached connection turns out to be dead (e.g. niri restarted)
+    /// before the request could be written, it is dropped and the request is
+    /// sent once more on a fresh connection. Once written, a request may
+    /// already have run, so it is never resent.
     fn send_request(&self, request: JsonRpcRequest) -> Result<JsonRpcResponse> {
-        self.ensure_connected()?;
-
-        let mut stream = self.stream.lock().unwrap();
-        let stream = stream.as_mut().ok_or_else(|| {
-            AmiyaError::Ipc("Not connected to niri socket".to_string())
-        })?;
-
-        // Serialize and send request
+        // Serialize request
         let request_json = serde_json::to_string(&request)
             .map_err(|e| AmiyaError::Ipc(format!("Failed to serialize request: {}", e)))?;
 
-        debug!("Sending request: {}", request_json);
-
-        writeln!(stream, "{}", request_json)
-            .map_err(|e| AmiyaError::Ipc(format!("Failed to write to socket: {}", e)))?;
-
-        stream.flush()
-            .map_err(|e| AmiyaError::Ipc(format!("Failed to flush socket: {}", e)))?;
-
-        // Read response
-        let mut reader = BufReader::new(stream.try_clone().map_err(|e| {
-            AmiyaError::Ipc(format!("Failed to clone stream: {}", e))
-        })?);
-
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line)
-            .map_err(|e| AmiyaError::Ipc(format!("Failed to read response: {}", e)))?;
+        let response_line = match self.exchange(&request_json) {
+            Err(Exchange::NotSent(e)) if Self::is_connection_error(&e) => {
+                warn!("Lost connection to niri ({}), reconnecting", e);
+                self.disconnect();
+                self.exchange(&request_json).map_err(Self::exchange_error)?
+            }
+            result => result.map_err(Self::exchange_error)?,
+        };
 
         debug!("Received response: {}", response_line);
 
@@ -137,6 +165,72 @@ impl NiriClient {
         Ok(response)
     }
 
+    /// Write a request line to the socket and read back the response line
+    fn exchange(&self, request_json: &str) -> std::result::Result<String, Exchange> {
+        self.ensure_connected().map_err(|e| {
+            Exchange::NotSent(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                e.to_string(),
+            ))
+        })?;
+
+        let mut guard = self.stream.lock().unwrap();
+        let stream = guard.as_mut().ok_or_else(|| {
+            Exchange::NotSent(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "Not connected to niri socket",
+            ))
+        })?;
+
+        debug!("Sending request: {}", request_json);
+
+        writeln!(stream, "{}", request_json).map_err(Exchange::NotSent)?;
+        stream.flush().map_err(Exchange::NotSent)?;
+
+        // Read response
+        let response = Self::read_response(stream);
+        if matches!(&response, Err(e) if Self::is_connection_error(e)) {
+            // Reconnect on the next request instead of writing into a dead socket
+            *guard = None;
+        }
+        response.map_err(Exchange::NoResponse)
+    }
+
+    /// Read one response line
+    fn read_response(stream: &UnixStream) -> std::io::Result<String> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let mut response_line = String::new();
+        if reader.read_line(&mut response_line)? == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "niri closed the connection",
+            ));
+        }
+
+        Ok(response_line)
+    }
+
+    /// Error reported for a failed exchange
+    fn exchange_error(error: Exchange) -> AmiyaError {
+        let (Exchange::NotSent(e) | Exchange::NoResponse(e)) = error;
+        AmiyaError::Ipc(format!("Failed to talk to niri: {}", e))
+    }
+
+    /// Whether an I/O error means the cached connection is dead
+    fn is_connection_error(error: &std::io::Error) -> bool {
+        use std::io::ErrorKind;
+
+        matches!(
+            error.kind(),
+            ErrorKind::BrokenPipe
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotConnected
+                | ErrorKind::UnexpectedEof
+        )
+    }
+
     /// Get next request ID
     fn next_id(&self) -> u64 {
         self.request_id.fetch_add(1, Ordering::SeqCst)
@@ -157,12 +251,36 @@ impl NiriClient {
         Ok(workspaces_response.workspaces)
     }
 
-    /// Focus a workspace by index
-    pub fn focus_workspace(&self, index: u32) -> Result<()> {
-        let action = NiriAction::FocusWorkspace {
-            reference: WorkspaceReference::Index(index),
-        };
+    /// Get all open windows
+    pub fn get_windows(&self) -> Result<Vec<NiriWindow>> {
+        let request = JsonRpcRequest::new(self.next_id(), commands::WINDOWS);
+        let response = self.send_request(request)?;
+
+        let result = response.result.ok_or_else(|| {
+            AmiyaError::Ipc("No result in windows response".to_string())
+        })?;
+
+        let windows_response: NiriWindowsResponse = serde_json::from_value(result)
+            .map_err(|e| AmiyaError::Ipc(format!("Failed to parse windows: {}", e)))?;
 
+        Ok(windows_response.windows)
+    }
+
+    /// Get the currently focused window, if any
+    pub fn get_focused_window(&self) -> Result<Option<NiriWindow>> {
+        let request = JsonRpcRequest::new(self.next_id(), commands::FOCUSED_WINDOW);
+        let response = self.send_request(request)?;
+
+        let result = response.result.unwrap_or(serde_json::Value::Null);
+
+        let window: Option<NiriWindow> = serde_json::from_value(result)
+            .map_err(|e| AmiyaError::Ipc(format!("Failed to parse focused window: {}", e)))?;
+
+        Ok(window)
+    }
+
+    /// Send an action request to niri
+    fn send_action(&self, action: NiriAction) -> Result<()> {
         let params = serde_json::json!({ "action": action });
         let request = JsonRpcRequest::with_params(self.next_id(), commands::ACTION, params);
 
@@ -170,19 +288,155 @@ impl NiriClient {
         Ok(())
     }
 
+    /// Focus a workspace by index
+    pub fn focus_workspace(&self, index: u32) -> Result<()> {
+        self.send_action(NiriAction::FocusWorkspace {
+            reference: WorkspaceReference::Index(index),
+        })
+    }
+
     /// Focus a workspace by name
     pub fn focus_workspace_by_name(&self, name: String) -> Result<()> {
-        let action = NiriAction::FocusWorkspace {
+        self.send_action(NiriAction::FocusWorkspace {
             reference: WorkspaceReference::Name(name),
-        };
+        })
+    }
 
-        let params = serde_json::json!({ "action": action });
-        let request = JsonRpcRequest::with_params(self.next_id(), commands::ACTION, params);
+    /// Focus the workspace above the current one
+    pub fn focus_workspace_up(&self) -> Result<()> {
+        self.send_action(NiriAction::FocusWorkspaceUp)
+    }
 
-        self.send_request(request)?;
+    /// Focus the workspace below the current one
+    pub fn focus_workspace_down(&self) -> Result<()> {
+        self.send_action(NiriAction::FocusWorkspaceDown)
+    }
+
+    /// Focus a window by id, switching to its workspace
+    pub fn focus_window(&self, id: u64) -> Result<()> {
+        self.send_action(NiriAction::FocusWindow { id })
+    }
+
+    /// Move the focused window to a workspace by index
+    pub fn move_window_to_workspace(&self, index: u32) -> Result<()> {
+        self.send_action(NiriAction::MoveWindowToWorkspace {
+            reference: WorkspaceReference::Index(index),
+        })
+    }
+
+    /// Move the focused window to a workspace by name
+    pub fn move_window_to_workspace_by_name(&self, name: String) -> Result<()> {
+        self.send_action(NiriAction::MoveWindowToWorkspace {
+            reference: WorkspaceReference::Name(name),
+        })
+    }
+
+    /// Move the focused window to the workspace above
+    pub fn move_window_to_workspace_up(&self) -> Result<()> {
+        self.send_action(NiriAction::MoveWindowToWorkspaceUp)
+    }
+
+    /// Move the focused window to the workspace below
+    pub fn move_window_to_workspace_down(&self) -> Result<()> {
+        self.send_action(NiriAction::MoveWindowToWorkspaceDown)
+    }
+
+    /// Run a command through the compositor
+    pub fn spawn(&self, command: Vec<String>) -> Result<()> {
+        self.send_action(NiriAction::Spawn { command })
+    }
+
+    /// Exit niri, ending the session
+    pub fn quit(&self) -> Result<()> {
+        self.send_action(NiriAction::Quit)
+    }
+
+    /// Blank all monitors; niri turns them back on at the next input
+    pub fn power_off_monitors(&self) -> Result<()> {
+        self.send_action(NiriAction::PowerOffMonitors)
+    }
+
+    /// Screenshot the focused window; niri saves it to its `screenshot-path`
+    pub fn screenshot_window(&self) -> Result<()> {
+        self.send_action(NiriAction::ScreenshotWindow)
+    }
+
+    /// Get all connected outputs
+    pub fn get_outputs(&self) -> Result<Vec<NiriOutput>> {
+        let request = JsonRpcRequest::new(self.next_id(), commands::OUTPUTS);
+        let response = self.send_request(request)?;
+
+        let result = response
+            .result
+            .ok_or_else(|| AmiyaError::Ipc("No result in outputs response".to_string()))?;
+
+        let outputs_response: NiriOutputsResponse = serde_json::from_value(result)
+            .map_err(|e| AmiyaError::Ipc(format!("Failed to parse outputs: {}", e)))?;
+
+        Ok(outputs_response.outputs)
+    }
+
+    /// Set the scale of an output until niri's config is reloaded
+    pub fn set_output_scale(&self, name: &str, scale: f64) -> Result<()> {
+        if !OUTPUT_SCALE_RANGE.contains(&scale) {
+            return Err(AmiyaError::Other(format!(
+                "Scale {} is out of range, expected {} to {}",
+                scale,
+                OUTPUT_SCALE_RANGE.start(),
+                OUTPUT_SCALE_RANGE.end()
+            )));
+        }
+        self.send_output_action(name, OutputAction::Scale { scale })
+    }
+
+    /// Rotate or flip an output until niri's config is reloaded
+    pub fn set_output_transform(&self, name: &str, transform: OutputTransform) -> Result<()> {
+        self.send_output_action(name, OutputAction::Transform { transform })
+    }
+
+    /// Send an output configuration request to niri
+    fn send_output_action(&self, name: &str, action: OutputAction) -> Result<()> {
+        let params = serde_json::json!({ "output": name, "action": action });
+        let request = JsonRpcRequest::with_params(self.next_id(), commands::OUTPUT, params);
+        let response = self.send_request(request)?;
+
+        if response.result.as_ref().and_then(|r| r.as_str()) == Some(OUTPUT_WAS_MISSING) {
+            let message = match self.get_outputs() {
+                Ok(outputs) => {
+                    let names: Vec<String> = outputs.into_iter().map(|o| o.name).collect();
+                    format!(
+                        "No output named '{}' (connected: {})",
+                        name,
+                        names.join(", ")
+                    )
+                }
+                Err(_) => format!("No output named '{}'", name),
+            };
+            return Err(AmiyaError::Other(message));
+        }
         Ok(())
     }
 
+    /// Get the configured keyboard layouts and the active one
+    pub fn get_keyboard_layouts(&self) -> Result<NiriKeyboardLayouts> {
+        let request = JsonRpcRequest::new(self.next_id(), commands::KEYBOARD_LAYOUTS);
+        let response = self.send_request(request)?;
+
+        let result = response.result.ok_or_else(|| {
+            AmiyaError::Ipc("No result in keyboard layouts response".to_string())
+        })?;
+
+        let layouts: NiriKeyboardLayouts = serde_json::from_value(result)
+            .map_err(|e| AmiyaError::Ipc(format!("Failed to parse keyboard layouts: {}", e)))?;
+
+        Ok(layouts)
+    }
+
+    /// Switch the keyboard layout
+    pub fn switch_layout(&self, target: LayoutSwitchTarget) -> Result<()> {
+        self.send_action(NiriAction::SwitchLayout { layout: target })
+    }
+
     /// Get the niri version
     pub fn get_version(&self) -> Result<String> {
         let request = JsonRpcRequest::new(self.next_id(), commands::VERSION);
@@ -212,12 +466,7 @@ impl Default for NiriClient {
     fn default() -> Self {
         Self::new().unwrap_or_else(|e| {
             warn!("Failed to create NiriClient: {}", e);
-            // Return a dummy client that won't work but won't crash
-            NiriClient {
-                socket_path: PathBuf::from("/tmp/niri-dummy.sock"),
-                stream: Arc::new(Mutex::new(None)),
-                request_id: AtomicU64::new(1),
-            }
+            Self::without_socket()
         })
     }
 }
@@ -247,4 +496,89 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_reconnects_after_connection_loss() {
+        use std::os::unix::net::UnixListener;
+
+        let socket_path = std::env::temp_dir()
+            .join(format!("amiya-niri-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let (closed_tx, closed_rx) = std::sync::mpsc::channel();
+
+        let server = std::thread::spawn(move || {
+            // First connection: answer one request, then hang up like a restarting niri
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            writeln!(&stream, r#"{{"jsonrpc":"2.0","id":1,"result":"1.0"}}"#).unwrap();
+            drop(reader);
+            drop(stream);
+            closed_tx.send(()).unwrap();
+
+            // Second connection: the client should come back here
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            writeln!(&stream, r#"{{"jsonrpc":"2.0","id":2,"result":"2.0"}}"#).unwrap();
+        });
+
+        let client = NiriClient {
+            socket_path: Mutex::new(Some(socket_path.clone())),
+            stream: Arc::new(Mutex::new(None)),
+            request_id: AtomicU64::new(1),
+        };
+
+        assert_eq!(client.get_version().unwrap(), "1.0");
+        // The next request is written into the dead connection and retried
+        closed_rx.recv().unwrap();
+        assert_eq!(client.get_version().unwrap(), "2.0");
+
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&socket_path);
+    }
+
+    #[test]
+    fn test_no_resend_after_write() {
+        use std::os::unix::net::UnixListener;
+
+        let socket_path = std::env::temp_dir()
+            .join(format!("amiya-niri-resend-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path).unwrap();
+
+        let server = std::thread::spawn(move || {
+            // Take the request, then hang up without answering
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            listener
+        });
+
+        let client = NiriClient {
+            socket_path: Mutex::new(Some(socket_path.clone())),
+            stream: Arc::new(Mutex::new(None)),
+            request_id: AtomicU64::new(1),
+        };
+        assert!(client.get_version().is_err());
+
+        // niri may have acted on the request, so it must not come again
+        let listener = server.join().unwrap();
+        listener.set_nonblocking(true).unwrap();
+        assert!(listener.accept().is_err());
+        let _ = std::fs::remove_file(&socket_path);
+    }
+
+    #[test]
+    fn test_connection_error_classification() {
+        use std::io::{Error, ErrorKind};
+
+        assert!(NiriClient::is_connection_error(&Error::from(ErrorKind::BrokenPipe)));
+        assert!(NiriClient::is_connection_error(&Error::from(ErrorKind::UnexpectedEof)));
+        assert!(!NiriClient::is_connection_error(&Error::from(ErrorKind::InvalidData)));
+    }
 }
@@ -1,20 +1,42 @@
 use super::client::NiriClient;
-use super::protocol::NiriEvent;
+use super::protocol::{NiriEvent, NiriWindow, NiriWorkspace};
 use crate::events::{Event, EventManager, WorkspaceInfo};
 use crate::error::Result;
+use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tracing::{debug, info, warn};
 
 /// Event listener for niri compositor events
 pub struct NiriEventListener {
     client: Arc<NiriClient>,
     events: EventManager,
+    /// Known windows, keyed by niri window ID
+    windows: Mutex<HashMap<u64, NiriWindow>>,
+    /// ID of the currently focused window, if any
+    focused_window: Mutex<Option<u64>>,
+    /// Configured keyboard layout names and the index of the active one
+    keyboard_layouts: Mutex<(Vec<String>, u32)>,
+    /// Last workspace list reported by niri
+    workspaces: Mutex<Vec<NiriWorkspace>>,
+    /// Workspaces already reported as urgent, by index
+    urgent_workspaces: Mutex<HashSet<u32>>,
+    /// Whether the last poll reached niri (None before the first poll)
+    connected: Mutex<Option<bool>>,
 }
 
 impl NiriEventListener {
     pub fn new(client: Arc<NiriClient>, events: EventManager) -> Self {
-        NiriEventListener { client, events }
+        NiriEventListener {
+            client,
+            events,
+            windows: Mutex::new(HashMap::new()),
+            focused_window: Mutex::new(None),
+            keyboard_layouts: Mutex::new((Vec::new(), 0)),
+            workspaces: Mutex::new(Vec::new()),
+            urgent_workspaces: Mutex::new(HashSet::new()),
+            connected: Mutex::new(None),
+        }
     }
 
     /// Start listening for niri events
@@ -32,31 +54,173 @@ impl NiriEventListener {
     /// Poll workspace state and emit events
     /// This is a temporary solution until we implement proper event streaming
     pub fn poll_workspaces(&self) -> Result<()> {
+        // Refresh the window list first so the per-workspace windows are current
+        match self.client.get_windows() {
+            Ok(windows) => {
+                *self.windows.lock().unwrap() = windows.into_iter().map(|w| (w.id, w)).collect();
+            }
+            Err(e) => debug!("Failed to poll windows: {}", e),
+        }
+
         match self.client.get_workspaces() {
             Ok(niri_workspaces) => {
-                // Convert niri workspaces to our event format
-                let workspaces: Vec<WorkspaceInfo> = niri_workspaces
-                    .iter()
-                    .map(|ws| WorkspaceInfo {
-                        id: ws.idx,
-                        name: ws.name.clone(),
-                        is_active: ws.is_active,
-                        is_focused: ws.is_focused,
-                    })
-                    .collect();
+                self.set_connected(true);
+                self.update_workspaces(&niri_workspaces);
+                Ok(())
+            }
+            Err(e) => {
+                debug!("Failed to poll workspaces: {}", e);
+                self.set_connected(false);
+                Ok(()) // Don't fail, just log
+            }
+        }
+    }
+
+    /// Emit NiriConnectionChanged when niri goes away or comes back
+    fn set_connected(&self, connected: bool) {
+        let previous = self.connected.lock().unwrap().replace(connected);
+        if previous == Some(connected) {
+            return;
+        }
+
+        if connected {
+            info!("Connected to niri");
+        } else {
+            warn!("Lost connection to niri");
+        }
+        self.events.emit(Event::NiriConnectionChanged { connected });
+    }
 
-                // Emit workspace update event
-                self.events.emit(Event::WorkspacesUpdated { workspaces });
+    /// Poll the focused window and emit an event if it changed
+    /// This is a temporary solution until we implement proper event streaming
+    pub fn poll_focused_window(&self) -> Result<()> {
+        match self.client.get_focused_window() {
+            Ok(Some(window)) => {
+                let id = window.id;
+                let changed = {
+                    let mut windows = self.windows.lock().unwrap();
+                    let previous = windows.insert(id, window.clone());
+                    previous.map_or(true, |w| w.title != window.title)
+                };
 
+                if changed || *self.focused_window.lock().unwrap() != Some(id) {
+                    self.handle_niri_event(NiriEvent::WindowFocusChanged { id: Some(id) });
+                }
+                Ok(())
+            }
+            Ok(None) => {
+                if self.focused_window.lock().unwrap().is_some() {
+                    self.handle_niri_event(NiriEvent::WindowFocusChanged { id: None });
+                }
                 Ok(())
             }
             Err(e) => {
-                debug!("Failed to poll workspaces: {}", e);
+                debug!("Failed to poll focused window: {}", e);
+                Ok(()) // Don't fail, just log
+            }
+        }
+    }
+
+    /// Poll the keyboard layouts and emit an event if they changed
+    /// This is a temporary solution until we implement proper event streaming
+    pub fn poll_keyboard_layouts(&self) -> Result<()> {
+        match self.client.get_keyboard_layouts() {
+            Ok(layouts) => {
+                let changed = {
+                    let (names, current_idx) = &*self.keyboard_layouts.lock().unwrap();
+                    *names != layouts.names || *current_idx != layouts.current_idx
+                };
+
+                if changed {
+                    self.handle_niri_event(NiriEvent::KeyboardLayoutsChanged {
+                        keyboard_layouts: layouts.names,
+                        current_idx: layouts.current_idx,
+                    });
+                }
+                Ok(())
+            }
+            Err(e) => {
+                debug!("Failed to poll keyboard layouts: {}", e);
                 Ok(()) // Don't fail, just log
             }
         }
     }
 
+    /// Record the workspace list and emit it
+    fn update_workspaces(&self, niri_workspaces: &[NiriWorkspace]) {
+        *self.workspaces.lock().unwrap() = niri_workspaces.to_vec();
+        self.emit_workspaces();
+    }
+
+    /// Emit the last workspace list along with the windows on each workspace,
+    /// and report workspaces that have just become urgent
+    ///
+    /// A workspace is urgent while niri flags it or any window on it, so it
+    /// stops being urgent once those windows have been attended to.
+    fn emit_workspaces(&self) {
+        let workspaces = self.workspaces.lock().unwrap().clone();
+        let mut windows: Vec<NiriWindow> = self.windows.lock().unwrap().values().cloned().collect();
+        windows.sort_by_key(|w| w.id);
+
+        // Convert niri workspaces to our event format
+        let workspaces: Vec<WorkspaceInfo> = workspaces
+            .iter()
+            .map(|ws| WorkspaceInfo {
+                id: ws.idx,
+                name: ws.name.clone(),
+                is_active: ws.is_active,
+                is_focused: ws.is_focused,
+                is_urgent: ws.is_urgent
+                    || windows
+                        .iter()
+                        .any(|w| w.is_urgent && w.workspace_id == Some(ws.id)),
+                windows: windows
+                    .iter()
+                    .filter(|w| w.workspace_id == Some(ws.id))
+                    .map(|w| w.app_id.clone().unwrap_or_default())
+                    .collect(),
+            })
+            .collect();
+
+        // Forget workspaces that are no longer urgent so the next request for
+        // attention is reported again
+        let newly_urgent: Vec<u32> = {
+            let mut urgent = self.urgent_workspaces.lock().unwrap();
+            urgent.retain(|idx| workspaces.iter().any(|ws| ws.id == *idx && ws.is_urgent));
+            workspaces
+                .iter()
+                .filter(|ws| ws.is_urgent && urgent.insert(ws.id))
+                .map(|ws| ws.id)
+                .collect()
+        };
+
+        // Emit workspace update event
+        self.events.emit(Event::WorkspacesUpdated { workspaces });
+
+        for id in newly_urgent {
+            debug!("Workspace {} needs attention", id);
+            self.events.emit(Event::WorkspaceUrgent { id });
+        }
+    }
+
+    /// Emit a KeyboardLayoutChanged event for the active layout
+    fn emit_keyboard_layout(&self) {
+        let (names, current_idx) = &*self.keyboard_layouts.lock().unwrap();
+        if let Some(layout) = names.get(*current_idx as usize) {
+            self.events.emit(Event::KeyboardLayoutChanged {
+                layout: layout.clone(),
+            });
+        }
+    }
+
+    /// Emit a FocusedWindowChanged event for the given window
+    fn emit_focused_window(&self, window: Option<&NiriWindow>) {
+        self.events.emit(Event::FocusedWindowChanged {
+            title: window.and_then(|w| w.title.clone()),
+            app_id: window.and_then(|w| w.app_id.clone()),
+        });
+    }
+
     /// Handle a niri event and emit corresponding application events
     fn handle_niri_event(&self, niri_event: NiriEvent) {
         match niri_event {
@@ -67,18 +231,7 @@ impl NiriEventListener {
             }
             NiriEvent::WorkspacesChanged { workspaces } => {
                 debug!("Workspaces changed: {} workspaces", workspaces.len());
-                let workspace_infos: Vec<WorkspaceInfo> = workspaces
-                    .iter()
-                    .map(|ws| WorkspaceInfo {
-                        id: ws.idx,
-                        name: ws.name.clone(),
-                        is_active: ws.is_active,
-                        is_focused: ws.is_focused,
-                    })
-                    .collect();
-                self.events.emit(Event::WorkspacesUpdated {
-                    workspaces: workspace_infos,
-                });
+                self.update_workspaces(&workspaces);
             }
             NiriEvent::WorkspaceActiveWindowChanged {
                 workspace_id,
@@ -92,13 +245,59 @@ impl NiriEventListener {
             }
             NiriEvent::WindowOpenedOrChanged { window } => {
                 debug!("Window opened or changed: {:?}", window.title);
-                // Could emit an event if we add window tracking
+                let mut focused = self.focused_window.lock().unwrap();
+                if window.is_focused {
+                    *focused = Some(window.id);
+                }
+                if *focused == Some(window.id) {
+                    self.emit_focused_window(Some(&window));
+                }
+                drop(focused);
+
+                // Title changes don't affect the per-workspace windows
+                let changed = {
+                    let mut windows = self.windows.lock().unwrap();
+                    let previous = windows.insert(window.id, window.clone());
+                    previous.map_or(true, |w| {
+                        w.workspace_id != window.workspace_id
+                            || w.app_id != window.app_id
+                            || w.is_urgent != window.is_urgent
+                    })
+                };
+                if changed {
+                    self.emit_workspaces();
+                }
             }
             NiriEvent::WindowClosed { id } => {
                 debug!("Window closed: {}", id);
+                let removed = self.windows.lock().unwrap().remove(&id).is_some();
+                let mut focused = self.focused_window.lock().unwrap();
+                if *focused == Some(id) {
+                    *focused = None;
+                    self.emit_focused_window(None);
+                }
+                drop(focused);
+                if removed {
+                    self.emit_workspaces();
+                }
             }
             NiriEvent::WindowFocusChanged { id } => {
                 debug!("Window focus changed: {:?}", id);
+                *self.focused_window.lock().unwrap() = id;
+                let windows = self.windows.lock().unwrap();
+                self.emit_focused_window(id.and_then(|id| windows.get(&id)));
+            }
+            NiriEvent::WindowUrgencyChanged { id, urgent } => {
+                debug!("Window {} urgency changed: {}", id, urgent);
+                let changed = {
+                    let mut windows = self.windows.lock().unwrap();
+                    windows.get_mut(&id).is_some_and(|window| {
+                        std::mem::replace(&mut window.is_urgent, urgent) != urgent
+                    })
+                };
+                if changed {
+                    self.emit_workspaces();
+                }
             }
             NiriEvent::KeyboardLayoutsChanged {
                 keyboard_layouts,
@@ -109,9 +308,13 @@ impl NiriEventListener {
                     keyboard_layouts.len(),
                     current_idx
                 );
+                *self.keyboard_layouts.lock().unwrap() = (keyboard_layouts, current_idx);
+                self.emit_keyboard_layout();
             }
             NiriEvent::KeyboardLayoutSwitched { idx } => {
                 debug!("Keyboard layout switched to: {}", idx);
+                self.keyboard_layouts.lock().unwrap().1 = idx;
+                self.emit_keyboard_layout();
             }
         }
     }
@@ -133,11 +336,25 @@ pub fn start_workspace_polling(
         warn!("Initial workspace poll failed: {}", e);
     }
 
+    if let Err(e) = listener.poll_focused_window() {
+        warn!("Initial focused window poll failed: {}", e);
+    }
+
+    if let Err(e) = listener.poll_keyboard_layouts() {
+        warn!("Initial keyboard layout poll failed: {}", e);
+    }
+
     // Poll periodically
     glib::timeout_add_seconds_local(interval_seconds, move || {
         if let Err(e) = listener.poll_workspaces() {
             warn!("Workspace poll failed: {}", e);
         }
+        if let Err(e) = listener.poll_focused_window() {
+            warn!("Focused window poll failed: {}", e);
+        }
+        if let Err(e) = listener.poll_keyboard_layouts() {
+            warn!("Keyboard layout poll failed: {}", e);
+        }
         glib::ControlFlow::Continue
     });
 }
@@ -4,4 +4,7 @@ pub mod protocol;
 
 pub use client::NiriClient;
 pub use events::{start_workspace_polling, NiriEventListener};
-pub use protocol::{NiriAction, NiriEvent, NiriWorkspace, WorkspaceReference};
+pub use protocol::{
+    LayoutSwitchTarget, NiriAction, NiriEvent, NiriOutput, NiriWindow, NiriWorkspace,
+    OutputTransform, WorkspaceReference,
+};
@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 /// JSON-RPC 2.0 Request
 #[derive(Debug, Clone, Serialize)]
@@ -59,6 +61,9 @@ pub struct NiriWorkspace {
     pub idx: u32,
     pub is_active: bool,
     pub is_focused: bool,
+    /// Whether a window on this workspace requests attention
+    #[serde(default)]
+    pub is_urgent: bool,
 }
 
 /// Niri workspaces response
@@ -67,6 +72,12 @@ pub struct NiriWorkspacesResponse {
     pub workspaces: Vec<NiriWorkspace>,
 }
 
+/// Niri windows response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NiriWindowsResponse {
+    pub windows: Vec<NiriWindow>,
+}
+
 /// Niri window information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NiriWindow {
@@ -75,6 +86,126 @@ pub struct NiriWindow {
     pub app_id: Option<String>,
     pub workspace_id: Option<u64>,
     pub is_focused: bool,
+    /// Whether the window requests attention
+    #[serde(default)]
+    pub is_urgent: bool,
+}
+
+/// Niri outputs response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NiriOutputsResponse {
+    pub outputs: Vec<NiriOutput>,
+}
+
+/// Niri output (monitor) information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NiriOutput {
+    pub name: String,
+    #[serde(default)]
+    pub make: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Placement in the layout; `None` while the output is turned off
+    #[serde(default)]
+    pub logical: Option<NiriLogicalOutput>,
+}
+
+/// Where an enabled output sits in niri's layout and how it is drawn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NiriLogicalOutput {
+    pub scale: f64,
+    pub transform: OutputTransform,
+}
+
+/// Rotation (counter-clockwise) and flip of an output, named as in niri's
+/// config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputTransform {
+    #[serde(rename = "normal")]
+    Normal,
+    #[serde(rename = "90")]
+    Rotate90,
+    #[serde(rename = "180")]
+    Rotate180,
+    #[serde(rename = "270")]
+    Rotate270,
+    #[serde(rename = "flipped")]
+    Flipped,
+    #[serde(rename = "flipped-90")]
+    Flipped90,
+    #[serde(rename = "flipped-180")]
+    Flipped180,
+    #[serde(rename = "flipped-270")]
+    Flipped270,
+}
+
+impl OutputTransform {
+    pub const ALL: [OutputTransform; 8] = [
+        OutputTransform::Normal,
+        OutputTransform::Rotate90,
+        OutputTransform::Rotate180,
+        OutputTransform::Rotate270,
+        OutputTransform::Flipped,
+        OutputTransform::Flipped90,
+        OutputTransform::Flipped180,
+        OutputTransform::Flipped270,
+    ];
+
+    /// Name used in niri's config and in IPC
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputTransform::Normal => "normal",
+            OutputTransform::Rotate90 => "90",
+            OutputTransform::Rotate180 => "180",
+            OutputTransform::Rotate270 => "270",
+            OutputTransform::Flipped => "flipped",
+            OutputTransform::Flipped90 => "flipped-90",
+            OutputTransform::Flipped180 => "flipped-180",
+            OutputTransform::Flipped270 => "flipped-270",
+        }
+    }
+}
+
+impl fmt::Display for OutputTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for OutputTransform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        OutputTransform::ALL
+            .into_iter()
+            .find(|transform| transform.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = OutputTransform::ALL.iter().map(|t| t.name()).collect();
+                format!(
+                    "Unknown transform '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Change to an output's configuration, sent with `commands::OUTPUT`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputAction {
+    Scale { scale: f64 },
+    Transform { transform: OutputTransform },
+}
+
+/// Reply to `commands::OUTPUT` when no connected output has the given name
+pub const OUTPUT_WAS_MISSING: &str = "output-was-missing";
+
+/// Niri keyboard layouts information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NiriKeyboardLayouts {
+    pub names: Vec<String>,
+    pub current_idx: u32,
 }
 
 /// Niri event notification
@@ -101,6 +232,10 @@ pub enum NiriEvent {
     WindowFocusChanged {
         id: Option<u64>,
     },
+    WindowUrgencyChanged {
+        id: u64,
+        urgent: bool,
+    },
     KeyboardLayoutsChanged {
         keyboard_layouts: Vec<String>,
         current_idx: u32,
@@ -113,6 +248,7 @@ pub enum NiriEvent {
 /// Niri commands
 pub mod commands {
     pub const WORKSPACES: &str = "Workspaces";
+    pub const WINDOWS: &str = "Windows";
     pub const FOCUSED_WINDOW: &str = "FocusedWindow";
     pub const ACTION: &str = "Action";
     pub const OUTPUT: &str = "Output";
@@ -130,13 +266,26 @@ pub enum NiriAction {
     FocusWorkspace { reference: WorkspaceReference },
     FocusWorkspaceDown,
     FocusWorkspaceUp,
+    FocusWindow { id: u64 },
     MoveWindowToWorkspace { reference: WorkspaceReference },
     MoveWindowToWorkspaceDown,
     MoveWindowToWorkspaceUp,
+    SwitchLayout { layout: LayoutSwitchTarget },
+    Spawn { command: Vec<String> },
+    ScreenshotWindow,
     Quit,
     PowerOffMonitors,
 }
 
+/// Target of a keyboard layout switch
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutSwitchTarget {
+    Next,
+    Prev,
+    Index(u8),
+}
+
 /// Reference to a workspace by index or name
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "lowercase")]
@@ -167,6 +316,128 @@ mod tests {
         assert!(json.is_object());
     }
 
+    #[test]
+    fn test_move_window_to_workspace_serialization() {
+        let action = NiriAction::MoveWindowToWorkspace {
+            reference: WorkspaceReference::Index(3),
+        };
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "move-window-to-workspace": { "reference": { "index": 3 } } })
+        );
+
+        let action = NiriAction::MoveWindowToWorkspace {
+            reference: WorkspaceReference::Name("web".to_string()),
+        };
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "move-window-to-workspace": { "reference": { "name": "web" } } })
+        );
+    }
+
+    #[test]
+    fn test_move_window_up_down_serialization() {
+        let up = serde_json::to_value(&NiriAction::MoveWindowToWorkspaceUp).unwrap();
+        assert_eq!(up, serde_json::json!("move-window-to-workspace-up"));
+
+        let down = serde_json::to_value(&NiriAction::MoveWindowToWorkspaceDown).unwrap();
+        assert_eq!(down, serde_json::json!("move-window-to-workspace-down"));
+    }
+
+    #[test]
+    fn test_focus_window_serialization() {
+        let json = serde_json::to_value(NiriAction::FocusWindow { id: 42 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "focus-window": { "id": 42 } }));
+    }
+
+    #[test]
+    fn test_spawn_serialization() {
+        let action = NiriAction::Spawn {
+            command: vec!["firefox".to_string(), "--new-window".to_string()],
+        };
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "spawn": { "command": ["firefox", "--new-window"] } })
+        );
+    }
+
+    #[test]
+    fn test_screenshot_window_serialization() {
+        let json = serde_json::to_value(NiriAction::ScreenshotWindow).unwrap();
+        assert_eq!(json, serde_json::json!("screenshot-window"));
+    }
+
+    #[test]
+    fn test_switch_layout_serialization() {
+        let action = NiriAction::SwitchLayout {
+            layout: LayoutSwitchTarget::Next,
+        };
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(json, serde_json::json!({ "switch-layout": { "layout": "next" } }));
+    }
+
+    #[test]
+    fn test_output_action_serialization() {
+        let json = serde_json::to_value(OutputAction::Scale { scale: 1.5 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "scale": { "scale": 1.5 } }));
+
+        let action = OutputAction::Transform {
+            transform: OutputTransform::Flipped90,
+        };
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "transform": { "transform": "flipped-90" } })
+        );
+    }
+
+    #[test]
+    fn test_output_transform_names() {
+        for transform in OutputTransform::ALL {
+            assert_eq!(transform.name().parse::<OutputTransform>(), Ok(transform));
+            assert_eq!(
+                serde_json::to_value(transform).unwrap(),
+                serde_json::json!(transform.name())
+            );
+        }
+
+        let err = "upside-down".parse::<OutputTransform>().unwrap_err();
+        assert!(err.contains("upside-down"));
+        assert!(err.contains("normal, 90, 180, 270, flipped"));
+    }
+
+    #[test]
+    fn test_outputs_response_deserialization() {
+        let json = r#"{
+            "outputs": [
+                {"name": "eDP-1", "make": "BOE", "model": "0x0BCA",
+                 "logical": {"scale": 1.5, "transform": "90"}},
+                {"name": "HDMI-A-1", "logical": null}
+            ]
+        }"#;
+        let response: NiriOutputsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.outputs.len(), 2);
+        let logical = response.outputs[0].logical.as_ref().unwrap();
+        assert_eq!(logical.scale, 1.5);
+        assert_eq!(logical.transform, OutputTransform::Rotate90);
+        assert!(response.outputs[1].logical.is_none());
+        assert_eq!(response.outputs[1].make, None);
+    }
+
+    #[test]
+    fn test_keyboard_layouts_deserialization() {
+        let json = r#"{
+            "names": ["English (US)", "German"],
+            "current_idx": 1
+        }"#;
+        let layouts: NiriKeyboardLayouts = serde_json::from_str(json).unwrap();
+        assert_eq!(layouts.names.len(), 2);
+        assert_eq!(layouts.current_idx, 1);
+    }
+
     #[test]
     fn test_workspace_deserialization() {
         let json = r#"{
@@ -181,4 +452,46 @@ mod tests {
         assert!(workspace.is_active);
         assert!(workspace.is_focused);
     }
+
+    #[test]
+    fn test_window_urgency_deserialization() {
+        let json = r#"{
+            "type": "window-opened-or-changed",
+            "window": {
+                "id": 7,
+                "title": "Chat",
+                "app_id": "org.telegram.desktop",
+                "workspace_id": 3,
+                "is_focused": false,
+                "is_urgent": true
+            }
+        }"#;
+        let NiriEvent::WindowOpenedOrChanged { window } = serde_json::from_str(json).unwrap()
+        else {
+            panic!("wrong event type");
+        };
+        assert!(window.is_urgent);
+        assert_eq!(window.workspace_id, Some(3));
+
+        // Older niri versions don't report urgency
+        let json = r#"{"id": 1, "idx": 1, "is_active": true, "is_focused": true}"#;
+        let workspace: NiriWorkspace = serde_json::from_str(json).unwrap();
+        assert!(!workspace.is_urgent);
+    }
+
+    #[test]
+    fn test_windows_response_deserialization() {
+        let json = r#"{
+            "windows": [
+                {"id": 1, "title": "Inbox", "app_id": "thunderbird",
+                 "workspace_id": 2, "is_focused": false},
+                {"id": 2, "title": null, "app_id": null,
+                 "workspace_id": null, "is_focused": true}
+            ]
+        }"#;
+        let response: NiriWindowsResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(response.windows.len(), 2);
+        assert_eq!(response.windows[0].app_id.as_deref(), Some("thunderbird"));
+        assert_eq!(response.windows[1].workspace_id, None);
+    }
 }
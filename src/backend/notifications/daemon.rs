@@ -0,0 +1,345 @@
+use crate::error::{AmiyaError, Result};
+use crate::events::{Event, EventManager, NotificationAction};
+use chrono::{DateTime, Local};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+use zbus::object_server::SignalContext;
+use zbus::zvariant::OwnedValue;
+use zbus::Connection;
+
+const BUS_NAME: &str = "org.freedesktop.Notifications";
+const OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+
+/// Number of notifications kept in history
+const HISTORY_LIMIT: usize = 50;
+
+/// Why a notification was closed, as defined by the notification spec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseReason {
+    Expired = 1,
+    Dismissed = 2,
+    Closed = 3,
+    Undefined = 4,
+}
+
+/// A received notification, kept for the notification center
+#[derive(Debug, Clone)]
+pub struct NotificationRecord {
+    pub id: u32,
+    pub app: String,
+    /// Icon name, file path or `file://` URI the app sent, if any
+    pub icon: Option<String>,
+    pub summary: String,
+    pub body: String,
+    pub timestamp: DateTime<Local>,
+}
+
+/// Add a record to the front of the history, replacing any entry with the same
+/// id and dropping the oldest entries beyond `HISTORY_LIMIT`
+fn push_history(history: &mut VecDeque<NotificationRecord>, record: NotificationRecord) {
+    history.retain(|r| r.id != record.id);
+    history.push_front(record);
+    history.truncate(HISTORY_LIMIT);
+}
+
+/// The `org.freedesktop.Notifications` D-Bus interface
+struct NotificationServer {
+    next_id: Arc<AtomicU32>,
+    history: Arc<RwLock<VecDeque<NotificationRecord>>>,
+    events: EventManager,
+}
+
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl NotificationServer {
+    #[allow(clippy::too_many_arguments)]
+    async fn notify(
+        &self,
+        app_name: String,
+        replaces_id: u32,
+        app_icon: String,
+        summary: String,
+        body: String,
+        actions: Vec<String>,
+        _hints: HashMap<String, OwnedValue>,
+        expire_timeout: i32,
+    ) -> u32 {
+        let id = if replaces_id != 0 {
+            replaces_id
+        } else {
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        };
+
+        debug!("Notification {} from {}: {}", id, app_name, summary);
+        let icon = (!app_icon.is_empty()).then_some(app_icon);
+
+        push_history(
+            &mut *self.history.write().await,
+            NotificationRecord {
+                id,
+                app: app_name.clone(),
+                icon: icon.clone(),
+                summary: summary.clone(),
+                body: body.clone(),
+                timestamp: Local::now(),
+            },
+        );
+
+        self.events.emit(Event::NotificationReceived {
+            id,
+            app: app_name,
+            icon,
+            summary,
+            body,
+            actions: parse_actions(actions),
+            timeout_ms: expire_timeout,
+        });
+
+        id
+    }
+
+    async fn close_notification(
+        &self,
+        id: u32,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> zbus::fdo::Result<()> {
+        self.events.emit(Event::NotificationClosed { id });
+        Self::notification_closed(&ctxt, id, CloseReason::Closed as u32).await?;
+        Ok(())
+    }
+
+    fn get_capabilities(&self) -> Vec<String> {
+        vec!["body".to_string(), "actions".to_string()]
+    }
+
+    fn get_server_information(&self) -> (String, String, String, String) {
+        (
+            "Amiya".to_string(),
+            "Amiya".to_string(),
+            env!("CARGO_PKG_VERSION").to_string(),
+            "1.2".to_string(),
+        )
+    }
+
+    #[zbus(signal)]
+    async fn notification_closed(ctxt: &SignalContext<'_>, id: u32, reason: u32)
+        -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn action_invoked(ctxt: &SignalContext<'_>, id: u32, action_key: &str)
+        -> zbus::Result<()>;
+}
+
+/// Turn the flat `[key, label, key, label, ...]` action list into pairs
+fn parse_actions(actions: Vec<String>) -> Vec<NotificationAction> {
+    actions
+        .chunks_exact(2)
+        .map(|pair| NotificationAction {
+            key: pair[0].clone(),
+            label: pair[1].clone(),
+        })
+        .collect()
+}
+
+/// Notification daemon serving `org.freedesktop.Notifications` on the session bus
+pub struct NotificationDaemon {
+    connection: Arc<RwLock<Option<Connection>>>,
+    next_id: Arc<AtomicU32>,
+    history: Arc<RwLock<VecDeque<NotificationRecord>>>,
+    dnd: AtomicBool,
+    events: EventManager,
+}
+
+impl NotificationDaemon {
+    /// Create a new notification daemon
+    pub fn new(events: EventManager) -> Self {
+        NotificationDaemon {
+            connection: Arc::new(RwLock::new(None)),
+            next_id: Arc::new(AtomicU32::new(1)),
+            history: Arc::new(RwLock::new(VecDeque::new())),
+            dnd: AtomicBool::new(false),
+            events,
+        }
+    }
+
+    /// Claim the notifications bus name and start serving requests
+    pub async fn connect(&self) -> Result<()> {
+        let server = NotificationServer {
+            next_id: self.next_id.clone(),
+            history: self.history.clone(),
+            events: self.events.clone(),
+        };
+
+        let conn = zbus::connection::Builder::session()
+            .and_then(|b| b.name(BUS_NAME))
+            .and_then(|b| b.serve_at(OBJECT_PATH, server))
+            .map_err(|e| AmiyaError::Backend(format!("Failed to set up notifications: {}", e)))?
+            .build()
+            .await
+            .map_err(|e| {
+                AmiyaError::Backend(format!(
+                    "Failed to claim {} (is another notification daemon running?): {}",
+                    BUS_NAME, e
+                ))
+            })?;
+
+        info!("Notification daemon registered as {}", BUS_NAME);
+
+        let mut connection = self.connection.write().await;
+        *connection = Some(conn);
+
+        Ok(())
+    }
+
+    /// Check if the daemon owns the notifications bus name
+    pub async fn is_available(&self) -> bool {
+        self.connection.read().await.is_some()
+    }
+
+    /// Recent notifications, newest first
+    pub async fn history(&self) -> Vec<NotificationRecord> {
+        self.history.read().await.iter().cloned().collect()
+    }
+
+    /// Forget all notifications in the history
+    pub async fn clear_history(&self) {
+        self.history.write().await.clear();
+    }
+
+    /// Whether do-not-disturb is on (notifications are logged but not shown)
+    pub fn is_dnd(&self) -> bool {
+        self.dnd.load(Ordering::Relaxed)
+    }
+
+    /// Turn do-not-disturb on or off
+    pub fn set_dnd(&self, enabled: bool) {
+        if self.dnd.swap(enabled, Ordering::Relaxed) != enabled {
+            info!("Do not disturb {}", if enabled { "enabled" } else { "disabled" });
+            self.events.emit(Event::DndChanged { enabled });
+        }
+    }
+
+    /// Close a notification from the UI and tell the sending app why
+    pub async fn close(&self, id: u32, reason: CloseReason) -> Result<()> {
+        self.events.emit(Event::NotificationClosed { id });
+
+        let ctxt = self.signal_context().await?;
+        NotificationServer::notification_closed(&ctxt, id, reason as u32)
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to emit NotificationClosed: {}", e)))
+    }
+
+    /// Report that the user picked an action, then close the notification
+    pub async fn invoke_action(&self, id: u32, action_key: &str) -> Result<()> {
+        let ctxt = self.signal_context().await?;
+        NotificationServer::action_invoked(&ctxt, id, action_key)
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to emit ActionInvoked: {}", e)))?;
+
+        self.close(id, CloseReason::Dismissed).await
+    }
+
+    async fn signal_context(&self) -> Result<SignalContext<'static>> {
+        let conn_guard = self.connection.read().await;
+        let conn = conn_guard
+            .as_ref()
+            .ok_or_else(|| AmiyaError::Backend("Notification daemon not connected".to_string()))?;
+
+        SignalContext::new(conn, OBJECT_PATH)
+            .map(|ctxt| ctxt.into_owned())
+            .map_err(|e| AmiyaError::Backend(format!("Invalid object path: {}", e)))
+    }
+}
+
+/// Create notification daemon and claim the bus name in the background
+pub fn create_notification_daemon_sync(events: EventManager) -> Arc<NotificationDaemon> {
+    let daemon = Arc::new(NotificationDaemon::new(events));
+
+    // Try to connect in background
+    let daemon_clone = daemon.clone();
+    tokio::spawn(async move {
+        if let Err(e) = daemon_clone.connect().await {
+            warn!("Failed to start notification daemon: {}", e);
+        }
+    });
+
+    daemon
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_actions() {
+        let actions = parse_actions(vec![
+            "default".to_string(),
+            "Open".to_string(),
+            "reply".to_string(),
+            "Reply".to_string(),
+        ]);
+
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].key, "default");
+        assert_eq!(actions[1].label, "Reply");
+    }
+
+    #[test]
+    fn test_parse_actions_ignores_dangling_key() {
+        let actions = parse_actions(vec!["default".to_string()]);
+        assert!(actions.is_empty());
+    }
+
+    fn record(id: u32) -> NotificationRecord {
+        NotificationRecord {
+            id,
+            app: "test".to_string(),
+            icon: None,
+            summary: format!("Notification {}", id),
+            body: String::new(),
+            timestamp: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_history_is_newest_first_and_bounded() {
+        let mut history = VecDeque::new();
+        for id in 1..=(HISTORY_LIMIT as u32 + 5) {
+            push_history(&mut history, record(id));
+        }
+
+        assert_eq!(history.len(), HISTORY_LIMIT);
+        assert_eq!(history.front().unwrap().id, HISTORY_LIMIT as u32 + 5);
+        assert_eq!(history.back().unwrap().id, 6);
+    }
+
+    #[test]
+    fn test_history_replaces_same_id() {
+        let mut history = VecDeque::new();
+        push_history(&mut history, record(1));
+        push_history(&mut history, record(2));
+        push_history(&mut history, record(1));
+
+        let ids: Vec<u32> = history.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_dnd_toggle() {
+        let daemon = NotificationDaemon::new(EventManager::default());
+        assert!(!daemon.is_dnd());
+        daemon.set_dnd(true);
+        assert!(daemon.is_dnd());
+        daemon.set_dnd(false);
+        assert!(!daemon.is_dnd());
+    }
+
+    #[tokio::test]
+    async fn test_daemon_not_connected() {
+        let daemon = NotificationDaemon::new(EventManager::default());
+        assert!(!daemon.is_available().await);
+        assert!(daemon.close(1, CloseReason::Dismissed).await.is_err());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod daemon;
+
+pub use daemon::{
+    create_notification_daemon_sync, CloseReason, NotificationDaemon, NotificationRecord,
+};
@@ -0,0 +1,125 @@
+use crate::error::Result;
+use std::future::Future;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Retry schedule for `connect_with_backoff`
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// Delay before the first retry; doubles after every failed attempt
+    pub initial: Duration,
+    /// Upper bound on the delay between attempts
+    pub max: Duration,
+    /// Attempts before giving up
+    pub attempts: u32,
+}
+
+impl Backoff {
+    /// Delay after failed attempt number `attempt` (starting at 1)
+    fn delay(&self, attempt: u32) -> Duration {
+        self.initial
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max)
+    }
+}
+
+impl Default for Backoff {
+    /// 0.5s, 1s, 2s, ... capped at 30s, for about four minutes in total
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+            attempts: 12,
+        }
+    }
+}
+
+/// Call `connect` until it succeeds, waiting exponentially longer between
+/// attempts
+///
+/// Services like PipeWire, BlueZ or NetworkManager are often still starting
+/// when amiya is launched right after login, so a single failed attempt
+/// shouldn't disable a backend for the whole session.
+pub async fn connect_with_backoff<F, Fut>(
+    name: &str,
+    backoff: Backoff,
+    mut connect: F,
+) -> Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<()>>,
+{
+    let mut attempt = 1;
+    loop {
+        match connect().await {
+            Ok(()) => {
+                if attempt > 1 {
+                    info!("{} connected after {} attempts", name, attempt);
+                }
+                return Ok(());
+            }
+            Err(e) if attempt >= backoff.attempts => {
+                warn!("Giving up on {} after {} attempts: {}", name, attempt, e);
+                return Err(e);
+            }
+            Err(e) => {
+                let delay = backoff.delay(attempt);
+                debug!("{} unavailable ({}), retrying in {:?}", name, e, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AmiyaError;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    const FAST: Backoff = Backoff {
+        initial: Duration::from_millis(1),
+        max: Duration::from_millis(2),
+        attempts: 4,
+    };
+
+    #[test]
+    fn test_backoff_delay() {
+        let backoff = Backoff::default();
+        assert_eq!(backoff.delay(1), Duration::from_millis(500));
+        assert_eq!(backoff.delay(2), Duration::from_secs(1));
+        assert_eq!(backoff.delay(4), Duration::from_secs(4));
+        assert_eq!(backoff.delay(7), backoff.max);
+        assert_eq!(backoff.delay(u32::MAX), backoff.max);
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let attempts = AtomicU32::new(0);
+        let result = connect_with_backoff("test", FAST, || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(AmiyaError::Backend("not yet".to_string()))
+            } else {
+                Ok(())
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up() {
+        let attempts = AtomicU32::new(0);
+        let result = connect_with_backoff("test", FAST, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(AmiyaError::Backend("never".to_string()))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), FAST.attempts);
+    }
+}
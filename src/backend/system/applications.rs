@@ -0,0 +1,450 @@
+use crate::backend::NiriClient;
+use crate::error::{AmiyaError, Result};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, warn};
+
+/// Terminal used for `Terminal=true` entries when `$TERMINAL` is unset
+const DEFAULT_TERMINAL: &str = "alacritty";
+
+/// An application from a `.desktop` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopEntry {
+    /// Desktop file id, e.g. "firefox.desktop"
+    pub id: String,
+    pub name: String,
+    pub exec: String,
+    pub icon: Option<String>,
+    pub comment: Option<String>,
+    pub terminal: bool,
+}
+
+impl DesktopEntry {
+    /// Parse the `[Desktop Entry]` group of a desktop file
+    ///
+    /// Returns `None` for entries that should not be shown in a launcher
+    /// (hidden, `NoDisplay`, non-applications or missing `Exec`).
+    pub fn parse(id: &str, contents: &str) -> Option<Self> {
+        let mut in_entry = false;
+        let mut name = None;
+        let mut exec = None;
+        let mut icon = None;
+        let mut comment = None;
+        let mut terminal = false;
+        let mut is_application = false;
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_entry = line == "[Desktop Entry]";
+                continue;
+            }
+            if !in_entry {
+                continue;
+            }
+
+            // Localized keys (Name[de]=...) are skipped
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "Type" => is_application = value == "Application",
+                "Name" => name = Some(value.to_string()),
+                "Exec" => exec = Some(value.to_string()),
+                "Icon" => icon = Some(value.to_string()),
+                "Comment" => comment = Some(value.to_string()),
+                "Terminal" => terminal = value == "true",
+                "NoDisplay" | "Hidden" if value == "true" => return None,
+                _ => {}
+            }
+        }
+
+        if !is_application {
+            return None;
+        }
+
+        Some(DesktopEntry {
+            id: id.to_string(),
+            name: name?,
+            exec: exec?,
+            icon,
+            comment,
+            terminal,
+        })
+    }
+
+    /// Command line to run, with field codes removed
+    pub fn command(&self) -> Vec<String> {
+        let mut args = exec_args(&self.exec);
+        if self.terminal {
+            let terminal = env::var("TERMINAL").unwrap_or_else(|_| DEFAULT_TERMINAL.to_string());
+            args.splice(0..0, [terminal, "-e".to_string()]);
+        }
+        args
+    }
+
+    /// Start the application, through niri when connected so it inherits the
+    /// compositor's environment
+    pub fn launch(&self, niri: Option<&NiriClient>) -> Result<()> {
+        let command = self.command();
+        if command.is_empty() {
+            return Err(AmiyaError::Other(format!("Empty Exec line for {}", self.id)));
+        }
+
+        debug!("Launching {}: {:?}", self.id, command);
+
+        if let Some(niri) = niri {
+            match niri.spawn(command.clone()) {
+                Ok(()) => return Ok(()),
+                Err(e) => warn!("niri spawn failed, launching directly: {}", e),
+            }
+        }
+
+        let mut child = Command::new(&command[0]).args(&command[1..]).spawn()?;
+
+        // Reap the child so it doesn't linger as a zombie
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+
+        Ok(())
+    }
+}
+
+/// Directories searched for `.desktop` files, most important first
+pub fn application_dirs() -> Vec<PathBuf> {
+    let data_home = env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var("HOME").ok().map(|home| Path::new(&home).join(".local/share")));
+
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    data_home
+        .into_iter()
+        .chain(data_dirs.split(':').map(PathBuf::from))
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// Load all launchable applications, sorted by name
+///
+/// Desktop file ids found earlier in the search path shadow later ones, so a
+/// user's override in `~/.local/share/applications` wins over the system copy.
+pub fn load_applications() -> Vec<DesktopEntry> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for dir in application_dirs() {
+        collect_entries(&dir, &dir, &mut seen, &mut entries);
+    }
+
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+fn collect_entries(
+    root: &Path,
+    dir: &Path,
+    seen: &mut HashSet<String>,
+    entries: &mut Vec<DesktopEntry>,
+) {
+    let Ok(dir_entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for dir_entry in dir_entries.flatten() {
+        let path = dir_entry.path();
+        if path.is_dir() {
+            collect_entries(root, &path, seen, entries);
+            continue;
+        }
+        if path.extension().and_then(|ext| ext.to_str()) != Some("desktop") {
+            continue;
+        }
+
+        // Files in subdirectories get ids like "kde-konsole.desktop"
+        let id = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('/', "-");
+        if !seen.insert(id.clone()) {
+            continue;
+        }
+
+        if let Ok(contents) = fs::read_to_string(&path) {
+            if let Some(entry) = DesktopEntry::parse(&id, &contents) {
+                entries.push(entry);
+            }
+        }
+    }
+}
+
+/// Split an `Exec` value into arguments, honouring double quotes and
+/// dropping field codes like `%U` that only make sense when opening files
+pub fn exec_args(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            '\\' if in_quotes => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(current);
+    }
+
+    args.iter().filter_map(|arg| strip_field_codes(arg)).collect()
+}
+
+/// Remove field codes from an argument, or drop it if nothing else is left
+fn strip_field_codes(arg: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut stripped = false;
+    let mut chars = arg.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => out.push('%'),
+            Some(_) => stripped = true,
+            None => {}
+        }
+    }
+
+    if stripped && out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+/// Score how well `query` matches `candidate`, or `None` if it doesn't
+///
+/// Query characters must appear in order (case-insensitive). Matches at the
+/// start of the candidate or of a word, and runs of consecutive characters,
+/// score higher; skipped characters cost a little.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let idx = (pos..candidate.len()).find(|&i| candidate[i] == qc)?;
+
+        score += 1;
+        if idx == 0 {
+            score += 10;
+        } else if !candidate[idx - 1].is_alphanumeric() {
+            score += 8;
+        }
+        if prev.is_some_and(|p| idx == p + 1) {
+            score += 5;
+        }
+        score -= (idx - pos).min(5) as i32;
+
+        prev = Some(idx);
+        pos = idx + 1;
+    }
+
+    Some(score)
+}
+
+/// Filter applications by a query, best matches first
+///
+/// Names are matched first; the executable is a weaker fallback so that e.g.
+/// "code" still finds "Visual Studio Code".
+pub fn search<'a>(apps: &'a [DesktopEntry], query: &str) -> Vec<&'a DesktopEntry> {
+    let mut matches: Vec<(i32, &DesktopEntry)> = apps
+        .iter()
+        .filter_map(|app| {
+            let by_name = fuzzy_score(query, &app.name);
+            let by_exec = fuzzy_score(query, &app.exec).map(|score| score - 10);
+            by_name.max(by_exec).map(|score| (score, app))
+        })
+        .collect();
+
+    // Stable sort keeps alphabetical order among equal scores
+    matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    matches.into_iter().map(|(_, app)| app).collect()
+}
+
+/// Icon names to try for a window's app id, best first
+///
+/// The desktop entry for the app id knows its icon, also for reverse-DNS ids
+/// like "org.gnome.Nautilus" whose icon is named differently. The app id
+/// itself, lowercased and without its reverse-DNS prefix, covers apps without
+/// a matching entry.
+pub fn icon_names(apps: &[DesktopEntry], app_id: &str) -> Vec<String> {
+    let lower = app_id.to_lowercase();
+    let desktop_id = format!("{}.desktop", lower);
+    let entry = apps.iter().find(|app| {
+        let id = app.id.to_lowercase();
+        id == desktop_id || id.ends_with(&format!(".{}", desktop_id))
+    });
+
+    let mut names = Vec::new();
+    let guesses = [
+        app_id.to_string(),
+        lower.clone(),
+        lower.rsplit('.').next().unwrap_or_default().to_string(),
+    ];
+    let from_entry = entry.and_then(|app| app.icon.clone());
+    for name in from_entry.into_iter().chain(guesses) {
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, exec: &str) -> DesktopEntry {
+        DesktopEntry {
+            id: format!("{}.desktop", name.to_lowercase()),
+            name: name.to_string(),
+            exec: exec.to_string(),
+            icon: None,
+            comment: None,
+            terminal: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_desktop_entry() {
+        let contents = "\
+[Desktop Entry]
+Type=Application
+Name=Firefox
+Name[de]=Feuerfuchs
+Comment=Browse the web
+Exec=firefox %u
+Icon=firefox
+
+[Desktop Action new-window]
+Name=New Window
+Exec=firefox --new-window
+";
+        let entry = DesktopEntry::parse("firefox.desktop", contents).unwrap();
+        assert_eq!(entry.name, "Firefox");
+        assert_eq!(entry.exec, "firefox %u");
+        assert_eq!(entry.icon.as_deref(), Some("firefox"));
+        assert_eq!(entry.comment.as_deref(), Some("Browse the web"));
+        assert!(!entry.terminal);
+    }
+
+    #[test]
+    fn test_parse_skips_hidden_entries() {
+        let hidden = "[Desktop Entry]\nType=Application\nName=X\nExec=x\nNoDisplay=true\n";
+        assert!(DesktopEntry::parse("x.desktop", hidden).is_none());
+
+        let link = "[Desktop Entry]\nType=Link\nName=X\nURL=https://example.com\n";
+        assert!(DesktopEntry::parse("x.desktop", link).is_none());
+    }
+
+    #[test]
+    fn test_exec_args() {
+        assert_eq!(exec_args("firefox %u"), vec!["firefox"]);
+        assert_eq!(
+            exec_args(r#""/opt/My App/app" --name "a \"b\"" %F"#),
+            vec!["/opt/My App/app", "--name", "a \"b\""]
+        );
+        assert_eq!(exec_args("printf 100%%"), vec!["printf", "100%"]);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("ff", "Firefox").is_some());
+        assert!(fuzzy_score("xf", "Firefox").is_none());
+        assert_eq!(fuzzy_score("", "Firefox"), Some(0));
+
+        // Prefix and word-start matches beat matches in the middle of a word
+        assert!(fuzzy_score("te", "Terminal") > fuzzy_score("te", "Kate"));
+        assert!(fuzzy_score("vsc", "Visual Studio Code") > fuzzy_score("vsc", "Movies Collection"));
+    }
+
+    #[test]
+    fn test_search_orders_by_score() {
+        let apps = vec![
+            entry("Files", "nautilus"),
+            entry("Firefox", "firefox"),
+            entry("Visual Studio Code", "code"),
+        ];
+
+        let names: Vec<&str> = search(&apps, "fire").iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["Firefox"]);
+
+        let names: Vec<&str> = search(&apps, "fi").iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, vec!["Files", "Firefox"]);
+
+        assert_eq!(search(&apps, "").len(), 3);
+        assert_eq!(search(&apps, "nautilus")[0].name, "Files");
+    }
+
+    #[test]
+    fn test_icon_names() {
+        let mut files = entry("Files", "nautilus");
+        files.id = "org.gnome.Nautilus.desktop".to_string();
+        files.icon = Some("org.gnome.Nautilus".to_string());
+        let mut firefox = entry("Firefox", "firefox");
+        firefox.icon = Some("firefox".to_string());
+        let apps = vec![files, firefox];
+
+        assert_eq!(
+            icon_names(&apps, "org.gnome.Nautilus"),
+            vec!["org.gnome.Nautilus", "org.gnome.nautilus", "nautilus"]
+        );
+        assert_eq!(icon_names(&apps, "firefox"), vec!["firefox"]);
+
+        // No desktop entry: guess from the app id
+        assert_eq!(
+            icon_names(&apps, "Alacritty"),
+            vec!["Alacritty", "alacritty"]
+        );
+        assert_eq!(
+            icon_names(&apps, "com.example.Tool"),
+            vec!["com.example.Tool", "com.example.tool", "tool"]
+        );
+    }
+}
@@ -1,15 +1,26 @@
-use crate::error::{AmiyaError, Result};
+use crate::backend::dbus::{Bus, DbusConnection};
+use crate::backend::retry::{connect_with_backoff, Backoff};
+use crate::error::Result;
 use crate::events::{Event, EventManager};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
-use zbus::Connection;
+
+/// Highest configurable volume in percent
+///
+/// PulseAudio and PipeWire accept software boost past 100%; beyond about 150%
+/// most output just clips.
+pub const MAX_VOLUME_BOOST: u32 = 150;
 
 /// Audio control via PulseAudio/PipeWire
 pub struct AudioControl {
-    connection: Arc<RwLock<Option<Connection>>>,
+    connection: DbusConnection,
     current_volume: Arc<RwLock<f64>>,
     current_mute: Arc<RwLock<bool>>,
+    unmute_on_volume_up: AtomicBool,
+    /// Volume ceiling in percent
+    max_volume: AtomicU32,
     events: Option<EventManager>,
 }
 
@@ -17,9 +28,11 @@ impl AudioControl {
     /// Create a new audio control instance
     pub fn new() -> Self {
         AudioControl {
-            connection: Arc::new(RwLock::new(None)),
+            connection: DbusConnection::new(Bus::Session),
             current_volume: Arc::new(RwLock::new(50.0)),
             current_mute: Arc::new(RwLock::new(false)),
+            unmute_on_volume_up: AtomicBool::new(true),
+            max_volume: AtomicU32::new(100),
             events: None,
         }
     }
@@ -27,47 +40,42 @@ impl AudioControl {
     /// Create with event manager for reactive updates
     pub fn with_events(events: EventManager) -> Self {
         AudioControl {
-            connection: Arc::new(RwLock::new(None)),
+            connection: DbusConnection::new(Bus::Session),
             current_volume: Arc::new(RwLock::new(50.0)),
             current_mute: Arc::new(RwLock::new(false)),
+            unmute_on_volume_up: AtomicBool::new(true),
+            max_volume: AtomicU32::new(100),
             events: Some(events),
         }
     }
 
     /// Initialize connection to audio system
     pub async fn connect(&self) -> Result<()> {
-        match Connection::session().await {
-            Ok(conn) => {
-                info!("Connected to D-Bus session for audio control");
-                let mut connection = self.connection.write().await;
-                *connection = Some(conn);
-
-                // Try to get initial state
-                if let Err(e) = self.update_state().await {
-                    warn!("Failed to get initial audio state: {}", e);
-                }
-
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Could not connect to D-Bus for audio: {}", e);
-                Err(AmiyaError::Backend(format!(
-                    "Failed to connect to D-Bus: {}",
-                    e
-                )))
-            }
+        self.connection.open().await?;
+        info!("Connected to D-Bus session for audio control");
+
+        // Try to get initial state
+        if let Err(e) = self.update_state().await {
+            warn!("Failed to get initial audio state: {}", e);
         }
+
+        Ok(())
+    }
+
+    /// Check if audio control is connected
+    pub fn is_available(&self) -> bool {
+        self.connection.is_connected()
     }
 
-    /// Get current volume level (0-100)
+    /// Get current volume level (0-100, higher when boosted)
     pub async fn get_volume(&self) -> Result<f64> {
         let volume = *self.current_volume.read().await;
         Ok(volume)
     }
 
-    /// Set volume level (0-100)
+    /// Set volume level (0 up to the configured maximum, 100 by default)
     pub async fn set_volume(&self, volume: f64) -> Result<()> {
-        let volume = volume.clamp(0.0, 100.0);
+        let volume = volume.clamp(0.0, self.max_volume() as f64);
 
         // For now, just update local state and emit event
         // In a full implementation, we'd send D-Bus commands to PulseAudio
@@ -91,9 +99,18 @@ impl AudioControl {
     }
 
     /// Increase volume by step
+    ///
+    /// If muted and `unmute_on_volume_up` is set, this also unmutes, with a
+    /// single `VolumeChanged` event for both changes.
     pub async fn increase_volume(&self, step: f64) -> Result<()> {
         let current = self.get_volume().await?;
-        let new_volume = (current + step).min(100.0);
+        let new_volume = current + step;
+
+        if self.unmute_on_volume_up.load(Ordering::Relaxed) && self.get_mute().await? {
+            *self.current_mute.write().await = false;
+            info!("Audio unmuted by volume up");
+        }
+
         self.set_volume(new_volume).await
     }
 
@@ -104,6 +121,24 @@ impl AudioControl {
         self.set_volume(new_volume).await
     }
 
+    /// Volume ceiling in percent
+    pub fn max_volume(&self) -> u32 {
+        self.max_volume.load(Ordering::Relaxed)
+    }
+
+    /// Set the volume ceiling, between 100 and `MAX_VOLUME_BOOST` percent
+    ///
+    /// Lowering it below the current volume doesn't change the volume until
+    /// it is next adjusted.
+    pub fn set_max_volume(&self, percent: u32) {
+        self.max_volume.store(percent.clamp(100, MAX_VOLUME_BOOST), Ordering::Relaxed);
+    }
+
+    /// Whether raising the volume while muted unmutes
+    pub fn set_unmute_on_volume_up(&self, enabled: bool) {
+        self.unmute_on_volume_up.store(enabled, Ordering::Relaxed);
+    }
+
     /// Get mute state
     pub async fn get_mute(&self) -> Result<bool> {
         let muted = *self.current_mute.read().await;
@@ -165,10 +200,14 @@ impl Default for AudioControl {
 pub fn create_audio_control_sync(events: EventManager) -> Arc<AudioControl> {
     let audio = Arc::new(AudioControl::with_events(events));
 
-    // Try to connect in background
+    // Connect in background, retrying while the service starts up
     let audio_clone = audio.clone();
     tokio::spawn(async move {
-        if let Err(e) = audio_clone.connect().await {
+        let result = connect_with_backoff("Audio control", Backoff::default(), || {
+            audio_clone.connect()
+        })
+        .await;
+        if let Err(e) = result {
             warn!("Failed to connect audio control: {}", e);
         }
     });
@@ -179,6 +218,8 @@ pub fn create_audio_control_sync(events: EventManager) -> Arc<AudioControl> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::system::MIN_RELATIVE_STEP;
+    use crate::backend::traits::VolumeBackend;
 
     #[tokio::test]
     async fn test_volume_control() {
@@ -194,6 +235,23 @@ mod tests {
         assert_eq!(audio.get_volume().await.unwrap(), 65.0);
     }
 
+    #[tokio::test]
+    async fn test_volume_relative() {
+        let audio = AudioControl::new();
+
+        audio.set_volume(50.0).await.unwrap();
+        audio.set_volume_relative(10.0).await.unwrap();
+        assert_eq!(audio.get_volume().await.unwrap(), 55.0);
+
+        audio.set_volume_relative(-20.0).await.unwrap();
+        assert_eq!(audio.get_volume().await.unwrap(), 44.0);
+
+        // From zero the minimum step applies
+        audio.set_volume(0.0).await.unwrap();
+        audio.set_volume_relative(10.0).await.unwrap();
+        assert_eq!(audio.get_volume().await.unwrap(), MIN_RELATIVE_STEP);
+    }
+
     #[tokio::test]
     async fn test_mute_control() {
         let audio = AudioControl::new();
@@ -207,6 +265,12 @@ mod tests {
         assert_eq!(audio.get_mute().await.unwrap(), false);
     }
 
+    #[tokio::test]
+    async fn test_unavailable_until_connected() {
+        let audio = AudioControl::new();
+        assert!(!audio.is_available());
+    }
+
     #[tokio::test]
     async fn test_volume_clamping() {
         let audio = AudioControl::new();
@@ -217,4 +281,51 @@ mod tests {
         audio.set_volume(-10.0).await.unwrap();
         assert_eq!(audio.get_volume().await.unwrap(), 0.0);
     }
+
+    #[tokio::test]
+    async fn test_volume_up_unmutes() {
+        let events = EventManager::default();
+        let mut receiver = events.subscribe();
+        let audio = AudioControl::with_events(events);
+
+        audio.set_mute(true).await.unwrap();
+        receiver.recv().await.unwrap();
+
+        audio.increase_volume(5.0).await.unwrap();
+        assert!(!audio.get_mute().await.unwrap());
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Event::VolumeChanged { level, muted: false } if level == 55.0
+        ));
+        assert!(receiver.try_recv().is_err());
+
+        // Volume down leaves mute alone
+        audio.set_mute(true).await.unwrap();
+        audio.decrease_volume(5.0).await.unwrap();
+        assert!(audio.get_mute().await.unwrap());
+
+        // Opted out: stays muted
+        audio.set_unmute_on_volume_up(false);
+        audio.increase_volume(5.0).await.unwrap();
+        assert!(audio.get_mute().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_volume_boost() {
+        let audio = AudioControl::new();
+        audio.set_volume(100.0).await.unwrap();
+        audio.increase_volume(5.0).await.unwrap();
+        assert_eq!(audio.get_volume().await.unwrap(), 100.0);
+
+        audio.set_max_volume(150);
+        audio.increase_volume(5.0).await.unwrap();
+        assert_eq!(audio.get_volume().await.unwrap(), 105.0);
+
+        audio.set_volume(200.0).await.unwrap();
+        assert_eq!(audio.get_volume().await.unwrap(), 150.0);
+
+        // Out-of-range ceilings are clamped
+        audio.set_max_volume(400);
+        assert_eq!(audio.max_volume(), MAX_VOLUME_BOOST);
+    }
 }
@@ -1,33 +1,59 @@
 use crate::error::{AmiyaError, Result};
 use crate::events::{Event, EventManager};
 use std::fs;
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+/// Duration of the fade used for brightness up/down requests
+pub const BRIGHTNESS_TRANSITION_MS: u64 = 150;
+
+/// Interval between steps of a smooth brightness transition (~60 fps)
+const TRANSITION_FRAME_MS: u64 = 16;
+
+/// How often sysfs is checked for changes made outside Amiya
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+
 /// Backlight control via sysfs
 pub struct BacklightControl {
     device_path: Option<PathBuf>,
     current_brightness: Arc<RwLock<f64>>,
     events: Option<EventManager>,
+    /// Bumped by every brightness change so an in-flight transition notices
+    /// it has been superseded and stops
+    transition_generation: AtomicU64,
+    /// Where the in-flight transition is heading, if any
+    transition_target: Mutex<Option<f64>>,
 }
 
 impl BacklightControl {
     /// Create a new backlight control instance
+    ///
+    /// Desktops usually have no backlight device; the control is then
+    /// unavailable and every operation fails with a "no backlight device"
+    /// error instead of pretending to work.
     pub fn new() -> Self {
         let device_path = Self::find_backlight_device();
 
         if device_path.is_none() {
-            warn!("No backlight device found in /sys/class/backlight");
+            info!("No backlight device found in /sys/class/backlight");
         } else {
             info!("Found backlight device: {:?}", device_path);
         }
 
+        Self::with_device(device_path)
+    }
+
+    fn with_device(device_path: Option<PathBuf>) -> Self {
         BacklightControl {
             device_path,
             current_brightness: Arc::new(RwLock::new(50.0)),
             events: None,
+            transition_generation: AtomicU64::new(0),
+            transition_target: Mutex::new(None),
         }
     }
 
@@ -70,20 +96,25 @@ impl BacklightControl {
         self.device_path.is_some()
     }
 
+    /// The backlight device, or the error to return without one
+    fn device(&self) -> Result<&Path> {
+        self.device_path
+            .as_deref()
+            .ok_or_else(|| AmiyaError::Backend("No backlight device".to_string()))
+    }
+
     /// Get current brightness level (0-100)
     pub async fn get_brightness(&self) -> Result<f64> {
         // Try to read from sysfs first
-        if let Some(device) = &self.device_path {
-            match self.read_brightness_from_sysfs(device) {
-                Ok(brightness) => {
-                    // Update cached value
-                    let mut current = self.current_brightness.write().await;
-                    *current = brightness;
-                    return Ok(brightness);
-                }
-                Err(e) => {
-                    debug!("Failed to read brightness from sysfs: {}", e);
-                }
+        match self.read_brightness_from_sysfs(self.device()?) {
+            Ok(brightness) => {
+                // Update cached value
+                let mut current = self.current_brightness.write().await;
+                *current = brightness;
+                return Ok(brightness);
+            }
+            Err(e) => {
+                debug!("Failed to read brightness from sysfs: {}", e);
             }
         }
 
@@ -93,7 +124,7 @@ impl BacklightControl {
     }
 
     /// Read brightness directly from sysfs
-    fn read_brightness_from_sysfs(&self, device: &PathBuf) -> Result<f64> {
+    fn read_brightness_from_sysfs(&self, device: &Path) -> Result<f64> {
         let current = fs::read_to_string(device.join("brightness"))
             .map_err(|e| AmiyaError::Backend(format!("Failed to read brightness: {}", e)))?
             .trim()
@@ -113,43 +144,198 @@ impl BacklightControl {
         Ok((current / max) * 100.0)
     }
 
-    /// Set brightness level (0-100)
+    /// Set brightness level (0-100) immediately
+    ///
+    /// Cancels any smooth transition that is still running.
     pub async fn set_brightness(&self, brightness: f64) -> Result<()> {
+        self.device()?;
         let brightness = brightness.clamp(0.0, 100.0);
+        let generation = self.begin_transition(None);
 
-        // Update cached value
-        {
-            let mut current = self.current_brightness.write().await;
-            *current = brightness;
+        if let Err(e) = self.apply_brightness(brightness, generation).await {
+            warn!("Failed to set brightness via sysfs: {}. You may need to configure udev rules.", e);
+            // Don't return error - we still updated cached value and emitted the event
+        } else {
+            info!("Brightness set to {:.1}%", brightness);
         }
 
-        // Try to write to sysfs
-        if let Some(device) = &self.device_path {
-            if let Err(e) = self.write_brightness_to_sysfs(device, brightness) {
-                warn!("Failed to set brightness via sysfs: {}. You may need to configure udev rules.", e);
-                // Don't return error - we still updated cached value and will emit event
-            } else {
-                info!("Brightness set to {:.1}%", brightness);
+        Ok(())
+    }
+
+    /// Fade brightness to `target` (0-100) over `duration_ms`
+    ///
+    /// The raw sysfs value is stepped on a timer, at most one raw unit per
+    /// step. A later call to this or `set_brightness` cancels the fade; the
+    /// superseded call returns `Ok` at whatever level it reached.
+    pub async fn set_brightness_smooth(&self, target: f64, duration_ms: u64) -> Result<()> {
+        self.device()?;
+        let target = target.clamp(0.0, 100.0);
+        let generation = self.begin_transition(Some(target));
+
+        let start = self.get_brightness().await?;
+        let steps = self.transition_steps(start, target, duration_ms);
+        let interval = Duration::from_millis(duration_ms / steps);
+
+        for step in 1..=steps {
+            let level = start + (target - start) * step as f64 / steps as f64;
+            match self.apply_brightness(level, generation).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    debug!("Brightness transition to {:.1}% superseded", target);
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!("Failed to set brightness via sysfs: {}. You may need to configure udev rules.", e);
+                    // Stepping would just repeat the failure; settle on the target
+                    self.apply_brightness(target, generation).await.ok();
+                    break;
+                }
             }
-        } else {
-            debug!("No backlight device available, using mock brightness: {:.1}%", brightness);
+
+            if step < steps {
+                tokio::time::sleep(interval).await;
+            }
+        }
+
+        // Only clear the target if no newer transition has taken over
+        if self.transition_generation.load(Ordering::SeqCst) == generation {
+            *self.transition_target.lock().unwrap() = None;
+            info!("Brightness faded to {:.1}%", target);
+        }
+
+        Ok(())
+    }
+
+    /// Cancel any in-flight transition and record the new target
+    fn begin_transition(&self, target: Option<f64>) -> u64 {
+        let mut current_target = self.transition_target.lock().unwrap();
+        *current_target = target;
+        self.transition_generation.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    /// Target of the in-flight transition, or the current brightness
+    ///
+    /// Relative changes start from here so repeated key presses during a fade
+    /// add up instead of restarting from an intermediate level.
+    pub async fn pending_brightness(&self) -> Result<f64> {
+        let target = *self.transition_target.lock().unwrap();
+        match target {
+            Some(target) => Ok(target),
+            None => self.get_brightness().await,
+        }
+    }
+
+    /// Number of steps for a transition: one per frame, but never more than
+    /// the number of raw values between start and target
+    fn transition_steps(&self, start: f64, target: f64, duration_ms: u64) -> u64 {
+        let frames = duration_ms / TRANSITION_FRAME_MS;
+
+        let raw_distance = self
+            .device_path
+            .as_deref()
+            .and_then(|device| Self::read_max_brightness(device).ok())
+            .map(|max| ((target - start).abs() / 100.0 * max).round() as u64);
+
+        match raw_distance {
+            Some(distance) => frames.min(distance).max(1),
+            None => frames.max(1),
+        }
+    }
+
+    /// Update the cached value, write it to sysfs and emit the change
+    ///
+    /// Returns `Ok(false)` without touching anything if a newer change has
+    /// superseded `generation`, and the sysfs write error, if any, after the
+    /// cache and event have been updated.
+    async fn apply_brightness(&self, brightness: f64, generation: u64) -> Result<bool> {
+        // Held across the write so a superseded step can't land after a newer one
+        let mut current = self.current_brightness.write().await;
+        if self.transition_generation.load(Ordering::SeqCst) != generation {
+            return Ok(false);
         }
 
+        // Update cached value
+        *current = brightness;
+
+        // Try to write to sysfs
+        let result = Self::write_brightness_to_sysfs(self.device()?, brightness);
+
         // Emit event
         if let Some(events) = &self.events {
             events.emit(Event::BrightnessChanged { level: brightness });
         }
 
+        result.map(|()| true)
+    }
+
+    /// Watch sysfs for changes made outside Amiya and emit them
+    ///
+    /// Brightness keys are often handled by the firmware or a tool like
+    /// `brightnessctl`, which write sysfs directly. sysfs attributes don't
+    /// reliably support inotify, so the raw value is polled instead.
+    pub async fn watch(&self) {
+        let Some(device) = self.device_path.clone() else {
+            return;
+        };
+
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check_external_change(&device).await {
+                debug!("Failed to check brightness: {}", e);
+            }
+        }
+    }
+
+    /// Emit `BrightnessChanged` if sysfs no longer holds the value Amiya last
+    /// wrote or read
+    async fn check_external_change(&self, device: &Path) -> Result<()> {
+        // Held while reading so a write from Amiya can't land in between
+        let mut current = self.current_brightness.write().await;
+        if self.transition_target.lock().unwrap().is_some() {
+            // Our own fade is stepping through values
+            return Ok(());
+        }
+
+        let max = Self::read_max_brightness(device)?;
+        let raw = fs::read_to_string(device.join("brightness"))
+            .map_err(|e| AmiyaError::Backend(format!("Failed to read brightness: {}", e)))?
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| AmiyaError::Backend(format!("Failed to parse brightness: {}", e)))?;
+
+        // Amiya's own writes round the cached percentage to a raw value, so
+        // comparing raw values ignores them
+        if !Self::is_external_change(*current, raw, max) {
+            return Ok(());
+        }
+
+        let level = raw / max * 100.0;
+        debug!("Brightness changed externally to {:.1}%", level);
+        *current = level;
+        if let Some(events) = &self.events {
+            events.emit(Event::BrightnessChanged { level });
+        }
         Ok(())
     }
 
-    /// Write brightness to sysfs
-    fn write_brightness_to_sysfs(&self, device: &PathBuf, percent: f64) -> Result<()> {
-        let max = fs::read_to_string(device.join("max_brightness"))
+    /// Whether the raw sysfs value differs from what writing `cached` gives
+    fn is_external_change(cached: f64, raw: f64, max: f64) -> bool {
+        max > 0.0 && ((cached / 100.0) * max).round() != raw
+    }
+
+    /// Read the device's maximum raw brightness
+    fn read_max_brightness(device: &Path) -> Result<f64> {
+        fs::read_to_string(device.join("max_brightness"))
             .map_err(|e| AmiyaError::Backend(format!("Failed to read max_brightness: {}", e)))?
             .trim()
             .parse::<f64>()
-            .map_err(|e| AmiyaError::Backend(format!("Failed to parse max_brightness: {}", e)))?;
+            .map_err(|e| AmiyaError::Backend(format!("Failed to parse max_brightness: {}", e)))
+    }
+
+    /// Write brightness to sysfs
+    fn write_brightness_to_sysfs(device: &Path, percent: f64) -> Result<()> {
+        let max = Self::read_max_brightness(device)?;
 
         let value = ((percent / 100.0) * max).round() as u32;
 
@@ -193,12 +379,13 @@ impl Default for BacklightControl {
 pub fn create_backlight_control_sync(events: EventManager) -> Arc<BacklightControl> {
     let backlight = Arc::new(BacklightControl::with_events(events));
 
-    // Try to initialize in background
+    // Try to initialize in background, then follow changes made elsewhere
     let backlight_clone = backlight.clone();
     tokio::spawn(async move {
         if let Err(e) = backlight_clone.initialize().await {
             debug!("Failed to initialize backlight: {}", e);
         }
+        backlight_clone.watch().await;
     });
 
     backlight
@@ -207,10 +394,46 @@ pub fn create_backlight_control_sync(events: EventManager) -> Arc<BacklightContr
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::system::MIN_RELATIVE_STEP;
+    use crate::backend::traits::BrightnessBackend;
+
+    /// Fake sysfs backlight device with a raw range of 0-1000
+    fn test_device(name: &str) -> PathBuf {
+        let device = std::env::temp_dir().join(format!(
+            "amiya-backlight-{}-{}",
+            std::process::id(),
+            name
+        ));
+        fs::create_dir_all(&device).unwrap();
+        fs::write(device.join("max_brightness"), "1000\n").unwrap();
+        fs::write(device.join("brightness"), "500\n").unwrap();
+        device
+    }
+
+    fn test_backlight(name: &str) -> BacklightControl {
+        BacklightControl::with_device(Some(test_device(name)))
+    }
+
+    #[tokio::test]
+    async fn test_no_backlight_device() {
+        let events = EventManager::new(10);
+        let mut receiver = events.subscribe();
+        let mut backlight = BacklightControl::with_device(None);
+        backlight.events = Some(events);
+        assert!(!backlight.is_available());
+
+        let err = backlight.set_brightness(50.0).await.unwrap_err();
+        assert!(err.to_string().contains("No backlight device"), "{}", err);
+        assert!(backlight.get_brightness().await.is_err());
+        assert!(backlight.increase_brightness_smooth(10.0, 0).await.is_err());
+
+        // Nothing to show a slider for
+        assert!(receiver.try_recv().is_err());
+    }
 
     #[tokio::test]
     async fn test_brightness_control() {
-        let backlight = BacklightControl::new();
+        let backlight = test_backlight("control");
 
         backlight.set_brightness(75.0).await.unwrap();
         assert_eq!(backlight.get_brightness().await.unwrap(), 75.0);
@@ -222,9 +445,41 @@ mod tests {
         assert_eq!(backlight.get_brightness().await.unwrap(), 65.0);
     }
 
+    #[tokio::test]
+    async fn test_brightness_relative() {
+        let backlight = test_backlight("relative");
+
+        backlight.set_brightness(40.0).await.unwrap();
+        backlight
+            .set_brightness_relative_smooth(50.0, 0)
+            .await
+            .unwrap();
+        assert_eq!(backlight.get_brightness().await.unwrap(), 60.0);
+
+        backlight
+            .set_brightness_relative_smooth(-25.0, 0)
+            .await
+            .unwrap();
+        assert_eq!(backlight.get_brightness().await.unwrap(), 45.0);
+
+        backlight.set_brightness(0.0).await.unwrap();
+        backlight
+            .set_brightness_relative_smooth(10.0, 0)
+            .await
+            .unwrap();
+        assert_eq!(backlight.get_brightness().await.unwrap(), MIN_RELATIVE_STEP);
+
+        backlight.set_brightness(0.5).await.unwrap();
+        backlight
+            .set_brightness_relative_smooth(-10.0, 0)
+            .await
+            .unwrap();
+        assert_eq!(backlight.get_brightness().await.unwrap(), 0.0);
+    }
+
     #[tokio::test]
     async fn test_brightness_clamping() {
-        let backlight = BacklightControl::new();
+        let backlight = test_backlight("clamping");
 
         backlight.set_brightness(150.0).await.unwrap();
         assert_eq!(backlight.get_brightness().await.unwrap(), 100.0);
@@ -235,7 +490,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_brightness_bounds() {
-        let backlight = BacklightControl::new();
+        let backlight = test_backlight("bounds");
 
         backlight.set_brightness(95.0).await.unwrap();
         backlight.increase_brightness(10.0).await.unwrap();
@@ -245,4 +500,41 @@ mod tests {
         backlight.decrease_brightness(10.0).await.unwrap();
         assert_eq!(backlight.get_brightness().await.unwrap(), 0.0);
     }
+
+    #[test]
+    fn test_external_change_detection() {
+        // 75% of 937 is written as 703, which reads back as 75.03%
+        assert!(!BacklightControl::is_external_change(75.0, 703.0, 937.0));
+        assert!(BacklightControl::is_external_change(75.0, 656.0, 937.0));
+        assert!(BacklightControl::is_external_change(75.0, 702.0, 937.0));
+        assert!(!BacklightControl::is_external_change(75.0, 0.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn test_brightness_smooth() {
+        let backlight = test_backlight("smooth");
+
+        backlight.set_brightness(20.0).await.unwrap();
+        backlight.set_brightness_smooth(60.0, 50).await.unwrap();
+        assert_eq!(backlight.get_brightness().await.unwrap(), 60.0);
+
+        backlight.decrease_brightness_smooth(10.0, 0).await.unwrap();
+        assert_eq!(backlight.get_brightness().await.unwrap(), 50.0);
+    }
+
+    #[tokio::test]
+    async fn test_brightness_smooth_cancelled() {
+        let backlight = Arc::new(test_backlight("cancelled"));
+        backlight.set_brightness(0.0).await.unwrap();
+
+        let fading = backlight.clone();
+        let fade = tokio::spawn(async move { fading.set_brightness_smooth(100.0, 1000).await });
+
+        // Relative changes during a fade build on its target
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        backlight.decrease_brightness_smooth(30.0, 0).await.unwrap();
+
+        fade.await.unwrap().unwrap();
+        assert_eq!(backlight.get_brightness().await.unwrap(), 70.0);
+    }
 }
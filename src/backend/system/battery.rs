@@ -1,5 +1,7 @@
+use crate::backend::dbus::{Bus, DbusConnection};
 use crate::error::{AmiyaError, Result};
 use crate::events::{Event, EventManager};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
@@ -63,20 +65,26 @@ impl Default for BatteryInfo {
 
 /// Battery control via UPower D-Bus
 pub struct BatteryControl {
-    connection: Arc<RwLock<Option<Connection>>>,
+    connection: DbusConnection,
     device_path: Arc<RwLock<Option<String>>>,
     info: Arc<RwLock<BatteryInfo>>,
     events: Option<EventManager>,
+    /// Percentage at or below which a discharging battery is critical
+    critical_percent: AtomicU32,
+    /// Whether `BatteryCritical` was sent for the current discharge
+    critical: AtomicBool,
 }
 
 impl BatteryControl {
     /// Create a new battery control instance
     pub fn new() -> Self {
         BatteryControl {
-            connection: Arc::new(RwLock::new(None)),
+            connection: DbusConnection::new(Bus::System),
             device_path: Arc::new(RwLock::new(None)),
             info: Arc::new(RwLock::new(BatteryInfo::default())),
             events: None,
+            critical_percent: AtomicU32::new(crate::config::default_critical_percent()),
+            critical: AtomicBool::new(false),
         }
     }
 
@@ -87,38 +95,42 @@ impl BatteryControl {
         battery
     }
 
+    /// Set the percentage at or below which the battery is critical
+    pub fn set_critical_percent(&self, percent: u32) {
+        self.critical_percent.store(percent, Ordering::Relaxed);
+    }
+
     /// Initialize connection to UPower
     pub async fn connect(&self) -> Result<()> {
-        match Connection::system().await {
-            Ok(conn) => {
-                info!("Connected to D-Bus system bus for Battery control");
-
-                // Store connection
-                {
-                    let mut connection = self.connection.write().await;
-                    *connection = Some(conn.clone());
-                }
+        self.reconnect().await?;
 
-                // Find battery device
-                if let Err(e) = self.find_battery_device(&conn).await {
-                    warn!("Failed to find battery device: {}", e);
-                    return Err(e);
-                }
+        // Update battery info
+        if let Err(e) = self.update_battery_info().await {
+            warn!("Failed to update battery info: {}", e);
+        }
 
-                // Update battery info
-                if let Err(e) = self.update_battery_info().await {
-                    warn!("Failed to update battery info: {}", e);
-                }
+        Ok(())
+    }
 
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Could not connect to D-Bus system bus: {}", e);
-                Err(AmiyaError::Backend(format!(
-                    "Failed to connect to D-Bus: {}",
-                    e
-                )))
-            }
+    /// Open a new connection and find the battery device again
+    async fn reconnect(&self) -> Result<Connection> {
+        let conn = self.connection.open().await?;
+        info!("Connected to D-Bus system bus for Battery control");
+
+        // Find battery device
+        if let Err(e) = self.find_battery_device(&conn).await {
+            warn!("Failed to find battery device: {}", e);
+            return Err(e);
+        }
+
+        Ok(conn)
+    }
+
+    /// Connection for the next call, reconnecting if it was lost
+    async fn connection(&self) -> Result<Connection> {
+        match self.connection.cached().await {
+            Some(conn) => Ok(conn),
+            None => self.reconnect().await,
         }
     }
 
@@ -136,7 +148,7 @@ impl BatteryControl {
         let devices: Vec<zbus::zvariant::OwnedObjectPath> = upower_proxy
             .call_method("EnumerateDevices", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to enumerate devices: {}", e)))?
+            .map_err(|e| self.connection.error("Failed to enumerate devices", e))?
             .body()
             .deserialize()
             .map_err(|e| AmiyaError::Backend(format!("Failed to deserialize devices: {}", e)))?;
@@ -177,17 +189,14 @@ impl BatteryControl {
 
     /// Update battery information from UPower
     async fn update_battery_info(&self) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+        let conn = self.connection().await?;
 
         let device_path_guard = self.device_path.read().await;
         let device_path = device_path_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No battery device found".to_string()))?;
 
-        let device_proxy = zbus::ProxyBuilder::new(conn)
+        let device_proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.freedesktop.UPower.Device")
             .path(device_path.as_str())?
             .destination("org.freedesktop.UPower")?
@@ -199,7 +208,7 @@ impl BatteryControl {
         let percentage: f64 = device_proxy
             .get_property("Percentage")
             .await
-            .unwrap_or(0.0);
+            .map_err(|e| self.connection.error("Failed to read battery", e))?;
 
         let state_raw: u32 = device_proxy.get_property("State").await.unwrap_or(0);
         let state = BatteryState::from(state_raw);
@@ -246,11 +255,28 @@ impl BatteryControl {
                 state: state.to_string(),
                 is_charging: matches!(state, BatteryState::Charging),
             });
+
+            let threshold = self.critical_percent.load(Ordering::Relaxed);
+            if Self::is_critical(&new_info, threshold) {
+                if !self.critical.swap(true, Ordering::Relaxed) {
+                    warn!("Battery critical: {}%", percentage);
+                    events.emit(Event::BatteryCritical { percentage });
+                }
+            } else {
+                self.critical.store(false, Ordering::Relaxed);
+            }
         }
 
         Ok(())
     }
 
+    /// Whether the battery is discharging at or below `threshold` percent
+    fn is_critical(info: &BatteryInfo, threshold: u32) -> bool {
+        info.is_present
+            && info.state == BatteryState::Discharging
+            && info.percentage <= threshold as f64
+    }
+
     /// Get current battery information
     pub async fn get_info(&self) -> BatteryInfo {
         // Update from D-Bus
@@ -327,3 +353,32 @@ pub fn create_battery_control_sync(events: EventManager) -> Arc<BatteryControl>
 
     battery
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_critical() {
+        let info = BatteryInfo {
+            percentage: 4.0,
+            state: BatteryState::Discharging,
+            is_present: true,
+            ..BatteryInfo::default()
+        };
+        assert!(BatteryControl::is_critical(&info, 5));
+        assert!(!BatteryControl::is_critical(&info, 3));
+
+        let charging = BatteryInfo {
+            state: BatteryState::Charging,
+            ..info.clone()
+        };
+        assert!(!BatteryControl::is_critical(&charging, 5));
+
+        let absent = BatteryInfo {
+            is_present: false,
+            ..info
+        };
+        assert!(!BatteryControl::is_critical(&absent, 5));
+    }
+}
@@ -1,18 +1,28 @@
+use crate::backend::dbus::{Bus, DbusConnection};
+use crate::backend::retry::{connect_with_backoff, Backoff};
 use crate::error::{AmiyaError, Result};
 use crate::events::{BluetoothDeviceInfo, Event, EventManager};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue};
 use zbus::Connection;
 
+/// Interfaces and their properties for each object, as returned by
+/// `ObjectManager.GetManagedObjects`
+type ManagedObjects = HashMap<OwnedObjectPath, HashMap<String, HashMap<String, OwnedValue>>>;
+
 // Re-export for convenience
 pub use crate::events::BluetoothDeviceInfo as BluetoothDevice;
 
 /// Bluetooth control via BlueZ D-Bus
 pub struct BluetoothControl {
-    connection: Arc<RwLock<Option<Connection>>>,
+    connection: DbusConnection,
     adapter_path: Arc<RwLock<Option<String>>>,
+    /// Whether `adapter_path` is set, readable without waiting on the lock
+    has_adapter: AtomicBool,
     devices: Arc<RwLock<HashMap<String, BluetoothDevice>>>,
     powered: Arc<RwLock<bool>>,
     scanning: Arc<RwLock<bool>>,
@@ -23,8 +33,9 @@ impl BluetoothControl {
     /// Create a new bluetooth control instance
     pub fn new() -> Self {
         BluetoothControl {
-            connection: Arc::new(RwLock::new(None)),
+            connection: DbusConnection::new(Bus::System),
             adapter_path: Arc::new(RwLock::new(None)),
+            has_adapter: AtomicBool::new(false),
             devices: Arc::new(RwLock::new(HashMap::new())),
             powered: Arc::new(RwLock::new(false)),
             scanning: Arc::new(RwLock::new(false)),
@@ -41,51 +52,52 @@ impl BluetoothControl {
 
     /// Initialize connection to BlueZ
     pub async fn connect(&self) -> Result<()> {
-        match Connection::system().await {
-            Ok(conn) => {
-                info!("Connected to D-Bus system bus for Bluetooth control");
-
-                // Store connection
-                {
-                    let mut connection = self.connection.write().await;
-                    *connection = Some(conn.clone());
-                }
+        self.reconnect().await?;
+        Ok(())
+    }
 
-                // Find default adapter
-                match self.find_adapter(&conn).await {
-                    Ok(path) => {
-                        info!("Found Bluetooth adapter: {}", path);
-                        let mut adapter_path = self.adapter_path.write().await;
-                        *adapter_path = Some(path.clone());
-
-                        // Get initial state
-                        if let Err(e) = self.update_adapter_state(&conn, &path).await {
-                            warn!("Failed to get initial adapter state: {}", e);
-                        }
-
-                        // Get initial device list
-                        if let Err(e) = self.update_device_list(&conn).await {
-                            warn!("Failed to get initial device list: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        warn!("No Bluetooth adapter found: {}", e);
-                        return Err(AmiyaError::Backend(
-                            "No Bluetooth adapter available".to_string(),
-                        ));
-                    }
+    /// Open a new connection and find the adapter and devices again
+    async fn reconnect(&self) -> Result<Connection> {
+        let conn = self.connection.open().await?;
+        info!("Connected to D-Bus system bus for Bluetooth control");
+
+        // Find default adapter
+        match self.find_adapter(&conn).await {
+            Ok(path) => {
+                info!("Found Bluetooth adapter: {}", path);
+                *self.adapter_path.write().await = Some(path.clone());
+                self.has_adapter.store(true, Ordering::Relaxed);
+
+                // Get initial state
+                if let Err(e) = self.update_adapter_state(&conn, &path).await {
+                    warn!("Failed to get initial adapter state: {}", e);
                 }
 
-                Ok(())
+                // Get initial device list
+                if let Err(e) = self.update_device_list(&conn).await {
+                    warn!("Failed to get initial device list: {}", e);
+                }
             }
             Err(e) => {
-                warn!("Could not connect to D-Bus system bus: {}", e);
-                Err(AmiyaError::Backend(format!(
-                    "Failed to connect to D-Bus: {}",
-                    e
-                )))
+                warn!("No Bluetooth adapter found: {}", e);
+                *self.adapter_path.write().await = None;
+                self.has_adapter.store(false, Ordering::Relaxed);
+                return Err(AmiyaError::Backend(
+                    "No Bluetooth adapter available".to_string(),
+                ));
             }
         }
+
+        Ok(conn)
+    }
+
+    /// Connection for the next call, reconnecting if it was lost or BlueZ
+    /// restarted
+    async fn connection(&self) -> Result<Connection> {
+        match self.connection.cached().await {
+            Some(conn) => Ok(conn),
+            None => self.reconnect().await,
+        }
     }
 
     /// Find the default Bluetooth adapter
@@ -153,11 +165,42 @@ impl BluetoothControl {
 
     /// Update device list from BlueZ
     async fn update_device_list(&self, conn: &Connection) -> Result<()> {
-        // In a full implementation, we'd use ObjectManager to enumerate devices
-        // For now, just clear and emit event
-
-        let devices = self.devices.read().await;
-        let device_list: Vec<BluetoothDevice> = devices.values().cloned().collect();
+        let objects: ManagedObjects = conn
+            .call_method(
+                Some("org.bluez"),
+                "/",
+                Some("org.freedesktop.DBus.ObjectManager"),
+                "GetManagedObjects",
+                &(),
+            )
+            .await
+            .map_err(|e| self.connection.error("Failed to list devices", e))?
+            .body()
+            .deserialize()
+            .map_err(|e| AmiyaError::Backend(format!("Failed to parse device list: {}", e)))?;
+
+        // Audio transports point back at their device's object path
+        let codecs: HashMap<String, String> = objects
+            .values()
+            .filter_map(|interfaces| interfaces.get("org.bluez.MediaTransport1"))
+            .filter_map(parse_transport)
+            .collect();
+
+        let device_list: Vec<BluetoothDevice> = {
+            let mut devices = self.devices.write().await;
+            *devices = objects
+                .iter()
+                .filter_map(|(path, interfaces)| {
+                    let mut device = parse_device(interfaces.get("org.bluez.Device1")?)?;
+                    if device.connected {
+                        device.codec = codecs.get(path.as_str()).cloned();
+                    }
+                    Some((device.address.clone(), device))
+                })
+                .collect();
+            devices.values().cloned().collect()
+        };
+        debug!("Found {} Bluetooth devices", device_list.len());
 
         if let Some(events) = &self.events {
             events.emit(Event::BluetoothDevicesUpdated {
@@ -171,13 +214,7 @@ impl BluetoothControl {
     /// Check if Bluetooth is available
     pub fn is_available(&self) -> bool {
         // Check if we have a connection and adapter
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let conn = self.connection.read().await;
-                let adapter = self.adapter_path.read().await;
-                conn.is_some() && adapter.is_some()
-            })
-        })
+        self.connection.is_connected() && self.has_adapter.load(Ordering::Relaxed)
     }
 
     /// Check if Bluetooth is powered on
@@ -188,18 +225,14 @@ impl BluetoothControl {
 
     /// Set Bluetooth powered state
     pub async fn set_powered(&self, enabled: bool) -> Result<()> {
-        let conn_guard = self.connection.read().await;
+        let conn = self.connection().await?;
         let adapter_guard = self.adapter_path.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let adapter_path = adapter_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No adapter available".to_string()))?;
 
-        let proxy = zbus::ProxyBuilder::new(conn)
+        let proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.bluez.Adapter1")
             .path(adapter_path.as_str())?
             .destination("org.bluez")?
@@ -210,7 +243,7 @@ impl BluetoothControl {
         proxy
             .set_property("Powered", enabled)
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to set powered: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to set powered", e))?;
 
         // Update local state
         {
@@ -230,18 +263,14 @@ impl BluetoothControl {
 
     /// Start device discovery/scanning
     pub async fn start_scan(&self) -> Result<()> {
-        let conn_guard = self.connection.read().await;
+        let conn = self.connection().await?;
         let adapter_guard = self.adapter_path.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let adapter_path = adapter_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No adapter available".to_string()))?;
 
-        let proxy = zbus::ProxyBuilder::new(conn)
+        let proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.bluez.Adapter1")
             .path(adapter_path.as_str())?
             .destination("org.bluez")?
@@ -252,7 +281,7 @@ impl BluetoothControl {
         proxy
             .call_method("StartDiscovery", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to start discovery: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to start discovery", e))?;
 
         {
             let mut scanning = self.scanning.write().await;
@@ -266,18 +295,14 @@ impl BluetoothControl {
 
     /// Stop device discovery/scanning
     pub async fn stop_scan(&self) -> Result<()> {
-        let conn_guard = self.connection.read().await;
+        let conn = self.connection().await?;
         let adapter_guard = self.adapter_path.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let adapter_path = adapter_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No adapter available".to_string()))?;
 
-        let proxy = zbus::ProxyBuilder::new(conn)
+        let proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.bluez.Adapter1")
             .path(adapter_path.as_str())?
             .destination("org.bluez")?
@@ -288,7 +313,7 @@ impl BluetoothControl {
         proxy
             .call_method("StopDiscovery", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to stop discovery: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to stop discovery", e))?;
 
         {
             let mut scanning = self.scanning.write().await;
@@ -308,11 +333,7 @@ impl BluetoothControl {
 
     /// Connect to a device by address
     pub async fn connect_device(&self, address: &str) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+        let conn = self.connection().await?;
 
         // Convert address to object path (e.g., AA:BB:CC:DD:EE:FF -> /org/bluez/hci0/dev_AA_BB_CC_DD_EE_FF)
         let device_path = format!(
@@ -320,7 +341,7 @@ impl BluetoothControl {
             address.replace(':', "_")
         );
 
-        let proxy = zbus::ProxyBuilder::new(conn)
+        let proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.bluez.Device1")
             .path(device_path.as_str())?
             .destination("org.bluez")?
@@ -331,30 +352,35 @@ impl BluetoothControl {
         proxy
             .call_method("Connect", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to connect device: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to connect device", e))?;
 
         info!("Connected to Bluetooth device: {}", address);
 
-        // Update device state
-        self.update_device_list(conn).await?;
+        // Update device state; the connection itself already succeeded
+        if let Err(e) = self.update_device_list(&conn).await {
+            warn!("Failed to refresh Bluetooth devices: {}", e);
+        }
+
+        if let Some(events) = &self.events {
+            events.emit(Event::BluetoothDeviceConnected {
+                address: address.to_string(),
+                name: self.device_name(address).await,
+            });
+        }
 
         Ok(())
     }
 
     /// Disconnect from a device by address
     pub async fn disconnect_device(&self, address: &str) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+        let conn = self.connection().await?;
 
         let device_path = format!(
             "/org/bluez/hci0/dev_{}",
             address.replace(':', "_")
         );
 
-        let proxy = zbus::ProxyBuilder::new(conn)
+        let proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.bluez.Device1")
             .path(device_path.as_str())?
             .destination("org.bluez")?
@@ -365,30 +391,44 @@ impl BluetoothControl {
         proxy
             .call_method("Disconnect", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to disconnect device: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to disconnect device", e))?;
 
         info!("Disconnected from Bluetooth device: {}", address);
 
-        // Update device state
-        self.update_device_list(conn).await?;
+        // Update device state; the disconnect itself already succeeded
+        if let Err(e) = self.update_device_list(&conn).await {
+            warn!("Failed to refresh Bluetooth devices: {}", e);
+        }
+
+        if let Some(events) = &self.events {
+            events.emit(Event::BluetoothDeviceDisconnected {
+                address: address.to_string(),
+            });
+        }
 
         Ok(())
     }
 
+    /// Display name of a known device, or its address if it has none
+    async fn device_name(&self, address: &str) -> String {
+        self.devices
+            .read()
+            .await
+            .get(address)
+            .map(|device| device.name.clone())
+            .unwrap_or_else(|| address.to_string())
+    }
+
     /// Pair with a device by address
     pub async fn pair_device(&self, address: &str) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+        let conn = self.connection().await?;
 
         let device_path = format!(
             "/org/bluez/hci0/dev_{}",
             address.replace(':', "_")
         );
 
-        let proxy = zbus::ProxyBuilder::new(conn)
+        let proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.bluez.Device1")
             .path(device_path.as_str())?
             .destination("org.bluez")?
@@ -399,25 +439,58 @@ impl BluetoothControl {
         proxy
             .call_method("Pair", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to pair device: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to pair device", e))?;
 
         info!("Paired with Bluetooth device: {}", address);
 
         // Update device state
-        self.update_device_list(conn).await?;
+        self.update_device_list(&conn).await?;
+
+        Ok(())
+    }
+
+    /// Mark a device as trusted (or not) so it can reconnect by itself
+    pub async fn set_trusted(&self, address: &str, trusted: bool) -> Result<()> {
+        let conn = self.connection().await?;
+
+        let device_path = format!(
+            "/org/bluez/hci0/dev_{}",
+            address.replace(':', "_")
+        );
+
+        let proxy = zbus::ProxyBuilder::new(&conn)
+            .interface("org.bluez.Device1")
+            .path(device_path.as_str())?
+            .destination("org.bluez")?
+            .build::<zbus::Proxy>()
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to create device proxy: {}", e)))?;
+
+        proxy
+            .set_property("Trusted", trusted)
+            .await
+            .map_err(|e| self.connection.error("Failed to set trusted", e))?;
+
+        {
+            let mut devices = self.devices.write().await;
+            if let Some(device) = devices.get_mut(address) {
+                device.trusted = trusted;
+            }
+        }
+
+        info!("Bluetooth device {} trusted: {}", address, trusted);
+
+        // Update device state
+        self.update_device_list(&conn).await?;
 
         Ok(())
     }
 
     /// Remove/unpair a device by address
     pub async fn remove_device(&self, address: &str) -> Result<()> {
-        let conn_guard = self.connection.read().await;
+        let conn = self.connection().await?;
         let adapter_guard = self.adapter_path.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let adapter_path = adapter_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No adapter available".to_string()))?;
@@ -427,7 +500,7 @@ impl BluetoothControl {
             address.replace(':', "_")
         );
 
-        let proxy = zbus::ProxyBuilder::new(conn)
+        let proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.bluez.Adapter1")
             .path(adapter_path.as_str())?
             .destination("org.bluez")?
@@ -438,12 +511,12 @@ impl BluetoothControl {
         proxy
             .call_method("RemoveDevice", &(device_path,))
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to remove device: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to remove device", e))?;
 
         info!("Removed Bluetooth device: {}", address);
 
         // Update device state
-        self.update_device_list(conn).await?;
+        self.update_device_list(&conn).await?;
 
         Ok(())
     }
@@ -460,6 +533,88 @@ impl BluetoothControl {
     }
 }
 
+/// Build a device from its `org.bluez.Device1` properties
+///
+/// Devices without an address are skipped; unnamed ones show the address.
+fn parse_device(props: &HashMap<String, OwnedValue>) -> Option<BluetoothDevice> {
+    let string_prop = |name: &str| {
+        props
+            .get(name)
+            .and_then(|v| v.downcast_ref::<&str>().ok())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let bool_prop = |name: &str| {
+        props
+            .get(name)
+            .and_then(|v| v.downcast_ref::<bool>().ok())
+            .unwrap_or(false)
+    };
+
+    let address = string_prop("Address")?;
+    Some(BluetoothDevice {
+        // Alias is the user-set name and falls back to Name inside BlueZ
+        name: string_prop("Alias")
+            .or_else(|| string_prop("Name"))
+            .unwrap_or_else(|| address.clone()),
+        address,
+        connected: bool_prop("Connected"),
+        paired: bool_prop("Paired"),
+        trusted: bool_prop("Trusted"),
+        icon: string_prop("Icon"),
+        codec: None,
+    })
+}
+
+/// Device object path and codec name from `org.bluez.MediaTransport1`
+/// properties
+fn parse_transport(props: &HashMap<String, OwnedValue>) -> Option<(String, String)> {
+    let device = props
+        .get("Device")
+        .and_then(|v| v.downcast_ref::<zbus::zvariant::ObjectPath>().ok())?;
+    let codec = props.get("Codec").and_then(|v| v.downcast_ref::<u8>().ok())?;
+    let configuration: Vec<u8> = props
+        .get("Configuration")
+        .and_then(|v| v.try_clone().ok())
+        .and_then(|v| Vec::try_from(v).ok())
+        .unwrap_or_default();
+
+    Some((device.to_string(), codec_name(codec, &configuration)))
+}
+
+/// Name of an A2DP codec
+///
+/// Vendor codecs (0xFF) start their configuration with a little-endian
+/// 32-bit vendor ID and 16-bit codec ID.
+fn codec_name(codec: u8, configuration: &[u8]) -> String {
+    match codec {
+        0x00 => "SBC".to_string(),
+        0x01 => "MP3".to_string(),
+        0x02 => "AAC".to_string(),
+        0x04 => "ATRAC".to_string(),
+        0xFF if configuration.len() >= 6 => {
+            let vendor = u32::from_le_bytes([
+                configuration[0],
+                configuration[1],
+                configuration[2],
+                configuration[3],
+            ]);
+            let id = u16::from_le_bytes([configuration[4], configuration[5]]);
+            match (vendor, id) {
+                (0x004F, 0x0001) => "aptX".to_string(),
+                (0x00D7, 0x0024) => "aptX HD".to_string(),
+                (0x000A, 0x0001) => "FastStream".to_string(),
+                (0x000A, 0x0002) => "aptX LL".to_string(),
+                (0x012D, 0x00AA) => "LDAC".to_string(),
+                (0x0075, 0x0102) => "Samsung Scalable".to_string(),
+                (0x08A9, 0x0001) => "LC3plus".to_string(),
+                _ => format!("Vendor {:04X}:{:04X}", vendor, id),
+            }
+        }
+        other => format!("Codec 0x{:02X}", other),
+    }
+}
+
 impl Default for BluetoothControl {
     fn default() -> Self {
         Self::new()
@@ -470,10 +625,14 @@ impl Default for BluetoothControl {
 pub fn create_bluetooth_control_sync(events: EventManager) -> Arc<BluetoothControl> {
     let bluetooth = Arc::new(BluetoothControl::with_events(events));
 
-    // Try to connect in background
+    // Connect in background, retrying while the service starts up
     let bluetooth_clone = bluetooth.clone();
     tokio::spawn(async move {
-        if let Err(e) = bluetooth_clone.connect().await {
+        let result = connect_with_backoff("Bluetooth control", Backoff::default(), || {
+            bluetooth_clone.connect()
+        })
+        .await;
+        if let Err(e) = result {
             warn!("Failed to connect Bluetooth control: {}", e);
         }
     });
@@ -498,4 +657,53 @@ mod tests {
         // Default state should be false (not connected)
         assert_eq!(bt.is_powered().await.unwrap(), false);
     }
+
+    #[test]
+    fn test_parse_device() {
+        use zbus::zvariant::Value;
+
+        let value = |v: Value<'static>| OwnedValue::try_from(v).unwrap();
+        let mut props = HashMap::new();
+        props.insert("Address".to_string(), value(Value::from("AA:BB:CC:DD:EE:FF")));
+        props.insert("Name".to_string(), value(Value::from("WH-1000XM4")));
+        props.insert("Icon".to_string(), value(Value::from("audio-headset")));
+        props.insert("Paired".to_string(), value(Value::Bool(true)));
+        props.insert("Connected".to_string(), value(Value::Bool(true)));
+
+        let device = parse_device(&props).unwrap();
+        assert_eq!(device.name, "WH-1000XM4");
+        assert_eq!(device.icon.as_deref(), Some("audio-headset"));
+        assert!(device.paired && device.connected && !device.trusted);
+
+        // The alias wins over the advertised name
+        props.insert("Alias".to_string(), value(Value::from("Headphones")));
+        assert_eq!(parse_device(&props).unwrap().name, "Headphones");
+
+        props.remove("Address");
+        assert!(parse_device(&props).is_none());
+    }
+
+    #[test]
+    fn test_codec_name() {
+        assert_eq!(codec_name(0x00, &[0x3F, 0xFF, 0x02, 0x35]), "SBC");
+        assert_eq!(codec_name(0x02, &[]), "AAC");
+        assert_eq!(
+            codec_name(0xFF, &[0x2D, 0x01, 0x00, 0x00, 0xAA, 0x00, 0x04, 0x01]),
+            "LDAC"
+        );
+        assert_eq!(
+            codec_name(0xFF, &[0x4F, 0x00, 0x00, 0x00, 0x01, 0x00, 0x22]),
+            "aptX"
+        );
+        assert_eq!(
+            codec_name(0xFF, &[0x0A, 0x00, 0x00, 0x00, 0x01, 0x00, 0x03, 0x32]),
+            "FastStream"
+        );
+        assert_eq!(
+            codec_name(0xFF, &[0x34, 0x12, 0x00, 0x00, 0x01, 0x00]),
+            "Vendor 1234:0001"
+        );
+        // Too short to carry a vendor ID
+        assert_eq!(codec_name(0xFF, &[0x4F]), "Codec 0xFF");
+    }
 }
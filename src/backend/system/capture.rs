@@ -0,0 +1,272 @@
+use crate::backend::niri::NiriClient;
+use crate::error::{AmiyaError, Result};
+use crate::events::{Event, EventManager};
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// How long to wait before checking that wf-recorder actually started
+const RECORDER_STARTUP_CHECK_MS: u64 = 300;
+
+/// What to capture in a screenshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotMode {
+    /// All outputs
+    Full,
+    /// A region selected with slurp
+    Region,
+    /// The focused window (taken by niri)
+    Window,
+}
+
+/// A running wf-recorder process
+struct Recording {
+    child: Child,
+    path: PathBuf,
+}
+
+/// Screenshots via grim/slurp and screen recording via wf-recorder
+pub struct ScreenCapture {
+    recording: Mutex<Option<Recording>>,
+    events: EventManager,
+}
+
+impl ScreenCapture {
+    /// Create a new screen capture instance
+    pub fn new(events: EventManager) -> Self {
+        ScreenCapture {
+            recording: Mutex::new(None),
+            events,
+        }
+    }
+
+    /// Take a screenshot
+    ///
+    /// Returns the saved file, or `None` for window screenshots, which niri
+    /// saves to its own `screenshot-path`.
+    pub async fn screenshot(
+        &self,
+        mode: ScreenshotMode,
+        niri: Option<&NiriClient>,
+    ) -> Result<Option<PathBuf>> {
+        let path = match mode {
+            ScreenshotMode::Full => {
+                let path = Self::output_path(&screenshot_dir(), "Screenshot", "png")?;
+                run("grim", &[path.as_os_str()]).await?;
+                Some(path)
+            }
+            ScreenshotMode::Region => {
+                let geometry = select_region().await?;
+                let path = Self::output_path(&screenshot_dir(), "Screenshot", "png")?;
+                run("grim", &["-g".as_ref(), geometry.as_ref(), path.as_os_str()]).await?;
+                Some(path)
+            }
+            ScreenshotMode::Window => {
+                // niri knows the window geometry; grim alone can't find it
+                let niri = niri.ok_or_else(|| {
+                    AmiyaError::Backend("Window screenshots need niri".to_string())
+                })?;
+                niri.screenshot_window()?;
+                None
+            }
+        };
+
+        match &path {
+            Some(path) => info!("Screenshot saved to {}", path.display()),
+            None => info!("Window screenshot taken by niri"),
+        }
+
+        self.events.emit(Event::ScreenshotTaken {
+            path: path.as_ref().map(|p| p.display().to_string()),
+        });
+
+        Ok(path)
+    }
+
+    /// Whether a recording is in progress
+    pub async fn is_recording(&self) -> bool {
+        self.recording.lock().await.is_some()
+    }
+
+    /// Start recording all outputs with wf-recorder
+    pub async fn start_recording(&self) -> Result<PathBuf> {
+        let mut recording = self.recording.lock().await;
+        if let Some(current) = recording.as_ref() {
+            return Err(AmiyaError::Backend(format!(
+                "Already recording to {}",
+                current.path.display()
+            )));
+        }
+
+        let path = Self::output_path(&recording_dir(), "Recording", "mp4")?;
+        let mut child = Command::new("wf-recorder")
+            .arg("-f")
+            .arg(&path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| AmiyaError::Backend(format!("Failed to start wf-recorder: {}", e)))?;
+
+        // wf-recorder fails fast on a bad output or missing portal permissions
+        tokio::time::sleep(Duration::from_millis(RECORDER_STARTUP_CHECK_MS)).await;
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(AmiyaError::Backend(format!(
+                "wf-recorder exited immediately ({})",
+                status
+            )));
+        }
+
+        info!("Recording to {}", path.display());
+        *recording = Some(Recording {
+            child,
+            path: path.clone(),
+        });
+
+        self.events.emit(Event::ScreenRecordingChanged {
+            recording: true,
+            path: Some(path.display().to_string()),
+        });
+
+        Ok(path)
+    }
+
+    /// Stop the current recording and return the saved file
+    pub async fn stop_recording(&self) -> Result<PathBuf> {
+        let Recording { mut child, path } = self
+            .recording
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| AmiyaError::Backend("Not recording".to_string()))?;
+
+        // wf-recorder finalizes the file on SIGINT; killing it would leave
+        // the container unreadable
+        if let Some(pid) = child.id() {
+            if let Err(e) = run("kill", &["-INT".as_ref(), pid.to_string().as_ref()]).await {
+                warn!("Failed to interrupt wf-recorder, killing it: {}", e);
+                child.start_kill().ok();
+            }
+        }
+
+        child
+            .wait()
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to wait for wf-recorder: {}", e)))?;
+
+        info!("Recording saved to {}", path.display());
+        self.events.emit(Event::ScreenRecordingChanged {
+            recording: false,
+            path: Some(path.display().to_string()),
+        });
+
+        Ok(path)
+    }
+
+    /// Create `dir` and pick a timestamped file name in it
+    fn output_path(dir: &Path, prefix: &str, extension: &str) -> Result<PathBuf> {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            AmiyaError::Backend(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+        Ok(timestamped_path(dir, prefix, extension, Local::now()))
+    }
+}
+
+/// `<dir>/<prefix>_2024-05-01_13-45-12.<extension>`
+fn timestamped_path(dir: &Path, prefix: &str, extension: &str, time: DateTime<Local>) -> PathBuf {
+    dir.join(format!(
+        "{}_{}.{}",
+        prefix,
+        time.format("%Y-%m-%d_%H-%M-%S"),
+        extension
+    ))
+}
+
+/// `$XDG_PICTURES_DIR/Screenshots`, falling back to `~/Pictures/Screenshots`
+fn screenshot_dir() -> PathBuf {
+    user_dir("XDG_PICTURES_DIR", "Pictures").join("Screenshots")
+}
+
+/// `$XDG_VIDEOS_DIR`, falling back to `~/Videos`
+fn recording_dir() -> PathBuf {
+    user_dir("XDG_VIDEOS_DIR", "Videos")
+}
+
+fn user_dir(var: &str, fallback: &str) -> PathBuf {
+    std::env::var_os(var)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            let home = std::env::var_os("HOME").unwrap_or_default();
+            PathBuf::from(home).join(fallback)
+        })
+}
+
+/// Let the user drag out a region with slurp and return its geometry
+async fn select_region() -> Result<String> {
+    let output = Command::new("slurp")
+        .output()
+        .await
+        .map_err(|e| AmiyaError::Backend(format!("Failed to run slurp: {}", e)))?;
+
+    // slurp exits non-zero when the selection is cancelled with Escape
+    if !output.status.success() {
+        return Err(AmiyaError::Backend("Region selection cancelled".to_string()));
+    }
+
+    let geometry = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if geometry.is_empty() {
+        return Err(AmiyaError::Backend("slurp returned no region".to_string()));
+    }
+
+    Ok(geometry)
+}
+
+/// Run a program to completion, turning a non-zero exit into an error with
+/// its stderr
+async fn run(program: &str, args: &[&std::ffi::OsStr]) -> Result<()> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| AmiyaError::Backend(format!("Failed to run {}: {}", program, e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AmiyaError::Backend(format!(
+            "{} failed ({}): {}",
+            program,
+            output.status,
+            stderr.trim()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_timestamped_path() {
+        let time = Local.with_ymd_and_hms(2024, 5, 1, 13, 45, 12).unwrap();
+        assert_eq!(
+            timestamped_path(Path::new("/tmp/shots"), "Screenshot", "png", time),
+            PathBuf::from("/tmp/shots/Screenshot_2024-05-01_13-45-12.png")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_without_recording() {
+        let capture = ScreenCapture::new(EventManager::default());
+        assert!(!capture.is_recording().await);
+        assert!(capture.stop_recording().await.is_err());
+    }
+}
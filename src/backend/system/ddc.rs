@@ -0,0 +1,197 @@
+//! Brightness of external monitors over DDC/CI
+//!
+//! Desktops have no `/sys/class/backlight` entry, but most external monitors
+//! accept brightness commands over the DDC/CI channel of their video cable.
+//! Each command takes tens of milliseconds and monitors drop commands sent
+//! too quickly, so one thread owns the monitors and sends only the latest
+//! requested level, at most once per `WRITE_INTERVAL`.
+
+use crate::error::{AmiyaError, Result};
+use crate::events::{Event, EventManager};
+use ddc_hi::{Ddc, Display};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// VCP feature code for luminance
+const BRIGHTNESS_FEATURE: u8 = 0x10;
+
+/// Minimum time between brightness writes to the monitors
+const WRITE_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A monitor that answered a brightness query
+struct Monitor {
+    display: Display,
+    /// Raw value for 100%
+    max: u16,
+    /// Brightness when it was found, in percent
+    level: f64,
+}
+
+/// Brightness control for external monitors over DDC/CI
+///
+/// Every monitor found is set to the same level. The level is cached rather
+/// than read back, as reads are as slow as writes; changes made with the
+/// monitor's own buttons aren't noticed.
+pub struct DdcBacklight {
+    /// Last level set, or `None` until a monitor has been found
+    level: Arc<Mutex<Option<f64>>>,
+    /// Levels for the writer thread to send
+    writer: Sender<f64>,
+    events: Option<EventManager>,
+}
+
+impl DdcBacklight {
+    /// Look for monitors that support DDC/CI brightness
+    ///
+    /// Probing every display takes a moment, so the writer thread does it in
+    /// the background; until it has found a monitor, reading or setting the
+    /// brightness fails.
+    pub fn new(events: Option<EventManager>) -> Self {
+        let level = Arc::new(Mutex::new(None));
+        let (writer, requests) = mpsc::channel();
+
+        let found = level.clone();
+        let spawned = thread::Builder::new()
+            .name("amiya-ddc".to_string())
+            .spawn(move || {
+                let mut monitors = Self::find_monitors();
+                let Some(first) = monitors.first() else {
+                    info!("No monitors with DDC/CI brightness control found");
+                    return;
+                };
+                info!(
+                    "Controlling brightness of {} monitor(s) over DDC/CI",
+                    monitors.len()
+                );
+                *found.lock().unwrap() = Some(first.level);
+                Self::write_loop(&mut monitors, requests);
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to start DDC/CI thread: {}", e);
+        }
+
+        DdcBacklight {
+            level,
+            writer,
+            events,
+        }
+    }
+
+    /// Displays that report a brightness, with their range and level
+    fn find_monitors() -> Vec<Monitor> {
+        Display::enumerate()
+            .into_iter()
+            .filter_map(
+                |mut handle| match handle.get_vcp_feature(BRIGHTNESS_FEATURE) {
+                    Ok(value) if value.maximum() > 0 => {
+                        let max = value.maximum();
+                        let level = to_percent(value.value(), max);
+                        debug!("DDC/CI monitor {} at {:.0}%", handle.info.id, level);
+                        Some(Monitor {
+                            display: handle,
+                            max,
+                            level,
+                        })
+                    }
+                    Ok(_) => None,
+                    Err(e) => {
+                        debug!("No DDC/CI brightness on {}: {}", handle.info.id, e);
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Send requested levels to every monitor until the control is dropped
+    fn write_loop(monitors: &mut [Monitor], requests: Receiver<f64>) {
+        while let Ok(mut level) = requests.recv() {
+            // Levels requested during the last write are already stale
+            while let Ok(newer) = requests.try_recv() {
+                level = newer;
+            }
+
+            for monitor in monitors.iter_mut() {
+                let raw = to_raw(level, monitor.max);
+                if let Err(e) = monitor.display.set_vcp_feature(BRIGHTNESS_FEATURE, raw) {
+                    warn!(
+                        "Failed to set brightness of {}: {}",
+                        monitor.display.info.id, e
+                    );
+                }
+            }
+
+            thread::sleep(WRITE_INTERVAL);
+        }
+    }
+
+    /// Whether a monitor has been found
+    pub fn is_available(&self) -> bool {
+        self.level.lock().unwrap().is_some()
+    }
+
+    /// Current brightness level (0-100)
+    pub fn get_brightness(&self) -> Result<f64> {
+        self.level
+            .lock()
+            .unwrap()
+            .ok_or_else(|| AmiyaError::Backend("No DDC/CI monitor found".to_string()))
+    }
+
+    /// Set brightness level (0-100) on every monitor
+    ///
+    /// Returns once the level is queued; the monitors follow shortly after.
+    pub fn set_brightness(&self, brightness: f64) -> Result<()> {
+        let brightness = brightness.clamp(0.0, 100.0);
+        let mut level = self.level.lock().unwrap();
+        if level.is_none() {
+            return Err(AmiyaError::Backend("No DDC/CI monitor found".to_string()));
+        }
+        self.writer
+            .send(brightness)
+            .map_err(|_| AmiyaError::Backend("DDC/CI thread has stopped".to_string()))?;
+        *level = Some(brightness);
+        drop(level);
+
+        if let Some(events) = &self.events {
+            events.emit(Event::BrightnessChanged { level: brightness });
+        }
+        Ok(())
+    }
+}
+
+/// Raw DDC value for `percent` of a monitor's range
+fn to_raw(percent: f64, max: u16) -> u16 {
+    ((percent.clamp(0.0, 100.0) / 100.0) * max as f64).round() as u16
+}
+
+/// Percentage of a monitor's range for a raw DDC value
+fn to_percent(raw: u16, max: u16) -> f64 {
+    (raw as f64 / max as f64 * 100.0).min(100.0)
+}
+
+// Helper function to create DDC/CI brightness control in GTK context
+pub fn create_ddc_backlight_sync(events: EventManager) -> Arc<DdcBacklight> {
+    Arc::new(DdcBacklight::new(Some(events)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_conversion() {
+        assert_eq!(to_raw(50.0, 100), 50);
+        assert_eq!(to_raw(75.0, 255), 191);
+        assert_eq!(to_raw(120.0, 100), 100);
+        assert_eq!(to_raw(-5.0, 100), 0);
+
+        assert_eq!(to_percent(50, 100), 50.0);
+        assert_eq!(to_percent(0, 255), 0.0);
+        // Some monitors report a value past their own maximum
+        assert_eq!(to_percent(120, 100), 100.0);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::error::{AmiyaError, Result};
+use crate::events::{Event, EventManager};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+use zbus::zvariant::OwnedFd;
+use zbus::Connection;
+
+/// Idle inhibitor via logind inhibitor locks
+///
+/// While enabled, an "idle" inhibitor lock is held so idle daemons that honour
+/// logind inhibitors (hypridle, swayidle) don't blank or lock the screen. The
+/// lock is a file descriptor; closing it releases the inhibitor, so it is also
+/// released automatically if amiya exits.
+pub struct IdleInhibitor {
+    connection: Arc<RwLock<Option<Connection>>>,
+    lock: Mutex<Option<OwnedFd>>,
+    events: EventManager,
+}
+
+impl IdleInhibitor {
+    /// Create a new idle inhibitor
+    pub fn new(events: EventManager) -> Self {
+        IdleInhibitor {
+            connection: Arc::new(RwLock::new(None)),
+            lock: Mutex::new(None),
+            events,
+        }
+    }
+
+    /// Initialize connection to logind
+    pub async fn connect(&self) -> Result<()> {
+        match Connection::system().await {
+            Ok(conn) => {
+                info!("Connected to D-Bus system bus for idle inhibition");
+
+                let mut connection = self.connection.write().await;
+                *connection = Some(conn);
+
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Could not connect to D-Bus system bus: {}", e);
+                Err(AmiyaError::Backend(format!(
+                    "Failed to connect to D-Bus: {}",
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Whether the inhibitor lock is currently held
+    pub fn is_enabled(&self) -> bool {
+        self.lock.lock().unwrap().is_some()
+    }
+
+    /// Take or release the inhibitor lock
+    pub async fn set_enabled(&self, enabled: bool) -> Result<()> {
+        if enabled {
+            self.inhibit().await
+        } else {
+            self.release();
+            Ok(())
+        }
+    }
+
+    /// Flip the current state
+    pub async fn toggle(&self) -> Result<()> {
+        self.set_enabled(!self.is_enabled()).await
+    }
+
+    /// Release the inhibitor lock, if held
+    pub fn release(&self) {
+        // Dropping the fd closes it, which releases the lock
+        let released = self.lock.lock().unwrap().take().is_some();
+        if released {
+            info!("Idle inhibitor released");
+            self.events
+                .emit(Event::IdleInhibitChanged { enabled: false });
+        }
+    }
+
+    async fn inhibit(&self) -> Result<()> {
+        if self.is_enabled() {
+            return Ok(());
+        }
+
+        let conn_guard = self.connection.read().await;
+        let conn = conn_guard
+            .as_ref()
+            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+
+        let proxy: zbus::Result<zbus::Proxy> = async {
+            zbus::ProxyBuilder::new(conn)
+                .interface("org.freedesktop.login1.Manager")?
+                .path("/org/freedesktop/login1")?
+                .destination("org.freedesktop.login1")?
+                .build()
+                .await
+        }
+        .await;
+        let proxy = proxy
+            .map_err(|e| AmiyaError::Backend(format!("Failed to create logind proxy: {}", e)))?;
+
+        // Inhibit(what, who, why, mode) -> fd
+        let fd: OwnedFd = proxy
+            .call_method(
+                "Inhibit",
+                &(
+                    "idle",
+                    "Amiya",
+                    "Idle inhibitor enabled by the user",
+                    "block",
+                ),
+            )
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to inhibit idle: {}", e)))?
+            .body()
+            .deserialize()
+            .map_err(|e| AmiyaError::Backend(format!("Failed to read inhibitor lock: {}", e)))?;
+
+        // Another request may have taken the lock while we waited; keep
+        // theirs and let ours drop
+        let mut lock = self.lock.lock().unwrap();
+        if lock.is_none() {
+            *lock = Some(fd);
+            info!("Idle inhibitor enabled");
+            self.events
+                .emit(Event::IdleInhibitChanged { enabled: true });
+        }
+
+        Ok(())
+    }
+}
+
+/// Create idle inhibitor with sync initialization (for GTK/glib event loop integration)
+pub fn create_idle_inhibitor_sync(events: EventManager) -> Arc<IdleInhibitor> {
+    let inhibitor = Arc::new(IdleInhibitor::new(events));
+
+    // Try to initialize in background
+    let inhibitor_clone = inhibitor.clone();
+    tokio::spawn(async move {
+        if let Err(e) = inhibitor_clone.connect().await {
+            debug!("Failed to initialize idle inhibitor: {}", e);
+        }
+    });
+
+    inhibitor
+}
@@ -1,11 +1,34 @@
+use crate::backend::dbus::{Bus, DbusConnection};
+use crate::backend::retry::{connect_with_backoff, Backoff};
 use crate::error::{AmiyaError, Result};
 use crate::events::{Event, EventManager};
+use futures::StreamExt;
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use tracing::{debug, info, warn};
+use zbus::zvariant::OwnedValue;
 use zbus::Connection;
 
+pub use crate::events::{LoopStatus, MediaCapabilities};
+
+/// Player properties for shuffle and repeat
+const MODE_PROPERTIES: &[&str] = &["Shuffle", "LoopStatus"];
+
+/// Player properties for what is playing
+const PLAYBACK_PROPERTIES: &[&str] = &["PlaybackStatus", "Metadata"];
+
+/// Player properties saying which controls it supports
+const CAPABILITY_PROPERTIES: &[&str] = &[
+    "CanControl",
+    "CanGoNext",
+    "CanGoPrevious",
+    "CanPlay",
+    "CanPause",
+];
+
 /// Media player information
 #[derive(Debug, Clone)]
 pub struct MediaPlayer {
@@ -44,12 +67,22 @@ impl std::fmt::Display for PlaybackStatus {
 
 /// Media control via MPRIS2
 pub struct MediaControl {
-    connection: Arc<RwLock<Option<Connection>>>,
+    connection: DbusConnection,
     active_player: Arc<RwLock<Option<String>>>,
+    /// Whether `active_player` is set, readable without waiting on the lock
+    has_player: AtomicBool,
     players: Arc<RwLock<Vec<MediaPlayer>>>,
     playback_status: Arc<RwLock<PlaybackStatus>>,
     current_track: Arc<RwLock<Option<TrackMetadata>>>,
     volume: Arc<RwLock<f64>>,
+    /// `None` while the active player doesn't support shuffle
+    shuffle: Arc<RwLock<Option<bool>>>,
+    /// `None` while the active player doesn't support repeat
+    loop_status: Arc<RwLock<Option<LoopStatus>>>,
+    capabilities: Arc<RwLock<MediaCapabilities>>,
+    /// Wakes `watch_players` when it should follow a different player or
+    /// connection
+    player_changed: Notify,
     events: Option<EventManager>,
 }
 
@@ -57,12 +90,17 @@ impl MediaControl {
     /// Create a new media control instance
     pub fn new() -> Self {
         MediaControl {
-            connection: Arc::new(RwLock::new(None)),
+            connection: DbusConnection::new(Bus::Session),
             active_player: Arc::new(RwLock::new(None)),
+            has_player: AtomicBool::new(false),
             players: Arc::new(RwLock::new(Vec::new())),
             playback_status: Arc::new(RwLock::new(PlaybackStatus::Stopped)),
             current_track: Arc::new(RwLock::new(None)),
             volume: Arc::new(RwLock::new(1.0)),
+            shuffle: Arc::new(RwLock::new(None)),
+            loop_status: Arc::new(RwLock::new(None)),
+            capabilities: Arc::new(RwLock::new(MediaCapabilities::default())),
+            player_changed: Notify::new(),
             events: None,
         }
     }
@@ -76,30 +114,32 @@ impl MediaControl {
 
     /// Initialize connection to D-Bus
     pub async fn connect(&self) -> Result<()> {
-        match Connection::session().await {
-            Ok(conn) => {
-                info!("Connected to D-Bus session bus for Media control");
-
-                // Store connection
-                {
-                    let mut connection = self.connection.write().await;
-                    *connection = Some(conn.clone());
-                }
+        self.reconnect().await?;
+        Ok(())
+    }
 
-                // Discover media players
-                if let Err(e) = self.discover_players(&conn).await {
-                    warn!("Failed to discover media players: {}", e);
-                }
+    /// Open a new connection and discover the players again
+    async fn reconnect(&self) -> Result<Connection> {
+        let conn = self.connection.open().await?;
+        info!("Connected to D-Bus session bus for Media control");
 
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Could not connect to D-Bus session bus: {}", e);
-                Err(AmiyaError::Backend(format!(
-                    "Failed to connect to D-Bus: {}",
-                    e
-                )))
-            }
+        // Discover media players
+        if let Err(e) = self.discover_players(&conn).await {
+            warn!("Failed to discover media players: {}", e);
+        }
+
+        // The old connection's property streams are dead
+        self.player_changed.notify_one();
+
+        Ok(conn)
+    }
+
+    /// Connection for the next call, reconnecting if it was lost or the
+    /// active player went away
+    async fn connection(&self) -> Result<Connection> {
+        match self.connection.cached().await {
+            Some(conn) => Ok(conn),
+            None => self.reconnect().await,
         }
     }
 
@@ -117,7 +157,7 @@ impl MediaControl {
         let names: Vec<String> = dbus_proxy
             .call_method("ListNames", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to list names: {}", e)))?
+            .map_err(|e| self.connection.error("Failed to list names", e))?
             .body()
             .deserialize()
             .map_err(|e| AmiyaError::Backend(format!("Failed to deserialize names: {}", e)))?;
@@ -135,21 +175,27 @@ impl MediaControl {
                         bus_name: name.clone(),
                         identity,
                     });
+                }
+            }
+        }
 
-                    // Set as active player if we don't have one
-                    let active = self.active_player.read().await;
-                    if active.is_none() {
-                        drop(active);
-                        let mut active = self.active_player.write().await;
-                        *active = Some(name.clone());
-
-                        // Update player state
-                        drop(active);
-                        if let Err(e) = self.update_player_state(conn, &name).await {
-                            debug!("Failed to update player state: {}", e);
-                        }
+        // Prefer the player the user last picked, then the current one
+        let preferred = load_preferred_player();
+        let current = self.active_player.read().await.clone();
+        let chosen = choose_player(&players, preferred.as_deref(), current.as_deref())
+            .map(|p| p.bus_name.clone());
+
+        if chosen != current {
+            *self.active_player.write().await = chosen.clone();
+            self.has_player.store(chosen.is_some(), Ordering::Relaxed);
+            self.player_changed.notify_one();
+            match &chosen {
+                Some(bus_name) => {
+                    if let Err(e) = self.update_player_state(conn, bus_name).await {
+                        debug!("Failed to update player state: {}", e);
                     }
                 }
+                None => self.set_capabilities(MediaCapabilities::default()).await,
             }
         }
 
@@ -160,12 +206,10 @@ impl MediaControl {
 
         info!("Discovered {} media players", players.len());
 
-        // Emit event
+        // Emit event, including when the last player went away
         if let Some(events) = &self.events {
-            if let Some(active) = self.active_player.read().await.as_ref() {
-                events.emit(Event::MediaPlayerChanged {
-                    player: Some(active.clone()),
-                });
+            if chosen != current {
+                events.emit(Event::MediaPlayerChanged { player: chosen });
             }
         }
 
@@ -200,7 +244,28 @@ impl MediaControl {
             .await
             .map_err(|e| AmiyaError::Backend(format!("Failed to create player proxy: {}", e)))?;
 
-        // Get playback status
+        self.update_playback_status(&player_proxy).await;
+        self.update_track(&player_proxy).await;
+
+        // Get volume
+        let volume: f64 = player_proxy
+            .get_property("Volume")
+            .await
+            .unwrap_or(1.0);
+
+        {
+            let mut vol = self.volume.write().await;
+            *vol = volume;
+        }
+
+        self.update_player_modes(&player_proxy).await;
+        self.update_capabilities(&player_proxy).await;
+
+        Ok(())
+    }
+
+    /// Read whether the player is playing
+    async fn update_playback_status(&self, player_proxy: &zbus::Proxy<'_>) {
         let status_str: String = player_proxy
             .get_property("PlaybackStatus")
             .await
@@ -223,9 +288,11 @@ impl MediaControl {
                 playing: status == PlaybackStatus::Playing,
             });
         }
+    }
 
-        // Get metadata
-        if let Ok(metadata) = self.get_metadata_internal(&player_proxy).await {
+    /// Read the track the player is on
+    async fn update_track(&self, player_proxy: &zbus::Proxy<'_>) {
+        if let Ok(metadata) = self.get_metadata_internal(player_proxy).await {
             let mut track = self.current_track.write().await;
             *track = Some(metadata.clone());
 
@@ -238,19 +305,61 @@ impl MediaControl {
                 });
             }
         }
+    }
 
-        // Get volume
-        let volume: f64 = player_proxy
-            .get_property("Volume")
-            .await
-            .unwrap_or(1.0);
+    /// Read which controls the player supports
+    ///
+    /// Flags the player doesn't report are assumed supported, as they were
+    /// before these were checked; a player that can't be controlled at all
+    /// supports nothing, whatever its other flags say.
+    async fn update_capabilities(&self, player_proxy: &zbus::Proxy<'_>) {
+        let flag = |name: &'static str| async move {
+            player_proxy
+                .get_property::<bool>(name)
+                .await
+                .unwrap_or(true)
+        };
 
-        {
-            let mut vol = self.volume.write().await;
-            *vol = volume;
+        let capabilities = if flag("CanControl").await {
+            MediaCapabilities {
+                can_control: true,
+                can_go_next: flag("CanGoNext").await,
+                can_go_previous: flag("CanGoPrevious").await,
+                can_play: flag("CanPlay").await,
+                can_pause: flag("CanPause").await,
+            }
+        } else {
+            MediaCapabilities::default()
+        };
+
+        self.set_capabilities(capabilities).await;
+    }
+
+    async fn set_capabilities(&self, capabilities: MediaCapabilities) {
+        *self.capabilities.write().await = capabilities;
+
+        if let Some(events) = &self.events {
+            events.emit(Event::MediaCapabilitiesChanged { capabilities });
         }
+    }
 
-        Ok(())
+    /// Read shuffle and repeat from the player, treating missing properties
+    /// as unsupported
+    async fn update_player_modes(&self, player_proxy: &zbus::Proxy<'_>) {
+        let shuffle: Option<bool> = player_proxy.get_property("Shuffle").await.ok();
+        let loop_status = player_proxy
+            .get_property::<String>("LoopStatus")
+            .await
+            .ok()
+            .and_then(|value| LoopStatus::from_mpris(&value));
+
+        *self.shuffle.write().await = shuffle;
+        *self.loop_status.write().await = loop_status;
+
+        if let Some(events) = &self.events {
+            events.emit(Event::MediaShuffleChanged { shuffle });
+            events.emit(Event::MediaLoopStatusChanged { loop_status });
+        }
     }
 
     /// Get metadata from player proxy
@@ -311,13 +420,7 @@ impl MediaControl {
 
     /// Check if media control is available
     pub fn is_available(&self) -> bool {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let conn = self.connection.read().await;
-                let player = self.active_player.read().await;
-                conn.is_some() && player.is_some()
-            })
-        })
+        self.connection.is_connected() && self.has_player.load(Ordering::Relaxed)
     }
 
     /// Get list of available players
@@ -333,31 +436,34 @@ impl MediaControl {
 
     /// Set active player
     pub async fn set_active_player(&self, bus_name: &str) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+        let conn = self.connection().await?;
 
         // Verify player exists
-        let players = self.players.read().await;
-        if !players.iter().any(|p| p.bus_name == bus_name) {
-            return Err(AmiyaError::Backend(format!(
-                "Player not found: {}",
-                bus_name
-            )));
-        }
+        let identity = self
+            .players
+            .read()
+            .await
+            .iter()
+            .find(|p| p.bus_name == bus_name)
+            .map(|p| p.identity.clone())
+            .ok_or_else(|| AmiyaError::Backend(format!("Player not found: {}", bus_name)))?;
 
         {
             let mut active = self.active_player.write().await;
             *active = Some(bus_name.to_string());
+            self.has_player.store(true, Ordering::Relaxed);
         }
+        self.player_changed.notify_one();
 
         // Update player state
-        self.update_player_state(conn, bus_name).await?;
+        self.update_player_state(&conn, bus_name).await?;
 
         info!("Active player set to: {}", bus_name);
 
+        // Bus names get a new instance suffix each launch, so remember the
+        // player by its identity
+        save_preferred_player(&identity);
+
         // Emit event
         if let Some(events) = &self.events {
             events.emit(Event::MediaPlayerChanged {
@@ -400,18 +506,14 @@ impl MediaControl {
 
     /// Call a method on the active player
     async fn call_player_method(&self, method: &str) -> Result<()> {
-        let conn_guard = self.connection.read().await;
+        let conn = self.connection().await?;
         let player_guard = self.active_player.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let bus_name = player_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No active player".to_string()))?;
 
-        let player_proxy = zbus::ProxyBuilder::new(conn)
+        let player_proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.mpris.MediaPlayer2.Player")
             .path("/org/mpris/MediaPlayer2")?
             .destination(bus_name.as_str())?
@@ -419,21 +521,18 @@ impl MediaControl {
             .await
             .map_err(|e| AmiyaError::Backend(format!("Failed to create player proxy: {}", e)))?;
 
-        player_proxy
-            .call_method(method, &())
-            .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to call {}: {}", method, e)))?;
+        player_proxy.call_method(method, &()).await.map_err(|e| {
+            self.connection
+                .error(&format!("Failed to call {}", method), e)
+        })?;
 
         info!("Called {} on player", method);
 
         // Update state after action
-        drop(conn_guard);
         drop(player_guard);
 
         if let Some(active) = self.active_player.read().await.as_ref() {
-            if let Some(conn) = self.connection.read().await.as_ref() {
-                let _ = self.update_player_state(conn, active).await;
-            }
+            let _ = self.update_player_state(&conn, active).await;
         }
 
         Ok(())
@@ -458,18 +557,14 @@ impl MediaControl {
     pub async fn set_volume(&self, volume: f64) -> Result<()> {
         let volume = volume.clamp(0.0, 1.0);
 
-        let conn_guard = self.connection.read().await;
+        let conn = self.connection().await?;
         let player_guard = self.active_player.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let bus_name = player_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No active player".to_string()))?;
 
-        let player_proxy = zbus::ProxyBuilder::new(conn)
+        let player_proxy = zbus::ProxyBuilder::new(&conn)
             .interface("org.mpris.MediaPlayer2.Player")
             .path("/org/mpris/MediaPlayer2")?
             .destination(bus_name.as_str())?
@@ -480,7 +575,7 @@ impl MediaControl {
         player_proxy
             .set_property("Volume", volume)
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to set volume: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to set volume", e))?;
 
         {
             let mut vol = self.volume.write().await;
@@ -497,14 +592,171 @@ impl MediaControl {
         Ok(())
     }
 
-    /// Start monitoring media events
-    pub async fn start_monitoring(&self) -> Result<()> {
-        debug!("Media monitoring started (basic implementation)");
-        // TODO: Subscribe to D-Bus signals:
-        // - PropertiesChanged (playback status, metadata, volume)
-        // - NameOwnerChanged (new/removed players)
+    /// Which controls the active player supports
+    pub async fn get_capabilities(&self) -> MediaCapabilities {
+        *self.capabilities.read().await
+    }
+
+    /// Whether the active player shuffles, or `None` if it can't
+    pub async fn get_shuffle(&self) -> Option<bool> {
+        *self.shuffle.read().await
+    }
+
+    /// Turn shuffle on or off
+    pub async fn set_shuffle(&self, shuffle: bool) -> Result<()> {
+        let player_proxy = self.active_player_proxy().await?;
+
+        // Fails for players whose Shuffle is missing or read-only
+        player_proxy
+            .set_property("Shuffle", shuffle)
+            .await
+            .map_err(|e| self.connection.error("Failed to set shuffle", e.into()))?;
+
+        *self.shuffle.write().await = Some(shuffle);
+
+        info!("Media shuffle set to {}", shuffle);
+
+        if let Some(events) = &self.events {
+            events.emit(Event::MediaShuffleChanged {
+                shuffle: Some(shuffle),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// How the active player repeats, or `None` if it can't
+    pub async fn get_loop_status(&self) -> Option<LoopStatus> {
+        *self.loop_status.read().await
+    }
+
+    /// Set how the active player repeats
+    pub async fn set_loop_status(&self, loop_status: LoopStatus) -> Result<()> {
+        let player_proxy = self.active_player_proxy().await?;
+
+        // Fails for players whose LoopStatus is missing or read-only
+        player_proxy
+            .set_property("LoopStatus", loop_status.as_mpris())
+            .await
+            .map_err(|e| self.connection.error("Failed to set repeat", e.into()))?;
+
+        *self.loop_status.write().await = Some(loop_status);
+
+        info!("Media repeat set to {}", loop_status.as_mpris());
+
+        if let Some(events) = &self.events {
+            events.emit(Event::MediaLoopStatusChanged {
+                loop_status: Some(loop_status),
+            });
+        }
+
         Ok(())
     }
+
+    /// Proxy for the active player's playback interface
+    async fn active_player_proxy(&self) -> Result<zbus::Proxy<'static>> {
+        let conn = self.connection().await?;
+        let bus_name = self
+            .active_player
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| AmiyaError::Backend("No active player".to_string()))?;
+
+        zbus::Proxy::new(
+            &conn,
+            bus_name,
+            "/org/mpris/MediaPlayer2",
+            "org.mpris.MediaPlayer2.Player",
+        )
+        .await
+        .map_err(|e| AmiyaError::Backend(format!("Failed to create player proxy: {}", e)))
+    }
+
+    /// Follow changes made in the active player itself (track, playback,
+    /// shuffle, repeat and capabilities), and discover the players again
+    /// whenever one starts or quits
+    ///
+    /// Starts over on the new connection or player whenever
+    /// `player_changed` fires.
+    pub async fn watch_players(&self) {
+        loop {
+            let conn = match self.connection().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    debug!("Not watching media players: {}", e);
+                    self.player_changed.notified().await;
+                    continue;
+                }
+            };
+
+            let owner_changes = match zbus::fdo::DBusProxy::new(&conn).await {
+                Ok(dbus) => dbus.receive_name_owner_changed().await,
+                Err(e) => Err(e),
+            };
+            let mut owner_changes = match owner_changes {
+                Ok(stream) => stream,
+                Err(e) => {
+                    debug!("Failed to watch media players: {}", e);
+                    self.player_changed.notified().await;
+                    continue;
+                }
+            };
+
+            // Each change carries the name of the property that changed
+            let player_proxy = self.active_player_proxy().await.ok();
+            let mut streams = Vec::new();
+            if let Some(player_proxy) = &player_proxy {
+                let names = PLAYBACK_PROPERTIES
+                    .iter()
+                    .chain(MODE_PROPERTIES)
+                    .chain(CAPABILITY_PROPERTIES);
+                for name in names {
+                    let stream = player_proxy
+                        .receive_property_changed::<OwnedValue>(name)
+                        .await;
+                    streams.push(stream.map(move |_| *name));
+                }
+            }
+            let mut changes = futures::stream::select_all(streams);
+            let mut watching = player_proxy.is_some();
+
+            loop {
+                tokio::select! {
+                    signal = owner_changes.next() => match signal {
+                        Some(signal) => {
+                            let is_player = signal
+                                .args()
+                                .is_ok_and(|args| args.name().starts_with("org.mpris.MediaPlayer2."));
+                            if is_player {
+                                if let Err(e) = self.discover_players(&conn).await {
+                                    debug!("Failed to discover media players: {}", e);
+                                }
+                            }
+                        }
+                        None => {
+                            // The connection is gone; wait for a new one
+                            // rather than spinning
+                            self.player_changed.notified().await;
+                            break;
+                        }
+                    },
+                    change = changes.next(), if watching => match (change, &player_proxy) {
+                        (Some(name), Some(proxy)) => match name {
+                            "PlaybackStatus" => self.update_playback_status(proxy).await,
+                            "Metadata" => self.update_track(proxy).await,
+                            name if MODE_PROPERTIES.contains(&name) => {
+                                self.update_player_modes(proxy).await
+                            }
+                            _ => self.update_capabilities(proxy).await,
+                        },
+                        _ => watching = false,
+                    },
+                    _ = self.player_changed.notified() => break,
+                }
+            }
+        }
+    }
 }
 
 impl Default for MediaControl {
@@ -517,17 +769,67 @@ impl Default for MediaControl {
 pub fn create_media_control_sync(events: EventManager) -> Arc<MediaControl> {
     let media = Arc::new(MediaControl::with_events(events));
 
-    // Try to connect in background
+    // Connect in background, retrying while the service starts up
     let media_clone = media.clone();
     tokio::spawn(async move {
-        if let Err(e) = media_clone.connect().await {
+        let result = connect_with_backoff("Media control", Backoff::default(), || {
+            media_clone.connect()
+        })
+        .await;
+        if let Err(e) = result {
             warn!("Failed to connect Media control: {}", e);
         }
+
+        // Waits for a later call to reconnect if the bus wasn't reachable
+        media_clone.watch_players().await;
     });
 
     media
 }
 
+/// File remembering the last player the user picked
+fn preferred_player_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/state")))
+        .map(|dir| dir.join("amiya").join("media-player"))
+}
+
+/// MPRIS identity of the last player the user picked
+fn load_preferred_player() -> Option<String> {
+    let identity = std::fs::read_to_string(preferred_player_path()?).ok()?;
+    let identity = identity.trim();
+    (!identity.is_empty()).then(|| identity.to_string())
+}
+
+fn save_preferred_player(identity: &str) {
+    let Some(path) = preferred_player_path() else {
+        return;
+    };
+
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(&path, identity));
+    if let Err(e) = result {
+        warn!("Failed to save preferred media player to {:?}: {}", path, e);
+    }
+}
+
+/// Pick the active player: the preferred identity if it is running, else the
+/// current player if it is still there, else the first one found
+fn choose_player<'a>(
+    players: &'a [MediaPlayer],
+    preferred_identity: Option<&str>,
+    current_bus_name: Option<&str>,
+) -> Option<&'a MediaPlayer> {
+    preferred_identity
+        .and_then(|identity| players.iter().find(|p| p.identity == identity))
+        .or_else(|| current_bus_name.and_then(|bus| players.iter().find(|p| p.bus_name == bus)))
+        .or_else(|| players.first())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -544,4 +846,35 @@ mod tests {
         let media = MediaControl::new();
         assert_eq!(media.get_volume().await, 1.0);
     }
+
+    fn player(identity: &str, bus_name: &str) -> MediaPlayer {
+        MediaPlayer {
+            name: bus_name.trim_start_matches("org.mpris.MediaPlayer2.").to_string(),
+            bus_name: bus_name.to_string(),
+            identity: identity.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_choose_player() {
+        let players = vec![
+            player("Firefox", "org.mpris.MediaPlayer2.firefox.instance_1_42"),
+            player("Spotify", "org.mpris.MediaPlayer2.spotify"),
+            player("mpv", "org.mpris.MediaPlayer2.mpv"),
+        ];
+
+        // The preferred identity wins even with a new bus name suffix
+        let chosen = choose_player(&players, Some("Firefox"), Some("org.mpris.MediaPlayer2.mpv"));
+        assert_eq!(chosen.unwrap().identity, "Firefox");
+
+        // Preferred player gone: keep the current one
+        let chosen = choose_player(&players, Some("VLC"), Some("org.mpris.MediaPlayer2.mpv"));
+        assert_eq!(chosen.unwrap().identity, "mpv");
+
+        // Neither available: first found
+        let chosen = choose_player(&players, Some("VLC"), Some("org.mpris.MediaPlayer2.vlc"));
+        assert_eq!(chosen.unwrap().identity, "Firefox");
+
+        assert!(choose_player(&[], Some("Firefox"), None).is_none());
+    }
 }
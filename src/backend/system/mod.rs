@@ -1,15 +1,51 @@
+pub mod applications;
 pub mod audio;
 pub mod backlight;
 pub mod battery;
 pub mod bluetooth;
+pub mod capture;
+#[cfg(feature = "ddc")]
+pub mod ddc;
+pub mod idle_inhibit;
 pub mod media;
 pub mod network;
 pub mod power;
+pub mod rfkill;
+pub mod thermal;
 
+pub use applications::DesktopEntry;
 pub use audio::AudioControl;
 pub use backlight::BacklightControl;
 pub use battery::BatteryControl;
 pub use bluetooth::BluetoothControl;
+pub use capture::ScreenCapture;
+#[cfg(feature = "ddc")]
+pub use ddc::DdcBacklight;
+pub use idle_inhibit::IdleInhibitor;
 pub use media::MediaControl;
 pub use network::NetworkControl;
 pub use power::PowerControl;
+pub use rfkill::AirplaneMode;
+pub use thermal::TempSensor;
+
+/// Smallest change made by a relative adjustment, in percent
+///
+/// Without it a level at or near zero could never be raised by a
+/// percentage of itself.
+pub const MIN_RELATIVE_STEP: f64 = 1.0;
+
+/// Change to apply for an adjustment of `percent` percent of `current`
+///
+/// Positive `percent` raises the level, negative lowers it. The result is at
+/// least `MIN_RELATIVE_STEP` in size unless `percent` is zero.
+pub fn relative_step(current: f64, percent: f64) -> f64 {
+    if percent == 0.0 {
+        return 0.0;
+    }
+    let step = current.abs() * percent / 100.0;
+    if step.abs() < MIN_RELATIVE_STEP {
+        MIN_RELATIVE_STEP.copysign(percent)
+    } else {
+        step
+    }
+}
@@ -1,20 +1,76 @@
+use crate::backend::dbus::{Bus, DbusConnection};
+use crate::backend::retry::{connect_with_backoff, Backoff};
 use crate::error::{AmiyaError, Result};
 use crate::events::{Event, EventManager, WifiNetworkInfo};
+use futures::StreamExt;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, RwLock};
 use tracing::{debug, info, warn};
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
 use zbus::Connection;
 
 // Re-export for convenience
 pub use crate::events::WifiNetworkInfo as WifiNetwork;
 
+/// Scans requested closer together than this reuse the access points
+/// NetworkManager already knows about; frequent scans can drop the connection
+/// on some drivers
+const MIN_SCAN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the connected access point's signal strength is read
+const WIFI_SIGNAL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Connection type of WiFi profiles
+const WIFI_TYPE: &str = "802-11-wireless";
+
+/// `NM_ACTIVE_CONNECTION_STATE_ACTIVATING` and `_ACTIVATED`
+const ACTIVE_CONNECTION_UP: [u32; 2] = [1, 2];
+
+/// A VPN profile saved in NetworkManager
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VpnConnection {
+    pub name: String,
+    pub uuid: String,
+    pub active: bool,
+}
+
+/// A WiFi network NetworkManager has a saved profile for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedConnection {
+    pub name: String,
+    pub uuid: String,
+    pub ssid: Option<String>,
+}
+
+/// A saved profile: object path, id, uuid, connection type and, for WiFi
+/// profiles, the SSID
+struct Profile {
+    path: OwnedObjectPath,
+    id: String,
+    uuid: String,
+    kind: String,
+    ssid: Option<String>,
+}
+
 /// Network control via NetworkManager
 pub struct NetworkControl {
-    connection: Arc<RwLock<Option<Connection>>>,
+    connection: DbusConnection,
     wifi_device_path: Arc<RwLock<Option<String>>>,
+    /// Whether `wifi_device_path` is set, readable without waiting on the lock
+    has_wifi_device: AtomicBool,
     networks: Arc<RwLock<HashMap<String, WifiNetwork>>>,
     wifi_enabled: Arc<RwLock<bool>>,
+    /// Name of the active VPN, as last reported
+    active_vpn: Arc<RwLock<Option<String>>>,
+    /// SSID and strength of the connected access point, as last reported
+    connected_ap: Arc<RwLock<Option<(String, u8)>>>,
+    /// When we last asked NetworkManager to scan
+    last_scan_request: Arc<RwLock<Option<Instant>>>,
+    /// Wakes `watch_vpn` when a new connection replaces the old one
+    connection_changed: Notify,
     events: Option<EventManager>,
 }
 
@@ -22,10 +78,15 @@ impl NetworkControl {
     /// Create a new network control instance
     pub fn new() -> Self {
         NetworkControl {
-            connection: Arc::new(RwLock::new(None)),
+            connection: DbusConnection::new(Bus::System),
             wifi_device_path: Arc::new(RwLock::new(None)),
+            has_wifi_device: AtomicBool::new(false),
             networks: Arc::new(RwLock::new(HashMap::new())),
             wifi_enabled: Arc::new(RwLock::new(false)),
+            active_vpn: Arc::new(RwLock::new(None)),
+            connected_ap: Arc::new(RwLock::new(None)),
+            last_scan_request: Arc::new(RwLock::new(None)),
+            connection_changed: Notify::new(),
             events: None,
         }
     }
@@ -39,46 +100,50 @@ impl NetworkControl {
 
     /// Initialize connection to NetworkManager
     pub async fn connect(&self) -> Result<()> {
-        match Connection::system().await {
-            Ok(conn) => {
-                info!("Connected to D-Bus system bus for Network control");
-
-                // Store connection
-                {
-                    let mut connection = self.connection.write().await;
-                    *connection = Some(conn.clone());
-                }
+        self.reconnect().await?;
+        Ok(())
+    }
 
-                // Find WiFi device
-                match self.find_wifi_device(&conn).await {
-                    Ok(path) => {
-                        info!("Found WiFi device: {}", path);
-                        let mut device_path = self.wifi_device_path.write().await;
-                        *device_path = Some(path.clone());
+    /// Open a new connection and find the WiFi device again
+    async fn reconnect(&self) -> Result<Connection> {
+        let conn = self.connection.open().await?;
+        info!("Connected to D-Bus system bus for Network control");
 
-                        // Get initial WiFi state
-                        if let Err(e) = self.update_wifi_state(&conn).await {
-                            warn!("Failed to get initial WiFi state: {}", e);
-                        }
-                    }
-                    Err(e) => {
-                        warn!("No WiFi device found: {}", e);
-                        return Err(AmiyaError::Backend(
-                            "No WiFi device available".to_string(),
-                        ));
-                    }
-                }
+        // The old connection's signals are gone
+        self.connection_changed.notify_one();
+
+        // Find WiFi device
+        match self.find_wifi_device(&conn).await {
+            Ok(path) => {
+                info!("Found WiFi device: {}", path);
+                *self.wifi_device_path.write().await = Some(path.clone());
+                self.has_wifi_device.store(true, Ordering::Relaxed);
 
-                Ok(())
+                // Get initial WiFi state
+                if let Err(e) = self.update_wifi_state(&conn).await {
+                    warn!("Failed to get initial WiFi state: {}", e);
+                }
             }
             Err(e) => {
-                warn!("Could not connect to D-Bus system bus: {}", e);
-                Err(AmiyaError::Backend(format!(
-                    "Failed to connect to D-Bus: {}",
-                    e
-                )))
+                warn!("No WiFi device found: {}", e);
+                *self.wifi_device_path.write().await = None;
+                self.has_wifi_device.store(false, Ordering::Relaxed);
+                return Err(AmiyaError::Backend(
+                    "No WiFi device available".to_string(),
+                ));
             }
         }
+
+        Ok(conn)
+    }
+
+    /// Connection for the next call, reconnecting if it was lost or
+    /// NetworkManager restarted
+    async fn connection(&self) -> Result<Connection> {
+        match self.connection.cached().await {
+            Some(conn) => Ok(conn),
+            None => self.reconnect().await,
+        }
     }
 
     /// Find WiFi device from NetworkManager
@@ -96,7 +161,7 @@ impl NetworkControl {
         let devices: Vec<zbus::zvariant::OwnedObjectPath> = nm_proxy
             .call_method("GetDevices", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to get devices: {}", e)))?
+            .map_err(|e| self.connection.error("Failed to get devices", e))?
             .body()
             .deserialize()
             .map_err(|e| AmiyaError::Backend(format!("Failed to deserialize devices: {}", e)))?;
@@ -153,13 +218,7 @@ impl NetworkControl {
 
     /// Check if network control is available
     pub fn is_available(&self) -> bool {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let conn = self.connection.read().await;
-                let device = self.wifi_device_path.read().await;
-                conn.is_some() && device.is_some()
-            })
-        })
+        self.connection.is_connected() && self.has_wifi_device.load(Ordering::Relaxed)
     }
 
     /// Check if WiFi is enabled
@@ -170,11 +229,7 @@ impl NetworkControl {
 
     /// Set WiFi enabled state
     pub async fn set_wifi_enabled(&self, enabled: bool) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+        let conn = &self.connection().await?;
 
         let nm_proxy = zbus::ProxyBuilder::new(conn)
             .interface("org.freedesktop.NetworkManager")
@@ -187,7 +242,7 @@ impl NetworkControl {
         nm_proxy
             .set_property("WirelessEnabled", enabled)
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to set WiFi enabled: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to set WiFi enabled", e))?;
 
         // Update local state
         {
@@ -206,14 +261,14 @@ impl NetworkControl {
     }
 
     /// Scan for WiFi networks
-    pub async fn scan(&self) -> Result<()> {
-        let conn_guard = self.connection.read().await;
+    ///
+    /// Returns `false` without scanning if a scan finished or was requested
+    /// within `MIN_SCAN_INTERVAL`; `get_networks` then returns the cached
+    /// access points right away.
+    pub async fn scan(&self) -> Result<bool> {
+        let conn = &self.connection().await?;
         let device_guard = self.wifi_device_path.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let device_path = device_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No WiFi device available".to_string()))?;
@@ -226,29 +281,69 @@ impl NetworkControl {
             .await
             .map_err(|e| AmiyaError::Backend(format!("Failed to create wireless proxy: {}", e)))?;
 
+        let mut last_request = self.last_scan_request.write().await;
+        let requested_recently = last_request.is_some_and(|t| t.elapsed() < MIN_SCAN_INTERVAL);
+
+        // NetworkManager also scans by itself; LastScan is in CLOCK_BOOTTIME
+        // milliseconds, or -1 if the device never scanned
+        let last_scan: i64 = wireless_proxy.get_property("LastScan").await.unwrap_or(-1);
+        let scanned_recently = boot_time()
+            .and_then(|now| scan_age(last_scan, now))
+            .is_some_and(|age| age < MIN_SCAN_INTERVAL);
+
+        if requested_recently || scanned_recently {
+            debug!("Skipping WiFi scan, results are recent");
+            return Ok(false);
+        }
+
         // Request scan with empty options
         let options: HashMap<String, zbus::zvariant::Value> = HashMap::new();
         wireless_proxy
             .call_method("RequestScan", &(options,))
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to request scan: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to request scan", e))?;
+        *last_request = Some(Instant::now());
 
         info!("WiFi scan requested");
 
         // Note: Actual scan results come via signals, which we'd subscribe to
         // For now, we'll just trigger a manual refresh after a delay
-        Ok(())
+        Ok(true)
+    }
+
+    /// Read an access point's properties, skipping it if the read fails
+    async fn read_access_point(
+        conn: &Connection,
+        ap_path: &zbus::zvariant::OwnedObjectPath,
+    ) -> Option<WifiNetwork> {
+        let props: zbus::Result<HashMap<String, OwnedValue>> = async {
+            conn.call_method(
+                Some("org.freedesktop.NetworkManager"),
+                ap_path.as_str(),
+                Some("org.freedesktop.DBus.Properties"),
+                "GetAll",
+                &("org.freedesktop.NetworkManager.AccessPoint",),
+            )
+            .await?
+            .body()
+            .deserialize()
+        }
+        .await;
+
+        match props {
+            Ok(props) => parse_access_point(&props),
+            Err(e) => {
+                debug!("Skipping access point {}: {}", ap_path.as_str(), e);
+                None
+            }
+        }
     }
 
     /// Get list of available WiFi networks
     pub async fn get_networks(&self) -> Result<Vec<WifiNetwork>> {
-        let conn_guard = self.connection.read().await;
+        let conn = &self.connection().await?;
         let device_guard = self.wifi_device_path.read().await;
 
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
         let device_path = device_guard
             .as_ref()
             .ok_or_else(|| AmiyaError::Backend("No WiFi device available".to_string()))?;
@@ -265,66 +360,21 @@ impl NetworkControl {
         let access_points: Vec<zbus::zvariant::OwnedObjectPath> = wireless_proxy
             .call_method("GetAccessPoints", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to get access points: {}", e)))?
+            .map_err(|e| self.connection.error("Failed to get access points", e))?
             .body()
             .deserialize()
             .map_err(|e| AmiyaError::Backend(format!("Failed to deserialize APs: {}", e)))?;
 
-        let mut networks = Vec::new();
-
-        // Get details for each access point
-        for ap_path in access_points {
-            if let Ok(ap_proxy) = zbus::ProxyBuilder::new(conn)
-                .interface("org.freedesktop.NetworkManager.AccessPoint")
-                .path(ap_path.as_str())
-                .ok()
-                .and_then(|b| b.destination("org.freedesktop.NetworkManager").ok())
-                .and_then(|b| tokio::task::block_in_place(|| {
-                    tokio::runtime::Handle::current().block_on(b.build::<zbus::Proxy>())
-                }).ok())
-            {
-                // Get SSID (as raw bytes)
-                let ssid_bytes: Vec<u8> = ap_proxy
-                    .get_property("Ssid")
-                    .await
-                    .unwrap_or_default();
-
-                let ssid = String::from_utf8(ssid_bytes).unwrap_or_default();
-
-                // Get signal strength (0-100)
-                let strength: u8 = ap_proxy
-                    .get_property("Strength")
-                    .await
-                    .unwrap_or(0);
-
-                // Get security flags
-                let flags: u32 = ap_proxy
-                    .get_property("Flags")
-                    .await
-                    .unwrap_or(0);
-
-                let wpa_flags: u32 = ap_proxy
-                    .get_property("WpaFlags")
-                    .await
-                    .unwrap_or(0);
-
-                let rsn_flags: u32 = ap_proxy
-                    .get_property("RsnFlags")
-                    .await
-                    .unwrap_or(0);
-
-                let secured = wpa_flags != 0 || rsn_flags != 0;
-
-                if !ssid.is_empty() {
-                    networks.push(WifiNetwork {
-                        ssid,
-                        signal_strength: strength,
-                        secured,
-                        connected: false, // TODO: Check active connection
-                    });
-                }
-            }
-        }
+        // Query all access points concurrently; each one is a single GetAll
+        // round trip instead of one call per property
+        let reads = access_points
+            .iter()
+            .map(|ap_path| Self::read_access_point(conn, ap_path));
+        let mut networks: Vec<WifiNetwork> = futures::future::join_all(reads)
+            .await
+            .into_iter()
+            .flatten()
+            .collect();
 
         // Sort by signal strength
         networks.sort_by(|a, b| b.signal_strength.cmp(&a.signal_strength));
@@ -340,21 +390,60 @@ impl NetworkControl {
     }
 
     /// Connect to a WiFi network
+    ///
+    /// A saved profile for `ssid` is activated as is, so its stored password
+    /// is reused and `password` is ignored. Otherwise a new profile is added.
     pub async fn connect(&self, ssid: &str, password: Option<&str>) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
-
-        info!("Connecting to WiFi network: {}", ssid);
+        {
+            let conn = &self.connection().await?;
 
-        // In a full implementation, we'd:
-        // 1. Create a connection settings dict with SSID, security type, password
-        // 2. Call AddAndActivateConnection on NetworkManager
-        // 3. Wait for connection state change signal
+            let device = self
+                .wifi_device_path
+                .read()
+                .await
+                .clone()
+                .ok_or_else(|| AmiyaError::Backend("No WiFi device available".to_string()))?;
+            let device = ObjectPath::try_from(device.as_str())
+                .map_err(|e| AmiyaError::Backend(format!("Invalid device path: {}", e)))?;
+            // "/" lets NetworkManager pick the access point
+            let root = ObjectPath::try_from("/").unwrap();
+
+            info!("Connecting to WiFi network: {}", ssid);
+
+            let saved = Self::profiles(conn)
+                .await?
+                .into_iter()
+                .find(|profile| profile.kind == WIFI_TYPE && profile.ssid.as_deref() == Some(ssid));
+
+            let result = match saved {
+                Some(profile) => {
+                    debug!("Reusing saved profile {} for {}", profile.id, ssid);
+                    conn.call_method(
+                        Some("org.freedesktop.NetworkManager"),
+                        "/org/freedesktop/NetworkManager",
+                        Some("org.freedesktop.NetworkManager"),
+                        "ActivateConnection",
+                        &(profile.path, &device, &root),
+                    )
+                    .await
+                }
+                None => {
+                    conn.call_method(
+                        Some("org.freedesktop.NetworkManager"),
+                        "/org/freedesktop/NetworkManager",
+                        Some("org.freedesktop.NetworkManager"),
+                        "AddAndActivateConnection",
+                        &(wifi_settings(ssid, password), &device, &root),
+                    )
+                    .await
+                }
+            };
+            result.map_err(|e| {
+                self.connection
+                    .error(&format!("Failed to connect to {}", ssid), e)
+            })?;
+        }
 
-        // For now, just emit event
         if let Some(events) = &self.events {
             events.emit(Event::WifiNetworkConnected {
                 ssid: ssid.to_string(),
@@ -380,6 +469,393 @@ impl NetworkControl {
         Ok(())
     }
 
+    /// Read the connected access point and emit `WifiSignalChanged` when its
+    /// SSID or strength changed, or `WifiNetworkDisconnected` when it's gone
+    pub async fn refresh_wifi_signal(&self) -> Result<()> {
+        let access_point = {
+            let conn = &self.connection().await?;
+            let device_guard = self.wifi_device_path.read().await;
+
+            let device_path = device_guard
+                .as_ref()
+                .ok_or_else(|| AmiyaError::Backend("No WiFi device available".to_string()))?;
+
+            let wireless_proxy = zbus::Proxy::new(
+                conn,
+                "org.freedesktop.NetworkManager",
+                device_path.as_str(),
+                "org.freedesktop.NetworkManager.Device.Wireless",
+            )
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to create wireless proxy: {}", e)))?;
+
+            let ap_path: OwnedObjectPath =
+                wireless_proxy
+                    .get_property("ActiveAccessPoint")
+                    .await
+                    .map_err(|e| self.connection.error("Failed to get access point", e))?;
+
+            // "/" means not connected
+            if ap_path.as_str() == "/" {
+                None
+            } else {
+                Self::read_access_point(conn, &ap_path)
+                    .await
+                    .map(|network| (network.ssid, network.signal_strength))
+            }
+        };
+
+        let previous = {
+            let mut connected_ap = self.connected_ap.write().await;
+            std::mem::replace(&mut *connected_ap, access_point.clone())
+        };
+        if previous == access_point {
+            return Ok(());
+        }
+
+        if let Some(events) = &self.events {
+            match access_point {
+                Some((ssid, strength)) => events.emit(Event::WifiSignalChanged { ssid, strength }),
+                None => events.emit(Event::WifiNetworkDisconnected),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Poll the connected access point's signal strength
+    ///
+    /// Each poll goes through `connection`, so it follows reconnects and
+    /// starts reporting once NetworkManager turns up.
+    pub async fn watch_wifi_signal(&self) {
+        let mut interval = tokio::time::interval(WIFI_SIGNAL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.refresh_wifi_signal().await {
+                debug!("Failed to read WiFi signal: {}", e);
+            }
+        }
+    }
+
+    /// Saved WiFi profiles, sorted by name
+    pub async fn list_saved_connections(&self) -> Result<Vec<SavedConnection>> {
+        let conn = &self.connection().await?;
+
+        let mut saved: Vec<SavedConnection> = Self::profiles(conn)
+            .await?
+            .into_iter()
+            .filter(|profile| profile.kind == WIFI_TYPE)
+            .map(|profile| SavedConnection {
+                name: profile.id,
+                uuid: profile.uuid,
+                ssid: profile.ssid,
+            })
+            .collect();
+        saved.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(saved)
+    }
+
+    /// Delete the saved profile with `uuid`, so its password is forgotten
+    pub async fn forget_connection(&self, uuid: &str) -> Result<()> {
+        let conn = &self.connection().await?;
+
+        let profile = Self::profiles(conn)
+            .await?
+            .into_iter()
+            .find(|profile| profile.uuid == uuid)
+            .ok_or_else(|| AmiyaError::Backend(format!("No saved connection {}", uuid)))?;
+
+        conn.call_method(
+            Some("org.freedesktop.NetworkManager"),
+            profile.path.as_str(),
+            Some("org.freedesktop.NetworkManager.Settings.Connection"),
+            "Delete",
+            &(),
+        )
+        .await
+        .map_err(|e| {
+            self.connection
+                .error(&format!("Failed to forget {}", profile.id), e)
+        })?;
+
+        info!("Forgot saved connection {}", profile.id);
+
+        Ok(())
+    }
+
+    /// VPN profiles, marking the ones that are up
+    pub async fn list_vpn_connections(&self) -> Result<Vec<VpnConnection>> {
+        let conn = &self.connection().await?;
+
+        let active = Self::active_connections(conn).await?;
+        let mut vpns: Vec<VpnConnection> = Self::profiles(conn)
+            .await?
+            .into_iter()
+            .filter(|profile| is_vpn_type(&profile.kind))
+            .map(|profile| VpnConnection {
+                active: active.iter().any(|(_, props)| {
+                    is_up(props) && string_prop(props, "Uuid").as_deref() == Some(&profile.uuid)
+                }),
+                name: profile.id,
+                uuid: profile.uuid,
+            })
+            .collect();
+        vpns.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(vpns)
+    }
+
+    /// Bring the VPN profile called `name` up or down
+    pub async fn set_vpn_active(&self, name: &str, active: bool) -> Result<()> {
+        {
+            let conn = &self.connection().await?;
+
+            if active {
+                let profile = Self::profiles(conn)
+                    .await?
+                    .into_iter()
+                    .find(|profile| is_vpn_type(&profile.kind) && profile.id == name)
+                    .ok_or_else(|| AmiyaError::Backend(format!("No VPN named {}", name)))?;
+
+                // "/" lets NetworkManager pick the device and specific object
+                let root = ObjectPath::try_from("/").unwrap();
+                conn.call_method(
+                    Some("org.freedesktop.NetworkManager"),
+                    "/org/freedesktop/NetworkManager",
+                    Some("org.freedesktop.NetworkManager"),
+                    "ActivateConnection",
+                    &(profile.path, &root, &root),
+                )
+                .await
+                .map_err(|e| self.connection.error("Failed to activate VPN", e))?;
+            } else {
+                let (path, _) = Self::active_connections(conn)
+                    .await?
+                    .into_iter()
+                    .find(|(_, props)| {
+                        string_prop(props, "Type").is_some_and(|t| is_vpn_type(&t))
+                            && string_prop(props, "Id").as_deref() == Some(name)
+                    })
+                    .ok_or_else(|| AmiyaError::Backend(format!("VPN {} is not active", name)))?;
+
+                conn.call_method(
+                    Some("org.freedesktop.NetworkManager"),
+                    "/org/freedesktop/NetworkManager",
+                    Some("org.freedesktop.NetworkManager"),
+                    "DeactivateConnection",
+                    &(path,),
+                )
+                .await
+                .map_err(|e| self.connection.error("Failed to deactivate VPN", e))?;
+            }
+        }
+
+        info!("VPN {} {}", name, if active { "activated" } else { "deactivated" });
+
+        self.refresh_vpn_state().await
+    }
+
+    /// Name of the active VPN, if one is up
+    pub async fn active_vpn(&self) -> Option<String> {
+        self.active_vpn.read().await.clone()
+    }
+
+    /// Re-read the active VPN and emit `VpnStateChanged` if it changed
+    pub async fn refresh_vpn_state(&self) -> Result<()> {
+        let name = {
+            let conn = &self.connection().await?;
+
+            let active = Self::active_connections(conn).await?;
+            let props: Vec<HashMap<String, OwnedValue>> =
+                active.into_iter().map(|(_, props)| props).collect();
+            active_vpn_name(&props)
+        };
+
+        let changed = {
+            let mut active_vpn = self.active_vpn.write().await;
+            let changed = *active_vpn != name;
+            *active_vpn = name.clone();
+            changed
+        };
+
+        if changed {
+            debug!("Active VPN: {:?}", name);
+            if let Some(events) = &self.events {
+                events.emit(Event::VpnStateChanged {
+                    active: name.is_some(),
+                    name,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Refresh the VPN state whenever NetworkManager's active connections
+    /// change
+    ///
+    /// Subscribes again on the new connection whenever `connection_changed`
+    /// fires.
+    pub async fn watch_vpn(&self) {
+        loop {
+            let conn = match self.connection().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    debug!("Not watching VPN state: {}", e);
+                    self.connection_changed.notified().await;
+                    continue;
+                }
+            };
+
+            let nm_proxy = match zbus::Proxy::new(
+                &conn,
+                "org.freedesktop.NetworkManager",
+                "/org/freedesktop/NetworkManager",
+                "org.freedesktop.NetworkManager",
+            )
+            .await
+            {
+                Ok(proxy) => proxy,
+                Err(e) => {
+                    debug!("Failed to create NM proxy: {}", e);
+                    self.connection_changed.notified().await;
+                    continue;
+                }
+            };
+
+            let mut changes = nm_proxy
+                .receive_property_changed::<Vec<OwnedObjectPath>>("ActiveConnections")
+                .await;
+
+            // Catch up on anything that changed while not subscribed
+            if let Err(e) = self.refresh_vpn_state().await {
+                debug!("Failed to read VPN state: {}", e);
+            }
+
+            loop {
+                tokio::select! {
+                    change = changes.next() => {
+                        if change.is_none() {
+                            // The connection is gone; wait for a new one
+                            // rather than spinning
+                            self.connection_changed.notified().await;
+                            break;
+                        }
+                        if let Err(e) = self.refresh_vpn_state().await {
+                            debug!("Failed to refresh VPN state: {}", e);
+                        }
+                    }
+                    _ = self.connection_changed.notified() => break,
+                }
+            }
+        }
+    }
+
+    /// Saved connection profiles from NetworkManager's settings service
+    async fn profiles(conn: &Connection) -> Result<Vec<Profile>> {
+        let paths: Vec<OwnedObjectPath> = conn
+            .call_method(
+                Some("org.freedesktop.NetworkManager"),
+                "/org/freedesktop/NetworkManager/Settings",
+                Some("org.freedesktop.NetworkManager.Settings"),
+                "ListConnections",
+                &(),
+            )
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to list connections: {}", e)))?
+            .body()
+            .deserialize()
+            .map_err(|e| AmiyaError::Backend(format!("Failed to parse connections: {}", e)))?;
+
+        let mut profiles = Vec::new();
+        for path in paths {
+            let settings: zbus::Result<HashMap<String, HashMap<String, OwnedValue>>> = async {
+                conn.call_method(
+                    Some("org.freedesktop.NetworkManager"),
+                    path.as_str(),
+                    Some("org.freedesktop.NetworkManager.Settings.Connection"),
+                    "GetSettings",
+                    &(),
+                )
+                .await?
+                .body()
+                .deserialize()
+            }
+            .await;
+
+            let settings = match settings {
+                Ok(settings) => settings,
+                Err(e) => {
+                    debug!("Skipping connection {}: {}", path.as_str(), e);
+                    continue;
+                }
+            };
+            let Some(connection) = settings.get("connection") else {
+                continue;
+            };
+            if let (Some(id), Some(uuid), Some(kind)) = (
+                string_prop(connection, "id"),
+                string_prop(connection, "uuid"),
+                string_prop(connection, "type"),
+            ) {
+                profiles.push(Profile {
+                    path,
+                    id,
+                    uuid,
+                    kind,
+                    ssid: wifi_ssid(&settings),
+                });
+            }
+        }
+
+        Ok(profiles)
+    }
+
+    /// Active connections and their properties
+    async fn active_connections(
+        conn: &Connection,
+    ) -> Result<Vec<(OwnedObjectPath, HashMap<String, OwnedValue>)>> {
+        let nm_proxy = zbus::Proxy::new(
+            conn,
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+        )
+        .await
+        .map_err(|e| AmiyaError::Backend(format!("Failed to create NM proxy: {}", e)))?;
+
+        let paths: Vec<OwnedObjectPath> = nm_proxy
+            .get_property("ActiveConnections")
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to get active connections: {}", e)))?;
+
+        let mut active = Vec::new();
+        for path in paths {
+            let props: zbus::Result<HashMap<String, OwnedValue>> = async {
+                conn.call_method(
+                    Some("org.freedesktop.NetworkManager"),
+                    path.as_str(),
+                    Some("org.freedesktop.DBus.Properties"),
+                    "GetAll",
+                    &("org.freedesktop.NetworkManager.Connection.Active",),
+                )
+                .await?
+                .body()
+                .deserialize()
+            }
+            .await;
+
+            match props {
+                Ok(props) => active.push((path, props)),
+                // The connection may have gone down since the list was read
+                Err(e) => debug!("Skipping active connection {}: {}", path.as_str(), e),
+            }
+        }
+
+        Ok(active)
+    }
+
     /// Start monitoring network events
     /// This would subscribe to D-Bus signals in a full implementation
     pub async fn start_monitoring(&self) -> Result<()> {
@@ -402,17 +878,140 @@ impl Default for NetworkControl {
 pub fn create_network_control_sync(events: EventManager) -> Arc<NetworkControl> {
     let network = Arc::new(NetworkControl::with_events(events));
 
-    // Try to connect in background
+    // Connect in background, retrying while the service starts up
     let network_clone = network.clone();
     tokio::spawn(async move {
-        if let Err(e) = network_clone.connect().await {
+        let result = connect_with_backoff("Network control", Backoff::default(), || {
+            network_clone.connect()
+        })
+        .await;
+        if let Err(e) = result {
             warn!("Failed to connect Network control: {}", e);
         }
+
+        // Both pick up a later reconnect if NetworkManager wasn't reachable
+        let signal_watcher = network_clone.clone();
+        tokio::spawn(async move { signal_watcher.watch_wifi_signal().await });
+        network_clone.watch_vpn().await;
     });
 
     network
 }
 
+/// Time since boot including suspend, the clock NetworkManager's LastScan uses
+fn boot_time() -> Option<Duration> {
+    let uptime = std::fs::read_to_string("/proc/uptime").ok()?;
+    let seconds: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    Some(Duration::from_secs_f64(seconds))
+}
+
+/// Age of a scan that finished at `last_scan_ms` (boot time), if it happened
+fn scan_age(last_scan_ms: i64, now: Duration) -> Option<Duration> {
+    let last_scan = Duration::from_millis(u64::try_from(last_scan_ms).ok()?);
+    now.checked_sub(last_scan)
+}
+
+/// SSID of a WiFi profile from its `GetSettings` reply
+fn wifi_ssid(settings: &HashMap<String, HashMap<String, OwnedValue>>) -> Option<String> {
+    let bytes: Vec<u8> = settings
+        .get(WIFI_TYPE)?
+        .get("ssid")?
+        .try_clone()
+        .ok()
+        .and_then(|v| Vec::try_from(v).ok())?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Settings for a new WiFi profile, WPA-PSK secured if `password` is given
+fn wifi_settings<'a>(
+    ssid: &'a str,
+    password: Option<&'a str>,
+) -> HashMap<&'static str, HashMap<&'static str, Value<'a>>> {
+    let mut settings = HashMap::new();
+    settings.insert(
+        "connection",
+        HashMap::from([("type", Value::from(WIFI_TYPE)), ("id", Value::from(ssid))]),
+    );
+    settings.insert(
+        WIFI_TYPE,
+        HashMap::from([("ssid", Value::from(ssid.as_bytes().to_vec()))]),
+    );
+    if let Some(password) = password {
+        settings.insert(
+            "802-11-wireless-security",
+            HashMap::from([
+                ("key-mgmt", Value::from("wpa-psk")),
+                ("psk", Value::from(password)),
+            ]),
+        );
+    }
+    settings
+}
+
+/// Connection types NetworkManager uses for VPNs
+fn is_vpn_type(kind: &str) -> bool {
+    matches!(kind, "vpn" | "wireguard")
+}
+
+fn string_prop(props: &HashMap<String, OwnedValue>, name: &str) -> Option<String> {
+    props
+        .get(name)
+        .and_then(|v| v.downcast_ref::<&str>().ok())
+        .map(str::to_string)
+}
+
+/// Whether an active connection is activating or activated
+fn is_up(props: &HashMap<String, OwnedValue>) -> bool {
+    props
+        .get("State")
+        .and_then(|v| v.downcast_ref::<u32>().ok())
+        .is_some_and(|state| ACTIVE_CONNECTION_UP.contains(&state))
+}
+
+/// Name of the first VPN among active connection properties
+fn active_vpn_name(active: &[HashMap<String, OwnedValue>]) -> Option<String> {
+    active
+        .iter()
+        .filter(|props| is_up(props))
+        .filter(|props| string_prop(props, "Type").is_some_and(|t| is_vpn_type(&t)))
+        .find_map(|props| string_prop(props, "Id"))
+}
+
+/// Build a network from an AccessPoint `GetAll` reply
+///
+/// Hidden networks (empty SSID) are skipped.
+fn parse_access_point(props: &HashMap<String, OwnedValue>) -> Option<WifiNetwork> {
+    let ssid_bytes: Vec<u8> = props
+        .get("Ssid")
+        .and_then(|v| v.try_clone().ok())
+        .and_then(|v| Vec::try_from(v).ok())
+        .unwrap_or_default();
+    let ssid = String::from_utf8(ssid_bytes).unwrap_or_default();
+    if ssid.is_empty() {
+        return None;
+    }
+
+    let u32_prop = |name: &str| {
+        props
+            .get(name)
+            .and_then(|v| v.downcast_ref::<u32>().ok())
+            .unwrap_or(0)
+    };
+
+    // Signal strength (0-100)
+    let strength = props
+        .get("Strength")
+        .and_then(|v| v.downcast_ref::<u8>().ok())
+        .unwrap_or(0);
+
+    Some(WifiNetwork {
+        ssid,
+        signal_strength: strength,
+        secured: u32_prop("WpaFlags") != 0 || u32_prop("RsnFlags") != 0,
+        connected: false, // TODO: Check active connection
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -430,4 +1029,75 @@ mod tests {
         // Default state should be false (not connected)
         assert_eq!(nm.is_wifi_enabled().await.unwrap(), false);
     }
+
+    #[test]
+    fn test_parse_access_point() {
+        let value = |v: Value<'static>| OwnedValue::try_from(v).unwrap();
+        let mut props = HashMap::new();
+        props.insert("Ssid".to_string(), value(Value::from(b"home".to_vec())));
+        props.insert("Strength".to_string(), value(Value::U8(72)));
+        props.insert("WpaFlags".to_string(), value(Value::U32(0)));
+        props.insert("RsnFlags".to_string(), value(Value::U32(0x188)));
+
+        let network = parse_access_point(&props).unwrap();
+        assert_eq!(network.ssid, "home");
+        assert_eq!(network.signal_strength, 72);
+        assert!(network.secured);
+
+        // Hidden networks have no SSID
+        props.insert("Ssid".to_string(), value(Value::from(Vec::<u8>::new())));
+        assert!(parse_access_point(&props).is_none());
+    }
+
+    #[test]
+    fn test_scan_age() {
+        let now = Duration::from_secs(100);
+        assert_eq!(scan_age(95_000, now), Some(Duration::from_secs(5)));
+        // Never scanned
+        assert_eq!(scan_age(-1, now), None);
+        // Clock mismatch, e.g. LastScan read after `now`
+        assert_eq!(scan_age(101_000, now), None);
+    }
+
+    #[test]
+    fn test_wifi_ssid() {
+        let value = |v: Value<'static>| OwnedValue::try_from(v).unwrap();
+        let mut wifi = HashMap::new();
+        wifi.insert("ssid".to_string(), value(Value::from(b"home".to_vec())));
+        let mut settings = HashMap::new();
+        settings.insert(WIFI_TYPE.to_string(), wifi);
+        assert_eq!(wifi_ssid(&settings), Some("home".to_string()));
+
+        // Wired and VPN profiles have no SSID
+        settings.clear();
+        settings.insert("connection".to_string(), HashMap::new());
+        assert_eq!(wifi_ssid(&settings), None);
+
+        let new = wifi_settings("cafe", None);
+        assert!(!new.contains_key("802-11-wireless-security"));
+        let new = wifi_settings("home", Some("hunter2"));
+        assert_eq!(new["802-11-wireless-security"]["psk"], Value::from("hunter2"));
+    }
+
+    #[test]
+    fn test_active_vpn_name() {
+        let value = |v: Value<'static>| OwnedValue::try_from(v).unwrap();
+        let connection = |id: &'static str, kind: &'static str, state: u32| {
+            let mut props = HashMap::new();
+            props.insert("Id".to_string(), value(Value::from(id)));
+            props.insert("Type".to_string(), value(Value::from(kind)));
+            props.insert("State".to_string(), value(Value::U32(state)));
+            props
+        };
+
+        let wifi = || connection("home", "802-11-wireless", 2);
+        assert_eq!(active_vpn_name(&[wifi()]), None);
+
+        let vpn = connection("work", "vpn", 2);
+        assert_eq!(active_vpn_name(&[wifi(), vpn]), Some("work".to_string()));
+
+        // Deactivating (3) counts as down
+        let wireguard = connection("wg0", "wireguard", 3);
+        assert_eq!(active_vpn_name(&[wifi(), wireguard]), None);
+    }
 }
@@ -1,7 +1,7 @@
+use crate::backend::dbus::{Bus, DbusConnection};
 use crate::error::{AmiyaError, Result};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tracing::{debug, info};
 use zbus::Connection;
 
 /// Power action types
@@ -28,54 +28,42 @@ impl std::fmt::Display for PowerAction {
 
 /// Power management via systemd/logind D-Bus
 pub struct PowerControl {
-    connection: Arc<RwLock<Option<Connection>>>,
+    connection: DbusConnection,
 }
 
 impl PowerControl {
     /// Create a new power control instance
     pub fn new() -> Self {
         PowerControl {
-            connection: Arc::new(RwLock::new(None)),
+            connection: DbusConnection::new(Bus::System),
         }
     }
 
     /// Initialize connection to systemd/logind
     pub async fn connect(&self) -> Result<()> {
-        match Connection::system().await {
-            Ok(conn) => {
-                info!("Connected to D-Bus system bus for Power control");
-
-                // Store connection
-                {
-                    let mut connection = self.connection.write().await;
-                    *connection = Some(conn);
-                }
+        self.connection.open().await?;
+        info!("Connected to D-Bus system bus for Power control");
+        Ok(())
+    }
 
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Could not connect to D-Bus system bus: {}", e);
-                Err(AmiyaError::Backend(format!(
-                    "Failed to connect to D-Bus: {}",
-                    e
-                )))
-            }
+    /// Connection for the next call, reconnecting if it was lost
+    async fn connection(&self) -> Result<Connection> {
+        match self.connection.cached().await {
+            Some(conn) => Ok(conn),
+            None => self.connection.open().await,
         }
     }
 
     /// Execute a power action
     pub async fn execute(&self, action: PowerAction) -> Result<()> {
-        let conn_guard = self.connection.read().await;
-        let conn = conn_guard
-            .as_ref()
-            .ok_or_else(|| AmiyaError::Backend("Not connected to D-Bus".to_string()))?;
+        let conn = self.connection().await?;
 
         match action {
-            PowerAction::Shutdown => self.shutdown(conn).await,
-            PowerAction::Reboot => self.reboot(conn).await,
-            PowerAction::Suspend => self.suspend(conn).await,
-            PowerAction::Hibernate => self.hibernate(conn).await,
-            PowerAction::Lock => self.lock(conn).await,
+            PowerAction::Shutdown => self.shutdown(&conn).await,
+            PowerAction::Reboot => self.reboot(&conn).await,
+            PowerAction::Suspend => self.suspend(&conn).await,
+            PowerAction::Hibernate => self.hibernate(&conn).await,
+            PowerAction::Lock => self.lock(&conn).await,
         }
     }
 
@@ -97,7 +85,7 @@ impl PowerControl {
         proxy
             .call_method("PowerOff", &(true,))
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to shutdown: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to shutdown", e))?;
 
         Ok(())
     }
@@ -120,7 +108,7 @@ impl PowerControl {
         proxy
             .call_method("Reboot", &(true,))
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to reboot: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to reboot", e))?;
 
         Ok(())
     }
@@ -143,7 +131,7 @@ impl PowerControl {
         proxy
             .call_method("Suspend", &(true,))
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to suspend: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to suspend", e))?;
 
         Ok(())
     }
@@ -166,7 +154,7 @@ impl PowerControl {
         proxy
             .call_method("Hibernate", &(true,))
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to hibernate: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to hibernate", e))?;
 
         Ok(())
     }
@@ -192,7 +180,7 @@ impl PowerControl {
         proxy
             .call_method("Lock", &())
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to lock screen: {}", e)))?;
+            .map_err(|e| self.connection.error("Failed to lock screen", e))?;
 
         Ok(())
     }
@@ -218,7 +206,7 @@ impl PowerControl {
         let session_path: zbus::zvariant::OwnedObjectPath = proxy
             .call_method("GetSessionByPID", &(pid,))
             .await
-            .map_err(|e| AmiyaError::Backend(format!("Failed to get session: {}", e)))?
+            .map_err(|e| self.connection.error("Failed to get session", e))?
             .body()
             .deserialize()
             .map_err(|e| AmiyaError::Backend(format!("Failed to deserialize session: {}", e)))?;
@@ -228,12 +216,11 @@ impl PowerControl {
 
     /// Check if action is available
     pub async fn can_execute(&self, action: PowerAction) -> bool {
-        let conn_guard = self.connection.read().await;
-        let Some(conn) = conn_guard.as_ref() else {
+        let Ok(conn) = self.connection().await else {
             return false;
         };
 
-        let Ok(proxy) = zbus::ProxyBuilder::new(conn)
+        let Ok(proxy) = zbus::ProxyBuilder::new(&conn)
             .interface("org.freedesktop.login1.Manager")
             .path("/org/freedesktop/login1")
             .ok()
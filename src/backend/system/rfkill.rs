@@ -0,0 +1,161 @@
+use crate::backend::system::NetworkControl;
+use crate::backend::traits::BluetoothBackend;
+use crate::error::Result;
+use crate::events::{Event, EventManager};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Radio states saved when airplane mode was turned on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RadioStates {
+    wifi: bool,
+    bluetooth: bool,
+}
+
+/// Airplane mode: turns WiFi and Bluetooth off together
+///
+/// The radios are switched through NetworkManager and BlueZ rather than
+/// `/dev/rfkill`, which needs extra permissions. Turning airplane mode off
+/// restores whichever radios were on before.
+pub struct AirplaneMode {
+    network: Option<Arc<NetworkControl>>,
+    bluetooth: Option<Arc<dyn BluetoothBackend>>,
+    /// `Some` while airplane mode is on
+    saved: Mutex<Option<RadioStates>>,
+    events: EventManager,
+}
+
+impl AirplaneMode {
+    pub fn new(
+        network: Option<Arc<NetworkControl>>,
+        bluetooth: Option<Arc<dyn BluetoothBackend>>,
+        events: EventManager,
+    ) -> Self {
+        AirplaneMode {
+            network,
+            bluetooth,
+            saved: Mutex::new(None),
+            events,
+        }
+    }
+
+    /// Whether airplane mode is on
+    pub async fn is_enabled(&self) -> bool {
+        self.saved.lock().await.is_some()
+    }
+
+    /// Turn airplane mode on or off
+    pub async fn set_enabled(&self, enabled: bool) -> Result<()> {
+        let mut saved = self.saved.lock().await;
+        self.switch(&mut saved, enabled).await
+    }
+
+    /// Flip the current state
+    pub async fn toggle(&self) -> Result<()> {
+        let mut saved = self.saved.lock().await;
+        let enabled = saved.is_none();
+        self.switch(&mut saved, enabled).await
+    }
+
+    /// Switch the radios with the saved states locked, so concurrent calls
+    /// don't both act on the same starting state
+    async fn switch(&self, saved: &mut Option<RadioStates>, enabled: bool) -> Result<()> {
+        if enabled == saved.is_some() {
+            return Ok(());
+        }
+
+        let result = match *saved {
+            None => {
+                // Saved before switching anything, so radios that did turn
+                // off come back even if another one failed to
+                *saved = Some(self.radio_states().await);
+                self.set_radios(RadioStates {
+                    wifi: false,
+                    bluetooth: false,
+                })
+                .await
+            }
+            Some(states) => {
+                self.set_radios(states).await?;
+                *saved = None;
+                Ok(())
+            }
+        };
+
+        info!("Airplane mode: {}", enabled);
+        self.events.emit(Event::AirplaneModeChanged { enabled });
+
+        result
+    }
+
+    async fn radio_states(&self) -> RadioStates {
+        let wifi = match &self.network {
+            Some(network) => network.is_wifi_enabled().await.unwrap_or(false),
+            None => false,
+        };
+        let bluetooth = match &self.bluetooth {
+            Some(bluetooth) => bluetooth.is_powered().await.unwrap_or(false),
+            None => false,
+        };
+        RadioStates { wifi, bluetooth }
+    }
+
+    /// Apply both radio states, trying both even if the first one fails
+    async fn set_radios(&self, states: RadioStates) -> Result<()> {
+        let mut result = Ok(());
+
+        if let Some(network) = &self.network {
+            if let Err(e) = network.set_wifi_enabled(states.wifi).await {
+                warn!("Failed to switch WiFi for airplane mode: {}", e);
+                result = Err(e);
+            }
+        }
+        if let Some(bluetooth) = &self.bluetooth {
+            if let Err(e) = bluetooth.set_powered(states.bluetooth).await {
+                warn!("Failed to switch Bluetooth for airplane mode: {}", e);
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::fake::FakeBluetooth;
+
+    #[tokio::test]
+    async fn test_airplane_mode_restores_radios() {
+        let events = EventManager::default();
+        let mut receiver = events.subscribe();
+        let bluetooth = Arc::new(FakeBluetooth::new(Vec::new()));
+        let airplane = AirplaneMode::new(None, Some(bluetooth.clone()), events);
+
+        airplane.set_enabled(true).await.unwrap();
+        assert!(airplane.is_enabled().await);
+        assert!(!bluetooth.is_powered().await.unwrap());
+        assert!(matches!(
+            receiver.recv().await.unwrap(),
+            Event::AirplaneModeChanged { enabled: true }
+        ));
+
+        airplane.toggle().await.unwrap();
+        assert!(!airplane.is_enabled().await);
+        assert!(bluetooth.is_powered().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_airplane_mode_keeps_radios_off() {
+        let bluetooth = Arc::new(FakeBluetooth::new(Vec::new()));
+        bluetooth.set_powered(false).await.unwrap();
+        let airplane = AirplaneMode::new(None, Some(bluetooth.clone()), EventManager::default());
+
+        // Bluetooth was off before, so it stays off afterwards
+        airplane.set_enabled(true).await.unwrap();
+        airplane.set_enabled(false).await.unwrap();
+        assert!(!bluetooth.is_powered().await.unwrap());
+    }
+}
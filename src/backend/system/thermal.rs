@@ -0,0 +1,145 @@
+use crate::error::{AmiyaError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Sensor names that report the CPU package/die temperature
+const CPU_SENSORS: [&str; 4] = ["x86_pkg_temp", "k10temp", "coretemp", "zenpower"];
+
+/// A temperature input file in sysfs
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempSensor {
+    /// File containing the temperature in millidegrees Celsius
+    pub path: PathBuf,
+
+    /// Human-readable sensor name, e.g. "k10temp" or "thermal_zone2 (acpitz)"
+    pub label: String,
+}
+
+impl TempSensor {
+    /// Use an explicit sensor file, labelled with its path
+    pub fn from_path(path: PathBuf) -> Self {
+        let label = path.display().to_string();
+        TempSensor { path, label }
+    }
+
+    /// Find the sensor most likely to report the CPU temperature
+    pub fn discover() -> Option<Self> {
+        Self::discover_in(Path::new("/sys/class"))
+    }
+
+    /// Scan thermal zones and hwmon devices under `sysfs_class`, preferring
+    /// known CPU sensors over chipset/ACPI zones
+    fn discover_in(sysfs_class: &Path) -> Option<Self> {
+        let mut candidates = Vec::new();
+
+        // thermal_zone*/type + thermal_zone*/temp
+        if let Ok(entries) = fs::read_dir(sysfs_class.join("thermal")) {
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                let zone = entry.file_name().to_string_lossy().to_string();
+                if !zone.starts_with("thermal_zone") {
+                    continue;
+                }
+                let Ok(kind) = fs::read_to_string(dir.join("type")) else {
+                    continue;
+                };
+                let kind = kind.trim().to_string();
+                candidates.push((
+                    sensor_priority(&kind),
+                    TempSensor {
+                        path: dir.join("temp"),
+                        label: format!("{} ({})", zone, kind),
+                    },
+                ));
+            }
+        }
+
+        // hwmon*/name + hwmon*/temp1_input (k10temp and coretemp usually live here)
+        if let Ok(entries) = fs::read_dir(sysfs_class.join("hwmon")) {
+            for entry in entries.flatten() {
+                let dir = entry.path();
+                let Ok(name) = fs::read_to_string(dir.join("name")) else {
+                    continue;
+                };
+                let name = name.trim().to_string();
+                candidates.push((
+                    sensor_priority(&name),
+                    TempSensor {
+                        path: dir.join("temp1_input"),
+                        label: name,
+                    },
+                ));
+            }
+        }
+
+        candidates.retain(|(_, sensor)| sensor.path.exists());
+        // Break ties by label so the choice doesn't depend on directory order
+        candidates.sort_by_key(|(priority, sensor)| (*priority, sensor.label.clone()));
+        candidates.into_iter().next().map(|(_, sensor)| sensor)
+    }
+
+    /// Read the temperature in degrees Celsius
+    pub fn read_celsius(&self) -> Result<i32> {
+        let content = fs::read_to_string(&self.path)?;
+        let millidegrees = content.trim().parse::<i32>().map_err(|e| {
+            AmiyaError::Backend(format!("Invalid temperature in {:?}: {}", self.path, e))
+        })?;
+        Ok(millidegrees / 1000)
+    }
+}
+
+/// Lower is better: CPU package sensors first, generic CPU zones next, ACPI last
+fn sensor_priority(name: &str) -> u8 {
+    let name = name.to_lowercase();
+    if CPU_SENSORS.iter().any(|s| name == *s) {
+        0
+    } else if name.contains("cpu") {
+        1
+    } else if name == "acpitz" {
+        3
+    } else {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_sensor_priority() {
+        assert_eq!(sensor_priority("x86_pkg_temp"), 0);
+        assert_eq!(sensor_priority("k10temp"), 0);
+        assert_eq!(sensor_priority("cpu-thermal"), 1);
+        assert_eq!(sensor_priority("nvme"), 2);
+        assert_eq!(sensor_priority("acpitz"), 3);
+    }
+
+    #[test]
+    fn test_discover_prefers_cpu_sensor() {
+        let root = std::env::temp_dir().join(format!("amiya-thermal-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        write(&root.join("thermal/thermal_zone0/type"), "acpitz\n");
+        write(&root.join("thermal/thermal_zone0/temp"), "40000\n");
+        write(&root.join("hwmon/hwmon1/name"), "k10temp\n");
+        write(&root.join("hwmon/hwmon1/temp1_input"), "55500\n");
+
+        let sensor = TempSensor::discover_in(&root).unwrap();
+        assert_eq!(sensor.label, "k10temp");
+        assert_eq!(sensor.read_celsius().unwrap(), 55);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_discover_without_sensors() {
+        let root = std::env::temp_dir().join(format!("amiya-no-thermal-{}", std::process::id()));
+        assert!(TempSensor::discover_in(&root).is_none());
+    }
+}
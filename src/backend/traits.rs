@@ -0,0 +1,288 @@
+//! Backend interfaces used by the UI and IPC layers
+//!
+//! `AppState` holds backends behind these traits so popups and IPC handlers
+//! can run against the in-memory fakes in `backend::fake` instead of a live
+//! D-Bus session.
+
+use crate::backend::system::audio::AudioControl;
+use crate::backend::system::backlight::BacklightControl;
+use crate::backend::system::bluetooth::{BluetoothControl, BluetoothDevice};
+#[cfg(feature = "ddc")]
+use crate::backend::system::ddc::DdcBacklight;
+use crate::backend::system::relative_step;
+use crate::error::Result;
+use futures::future::BoxFuture;
+
+/// Output volume and mute
+pub trait VolumeBackend: Send + Sync {
+    /// Current volume level (0-100, higher when boosted)
+    fn get_volume(&self) -> BoxFuture<'_, Result<f64>>;
+
+    /// Set volume level, clamped to the configured maximum
+    fn set_volume(&self, volume: f64) -> BoxFuture<'_, Result<()>>;
+
+    /// Current mute state
+    fn get_mute(&self) -> BoxFuture<'_, Result<bool>>;
+
+    /// Set mute state
+    fn set_mute(&self, muted: bool) -> BoxFuture<'_, Result<()>>;
+
+    /// Whether raising the volume while muted unmutes
+    fn set_unmute_on_volume_up(&self, enabled: bool);
+
+    /// Set the volume ceiling in percent (100 or more to allow boost)
+    fn set_max_volume(&self, percent: u32);
+
+    /// Increase volume by step
+    fn increase_volume(&self, step: f64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let current = self.get_volume().await?;
+            self.set_volume(current + step).await
+        })
+    }
+
+    /// Decrease volume by step
+    fn decrease_volume(&self, step: f64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let current = self.get_volume().await?;
+            self.set_volume((current - step).max(0.0)).await
+        })
+    }
+
+    /// Change volume by `percent` percent of the current level
+    ///
+    /// Raising goes through `increase_volume`, so it unmutes the same way.
+    fn set_volume_relative(&self, percent: f64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let step = relative_step(self.get_volume().await?, percent);
+            if step > 0.0 {
+                self.increase_volume(step).await
+            } else {
+                self.decrease_volume(-step).await
+            }
+        })
+    }
+
+    /// Toggle mute state
+    fn toggle_mute(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let muted = self.get_mute().await?;
+            self.set_mute(!muted).await
+        })
+    }
+}
+
+/// Screen brightness, of a laptop panel or external monitors
+pub trait BrightnessBackend: Send + Sync {
+    /// Whether a device to control has been found
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Current brightness level (0-100)
+    fn get_brightness(&self) -> BoxFuture<'_, Result<f64>>;
+
+    /// Set brightness level (0-100) immediately
+    fn set_brightness(&self, brightness: f64) -> BoxFuture<'_, Result<()>>;
+
+    /// Fade brightness to `target` over `duration_ms`; backends too slow to
+    /// fade go straight to `target`
+    fn set_brightness_smooth(&self, target: f64, _duration_ms: u64) -> BoxFuture<'_, Result<()>> {
+        self.set_brightness(target)
+    }
+
+    /// Level relative changes start from: the target of a running fade, so
+    /// repeated key presses add up, or else the current level
+    fn pending_brightness(&self) -> BoxFuture<'_, Result<f64>> {
+        self.get_brightness()
+    }
+
+    /// Fade brightness up by step over `duration_ms`
+    fn increase_brightness_smooth(&self, step: f64, duration_ms: u64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let current = self.pending_brightness().await?;
+            self.set_brightness_smooth((current + step).min(100.0), duration_ms)
+                .await
+        })
+    }
+
+    /// Fade brightness down by step over `duration_ms`
+    fn decrease_brightness_smooth(&self, step: f64, duration_ms: u64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let current = self.pending_brightness().await?;
+            self.set_brightness_smooth((current - step).max(0.0), duration_ms)
+                .await
+        })
+    }
+
+    /// Fade brightness by `percent` percent of the current level over
+    /// `duration_ms`
+    fn set_brightness_relative_smooth(
+        &self,
+        percent: f64,
+        duration_ms: u64,
+    ) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let current = self.pending_brightness().await?;
+            let step = relative_step(current, percent);
+            self.set_brightness_smooth((current + step).clamp(0.0, 100.0), duration_ms)
+                .await
+        })
+    }
+}
+
+/// Bluetooth adapter and devices
+pub trait BluetoothBackend: Send + Sync {
+    /// Whether the adapter is powered
+    fn is_powered(&self) -> BoxFuture<'_, Result<bool>>;
+
+    /// Power the adapter on or off
+    fn set_powered(&self, enabled: bool) -> BoxFuture<'_, Result<()>>;
+
+    /// Start device discovery
+    fn start_scan(&self) -> BoxFuture<'_, Result<()>>;
+
+    /// Stop device discovery
+    fn stop_scan(&self) -> BoxFuture<'_, Result<()>>;
+
+    /// Known devices
+    fn get_devices(&self) -> BoxFuture<'_, Result<Vec<BluetoothDevice>>>;
+
+    /// Connect to a device by address
+    fn connect_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Disconnect from a device by address
+    fn disconnect_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Pair with a device by address
+    fn pair_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Remove (unpair) a device by address
+    fn remove_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Mark a device as trusted or untrusted by address
+    fn set_trusted<'a>(&'a self, address: &'a str, trusted: bool) -> BoxFuture<'a, Result<()>>;
+}
+
+impl VolumeBackend for AudioControl {
+    fn get_volume(&self) -> BoxFuture<'_, Result<f64>> {
+        Box::pin(AudioControl::get_volume(self))
+    }
+
+    fn set_volume(&self, volume: f64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(AudioControl::set_volume(self, volume))
+    }
+
+    fn get_mute(&self) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(AudioControl::get_mute(self))
+    }
+
+    fn set_mute(&self, muted: bool) -> BoxFuture<'_, Result<()>> {
+        Box::pin(AudioControl::set_mute(self, muted))
+    }
+
+    fn set_unmute_on_volume_up(&self, enabled: bool) {
+        AudioControl::set_unmute_on_volume_up(self, enabled)
+    }
+
+    fn set_max_volume(&self, percent: u32) {
+        AudioControl::set_max_volume(self, percent)
+    }
+
+    fn increase_volume(&self, step: f64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(AudioControl::increase_volume(self, step))
+    }
+
+    fn decrease_volume(&self, step: f64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(AudioControl::decrease_volume(self, step))
+    }
+
+    fn toggle_mute(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(AudioControl::toggle_mute(self))
+    }
+}
+
+impl BrightnessBackend for BacklightControl {
+    fn is_available(&self) -> bool {
+        BacklightControl::is_available(self)
+    }
+
+    fn get_brightness(&self) -> BoxFuture<'_, Result<f64>> {
+        Box::pin(BacklightControl::get_brightness(self))
+    }
+
+    fn set_brightness(&self, brightness: f64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(BacklightControl::set_brightness(self, brightness))
+    }
+
+    fn set_brightness_smooth(&self, target: f64, duration_ms: u64) -> BoxFuture<'_, Result<()>> {
+        Box::pin(BacklightControl::set_brightness_smooth(
+            self,
+            target,
+            duration_ms,
+        ))
+    }
+
+    fn pending_brightness(&self) -> BoxFuture<'_, Result<f64>> {
+        Box::pin(BacklightControl::pending_brightness(self))
+    }
+}
+
+#[cfg(feature = "ddc")]
+impl BrightnessBackend for DdcBacklight {
+    fn is_available(&self) -> bool {
+        DdcBacklight::is_available(self)
+    }
+
+    fn get_brightness(&self) -> BoxFuture<'_, Result<f64>> {
+        let result = DdcBacklight::get_brightness(self);
+        Box::pin(async move { result })
+    }
+
+    fn set_brightness(&self, brightness: f64) -> BoxFuture<'_, Result<()>> {
+        let result = DdcBacklight::set_brightness(self, brightness);
+        Box::pin(async move { result })
+    }
+}
+
+impl BluetoothBackend for BluetoothControl {
+    fn is_powered(&self) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(BluetoothControl::is_powered(self))
+    }
+
+    fn set_powered(&self, enabled: bool) -> BoxFuture<'_, Result<()>> {
+        Box::pin(BluetoothControl::set_powered(self, enabled))
+    }
+
+    fn start_scan(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(BluetoothControl::start_scan(self))
+    }
+
+    fn stop_scan(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(BluetoothControl::stop_scan(self))
+    }
+
+    fn get_devices(&self) -> BoxFuture<'_, Result<Vec<BluetoothDevice>>> {
+        Box::pin(BluetoothControl::get_devices(self))
+    }
+
+    fn connect_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(BluetoothControl::connect_device(self, address))
+    }
+
+    fn disconnect_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(BluetoothControl::disconnect_device(self, address))
+    }
+
+    fn pair_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(BluetoothControl::pair_device(self, address))
+    }
+
+    fn remove_device<'a>(&'a self, address: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(BluetoothControl::remove_device(self, address))
+    }
+
+    fn set_trusted<'a>(&'a self, address: &'a str, trusted: bool) -> BoxFuture<'a, Result<()>> {
+        Box::pin(BluetoothControl::set_trusted(self, address, trusted))
+    }
+}
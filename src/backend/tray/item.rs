@@ -0,0 +1,196 @@
+use crate::error::{AmiyaError, Result};
+use crate::events::{TrayIconPixmap, TrayItemInfo};
+use zbus::proxy::CacheProperties;
+use zbus::Connection;
+
+pub(crate) const ITEM_INTERFACE: &str = "org.kde.StatusNotifierItem";
+
+/// Object path used by items that register with just a bus name
+const DEFAULT_ITEM_PATH: &str = "/StatusNotifierItem";
+
+/// Preferred icon size when an item offers several pixmaps
+const PREFERRED_ICON_SIZE: i32 = 22;
+
+/// `(width, height, ARGB32 data)` as sent over D-Bus
+type Pixmap = (i32, i32, Vec<u8>);
+
+/// `(icon name, icon pixmaps, title, description)`
+type ToolTip = (String, Vec<Pixmap>, String, String);
+
+/// Build an item id ("bus name" + "object path") from what an item passed to
+/// `RegisterStatusNotifierItem`
+///
+/// Items either register a bus name (KDE style, path defaults to
+/// `/StatusNotifierItem`) or only an object path (libappindicator style), in
+/// which case the bus name is the caller's.
+pub fn item_id(service: &str, sender: &str) -> String {
+    if service.starts_with('/') {
+        format!("{}{}", sender, service)
+    } else if service.contains('/') {
+        service.to_string()
+    } else {
+        format!("{}{}", service, DEFAULT_ITEM_PATH)
+    }
+}
+
+/// Split an item id into bus name and object path
+pub fn split_id(id: &str) -> (&str, &str) {
+    match id.find('/') {
+        Some(index) => id.split_at(index),
+        None => (id, DEFAULT_ITEM_PATH),
+    }
+}
+
+/// Proxy for an item; SNI items signal changes with `New*` signals rather
+/// than `PropertiesChanged`, so properties must not be cached
+pub(crate) async fn item_proxy(conn: &Connection, id: &str) -> Result<zbus::Proxy<'static>> {
+    let (bus_name, path) = split_id(id);
+
+    let proxy: zbus::Result<zbus::Proxy<'static>> = async {
+        zbus::ProxyBuilder::new(conn)
+            .interface(ITEM_INTERFACE)?
+            .path(path.to_string())?
+            .destination(bus_name.to_string())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await
+    }
+    .await;
+
+    proxy.map_err(|e| AmiyaError::Backend(format!("Failed to create tray item proxy: {}", e)))
+}
+
+/// Read the properties the tray displays
+pub(crate) async fn read_item(proxy: &zbus::Proxy<'_>, id: &str) -> Result<TrayItemInfo> {
+    // Title and IconName are the only commonly implemented properties; the
+    // rest are optional
+    let title: String = proxy
+        .get_property("Title")
+        .await
+        .map_err(|e| AmiyaError::Backend(format!("Failed to read tray item {}: {}", id, e)))?;
+
+    let icon_name: Option<String> = proxy
+        .get_property::<String>("IconName")
+        .await
+        .ok()
+        .filter(|name| !name.is_empty());
+
+    let icon_theme_path: Option<String> = proxy
+        .get_property::<String>("IconThemePath")
+        .await
+        .ok()
+        .filter(|path| !path.is_empty());
+
+    let icon_pixmap = proxy
+        .get_property::<Vec<Pixmap>>("IconPixmap")
+        .await
+        .ok()
+        .and_then(|pixmaps| best_pixmap(&pixmaps));
+
+    let tooltip = proxy
+        .get_property::<ToolTip>("ToolTip")
+        .await
+        .ok()
+        .and_then(|(_, _, title, description)| tooltip_text(&title, &description));
+
+    let status: String = proxy
+        .get_property("Status")
+        .await
+        .unwrap_or_else(|_| "Active".to_string());
+
+    Ok(TrayItemInfo {
+        id: id.to_string(),
+        title,
+        icon_name,
+        icon_theme_path,
+        icon_pixmap,
+        tooltip,
+        passive: status == "Passive",
+    })
+}
+
+fn tooltip_text(title: &str, description: &str) -> Option<String> {
+    match (title.is_empty(), description.is_empty()) {
+        (true, true) => None,
+        (false, true) => Some(title.to_string()),
+        (true, false) => Some(description.to_string()),
+        (false, false) => Some(format!("{}\n{}", title, description)),
+    }
+}
+
+/// Pick the smallest pixmap at least `PREFERRED_ICON_SIZE` wide (or the
+/// largest one if all are smaller) and convert it to RGBA
+fn best_pixmap(pixmaps: &[Pixmap]) -> Option<TrayIconPixmap> {
+    let valid = pixmaps
+        .iter()
+        .filter(|(w, h, data)| *w > 0 && *h > 0 && data.len() == (*w * *h * 4) as usize);
+
+    let pixmap = valid
+        .clone()
+        .filter(|(w, _, _)| *w >= PREFERRED_ICON_SIZE)
+        .min_by_key(|(w, _, _)| *w)
+        .or_else(|| valid.max_by_key(|(w, _, _)| *w))?;
+
+    let (width, height, data) = pixmap;
+    Some(TrayIconPixmap {
+        width: *width,
+        height: *height,
+        rgba: argb_to_rgba(data),
+    })
+}
+
+/// Convert ARGB32 in network byte order to RGBA8
+fn argb_to_rgba(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .flat_map(|px| [px[1], px[2], px[3], px[0]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_id() {
+        assert_eq!(
+            item_id("org.kde.StatusNotifierItem-1234-1", ":1.5"),
+            "org.kde.StatusNotifierItem-1234-1/StatusNotifierItem"
+        );
+        assert_eq!(
+            item_id("/org/ayatana/NotificationItem/steam", ":1.42"),
+            ":1.42/org/ayatana/NotificationItem/steam"
+        );
+        assert_eq!(item_id(":1.7/MyItem", ":1.9"), ":1.7/MyItem");
+    }
+
+    #[test]
+    fn test_split_id() {
+        assert_eq!(
+            split_id(":1.42/org/ayatana/NotificationItem/steam"),
+            (":1.42", "/org/ayatana/NotificationItem/steam")
+        );
+        assert_eq!(split_id(":1.5"), (":1.5", "/StatusNotifierItem"));
+    }
+
+    #[test]
+    fn test_argb_to_rgba() {
+        assert_eq!(
+            argb_to_rgba(&[0xff, 0x10, 0x20, 0x30]),
+            vec![0x10, 0x20, 0x30, 0xff]
+        );
+    }
+
+    #[test]
+    fn test_best_pixmap() {
+        let pixmap = |size: i32| (size, size, vec![0u8; (size * size * 4) as usize]);
+
+        let chosen = best_pixmap(&[pixmap(16), pixmap(32), pixmap(24)]).unwrap();
+        assert_eq!(chosen.width, 24);
+
+        let chosen = best_pixmap(&[pixmap(16), pixmap(8)]).unwrap();
+        assert_eq!(chosen.width, 16);
+
+        // Data that doesn't match the size is ignored
+        assert!(best_pixmap(&[(16, 16, vec![0u8; 10])]).is_none());
+    }
+}
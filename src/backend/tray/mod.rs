@@ -0,0 +1,4 @@
+pub mod item;
+pub mod watcher;
+
+pub use watcher::{create_tray_host_sync, TrayHost};
@@ -0,0 +1,475 @@
+use super::item::{self, item_id, split_id};
+use crate::error::{AmiyaError, Result};
+use crate::events::{Event, EventManager, TrayItemInfo};
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+use zbus::message::Header;
+use zbus::object_server::SignalContext;
+use zbus::proxy::CacheProperties;
+use zbus::Connection;
+
+const WATCHER_BUS_NAME: &str = "org.kde.StatusNotifierWatcher";
+const WATCHER_PATH: &str = "/StatusNotifierWatcher";
+
+/// Signals an item sends when something the tray shows has changed
+const ITEM_CHANGE_SIGNALS: [&str; 5] = [
+    "NewTitle",
+    "NewIcon",
+    "NewAttentionIcon",
+    "NewToolTip",
+    "NewStatus",
+];
+
+/// Changes in the set of registered items, fed to the host loop
+enum WatcherMessage {
+    Registered(String),
+    Unregistered(String),
+    OwnerLost(String),
+}
+
+/// The `org.kde.StatusNotifierWatcher` D-Bus interface, served when no other
+/// watcher is running
+struct Watcher {
+    items: Arc<RwLock<Vec<String>>>,
+    tx: mpsc::UnboundedSender<WatcherMessage>,
+}
+
+#[zbus::interface(name = "org.kde.StatusNotifierWatcher")]
+impl Watcher {
+    async fn register_status_notifier_item(
+        &self,
+        service: &str,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) {
+        let sender = header.sender().map(|s| s.to_string()).unwrap_or_default();
+        let id = item_id(service, &sender);
+
+        {
+            let mut items = self.items.write().await;
+            if items.contains(&id) {
+                return;
+            }
+            items.push(id.clone());
+        }
+
+        debug!("Tray item registered: {}", id);
+
+        if let Err(e) = Self::status_notifier_item_registered(&ctxt, &id).await {
+            warn!("Failed to emit StatusNotifierItemRegistered: {}", e);
+        }
+        let _ = self.tx.send(WatcherMessage::Registered(id));
+    }
+
+    async fn register_status_notifier_host(
+        &self,
+        _service: &str,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) {
+        if let Err(e) = Self::status_notifier_host_registered(&ctxt).await {
+            warn!("Failed to emit StatusNotifierHostRegistered: {}", e);
+        }
+    }
+
+    #[zbus(property)]
+    async fn registered_status_notifier_items(&self) -> Vec<String> {
+        self.items.read().await.clone()
+    }
+
+    #[zbus(property)]
+    fn is_status_notifier_host_registered(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn protocol_version(&self) -> i32 {
+        0
+    }
+
+    #[zbus(signal)]
+    async fn status_notifier_item_registered(
+        ctxt: &SignalContext<'_>,
+        service: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn status_notifier_item_unregistered(
+        ctxt: &SignalContext<'_>,
+        service: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn status_notifier_host_registered(ctxt: &SignalContext<'_>) -> zbus::Result<()>;
+}
+
+/// An item being shown, with the task listening for its change signals
+struct TrackedItem {
+    info: TrayItemInfo,
+    listener: JoinHandle<()>,
+}
+
+/// Keeps the tracked items in sync with the watcher and the items themselves
+#[derive(Clone)]
+struct Tracker {
+    conn: Connection,
+    items: Arc<RwLock<HashMap<String, TrackedItem>>>,
+    /// Registered ids when we are the watcher ourselves
+    watched: Option<Arc<RwLock<Vec<String>>>>,
+    events: EventManager,
+}
+
+impl Tracker {
+    async fn run(self, mut rx: mpsc::UnboundedReceiver<WatcherMessage>) {
+        while let Some(message) = rx.recv().await {
+            match message {
+                WatcherMessage::Registered(id) => self.add(id).await,
+                WatcherMessage::Unregistered(id) => self.remove(&id).await,
+                WatcherMessage::OwnerLost(name) => self.owner_lost(&name).await,
+            }
+        }
+    }
+
+    async fn add(&self, id: String) {
+        if self.items.read().await.contains_key(&id) {
+            return;
+        }
+
+        let proxy = match item::item_proxy(&self.conn, &id).await {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                warn!("{}", e);
+                return;
+            }
+        };
+
+        let info = match item::read_item(&proxy, &id).await {
+            Ok(info) => info,
+            Err(e) => {
+                warn!("{}", e);
+                return;
+            }
+        };
+
+        let mut streams = Vec::new();
+        for signal in ITEM_CHANGE_SIGNALS {
+            match proxy.receive_signal(signal).await {
+                Ok(stream) => streams.push(stream),
+                Err(e) => debug!("Failed to subscribe to {} on {}: {}", signal, id, e),
+            }
+        }
+
+        // Re-read the item whenever it reports a change
+        let tracker = self.clone();
+        let item_id = id.clone();
+        let listener = tokio::spawn(async move {
+            let mut changes = futures::stream::select_all(streams);
+            while changes.next().await.is_some() {
+                match item::read_item(&proxy, &item_id).await {
+                    Ok(info) => {
+                        if let Some(tracked) = tracker.items.write().await.get_mut(&item_id) {
+                            tracked.info = info.clone();
+                        }
+                        tracker.events.emit(Event::TrayItemUpdated { item: info });
+                    }
+                    Err(e) => debug!("{}", e),
+                }
+            }
+        });
+
+        info!("Tray item added: {} ({})", info.title, id);
+
+        self.items.write().await.insert(
+            id,
+            TrackedItem {
+                info: info.clone(),
+                listener,
+            },
+        );
+        self.events.emit(Event::TrayItemUpdated { item: info });
+    }
+
+    async fn remove(&self, id: &str) {
+        if let Some(tracked) = self.items.write().await.remove(id) {
+            tracked.listener.abort();
+            info!("Tray item removed: {}", id);
+            self.events
+                .emit(Event::TrayItemRemoved { id: id.to_string() });
+        }
+    }
+
+    /// Forget every item owned by a bus name that left the bus
+    async fn owner_lost(&self, name: &str) {
+        let owned_by = |id: &String| split_id(id).0 == name;
+
+        let ids: Vec<String> = self
+            .items
+            .read()
+            .await
+            .keys()
+            .filter(|id| owned_by(id))
+            .cloned()
+            .collect();
+        for id in &ids {
+            self.remove(id).await;
+        }
+
+        // As the watcher, also drop them from the registered list
+        let Some(watched) = &self.watched else {
+            return;
+        };
+        let gone: Vec<String> = {
+            let mut watched = watched.write().await;
+            let gone = watched.iter().filter(|id| owned_by(id)).cloned().collect();
+            watched.retain(|id| !owned_by(id));
+            gone
+        };
+        if gone.is_empty() {
+            return;
+        }
+
+        match SignalContext::new(&self.conn, WATCHER_PATH) {
+            Ok(ctxt) => {
+                for id in gone {
+                    if let Err(e) = Watcher::status_notifier_item_unregistered(&ctxt, &id).await {
+                        warn!("Failed to emit StatusNotifierItemUnregistered: {}", e);
+                    }
+                }
+            }
+            Err(e) => warn!("Invalid watcher path: {}", e),
+        }
+    }
+}
+
+/// System tray host: tracks StatusNotifierItems and forwards user input to them
+///
+/// Acts as the `org.kde.StatusNotifierWatcher` too, unless another watcher is
+/// already running, in which case it registers with that one as a host.
+pub struct TrayHost {
+    connection: Arc<RwLock<Option<Connection>>>,
+    items: Arc<RwLock<HashMap<String, TrackedItem>>>,
+    events: EventManager,
+}
+
+impl TrayHost {
+    /// Create a new tray host
+    pub fn new(events: EventManager) -> Self {
+        TrayHost {
+            connection: Arc::new(RwLock::new(None)),
+            items: Arc::new(RwLock::new(HashMap::new())),
+            events,
+        }
+    }
+
+    /// Connect to the session bus and start tracking items
+    pub async fn connect(&self) -> Result<()> {
+        let conn = Connection::session()
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to connect to D-Bus: {}", e)))?;
+
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        // Watch for items' owners leaving the bus before any are registered
+        let dbus = zbus::fdo::DBusProxy::new(&conn)
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to create D-Bus proxy: {}", e)))?;
+        let mut owner_changes = dbus.receive_name_owner_changed().await.map_err(|e| {
+            AmiyaError::Backend(format!("Failed to watch name owner changes: {}", e))
+        })?;
+        let owner_tx = tx.clone();
+        tokio::spawn(async move {
+            while let Some(signal) = owner_changes.next().await {
+                let Ok(args) = signal.args() else {
+                    continue;
+                };
+                if args.new_owner().is_none() {
+                    let _ = owner_tx.send(WatcherMessage::OwnerLost(args.name().to_string()));
+                }
+            }
+        });
+
+        let watched = Arc::new(RwLock::new(Vec::new()));
+        let own_watcher = Self::serve_watcher(&conn, watched.clone(), tx.clone()).await;
+        if own_watcher {
+            info!("System tray watcher registered as {}", WATCHER_BUS_NAME);
+        } else {
+            Self::use_external_watcher(&conn, tx).await?;
+            info!("System tray registered with existing {}", WATCHER_BUS_NAME);
+        }
+
+        let tracker = Tracker {
+            conn: conn.clone(),
+            items: self.items.clone(),
+            watched: own_watcher.then_some(watched),
+            events: self.events.clone(),
+        };
+        tokio::spawn(tracker.run(rx));
+
+        *self.connection.write().await = Some(conn);
+
+        Ok(())
+    }
+
+    /// Serve the watcher interface; returns false if another watcher owns the name
+    async fn serve_watcher(
+        conn: &Connection,
+        watched: Arc<RwLock<Vec<String>>>,
+        tx: mpsc::UnboundedSender<WatcherMessage>,
+    ) -> bool {
+        let watcher = Watcher { items: watched, tx };
+        if let Err(e) = conn.object_server().at(WATCHER_PATH, watcher).await {
+            warn!("Failed to serve {}: {}", WATCHER_BUS_NAME, e);
+            return false;
+        }
+
+        match conn.request_name(WATCHER_BUS_NAME).await {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("Could not claim {}: {}", WATCHER_BUS_NAME, e);
+                let _ = conn
+                    .object_server()
+                    .remove::<Watcher, _>(WATCHER_PATH)
+                    .await;
+                false
+            }
+        }
+    }
+
+    /// Register as a host with a watcher run by someone else and follow its items
+    async fn use_external_watcher(
+        conn: &Connection,
+        tx: mpsc::UnboundedSender<WatcherMessage>,
+    ) -> Result<()> {
+        let watcher: zbus::Result<zbus::Proxy<'static>> = async {
+            zbus::ProxyBuilder::new(conn)
+                .interface(WATCHER_BUS_NAME)?
+                .path(WATCHER_PATH)?
+                .destination(WATCHER_BUS_NAME)?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await
+        }
+        .await;
+        let watcher = watcher
+            .map_err(|e| AmiyaError::Backend(format!("Failed to create watcher proxy: {}", e)))?;
+
+        let host_name = format!("org.kde.StatusNotifierHost-{}", std::process::id());
+        conn.request_name(host_name.as_str())
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to claim {}: {}", host_name, e)))?;
+        watcher
+            .call_method("RegisterStatusNotifierHost", &(host_name.as_str(),))
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to register tray host: {}", e)))?;
+
+        // Subscribe before listing so no registration is missed in between
+        let mut registered = watcher
+            .receive_signal("StatusNotifierItemRegistered")
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to watch tray items: {}", e)))?;
+        let mut unregistered = watcher
+            .receive_signal("StatusNotifierItemUnregistered")
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("Failed to watch tray items: {}", e)))?;
+
+        let existing: Vec<String> = watcher
+            .get_property("RegisteredStatusNotifierItems")
+            .await
+            .unwrap_or_default();
+        for service in existing {
+            let _ = tx.send(WatcherMessage::Registered(item_id(&service, "")));
+        }
+
+        let registered_tx = tx.clone();
+        tokio::spawn(async move {
+            while let Some(message) = registered.next().await {
+                if let Ok(service) = message.body().deserialize::<String>() {
+                    let _ = registered_tx.send(WatcherMessage::Registered(item_id(&service, "")));
+                }
+            }
+        });
+        tokio::spawn(async move {
+            while let Some(message) = unregistered.next().await {
+                if let Ok(service) = message.body().deserialize::<String>() {
+                    let _ = tx.send(WatcherMessage::Unregistered(item_id(&service, "")));
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Check if the tray is connected to the session bus
+    pub async fn is_available(&self) -> bool {
+        self.connection.read().await.is_some()
+    }
+
+    /// Items currently in the tray
+    pub async fn items(&self) -> Vec<TrayItemInfo> {
+        self.items
+            .read()
+            .await
+            .values()
+            .map(|tracked| tracked.info.clone())
+            .collect()
+    }
+
+    /// Primary action, usually a left click
+    pub async fn activate(&self, id: &str, x: i32, y: i32) -> Result<()> {
+        self.call_item(id, "Activate", &(x, y)).await
+    }
+
+    /// Secondary action, usually a middle click
+    pub async fn secondary_activate(&self, id: &str, x: i32, y: i32) -> Result<()> {
+        self.call_item(id, "SecondaryActivate", &(x, y)).await
+    }
+
+    /// Ask the item to show its own context menu
+    ///
+    /// Items that only export a `com.canonical.dbusmenu` menu reject this.
+    pub async fn context_menu(&self, id: &str, x: i32, y: i32) -> Result<()> {
+        self.call_item(id, "ContextMenu", &(x, y)).await
+    }
+
+    /// Forward a scroll; `delta` is in steps, positive is down/right
+    pub async fn scroll(&self, id: &str, delta: i32, horizontal: bool) -> Result<()> {
+        let orientation = if horizontal { "horizontal" } else { "vertical" };
+        self.call_item(id, "Scroll", &(delta, orientation)).await
+    }
+
+    async fn call_item<B>(&self, id: &str, method: &str, body: &B) -> Result<()>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        let conn_guard = self.connection.read().await;
+        let conn = conn_guard
+            .as_ref()
+            .ok_or_else(|| AmiyaError::Backend("System tray not connected".to_string()))?;
+
+        let proxy = item::item_proxy(conn, id).await?;
+        proxy
+            .call_method(method, body)
+            .await
+            .map_err(|e| AmiyaError::Backend(format!("{} failed on {}: {}", method, id, e)))?;
+
+        Ok(())
+    }
+}
+
+/// Create the tray host and connect in the background
+pub fn create_tray_host_sync(events: EventManager) -> Arc<TrayHost> {
+    let host = Arc::new(TrayHost::new(events));
+
+    // Try to connect in background
+    let host_clone = host.clone();
+    tokio::spawn(async move {
+        if let Err(e) = host_clone.connect().await {
+            warn!("Failed to start system tray: {}", e);
+        }
+    });
+
+    host
+}
@@ -1,19 +1,127 @@
 use crate::app::AppState;
-use crate::config::Position;
-use crate::widgets::{battery::Battery, clock::Clock, system_info::SystemInfo, workspaces::Workspaces};
+use crate::config::{BarConfig, Config, Position};
+use crate::events::Event;
+use crate::widgets::{
+    battery::Battery, clock::Clock, idle_inhibitor::IdleInhibitorToggle,
+    keyboard_layout::KeyboardLayout,
+    media::MediaWidget,
+    network_speed::NetworkSpeed,
+    sparkline::{Metric, Sparkline},
+    system_info::SystemInfo,
+    tray::Tray,
+    window_title::WindowTitle,
+    workspaces::Workspaces,
+};
 use anyhow::Result;
 use gtk4::prelude::*;
-use gtk4::{Application, ApplicationWindow, Box as GtkBox, Orientation};
+use gtk4::{glib, Application, ApplicationWindow, Box as GtkBox, EventControllerMotion, Orientation};
 use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Height of the strip an auto-hidden bar leaves at its screen edge;
+/// touching it brings the bar back
+const AUTO_HIDE_STRIP: i32 = 2;
+
+/// How long the pointer has to be away before an auto-hidden bar hides
+const AUTO_HIDE_DELAY: Duration = Duration::from_millis(500);
 
 pub struct Bar {
     window: ApplicationWindow,
 }
 
+/// Hides the bar to a thin strip while the pointer is elsewhere, when
+/// `bar.auto_hide` is set
+#[derive(Clone)]
+struct AutoHide {
+    window: ApplicationWindow,
+    content: GtkBox,
+    state: Arc<AppState>,
+    revealed: Rc<Cell<bool>>,
+    /// Pending hide after the pointer left
+    timer: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl AutoHide {
+    fn new(window: &ApplicationWindow, content: &GtkBox, state: &Arc<AppState>) -> Self {
+        let auto_hide = AutoHide {
+            window: window.clone(),
+            content: content.clone(),
+            state: state.clone(),
+            revealed: Rc::new(Cell::new(false)),
+            timer: Rc::new(RefCell::new(None)),
+        };
+
+        let motion = EventControllerMotion::new();
+        let enter = auto_hide.clone();
+        motion.connect_enter(move |_, _, _| {
+            enter.cancel_timer();
+            enter.set_revealed(true);
+        });
+        let leave = auto_hide.clone();
+        motion.connect_leave(move |_| {
+            leave.cancel_timer();
+            let hide = leave.clone();
+            let source = glib::timeout_add_local_once(AUTO_HIDE_DELAY, move || {
+                hide.timer.borrow_mut().take();
+                hide.set_revealed(false);
+            });
+            *leave.timer.borrow_mut() = Some(source);
+        });
+        window.add_controller(motion);
+
+        auto_hide
+    }
+
+    fn cancel_timer(&self) {
+        if let Some(source) = self.timer.borrow_mut().take() {
+            source.remove();
+        }
+    }
+
+    /// Re-apply the current state, e.g. after a config reload
+    fn apply(&self) {
+        self.set_revealed(self.revealed.get());
+    }
+
+    /// Show the whole bar and reserve its space (if `bar.reserve_space` is
+    /// set), or shrink it to the strip and release the exclusive zone
+    fn set_revealed(&self, revealed: bool) {
+        self.revealed.set(revealed);
+
+        let config = self.state.config().bar;
+        let hidden = config.auto_hide && !revealed;
+        self.content.set_visible(!hidden);
+        if hidden {
+            self.window.add_css_class("auto-hidden");
+            self.window.set_height_request(AUTO_HIDE_STRIP);
+            self.window.set_exclusive_zone(0);
+        } else {
+            self.window.remove_css_class("auto-hidden");
+            apply_layout(&self.window, &config);
+        }
+    }
+}
+
+/// Bar modules by name, whose visibility is controlled by the config
+#[derive(Clone)]
+struct ToggleableWidgets {
+    modules: Vec<(String, gtk4::Widget)>,
+}
+
+impl ToggleableWidgets {
+    fn apply(&self, config: &BarConfig) {
+        for (name, widget) in &self.modules {
+            widget.set_visible(config.shows_module(name));
+        }
+    }
+}
+
 impl Bar {
     pub fn new(app: &Application, state: &Arc<AppState>) -> Result<Self> {
-        let config = &state.config;
+        let config = state.config();
         let window = ApplicationWindow::builder()
             .application(app)
             .title("Amiya Bar")
@@ -23,24 +131,11 @@ impl Bar {
         window.init_layer_shell();
         window.set_layer(Layer::Top);
         window.set_namespace("amiya-bar");
+        window.add_css_class("amiya-bar");
 
-        // Anchor to edges
-        window.set_anchor(Edge::Left, true);
-        window.set_anchor(Edge::Right, true);
-
-        match config.bar.position {
-            Position::Top => {
-                window.set_anchor(Edge::Top, true);
-                window.set_anchor(Edge::Bottom, false);
-            }
-            Position::Bottom => {
-                window.set_anchor(Edge::Bottom, true);
-                window.set_anchor(Edge::Top, false);
-            }
-        }
-
-        // Set exclusive zone (reserves space)
-        window.set_exclusive_zone(config.bar.height);
+        // Anchor to edges and reserve space
+        apply_layout(&window, &config.bar);
+        window.connect_map(log_output_scale);
 
         // Main container
         let main_box = GtkBox::new(Orientation::Horizontal, 0);
@@ -48,31 +143,27 @@ impl Bar {
         main_box.set_vexpand(true);
 
         // Apply theme
-        apply_theme(&window, config);
-
-        // Left section: Workspaces
+        let provider = gtk4::CssProvider::new();
+        provider.load_from_string(&theme_css(&config));
+        gtk4::style_context_add_provider_for_display(
+            &window.display(),
+            &provider,
+            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+        );
+
+        // Left section
         let left_box = GtkBox::new(Orientation::Horizontal, 8);
         left_box.set_margin_start(12);
         left_box.set_margin_end(12);
         left_box.set_margin_top(4);
         left_box.set_margin_bottom(4);
 
-        if config.bar.show_workspaces {
-            let workspaces = Workspaces::new(state);
-            left_box.append(&workspaces.widget());
-        }
-
-        // Center section: Clock
+        // Center section
         let center_box = GtkBox::new(Orientation::Horizontal, 0);
         center_box.set_halign(gtk4::Align::Center);
         center_box.set_hexpand(true);
 
-        if config.bar.show_clock {
-            let clock = Clock::new(state);
-            center_box.append(&clock.widget());
-        }
-
-        // Right section: System info and battery
+        // Right section
         let right_box = GtkBox::new(Orientation::Horizontal, 12);
         right_box.set_margin_start(12);
         right_box.set_margin_end(12);
@@ -80,16 +171,23 @@ impl Bar {
         right_box.set_margin_bottom(4);
         right_box.set_halign(gtk4::Align::End);
 
-        // Battery widget (if available)
-        if state.battery_control.is_some() {
-            let battery = Battery::new(state);
-            right_box.append(&battery.widget());
-        }
-
-        if config.bar.show_system_info {
-            let system_info = SystemInfo::new(state);
-            right_box.append(&system_info.widget());
+        // Modules are placed at startup; they are always built so the
+        // show_* toggles can be applied on config reload
+        let mut modules = Vec::new();
+        for (section, names) in [
+            (&left_box, &config.bar.modules_left),
+            (&center_box, &config.bar.modules_center),
+            (&right_box, &config.bar.modules_right),
+        ] {
+            for name in names {
+                if let Some(widget) = build_module(name, state) {
+                    section.append(&widget);
+                    modules.push((name.clone(), widget));
+                }
+            }
         }
+        let toggleable = ToggleableWidgets { modules };
+        toggleable.apply(&config.bar);
 
         // Add all sections to main box
         main_box.append(&left_box);
@@ -98,25 +196,162 @@ impl Bar {
 
         window.set_child(Some(&main_box));
 
+        let auto_hide = AutoHide::new(&window, &main_box, state);
+        auto_hide.apply();
+
+        Self::subscribe_to_events(state.clone(), window.clone(), provider, toggleable, auto_hide);
+
         Ok(Bar { window })
     }
 
     pub fn show(&self) {
         self.window.present();
     }
+
+    fn subscribe_to_events(
+        state: Arc<AppState>,
+        window: ApplicationWindow,
+        provider: gtk4::CssProvider,
+        toggleable: ToggleableWidgets,
+        auto_hide: AutoHide,
+    ) {
+        let mut receiver = state.events.subscribe();
+
+        glib::spawn_future_local(async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::ConfigReloaded => {
+                        let config = state.config();
+                        provider.load_from_string(&theme_css(&config));
+                        apply_layout(&window, &config.bar);
+                        toggleable.apply(&config.bar);
+                        auto_hide.apply();
+                    }
+                    _ => {} // Ignore other events
+                }
+            }
+        });
+    }
 }
 
-fn apply_theme(window: &ApplicationWindow, config: &crate::config::Config) {
-    let provider = gtk4::CssProvider::new();
-    let css = format!(
+/// Build the bar module called `name` (one of `config::BAR_MODULES`)
+fn build_module(name: &str, state: &Arc<AppState>) -> Option<gtk4::Widget> {
+    let widget = match name {
+        "workspaces" => Workspaces::new(state).widget().upcast(),
+        "window-title" => WindowTitle::new(state).widget().upcast(),
+        "clock" => Clock::new(state).widget().upcast(),
+        "tray" => Tray::new(state).widget().upcast(),
+        "network-speed" => NetworkSpeed::new(state).widget().upcast(),
+        "keyboard-layout" => KeyboardLayout::new(state).widget().upcast(),
+        "idle-inhibitor" => IdleInhibitorToggle::new(state).widget().upcast(),
+        "battery" => {
+            // Only shown on machines with a battery
+            state.battery_control.as_ref()?;
+            Battery::new(state).widget().upcast()
+        }
+        "system-info" => SystemInfo::new(state).widget().upcast(),
+        "media" => MediaWidget::new(state).widget().upcast(),
+        "cpu-graph" => Sparkline::new(state, Metric::Cpu).widget().upcast(),
+        "memory-graph" => Sparkline::new(state, Metric::Memory).widget().upcast(),
+        _ => {
+            tracing::warn!("Unknown bar module {:?}", name);
+            return None;
+        }
+    };
+    Some(widget)
+}
+
+/// Apply anchoring, size and exclusive zone from the bar config
+fn apply_layout(window: &ApplicationWindow, config: &BarConfig) {
+    window.set_anchor(Edge::Left, true);
+    window.set_anchor(Edge::Right, true);
+
+    match config.position {
+        Position::Top => {
+            window.set_anchor(Edge::Top, true);
+            window.set_anchor(Edge::Bottom, false);
+        }
+        Position::Bottom => {
+            window.set_anchor(Edge::Bottom, true);
+            window.set_anchor(Edge::Top, false);
+        }
+    }
+
+    // Sizes are in logical pixels; GTK renders them at the output's scale
+    window.set_height_request(config.height);
+
+    if config.reserve_space {
+        // Reserve the bar's actual height, which can exceed the configured
+        // one when larger fonts need more room
+        window.auto_exclusive_zone_enable();
+    } else {
+        // Let windows extend under the bar
+        window.set_exclusive_zone(0);
+    }
+}
+
+/// Physical DPI above which an unscaled output is likely too dense to read
+const HIDPI_THRESHOLD: f64 = 180.0;
+
+/// Log the scale of the output the bar is on, and suggest scaling outputs
+/// that are dense enough to make the bar hard to read
+fn log_output_scale(window: &ApplicationWindow) {
+    let Some(monitor) = window
+        .surface()
+        .and_then(|surface| WidgetExt::display(window).monitor_at_surface(&surface))
+    else {
+        return;
+    };
+
+    let name = monitor.connector().unwrap_or_else(|| "unknown output".into());
+    let scale = monitor.scale_factor();
+    tracing::info!("Bar on {} at scale {}", name, scale);
+
+    let dpi = physical_dpi(monitor.geometry().width(), scale, monitor.width_mm());
+    if let Some(dpi) = dpi.filter(|dpi| scale == 1 && *dpi >= HIDPI_THRESHOLD) {
+        tracing::warn!(
+            "{} is about {:.0} DPI but unscaled, so the bar will look small; \
+             set a scale for it in niri's config (e.g. `scale 2`)",
+            name,
+            dpi
+        );
+    }
+}
+
+/// Horizontal DPI of an output from its logical width, scale and physical size
+fn physical_dpi(logical_width: i32, scale: i32, width_mm: i32) -> Option<f64> {
+    (width_mm > 0).then(|| (logical_width * scale) as f64 / (width_mm as f64 / 25.4))
+}
+
+/// Build the bar stylesheet from the theme config
+fn theme_css(config: &Config) -> String {
+    let theme = &config.theme;
+    let active = theme.workspace_active.as_deref().unwrap_or(&theme.accent);
+    let occupied = theme
+        .workspace_occupied
+        .as_deref()
+        .unwrap_or(&theme.foreground);
+    // Dimmed so empty workspaces stand apart from occupied ones
+    let empty = theme
+        .workspace_empty
+        .clone()
+        .unwrap_or_else(|| format!("alpha({}, 0.5)", theme.foreground));
+    let urgent = theme.workspace_urgent.as_deref().unwrap_or(&theme.critical);
+    let warning = &theme.warning;
+    let critical = &theme.critical;
+    format!(
         r#"
-        window {{
+        window.amiya-bar {{
             background-color: {};
             color: {};
             font-family: "{}";
             font-size: {}pt;
         }}
 
+        window.amiya-bar.auto-hidden {{
+            background-color: transparent;
+        }}
+
         .workspace-button {{
             background-color: transparent;
             border: 2px solid transparent;
@@ -131,28 +366,78 @@ fn apply_theme(window: &ApplicationWindow, config: &crate::config::Config) {
             background-color: alpha({}, 0.1);
         }}
 
+        .workspace-button.occupied {{
+            color: {occupied};
+        }}
+
+        .workspace-button.empty {{
+            color: {empty};
+        }}
+
         .workspace-button.active {{
-            background-color: {};
-            border-color: {};
+            background-color: {active};
+            border-color: {active};
             color: {};
         }}
 
+        .workspace-button.urgent {{
+            border-color: {urgent};
+            color: {urgent};
+        }}
+
+        .workspace-windows {{
+            font-size: 0.7em;
+            opacity: 0.7;
+        }}
+
+        .workspaces-disconnected {{
+            padding: 0 6px;
+            color: {warning};
+            opacity: 0.8;
+        }}
+
+        .workspaces.disconnected .workspace-button {{
+            opacity: 0.5;
+        }}
+
+        .sparkline {{
+            min-height: 16px;
+            margin: 4px 0;
+        }}
+
         .system-info-label {{
             padding: 2px 8px;
             margin: 0 2px;
         }}
 
+        .system-info-label.temp-warning {{
+            color: {warning};
+        }}
+
+        .system-info-label.weak-signal {{
+            color: {warning};
+        }}
+
+        .system-info-label.temp-critical {{
+            color: {critical};
+            font-weight: bold;
+        }}
+
+        .system-info-label.muted {{
+            opacity: 0.6;
+        }}
+
         .battery-label {{
             padding: 2px 8px;
             margin: 0 2px;
         }}
 
         .battery-label.battery-low {{
-            color: #FFA500;
+            color: {warning};
         }}
 
         .battery-label.battery-critical {{
-            color: #FF0000;
+            color: {critical};
             font-weight: bold;
         }}
 
@@ -160,6 +445,65 @@ fn apply_theme(window: &ApplicationWindow, config: &crate::config::Config) {
             color: #00FF00;
         }}
 
+        .keyboard-layout-button {{
+            background-color: transparent;
+            border: none;
+            padding: 2px 8px;
+            margin: 0 2px;
+            color: {};
+        }}
+
+        .idle-inhibitor-button {{
+            background-color: transparent;
+            border: none;
+            padding: 2px 8px;
+            margin: 0 2px;
+            opacity: 0.5;
+        }}
+
+        .idle-inhibitor-button.active {{
+            opacity: 1;
+            color: {};
+        }}
+
+        .media-button {{
+            background-color: transparent;
+            border: none;
+            padding: 2px 8px;
+        }}
+
+        .media-status.playing {{
+            color: {};
+        }}
+
+        .network-speed-label {{
+            padding: 2px 8px;
+            font-family: monospace;
+        }}
+
+        .tray-item {{
+            background-color: transparent;
+            border: none;
+            padding: 2px 4px;
+            min-width: 0;
+            min-height: 0;
+        }}
+
+        .window-title-label {{
+            padding: 2px 8px;
+            opacity: 0.8;
+        }}
+
+        .window-title-icon {{
+            margin-left: 8px;
+        }}
+
+        .clock-button {{
+            background-color: transparent;
+            border: none;
+            padding: 0 8px;
+        }}
+
         .clock-label {{
             font-size: {}pt;
             font-weight: bold;
@@ -175,17 +519,10 @@ fn apply_theme(window: &ApplicationWindow, config: &crate::config::Config) {
         config.theme.font_size,
         config.theme.foreground,
         config.theme.accent,
+        config.theme.background,
+        config.theme.foreground,
         config.theme.accent,
         config.theme.accent,
-        config.theme.background,
         config.theme.font_size + 1,
-    );
-
-    provider.load_from_string(&css);
-
-    gtk4::style_context_add_provider_for_display(
-        &window.display(),
-        &provider,
-        gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-    );
+    )
 }
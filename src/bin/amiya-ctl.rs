@@ -1,14 +1,27 @@
+use amiya::ipc::protocol::{self, Command, PopupType, Response};
 use clap::{Parser, Subcommand};
-use serde::{Deserialize, Serialize};
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::UnixStream;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Delay between attempts while waiting for the socket
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Amiya Control - CLI tool for controlling Amiya desktop environment
 #[derive(Parser)]
 #[command(name = "amiya-ctl")]
 #[command(about = "Control Amiya desktop environment", long_about = None)]
 struct Cli {
+    /// Print the raw JSON response instead of formatted text
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Keep retrying for this many milliseconds if Amiya isn't listening yet
+    /// (e.g. while it starts up at login)
+    #[arg(long, global = true, value_name = "MS", default_value_t = 500)]
+    wait: u64,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -39,6 +52,75 @@ enum Commands {
         action: PowerActionCli,
     },
 
+    /// Control the active media player
+    Media {
+        #[command(subcommand)]
+        action: MediaActionCli,
+    },
+
+    /// Workspace and window management
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceActionCli,
+    },
+
+    /// Run the action bound to a key combination in the Amiya config
+    Hotkey {
+        /// Key combination as written in config.toml (e.g. Super+B)
+        key: String,
+    },
+
+    /// Do-not-disturb mode for notifications
+    Dnd {
+        #[command(subcommand)]
+        action: DndAction,
+    },
+
+    /// Keep the screen from blanking or locking while idle
+    IdleInhibit {
+        #[command(subcommand)]
+        action: IdleInhibitAction,
+    },
+
+    /// Turn WiFi and Bluetooth off together
+    Airplane {
+        #[command(subcommand)]
+        action: AirplaneAction,
+    },
+
+    /// VPN connections managed by NetworkManager
+    Vpn {
+        #[command(subcommand)]
+        action: VpnActionCli,
+    },
+
+    /// Take a screenshot with grim
+    Screenshot {
+        #[command(subcommand)]
+        mode: ScreenshotModeCli,
+    },
+
+    /// Record the screen with wf-recorder
+    Record {
+        #[command(subcommand)]
+        action: RecordActionCli,
+    },
+
+    /// Blank all monitors until the next key press or mouse move (needs niri)
+    PowerOffMonitors,
+
+    /// Change output scale or rotation until niri's config is reloaded (needs niri)
+    Output {
+        #[command(subcommand)]
+        action: OutputActionCli,
+    },
+
+    /// Re-read the config file and apply it
+    Reload,
+
+    /// Exit Amiya cleanly
+    Quit,
+
     /// Get status
     Status,
 
@@ -46,23 +128,108 @@ enum Commands {
     Ping,
 }
 
+#[derive(Subcommand)]
+enum DndAction {
+    /// Suppress notification popups (they are still kept in history)
+    On,
+
+    /// Show notification popups again
+    Off,
+}
+
+#[derive(Subcommand)]
+enum IdleInhibitAction {
+    /// Hold an idle inhibitor lock
+    On,
+
+    /// Release the idle inhibitor lock
+    Off,
+}
+
+#[derive(Subcommand)]
+enum AirplaneAction {
+    /// Turn all radios off
+    On,
+
+    /// Restore the radios that were on before
+    Off,
+}
+
+#[derive(Subcommand)]
+enum VpnActionCli {
+    /// Activate a VPN by connection name
+    Up { name: String },
+
+    /// Deactivate a VPN by connection name
+    Down { name: String },
+
+    /// List VPN connections
+    List,
+}
+
+#[derive(Subcommand)]
+enum OutputActionCli {
+    /// List outputs with their scale and transform
+    List,
+
+    /// Set the scale of an output
+    Scale {
+        /// Output name, e.g. eDP-1
+        name: String,
+        /// Scale from 0.1 to 10, e.g. 1.5
+        scale: f64,
+    },
+
+    /// Rotate or flip an output
+    Transform {
+        /// Output name, e.g. eDP-1
+        name: String,
+        /// normal, 90, 180, 270, flipped, flipped-90, flipped-180 or flipped-270
+        transform: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScreenshotModeCli {
+    /// Capture all outputs
+    Full,
+
+    /// Capture a region selected with slurp
+    Region,
+
+    /// Capture the focused window (saved by niri)
+    Window,
+}
+
+#[derive(Subcommand)]
+enum RecordActionCli {
+    /// Start recording all outputs
+    Start,
+
+    /// Stop recording and save the file
+    Stop,
+
+    /// Start recording, or stop if already recording
+    Toggle,
+}
+
 #[derive(Subcommand)]
 enum PopupAction {
     /// Show a popup
     Show {
-        /// Type of popup (bluetooth, wifi, media-control)
+        /// Type of popup (bluetooth, wifi, media-control, power, calendar, notifications, launcher, debug, quick-settings, windows)
         popup: String,
     },
 
     /// Hide a popup
     Hide {
-        /// Type of popup (bluetooth, wifi, media-control)
+        /// Type of popup (bluetooth, wifi, media-control, power, calendar, notifications, launcher, debug, quick-settings, windows)
         popup: String,
     },
 
     /// Toggle a popup
     Toggle {
-        /// Type of popup (bluetooth, wifi, media-control)
+        /// Type of popup (bluetooth, wifi, media-control, power, calendar, notifications, launcher, debug, quick-settings, windows)
         popup: String,
     },
 }
@@ -89,6 +256,13 @@ enum VolumeAction {
         level: f64,
     },
 
+    /// Change volume by a percentage of the current level
+    SetRelative {
+        /// Percent of the current level, negative to lower (e.g. 10 or -10)
+        #[arg(allow_negative_numbers = true)]
+        percent: f64,
+    },
+
     /// Mute audio
     Mute,
 
@@ -120,6 +294,13 @@ enum BrightnessAction {
         /// Brightness level (0-100)
         level: f64,
     },
+
+    /// Change brightness by a percentage of the current level
+    SetRelative {
+        /// Percent of the current level, negative to lower (e.g. 10 or -10)
+        #[arg(allow_negative_numbers = true)]
+        percent: f64,
+    },
 }
 
 #[derive(Subcommand)]
@@ -140,65 +321,40 @@ enum PowerActionCli {
     Lock,
 }
 
-// Mirror the IPC protocol types
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "kebab-case")]
-enum Command {
-    ShowPopup { popup: PopupType },
-    HidePopup { popup: PopupType },
-    TogglePopup { popup: PopupType },
-    Volume { action: VolumeActionData },
-    Brightness { action: BrightnessActionData },
-    Power { action: PowerActionData },
-    Status,
-    Ping,
-}
+#[derive(Subcommand)]
+enum MediaActionCli {
+    /// Start playback
+    Play,
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-enum PopupType {
-    Bluetooth,
-    Wifi,
-    MediaControl,
-    Power,
-}
+    /// Pause playback
+    Pause,
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "action", rename_all = "kebab-case")]
-enum VolumeActionData {
-    Up { amount: Option<f64> },
-    Down { amount: Option<f64> },
-    Set { level: f64 },
-    Mute,
-    Unmute,
-    ToggleMute,
-}
+    /// Play if paused, pause if playing
+    PlayPause,
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "action", rename_all = "kebab-case")]
-enum BrightnessActionData {
-    Up { amount: Option<f64> },
-    Down { amount: Option<f64> },
-    Set { level: f64 },
-}
+    /// Skip to the next track
+    Next,
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "kebab-case")]
-enum PowerActionData {
-    Shutdown,
-    Reboot,
-    Suspend,
-    Hibernate,
-    Lock,
+    /// Go back to the previous track
+    Previous,
+
+    /// Stop playback
+    Stop,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "status", rename_all = "kebab-case")]
-enum Response {
-    Success { message: Option<String> },
-    Error { message: String },
-    Status { version: String, uptime: u64 },
-    Pong,
+#[derive(Subcommand)]
+enum WorkspaceActionCli {
+    /// Move the focused window to a workspace
+    MoveWindow {
+        /// Workspace index or name
+        workspace: String,
+    },
+
+    /// Move the focused window to the workspace above
+    MoveWindowUp,
+
+    /// Move the focused window to the workspace below
+    MoveWindowDown,
 }
 
 fn main() -> anyhow::Result<()> {
@@ -218,35 +374,106 @@ fn main() -> anyhow::Result<()> {
         },
         Commands::Volume { action } => Command::Volume {
             action: match action {
-                VolumeAction::Up { amount } => VolumeActionData::Up { amount },
-                VolumeAction::Down { amount } => VolumeActionData::Down { amount },
-                VolumeAction::Set { level } => VolumeActionData::Set { level },
-                VolumeAction::Mute => VolumeActionData::Mute,
-                VolumeAction::Unmute => VolumeActionData::Unmute,
-                VolumeAction::ToggleMute => VolumeActionData::ToggleMute,
+                VolumeAction::Up { amount } => protocol::VolumeAction::Up { amount },
+                VolumeAction::Down { amount } => protocol::VolumeAction::Down { amount },
+                VolumeAction::Set { level } => protocol::VolumeAction::Set { level },
+                VolumeAction::SetRelative { percent } => {
+                    protocol::VolumeAction::SetRelative { percent }
+                }
+                VolumeAction::Mute => protocol::VolumeAction::Mute,
+                VolumeAction::Unmute => protocol::VolumeAction::Unmute,
+                VolumeAction::ToggleMute => protocol::VolumeAction::ToggleMute,
             },
         },
         Commands::Brightness { action } => Command::Brightness {
             action: match action {
-                BrightnessAction::Up { amount } => BrightnessActionData::Up { amount },
-                BrightnessAction::Down { amount } => BrightnessActionData::Down { amount },
-                BrightnessAction::Set { level } => BrightnessActionData::Set { level },
+                BrightnessAction::Up { amount } => protocol::BrightnessAction::Up { amount },
+                BrightnessAction::Down { amount } => protocol::BrightnessAction::Down { amount },
+                BrightnessAction::Set { level } => protocol::BrightnessAction::Set { level },
+                BrightnessAction::SetRelative { percent } => {
+                    protocol::BrightnessAction::SetRelative { percent }
+                }
             },
         },
         Commands::Power { action } => Command::Power {
             action: match action {
-                PowerActionCli::Shutdown => PowerActionData::Shutdown,
-                PowerActionCli::Reboot => PowerActionData::Reboot,
-                PowerActionCli::Suspend => PowerActionData::Suspend,
-                PowerActionCli::Hibernate => PowerActionData::Hibernate,
-                PowerActionCli::Lock => PowerActionData::Lock,
+                PowerActionCli::Shutdown => protocol::PowerAction::Shutdown,
+                PowerActionCli::Reboot => protocol::PowerAction::Reboot,
+                PowerActionCli::Suspend => protocol::PowerAction::Suspend,
+                PowerActionCli::Hibernate => protocol::PowerAction::Hibernate,
+                PowerActionCli::Lock => protocol::PowerAction::Lock,
+            },
+        },
+        Commands::Media { action } => Command::Media {
+            action: match action {
+                MediaActionCli::Play => protocol::MediaAction::Play,
+                MediaActionCli::Pause => protocol::MediaAction::Pause,
+                MediaActionCli::PlayPause => protocol::MediaAction::PlayPause,
+                MediaActionCli::Next => protocol::MediaAction::Next,
+                MediaActionCli::Previous => protocol::MediaAction::Previous,
+                MediaActionCli::Stop => protocol::MediaAction::Stop,
+            },
+        },
+        Commands::Workspace { action } => Command::Workspace {
+            action: match action {
+                WorkspaceActionCli::MoveWindow { workspace } => match workspace.parse::<u32>() {
+                    Ok(index) => protocol::WorkspaceAction::MoveWindow { index },
+                    Err(_) => protocol::WorkspaceAction::MoveWindowByName { name: workspace },
+                },
+                WorkspaceActionCli::MoveWindowUp => protocol::WorkspaceAction::MoveWindowUp,
+                WorkspaceActionCli::MoveWindowDown => protocol::WorkspaceAction::MoveWindowDown,
+            },
+        },
+        Commands::Hotkey { key } => Command::Hotkey { key },
+        Commands::Dnd { action } => Command::SetDnd {
+            enabled: matches!(action, DndAction::On),
+        },
+        Commands::IdleInhibit { action } => Command::SetIdleInhibit {
+            enabled: matches!(action, IdleInhibitAction::On),
+        },
+        Commands::Airplane { action } => Command::SetAirplaneMode {
+            enabled: matches!(action, AirplaneAction::On),
+        },
+        Commands::Vpn { action } => Command::Vpn {
+            action: match action {
+                VpnActionCli::Up { name } => protocol::VpnAction::Up { name },
+                VpnActionCli::Down { name } => protocol::VpnAction::Down { name },
+                VpnActionCli::List => protocol::VpnAction::List,
+            },
+        },
+        Commands::Screenshot { mode } => Command::Screenshot {
+            mode: match mode {
+                ScreenshotModeCli::Full => protocol::ScreenshotMode::Full,
+                ScreenshotModeCli::Region => protocol::ScreenshotMode::Region,
+                ScreenshotModeCli::Window => protocol::ScreenshotMode::Window,
+            },
+        },
+        Commands::Record { action } => Command::ScreenRecord {
+            action: match action {
+                RecordActionCli::Start => protocol::RecordAction::Start,
+                RecordActionCli::Stop => protocol::RecordAction::Stop,
+                RecordActionCli::Toggle => protocol::RecordAction::Toggle,
+            },
+        },
+        Commands::PowerOffMonitors => Command::PowerOffMonitors,
+        Commands::Output { action } => Command::Output {
+            action: match action {
+                OutputActionCli::List => protocol::OutputAction::List,
+                OutputActionCli::Scale { name, scale } => {
+                    protocol::OutputAction::Scale { name, scale }
+                }
+                OutputActionCli::Transform { name, transform } => {
+                    protocol::OutputAction::Transform { name, transform }
+                }
             },
         },
+        Commands::Reload => Command::Reload,
+        Commands::Quit => Command::Quit,
         Commands::Status => Command::Status,
         Commands::Ping => Command::Ping,
     };
 
-    send_command(command)?;
+    send_command(command, cli.json, Duration::from_millis(cli.wait))?;
 
     Ok(())
 }
@@ -257,8 +484,14 @@ fn parse_popup_type(s: &str) -> anyhow::Result<PopupType> {
         "wifi" | "network" => Ok(PopupType::Wifi),
         "media-control" | "media" => Ok(PopupType::MediaControl),
         "power" => Ok(PopupType::Power),
+        "calendar" => Ok(PopupType::Calendar),
+        "notifications" => Ok(PopupType::Notifications),
+        "launcher" => Ok(PopupType::Launcher),
+        "debug" | "events" => Ok(PopupType::Debug),
+        "quick-settings" | "quick" => Ok(PopupType::QuickSettings),
+        "windows" | "window-switcher" => Ok(PopupType::Windows),
         _ => Err(anyhow::anyhow!(
-            "Invalid popup type: {}. Valid types: bluetooth, wifi, media-control, power",
+            "Invalid popup type: {}. Valid types: bluetooth, wifi, media-control, power, calendar, notifications, launcher, debug, quick-settings, windows",
             s
         )),
     }
@@ -269,24 +502,38 @@ fn get_socket_path() -> anyhow::Result<PathBuf> {
         .or_else(|_| std::env::var("TMPDIR"))
         .unwrap_or_else(|_| "/tmp".to_string());
 
-    let socket_path = PathBuf::from(runtime_dir).join("amiya").join("amiya.sock");
+    Ok(PathBuf::from(runtime_dir).join("amiya").join("amiya.sock"))
+}
 
-    if !socket_path.exists() {
-        return Err(anyhow::anyhow!(
-            "Amiya socket not found at {:?}. Is Amiya running?",
-            socket_path
-        ));
+/// Connect to the socket, retrying until `wait` has passed
+fn connect(socket_path: &Path, wait: Duration) -> anyhow::Result<UnixStream> {
+    let deadline = Instant::now() + wait;
+    loop {
+        match UnixStream::connect(socket_path) {
+            Ok(stream) => return Ok(stream),
+            Err(_) if Instant::now() < deadline => std::thread::sleep(RETRY_INTERVAL),
+            Err(e) if !socket_path.exists() => {
+                return Err(anyhow::anyhow!(
+                    "Amiya socket not found at {:?} ({}). Is Amiya running?",
+                    socket_path,
+                    e
+                ));
+            }
+            Err(e) => {
+                return Err(anyhow::anyhow!(
+                    "Failed to connect to Amiya: {}. Is Amiya running?",
+                    e
+                ));
+            }
+        }
     }
-
-    Ok(socket_path)
 }
 
-fn send_command(command: Command) -> anyhow::Result<()> {
+fn send_command(command: Command, json: bool, wait: Duration) -> anyhow::Result<()> {
     let socket_path = get_socket_path()?;
 
     // Connect to Unix socket
-    let mut stream = UnixStream::connect(&socket_path)
-        .map_err(|e| anyhow::anyhow!("Failed to connect to Amiya: {}. Is Amiya running?", e))?;
+    let mut stream = connect(&socket_path, wait)?;
 
     // Serialize command
     let command_json = serde_json::to_string(&command)?;
@@ -304,6 +551,14 @@ fn send_command(command: Command) -> anyhow::Result<()> {
     // Parse response
     let response: Response = serde_json::from_str(&response_line)?;
 
+    if json {
+        println!("{}", serde_json::to_string(&response)?);
+        if matches!(response, Response::Error { .. }) {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Print response
     match response {
         Response::Success { message } => {
@@ -317,10 +572,17 @@ fn send_command(command: Command) -> anyhow::Result<()> {
             eprintln!("✗ Error: {}", message);
             std::process::exit(1);
         }
-        Response::Status { version, uptime } => {
+        Response::Status {
+            version,
+            uptime,
+            subscribers,
+            dropped_events,
+        } => {
             println!("Amiya Desktop Environment");
             println!("Version: {}", version);
             println!("Uptime: {} seconds", uptime);
+            println!("Event subscribers: {}", subscribers);
+            println!("Dropped events: {}", dropped_events);
         }
         Response::Pong => {
             println!("✓ Pong! Server is alive.");
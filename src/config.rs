@@ -1,8 +1,19 @@
+use crate::error::AmiyaError;
 use anyhow::{Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::warn;
+
+/// Commented config written on first run, listing every option
+const DEFAULT_CONFIG: &str = include_str!("../config.example.toml");
+
+/// Config file given on the command line, used instead of the user's
+static PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -14,6 +25,24 @@ pub struct Config {
 
     #[serde(default)]
     pub theme: ThemeConfig,
+
+    #[serde(default)]
+    pub popup: PopupConfig,
+
+    #[serde(default)]
+    pub overlay: OverlayConfig,
+
+    #[serde(default)]
+    pub events: EventsConfig,
+
+    #[serde(default)]
+    pub audio: AudioConfig,
+
+    #[serde(default)]
+    pub power: PowerConfig,
+
+    #[serde(default)]
+    pub logging: LoggingConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,14 +53,141 @@ pub struct BarConfig {
     #[serde(default = "default_position")]
     pub position: Position,
 
+    /// Slide the bar out of the way until the pointer touches its screen
+    /// edge, giving its space back to windows while hidden
+    #[serde(default)]
+    pub auto_hide: bool,
+
+    /// Keep windows out of the space the bar covers. When off, windows
+    /// extend under the bar and it floats on top of them
+    #[serde(default = "default_true")]
+    pub reserve_space: bool,
+
     #[serde(default = "default_true")]
     pub show_workspaces: bool,
 
+    /// Show a dot per open window on each workspace button
+    #[serde(default = "default_true")]
+    pub workspace_show_windows: bool,
+
+    /// Text shown on workspace buttons, keyed by workspace name or index,
+    /// e.g. `web = ""` or `"1" = "一"`
+    #[serde(default)]
+    pub workspace_labels: HashMap<String, String>,
+
+    /// Scrolling down over the workspaces focuses the next workspace; set to
+    /// go to the previous one instead
+    #[serde(default)]
+    pub workspace_scroll_reverse: bool,
+
     #[serde(default = "default_true")]
     pub show_clock: bool,
 
     #[serde(default = "default_true")]
     pub show_system_info: bool,
+
+    /// Show a mini bar per CPU core next to the CPU percentage
+    #[serde(default)]
+    pub show_cpu_cores: bool,
+
+    /// Show the 1/5/15-minute load average
+    #[serde(default)]
+    pub show_load_average: bool,
+
+    /// Explicit temperature input file, e.g. /sys/class/hwmon/hwmon2/temp1_input
+    /// (auto-detected when unset)
+    #[serde(default)]
+    pub temperature_sensor: Option<PathBuf>,
+
+    /// Unit the temperature is displayed in
+    #[serde(default = "default_temperature_unit")]
+    pub temperature_unit: TemperatureUnit,
+
+    /// Temperature (°C) above which the label is styled as a warning
+    #[serde(default = "default_temperature_warning")]
+    pub temperature_warning: i32,
+
+    /// Temperature (°C) above which the label is styled as critical
+    #[serde(default = "default_temperature_critical")]
+    pub temperature_critical: i32,
+
+    /// Mount points whose disk usage is shown (empty to hide the disk label)
+    #[serde(default = "default_disk_mounts")]
+    pub disk_mounts: Vec<String>,
+
+    #[serde(default = "default_true")]
+    pub show_window_title: bool,
+
+    #[serde(default = "default_window_title_max_length")]
+    pub window_title_max_length: usize,
+
+    #[serde(default = "default_true")]
+    pub show_keyboard_layout: bool,
+
+    #[serde(default = "default_true")]
+    pub show_network_speed: bool,
+
+    /// Show StatusNotifierItem tray icons
+    #[serde(default = "default_true")]
+    pub show_tray: bool,
+
+    /// Show the idle inhibitor toggle
+    #[serde(default = "default_true")]
+    pub show_idle_inhibitor: bool,
+
+    /// strftime format for the clock
+    #[serde(default = "default_clock_format")]
+    pub clock_format: String,
+
+    /// strftime format for the clock tooltip (empty to disable)
+    #[serde(default = "default_clock_format_tooltip")]
+    pub clock_format_tooltip: String,
+
+    /// Samples kept by the cpu-graph and memory-graph modules (one every 2s)
+    #[serde(default = "default_sparkline_history")]
+    pub sparkline_history: usize,
+
+    /// Modules shown on the left of the bar, in order (see `BAR_MODULES`)
+    #[serde(default = "default_modules_left")]
+    pub modules_left: Vec<String>,
+
+    /// Modules shown in the center of the bar, in order
+    #[serde(default = "default_modules_center")]
+    pub modules_center: Vec<String>,
+
+    /// Modules shown on the right of the bar, in order
+    #[serde(default = "default_modules_right")]
+    pub modules_right: Vec<String>,
+
+    /// Actions run when clicking bar widgets, keyed by widget name ("battery",
+    /// "volume", "wifi", "bluetooth", "cpu", "memory", "temperature", "disk")
+    #[serde(default = "default_click_actions")]
+    pub click_actions: HashMap<String, ClickActions>,
+}
+
+/// Actions bound to the mouse buttons on a bar widget
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClickActions {
+    #[serde(default)]
+    pub left: Option<HotkeyAction>,
+
+    #[serde(default)]
+    pub middle: Option<HotkeyAction>,
+
+    #[serde(default)]
+    pub right: Option<HotkeyAction>,
+}
+
+impl ClickActions {
+    /// Action for a GDK button number (1 = left, 2 = middle, 3 = right)
+    pub fn for_button(&self, button: u32) -> Option<&HotkeyAction> {
+        match button {
+            1 => self.left.as_ref(),
+            2 => self.middle.as_ref(),
+            3 => self.right.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -41,12 +197,164 @@ pub enum Position {
     Bottom,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    /// Convert a Celsius reading to this unit
+    pub fn convert(self, celsius: i32) -> i32 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => (celsius as f64 * 9.0 / 5.0 + 32.0).round() as i32,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Fahrenheit => "°F",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PopupConfig {
+    /// Screen corner (or center) popups are anchored to
+    #[serde(default = "default_popup_anchor")]
+    pub anchor: PopupAnchor,
+
+    /// Gap in pixels between a popup and the screen edges or the bar
+    #[serde(default = "default_popup_margin")]
+    pub margin: i32,
+
+    /// Toggles in the quick settings popup, in order
+    #[serde(default = "default_quick_settings")]
+    pub quick_settings: Vec<QuickToggle>,
+}
+
+/// A toggle in the quick settings popup
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuickToggle {
+    Wifi,
+    Bluetooth,
+    Dnd,
+    Airplane,
+    IdleInhibitor,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PopupAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayConfig {
+    /// How long the volume/brightness overlay stays up after the last change
+    #[serde(default = "default_overlay_timeout_ms")]
+    pub timeout_ms: u64,
+
+    #[serde(default = "default_overlay_position")]
+    pub position: OverlayPosition,
+
+    /// Distance in pixels from the anchored screen edge
+    #[serde(default = "default_overlay_margin")]
+    pub margin: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventsConfig {
+    /// Events buffered per subscriber before the oldest are dropped; only
+    /// read at startup
+    #[serde(default = "default_event_capacity")]
+    pub capacity: usize,
+
+    /// Allow opening the event log popup (`amiya-ctl popup show debug`),
+    /// which `--debug` also enables
+    #[serde(default)]
+    pub debug_popup: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioConfig {
+    /// Unmute when the volume is raised while muted
+    #[serde(default = "default_true")]
+    pub unmute_on_volume_up: bool,
+
+    /// Volume ceiling in percent; above 100 boosts the signal in software
+    #[serde(default = "default_max_volume_percent")]
+    pub max_volume_percent: u32,
+}
+
+/// What to do when the battery runs critically low
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PowerConfig {
+    /// Action taken once the battery is critical while discharging, after a
+    /// countdown that can be cancelled
+    #[serde(default)]
+    pub critical_action: CriticalAction,
+
+    /// Battery percentage at or below which it counts as critical
+    #[serde(default = "default_critical_percent")]
+    pub critical_percent: u32,
+
+    /// Seconds the countdown runs before `critical_action` is taken
+    #[serde(default = "default_critical_delay_secs")]
+    pub critical_delay_secs: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CriticalAction {
+    /// Take no action
+    #[default]
+    None,
+    /// Lower the screen brightness
+    Dim,
+    /// Suspend the system
+    Suspend,
+}
+
+/// Log verbosity and destination; only read at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Level, or comma-separated `target=level` directives such as
+    /// `info,amiya::backend=debug`; `RUST_LOG` overrides it
+    #[serde(default = "default_log_level")]
+    pub level: String,
+
+    /// Also write logs to `$XDG_STATE_HOME/amiya/amiya.log`
+    #[serde(default)]
+    pub file: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayPosition {
+    Top,
+    Center,
+    Bottom,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum HotkeyAction {
     ShowBluetooth,
     ShowWifi,
     ShowMediaControl,
+    ShowLauncher,
+    ShowPower,
+    ShowQuickSettings,
+    ShowWindows,
     IncreaseBrightness,
     DecreaseBrightness,
     IncreaseVolume,
@@ -65,11 +373,40 @@ pub struct ThemeConfig {
     #[serde(default = "default_accent")]
     pub accent: String,
 
+    /// Color for warnings: low battery, high temperature, a weak signal and
+    /// a lost niri connection
+    #[serde(default = "default_warning")]
+    pub warning: String,
+
+    /// Color for critical warnings: volume boosted past 100%, critical
+    /// battery and temperature, and the critical battery countdown
+    #[serde(default = "default_critical")]
+    pub critical: String,
+
     #[serde(default = "default_font")]
     pub font: String,
 
     #[serde(default = "default_font_size")]
     pub font_size: u32,
+
+    /// Workspace button colors by state; unset ones derive from the colors
+    /// above (the accent for active, dimmed foreground for empty, critical
+    /// for urgent)
+    #[serde(default)]
+    pub workspace_active: Option<String>,
+
+    #[serde(default)]
+    pub workspace_occupied: Option<String>,
+
+    #[serde(default)]
+    pub workspace_empty: Option<String>,
+
+    #[serde(default)]
+    pub workspace_urgent: Option<String>,
+
+    /// User stylesheet applied on top of the built-in styles
+    #[serde(default)]
+    pub style_path: Option<PathBuf>,
 }
 
 // Default values
@@ -77,12 +414,79 @@ fn default_bar() -> BarConfig {
     BarConfig {
         height: default_height(),
         position: default_position(),
+        auto_hide: false,
+        reserve_space: true,
         show_workspaces: true,
+        workspace_show_windows: true,
+        workspace_labels: HashMap::new(),
+        workspace_scroll_reverse: false,
         show_clock: true,
         show_system_info: true,
+        show_cpu_cores: false,
+        show_load_average: false,
+        disk_mounts: default_disk_mounts(),
+        temperature_sensor: None,
+        temperature_unit: default_temperature_unit(),
+        temperature_warning: default_temperature_warning(),
+        temperature_critical: default_temperature_critical(),
+        show_window_title: true,
+        window_title_max_length: default_window_title_max_length(),
+        show_keyboard_layout: true,
+        show_network_speed: true,
+        show_tray: true,
+        show_idle_inhibitor: true,
+        clock_format: default_clock_format(),
+        clock_format_tooltip: default_clock_format_tooltip(),
+        sparkline_history: default_sparkline_history(),
+        modules_left: default_modules_left(),
+        modules_center: default_modules_center(),
+        modules_right: default_modules_right(),
+        click_actions: default_click_actions(),
     }
 }
 
+/// Module names accepted in `bar.modules_left/center/right`
+pub const BAR_MODULES: &[&str] = &[
+    "workspaces",
+    "window-title",
+    "clock",
+    "tray",
+    "network-speed",
+    "keyboard-layout",
+    "idle-inhibitor",
+    "battery",
+    "system-info",
+    "cpu-graph",
+    "memory-graph",
+    "media",
+];
+
+fn default_sparkline_history() -> usize {
+    30
+}
+
+fn default_modules_left() -> Vec<String> {
+    vec!["workspaces".to_string(), "window-title".to_string()]
+}
+
+fn default_modules_center() -> Vec<String> {
+    vec!["clock".to_string()]
+}
+
+fn default_modules_right() -> Vec<String> {
+    [
+        "tray",
+        "network-speed",
+        "keyboard-layout",
+        "idle-inhibitor",
+        "battery",
+        "system-info",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect()
+}
+
 fn default_height() -> i32 {
     32
 }
@@ -91,6 +495,99 @@ fn default_position() -> Position {
     Position::Top
 }
 
+fn default_disk_mounts() -> Vec<String> {
+    vec!["/".to_string()]
+}
+
+fn default_temperature_unit() -> TemperatureUnit {
+    TemperatureUnit::Celsius
+}
+
+fn default_temperature_warning() -> i32 {
+    80
+}
+
+fn default_temperature_critical() -> i32 {
+    90
+}
+
+fn default_window_title_max_length() -> usize {
+    60
+}
+
+fn default_click_actions() -> HashMap<String, ClickActions> {
+    let mut actions = HashMap::new();
+    actions.insert(
+        "battery".to_string(),
+        ClickActions {
+            right: Some(HotkeyAction::ShowPower),
+            ..Default::default()
+        },
+    );
+    actions.insert(
+        "wifi".to_string(),
+        ClickActions {
+            left: Some(HotkeyAction::ShowWifi),
+            right: Some(HotkeyAction::ShowWifi),
+            ..Default::default()
+        },
+    );
+    actions.insert(
+        "volume".to_string(),
+        ClickActions {
+            left: Some(HotkeyAction::Mute),
+            middle: Some(HotkeyAction::Mute),
+            ..Default::default()
+        },
+    );
+    actions.insert(
+        "bluetooth".to_string(),
+        ClickActions {
+            left: Some(HotkeyAction::ShowBluetooth),
+            ..Default::default()
+        },
+    );
+    actions
+}
+
+fn default_clock_format() -> String {
+    "%a %b %d  %H:%M:%S".to_string()
+}
+
+fn default_clock_format_tooltip() -> String {
+    "%A, %B %d, %Y".to_string()
+}
+
+fn default_popup_anchor() -> PopupAnchor {
+    PopupAnchor::TopRight
+}
+
+fn default_popup_margin() -> i32 {
+    10
+}
+
+fn default_quick_settings() -> Vec<QuickToggle> {
+    vec![
+        QuickToggle::Wifi,
+        QuickToggle::Bluetooth,
+        QuickToggle::Dnd,
+        QuickToggle::Airplane,
+        QuickToggle::IdleInhibitor,
+    ]
+}
+
+fn default_overlay_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_overlay_position() -> OverlayPosition {
+    OverlayPosition::Top
+}
+
+fn default_overlay_margin() -> i32 {
+    100
+}
+
 fn default_true() -> bool {
     true
 }
@@ -107,6 +604,14 @@ fn default_accent() -> String {
     "#89b4fa".to_string()
 }
 
+fn default_warning() -> String {
+    "#f9e2af".to_string()
+}
+
+fn default_critical() -> String {
+    "#f38ba8".to_string()
+}
+
 fn default_font() -> String {
     "Sans".to_string()
 }
@@ -115,38 +620,348 @@ fn default_font_size() -> u32 {
     11
 }
 
+impl Default for PopupConfig {
+    fn default() -> Self {
+        Self {
+            anchor: default_popup_anchor(),
+            margin: default_popup_margin(),
+            quick_settings: default_quick_settings(),
+        }
+    }
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_overlay_timeout_ms(),
+            position: default_overlay_position(),
+            margin: default_overlay_margin(),
+        }
+    }
+}
+
+fn default_event_capacity() -> usize {
+    crate::events::DEFAULT_EVENT_CAPACITY
+}
+
+impl Default for EventsConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_event_capacity(),
+            debug_popup: false,
+        }
+    }
+}
+
+fn default_max_volume_percent() -> u32 {
+    100
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            unmute_on_volume_up: true,
+            max_volume_percent: default_max_volume_percent(),
+        }
+    }
+}
+
+pub(crate) fn default_critical_percent() -> u32 {
+    5
+}
+
+fn default_critical_delay_secs() -> u32 {
+    60
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            critical_action: CriticalAction::default(),
+            critical_percent: default_critical_percent(),
+            critical_delay_secs: default_critical_delay_secs(),
+        }
+    }
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: default_log_level(),
+            file: false,
+        }
+    }
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+/// Check that a strftime format only contains specifiers chrono understands
+fn is_valid_time_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| !matches!(item, Item::Error))
+}
+
+impl BarConfig {
+    /// Text for the button of workspace `index`: the label configured for its
+    /// name or index, else its name, else the index
+    pub fn workspace_label(&self, index: u32, name: Option<&str>) -> String {
+        name.and_then(|name| self.workspace_labels.get(name))
+            .or_else(|| self.workspace_labels.get(&index.to_string()))
+            .cloned()
+            .or_else(|| name.map(str::to_string))
+            .unwrap_or_else(|| index.to_string())
+    }
+
+    /// Whether the `show_*` toggle for module `name` is on
+    pub fn shows_module(&self, name: &str) -> bool {
+        match name {
+            "workspaces" => self.show_workspaces,
+            "window-title" => self.show_window_title,
+            "clock" => self.show_clock,
+            "tray" => self.show_tray,
+            "network-speed" => self.show_network_speed,
+            "keyboard-layout" => self.show_keyboard_layout,
+            "idle-inhibitor" => self.show_idle_inhibitor,
+            "system-info" => self.show_system_info,
+            _ => true,
+        }
+    }
+
+    /// Check clock formats, temperature thresholds and module names
+    pub fn validate(&self) -> crate::error::Result<()> {
+        for (field, value) in [
+            ("clock_format", &self.clock_format),
+            ("clock_format_tooltip", &self.clock_format_tooltip),
+        ] {
+            if !is_valid_time_format(value) {
+                return Err(AmiyaError::Config(format!(
+                    "bar.{}: invalid strftime format {:?}",
+                    field, value
+                )));
+            }
+        }
+
+        if self.temperature_warning > self.temperature_critical {
+            return Err(AmiyaError::Config(format!(
+                "bar.temperature_warning ({}) must not exceed bar.temperature_critical ({})",
+                self.temperature_warning, self.temperature_critical
+            )));
+        }
+
+        if self.sparkline_history < 2 {
+            return Err(AmiyaError::Config(format!(
+                "bar.sparkline_history: {} is too short to draw (at least 2)",
+                self.sparkline_history
+            )));
+        }
+
+        for (field, modules) in [
+            ("modules_left", &self.modules_left),
+            ("modules_center", &self.modules_center),
+            ("modules_right", &self.modules_right),
+        ] {
+            if let Some(name) = modules.iter().find(|m| !BAR_MODULES.contains(&m.as_str())) {
+                return Err(AmiyaError::Config(format!(
+                    "bar.{}: unknown module {:?} (expected one of {})",
+                    field,
+                    name,
+                    BAR_MODULES.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Accepted range for `theme.font_size`, in points
+const FONT_SIZE_RANGE: std::ops::RangeInclusive<u32> = 6..=72;
+
+/// CSS named colors accepted in theme fields
+const NAMED_COLORS: &[&str] = &[
+    "transparent", "aliceblue", "antiquewhite", "aqua", "aquamarine", "azure", "beige",
+    "bisque", "black", "blanchedalmond", "blue", "blueviolet", "brown", "burlywood",
+    "cadetblue", "chartreuse", "chocolate", "coral", "cornflowerblue", "cornsilk", "crimson",
+    "cyan", "darkblue", "darkcyan", "darkgoldenrod", "darkgray", "darkgreen", "darkgrey",
+    "darkkhaki", "darkmagenta", "darkolivegreen", "darkorange", "darkorchid", "darkred",
+    "darksalmon", "darkseagreen", "darkslateblue", "darkslategray", "darkslategrey",
+    "darkturquoise", "darkviolet", "deeppink", "deepskyblue", "dimgray", "dimgrey",
+    "dodgerblue", "firebrick", "floralwhite", "forestgreen", "fuchsia", "gainsboro",
+    "ghostwhite", "gold", "goldenrod", "gray", "green", "greenyellow", "grey", "honeydew",
+    "hotpink", "indianred", "indigo", "ivory", "khaki", "lavender", "lavenderblush",
+    "lawngreen", "lemonchiffon", "lightblue", "lightcoral", "lightcyan",
+    "lightgoldenrodyellow", "lightgray", "lightgreen", "lightgrey", "lightpink",
+    "lightsalmon", "lightseagreen", "lightskyblue", "lightslategray", "lightslategrey",
+    "lightsteelblue", "lightyellow", "lime", "limegreen", "linen", "magenta", "maroon",
+    "mediumaquamarine", "mediumblue", "mediumorchid", "mediumpurple", "mediumseagreen",
+    "mediumslateblue", "mediumspringgreen", "mediumturquoise", "mediumvioletred",
+    "midnightblue", "mintcream", "mistyrose", "moccasin", "navajowhite", "navy", "oldlace",
+    "olive", "olivedrab", "orange", "orangered", "orchid", "palegoldenrod", "palegreen",
+    "paleturquoise", "palevioletred", "papayawhip", "peachpuff", "peru", "pink", "plum",
+    "powderblue", "purple", "rebeccapurple", "red", "rosybrown", "royalblue", "saddlebrown",
+    "salmon", "sandybrown", "seagreen", "seashell", "sienna", "silver", "skyblue",
+    "slateblue", "slategray", "slategrey", "snow", "springgreen", "steelblue", "tan", "teal",
+    "thistle", "tomato", "turquoise", "violet", "wheat", "white", "whitesmoke", "yellow",
+    "yellowgreen",
+];
+
+/// Check that a color is a `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`,
+/// `rgba()` or CSS named color
+fn is_valid_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => {
+            matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => is_valid_rgb(color) || NAMED_COLORS.contains(&color.to_ascii_lowercase().as_str()),
+    }
+}
+
+/// Check an `rgb(r, g, b)` or `rgba(r, g, b, a)` color, with channels from
+/// 0-255 or as percentages and alpha from 0 to 1
+fn is_valid_rgb(color: &str) -> bool {
+    let color = color.to_ascii_lowercase();
+    let (args, count) = if let Some(args) = color.strip_prefix("rgba(") {
+        (args, 4)
+    } else if let Some(args) = color.strip_prefix("rgb(") {
+        (args, 3)
+    } else {
+        return false;
+    };
+    let Some(args) = args.strip_suffix(')') else {
+        return false;
+    };
+
+    let parts: Vec<&str> = args.split(',').map(str::trim).collect();
+    let channel = |part: &str| match part.strip_suffix('%') {
+        Some(percent) => percent
+            .parse::<f64>()
+            .is_ok_and(|value| (0.0..=100.0).contains(&value)),
+        None => part.parse::<u8>().is_ok(),
+    };
+    let alpha = |part: &str| part.parse::<f64>().is_ok_and(|value| (0.0..=1.0).contains(&value));
+
+    parts.len() == count
+        && parts[..3].iter().all(|part| channel(part))
+        && parts.get(3).is_none_or(|part| alpha(part))
+}
+
+impl ThemeConfig {
+    /// Check colors and font size before they are interpolated into CSS
+    pub fn validate(&self) -> crate::error::Result<()> {
+        let optional = [
+            ("workspace_active", &self.workspace_active),
+            ("workspace_occupied", &self.workspace_occupied),
+            ("workspace_empty", &self.workspace_empty),
+            ("workspace_urgent", &self.workspace_urgent),
+        ];
+        let optional = optional
+            .into_iter()
+            .filter_map(|(field, value)| value.as_ref().map(|value| (field, value)));
+        for (field, value) in [
+            ("background", &self.background),
+            ("foreground", &self.foreground),
+            ("accent", &self.accent),
+            ("warning", &self.warning),
+            ("critical", &self.critical),
+        ]
+        .into_iter()
+        .chain(optional)
+        {
+            if !is_valid_color(value) {
+                return Err(AmiyaError::Config(format!(
+                    "theme.{}: invalid color {:?} (expected #rrggbb, #rgb, #rrggbbaa, rgba(r, g, b, a) or a CSS color name)",
+                    field, value
+                )));
+            }
+        }
+
+        if !FONT_SIZE_RANGE.contains(&self.font_size) {
+            return Err(AmiyaError::Config(format!(
+                "theme.font_size: {} is out of range ({}-{})",
+                self.font_size,
+                FONT_SIZE_RANGE.start(),
+                FONT_SIZE_RANGE.end()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
 impl Default for ThemeConfig {
     fn default() -> Self {
         Self {
             background: default_background(),
             foreground: default_foreground(),
             accent: default_accent(),
+            warning: default_warning(),
+            critical: default_critical(),
             font: default_font(),
             font_size: default_font_size(),
+            workspace_active: None,
+            workspace_occupied: None,
+            workspace_empty: None,
+            workspace_urgent: None,
+            style_path: None,
         }
     }
 }
 
 impl Config {
+    /// Load the config, creating the user config file from the commented
+    /// template if neither it nor a system default exists
     pub fn load() -> Result<Self> {
-        let config_path = Self::config_path()?;
-
-        if config_path.exists() {
-            let content = fs::read_to_string(&config_path)
-                .context("Failed to read config file")?;
-            let config: Config = toml::from_str(&content)
-                .context("Failed to parse config file")?;
-            Ok(config)
-        } else {
+        let config_path = Self::source_path()?;
+
+        if !config_path.exists() && PATH_OVERRIDE.get().is_none() {
             // Create default config
-            let config = Self::default();
-            config.save()?;
-            Ok(config)
+            if let Some(parent) = config_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&config_path, DEFAULT_CONFIG)
+                .with_context(|| format!("Failed to write {}", config_path.display()))?;
+        }
+
+        Self::read_from(&config_path)
+    }
+
+    /// Read and validate the config file, without creating it if it's missing
+    pub fn read() -> Result<Self> {
+        Self::read_from(&Self::source_path()?)
+    }
+
+    fn read_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let config: Config = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Use `path` instead of the user config file from now on (`--config`)
+    pub fn set_path(path: PathBuf) {
+        if PATH_OVERRIDE.set(path).is_err() {
+            warn!("Config path already set; ignoring the new one");
         }
     }
 
+    /// File the config is read from: the user config file, or the first
+    /// system default in `$XDG_CONFIG_DIRS` while there is none
+    fn source_path() -> Result<PathBuf> {
+        let config_path = Self::path()?;
+        if config_path.exists() || PATH_OVERRIDE.get().is_some() {
+            return Ok(config_path);
+        }
+
+        let config_dirs =
+            std::env::var("XDG_CONFIG_DIRS").unwrap_or_else(|_| "/etc/xdg".to_string());
+        Ok(find_in_config_dirs(&config_dirs).unwrap_or(config_path))
+    }
+
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::config_path()?;
+        let config_path = Self::path()?;
 
         // Create parent directory if it doesn't exist
         if let Some(parent) = config_path.parent() {
@@ -159,13 +974,131 @@ impl Config {
         Ok(())
     }
 
-    fn config_path() -> Result<PathBuf> {
+    /// Check values that serde can't, such as theme colors that end up in CSS
+    pub fn validate(&self) -> crate::error::Result<()> {
+        self.bar.validate()?;
+        self.theme.validate()?;
+
+        if self.events.capacity == 0 {
+            return Err(AmiyaError::Config(
+                "events.capacity must be at least 1".to_string(),
+            ));
+        }
+
+        let max_volume = self.audio.max_volume_percent;
+        if !(100..=crate::backend::system::audio::MAX_VOLUME_BOOST).contains(&max_volume) {
+            return Err(AmiyaError::Config(format!(
+                "audio.max_volume_percent must be between 100 and {}, got {}",
+                crate::backend::system::audio::MAX_VOLUME_BOOST,
+                max_volume
+            )));
+        }
+
+        if !(1..=50).contains(&self.power.critical_percent) {
+            return Err(AmiyaError::Config(format!(
+                "power.critical_percent must be between 1 and 50, got {}",
+                self.power.critical_percent
+            )));
+        }
+
+        // Long enough to notice the countdown and cancel it
+        if !(10..=600).contains(&self.power.critical_delay_secs) {
+            return Err(AmiyaError::Config(format!(
+                "power.critical_delay_secs must be between 10 and 600, got {}",
+                self.power.critical_delay_secs
+            )));
+        }
+
+        if let Err(e) = self.logging.level.parse::<tracing_subscriber::filter::Targets>() {
+            return Err(AmiyaError::Config(format!(
+                "logging.level: invalid filter {:?}: {}",
+                self.logging.level, e
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Path of the user config file, or the one given with `--config`
+    pub fn path() -> Result<PathBuf> {
+        if let Some(path) = PATH_OVERRIDE.get() {
+            return Ok(path.clone());
+        }
+
         let config_dir = dirs::config_dir()
             .or_else(|| std::env::var("HOME").ok().map(|h| PathBuf::from(h).join(".config")))
             .context("Could not determine config directory")?;
 
         Ok(config_dir.join("amiya").join("config.toml"))
     }
+
+    /// Path of the log file written when `logging.file` is set
+    pub fn log_path() -> Result<PathBuf> {
+        let state_dir = dirs::state_dir().context("Could not determine state directory")?;
+
+        Ok(state_dir.join("amiya").join("amiya.log"))
+    }
+
+    /// Watch the config file and call `on_change` with the new config whenever
+    /// its contents change. Parse errors are logged and the old config is kept.
+    ///
+    /// The watcher stops when the returned handle is dropped.
+    pub fn watch<F>(on_change: F) -> Result<RecommendedWatcher>
+    where
+        F: Fn(Config) + Send + 'static,
+    {
+        let config_path = Self::path()?;
+        let config_dir = config_path
+            .parent()
+            .context("Config file has no parent directory")?
+            .to_path_buf();
+        let file_name = config_path.file_name().map(|n| n.to_os_string());
+
+        // Editors often replace the file instead of writing it in place, so watch
+        // the directory and filter events by file name.
+        let mut last_content = fs::read_to_string(&config_path).ok();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("Config watcher error: {}", e);
+                    return;
+                }
+            };
+
+            if !(event.kind.is_create() || event.kind.is_modify()) {
+                return;
+            }
+            if !event.paths.iter().any(|p| p.file_name() == file_name.as_deref()) {
+                return;
+            }
+
+            let content = match fs::read_to_string(&config_path) {
+                Ok(content) => content,
+                Err(_) => return, // File is mid-replace; a later event will follow
+            };
+            if last_content.as_deref() == Some(content.as_str()) {
+                return;
+            }
+            last_content = Some(content.clone());
+
+            let config = match toml::from_str::<Config>(&content) {
+                Ok(config) => config,
+                Err(e) => {
+                    warn!("Ignoring invalid config change: {}", e);
+                    return;
+                }
+            };
+            match config.validate() {
+                Ok(()) => on_change(config),
+                Err(e) => warn!("Ignoring invalid config change: {}", e),
+            }
+        })?;
+
+        watcher.watch(&config_dir, RecursiveMode::NonRecursive)?;
+
+        Ok(watcher)
+    }
 }
 
 impl Default for Config {
@@ -174,15 +1107,31 @@ impl Default for Config {
         hotkeys.insert("Super+B".to_string(), HotkeyAction::ShowBluetooth);
         hotkeys.insert("Super+W".to_string(), HotkeyAction::ShowWifi);
         hotkeys.insert("Super+M".to_string(), HotkeyAction::ShowMediaControl);
+        hotkeys.insert("Super+D".to_string(), HotkeyAction::ShowLauncher);
 
         Self {
             bar: default_bar(),
             hotkeys,
             theme: ThemeConfig::default(),
+            popup: PopupConfig::default(),
+            overlay: OverlayConfig::default(),
+            events: EventsConfig::default(),
+            audio: AudioConfig::default(),
+            power: PowerConfig::default(),
+            logging: LoggingConfig::default(),
         }
     }
 }
 
+/// First `amiya/config.toml` in a colon-separated list of directories
+fn find_in_config_dirs(config_dirs: &str) -> Option<PathBuf> {
+    config_dirs
+        .split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join("amiya").join("config.toml"))
+        .find(|path| path.is_file())
+}
+
 // Helper module since dirs crate is not in dependencies
 mod dirs {
     use std::env;
@@ -198,4 +1147,257 @@ mod dirs {
                     .map(|h| PathBuf::from(h).join(".config"))
             })
     }
+
+    pub fn state_dir() -> Option<PathBuf> {
+        env::var("XDG_STATE_HOME")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var("HOME")
+                    .ok()
+                    .map(|h| PathBuf::from(h).join(".local").join("state"))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_theme_is_valid() {
+        assert!(ThemeConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_color_validation() {
+        assert!(is_valid_color("#fff"));
+        assert!(is_valid_color("#1e1e2e"));
+        assert!(is_valid_color("#1e1e2e80"));
+        assert!(is_valid_color("RebeccaPurple"));
+        assert!(is_valid_color("transparent"));
+        assert!(is_valid_color("rgba(30, 30, 46, 0.8)"));
+        assert!(is_valid_color("rgb(100%, 50%, 0%)"));
+
+        assert!(!is_valid_color("#gggggg"));
+        assert!(!is_valid_color("#12345"));
+        assert!(!is_valid_color("1e1e2e"));
+        assert!(!is_valid_color("notacolor"));
+        assert!(!is_valid_color("red; }"));
+        assert!(!is_valid_color("rgba(30, 30, 46, 1.5)"));
+        assert!(!is_valid_color("rgba(30, 30, 46)"));
+        assert!(!is_valid_color("rgb(300, 0, 0)"));
+        assert!(!is_valid_color("rgb(0, 0, 0); }"));
+    }
+
+    #[test]
+    fn test_invalid_theme_names_field() {
+        let theme = ThemeConfig {
+            accent: "#gggggg".to_string(),
+            ..ThemeConfig::default()
+        };
+        let err = theme.validate().unwrap_err().to_string();
+        assert!(err.contains("theme.accent"), "{}", err);
+        assert!(err.contains("#gggggg"), "{}", err);
+
+        // Optional colors are checked when set
+        let theme = ThemeConfig {
+            workspace_occupied: Some("#a6e3a1".to_string()),
+            workspace_urgent: Some("red; }".to_string()),
+            ..ThemeConfig::default()
+        };
+        let err = theme.validate().unwrap_err().to_string();
+        assert!(err.contains("theme.workspace_urgent"), "{}", err);
+    }
+
+    #[test]
+    fn test_clock_format_validation() {
+        assert!(default_bar().validate().is_ok());
+        assert!(is_valid_time_format("%I:%M %p"));
+        assert!(is_valid_time_format("%Y-%m-%d"));
+
+        let bar = BarConfig {
+            clock_format_tooltip: "%Q".to_string(),
+            ..default_bar()
+        };
+        let err = bar.validate().unwrap_err().to_string();
+        assert!(err.contains("bar.clock_format_tooltip"), "{}", err);
+    }
+
+    #[test]
+    fn test_temperature_conversion() {
+        assert_eq!(TemperatureUnit::Celsius.convert(42), 42);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert(0), 32);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert(100), 212);
+        assert_eq!(TemperatureUnit::Fahrenheit.convert(37), 99);
+    }
+
+    #[test]
+    fn test_font_size_range() {
+        let mut theme = ThemeConfig::default();
+
+        theme.font_size = 0;
+        let err = theme.validate().unwrap_err().to_string();
+        assert!(err.contains("theme.font_size"), "{}", err);
+
+        theme.font_size = 500;
+        assert!(theme.validate().is_err());
+
+        theme.font_size = 6;
+        assert!(theme.validate().is_ok());
+    }
+
+    #[test]
+    fn test_event_capacity_validation() {
+        let mut config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.events.capacity, crate::events::DEFAULT_EVENT_CAPACITY);
+
+        config.events.capacity = 0;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("events.capacity"), "{}", err);
+    }
+
+    #[test]
+    fn test_default_config_template() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        config.validate().unwrap();
+
+        // The template documents the same defaults serde fills in
+        let defaults = Config::default();
+        assert_eq!(config.bar.height, defaults.bar.height);
+        assert_eq!(config.bar.reserve_space, defaults.bar.reserve_space);
+        assert_eq!(config.bar.modules_right, defaults.bar.modules_right);
+        assert_eq!(config.popup.quick_settings, defaults.popup.quick_settings);
+        assert_eq!(config.theme.background, defaults.theme.background);
+        assert_eq!(config.power.critical_action, defaults.power.critical_action);
+        assert_eq!(
+            config.power.critical_percent,
+            defaults.power.critical_percent
+        );
+        assert_eq!(config.logging.level, defaults.logging.level);
+    }
+
+    #[test]
+    fn test_find_in_config_dirs() {
+        let root = std::env::temp_dir().join(format!("amiya-test-{}-xdg", std::process::id()));
+        let (empty, vendor) = (root.join("empty"), root.join("vendor"));
+        fs::create_dir_all(&empty).unwrap();
+        fs::create_dir_all(vendor.join("amiya")).unwrap();
+        fs::write(vendor.join("amiya").join("config.toml"), "").unwrap();
+
+        let dirs = format!("{}::{}", empty.display(), vendor.display());
+        assert_eq!(
+            find_in_config_dirs(&dirs),
+            Some(vendor.join("amiya").join("config.toml"))
+        );
+        assert_eq!(find_in_config_dirs(&empty.display().to_string()), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_log_level_validation() {
+        let mut config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.logging.level, "info");
+        assert!(!config.logging.file);
+
+        config.logging.level = "warn,amiya::backend=debug".to_string();
+        assert!(config.validate().is_ok());
+
+        config.logging.level = "amiya=loud".to_string();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("logging.level"), "{}", err);
+    }
+
+    #[test]
+    fn test_max_volume_validation() {
+        let mut config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.audio.max_volume_percent, 100);
+
+        config.audio.max_volume_percent = 150;
+        assert!(config.validate().is_ok());
+
+        for invalid in [50, 400] {
+            config.audio.max_volume_percent = invalid;
+            let err = config.validate().unwrap_err().to_string();
+            assert!(err.contains("audio.max_volume_percent"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_critical_battery_config() {
+        let mut config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.power.critical_action, CriticalAction::None);
+        assert_eq!(config.power.critical_percent, 5);
+
+        let toml = "[power]\ncritical_action = \"suspend\"\ncritical_delay_secs = 30";
+        let parsed: Config = toml::from_str(toml).unwrap();
+        assert_eq!(parsed.power.critical_action, CriticalAction::Suspend);
+        assert_eq!(parsed.power.critical_delay_secs, 30);
+        assert!(toml::from_str::<Config>("[power]\ncritical_action = \"explode\"").is_err());
+
+        config.power.critical_percent = 0;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("power.critical_percent"), "{}", err);
+
+        config.power.critical_percent = 5;
+        config.power.critical_delay_secs = 3;
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("power.critical_delay_secs"), "{}", err);
+    }
+
+    #[test]
+    fn test_click_actions() {
+        let bar = default_bar();
+        let battery = &bar.click_actions["battery"];
+        assert!(matches!(battery.for_button(3), Some(HotkeyAction::ShowPower)));
+        assert!(battery.for_button(1).is_none());
+        assert!(matches!(
+            bar.click_actions["bluetooth"].for_button(1),
+            Some(HotkeyAction::ShowBluetooth)
+        ));
+
+        let config: Config = toml::from_str(
+            r#"
+            [bar.click_actions.cpu]
+            middle = "show-launcher"
+            "#,
+        )
+        .unwrap();
+        let cpu = &config.bar.click_actions["cpu"];
+        assert!(matches!(
+            cpu.for_button(2),
+            Some(HotkeyAction::ShowLauncher)
+        ));
+        assert!(cpu.for_button(8).is_none());
+    }
+
+    #[test]
+    fn test_module_validation() {
+        let bar = BarConfig {
+            modules_right: vec!["clock".to_string(), "volume-knob".to_string()],
+            ..default_bar()
+        };
+        let err = bar.validate().unwrap_err().to_string();
+        assert!(err.contains("bar.modules_right"), "{}", err);
+        assert!(err.contains("volume-knob"), "{}", err);
+    }
+
+    #[test]
+    fn test_workspace_labels() {
+        let config: Config = toml::from_str(
+            r#"
+            [bar.workspace_labels]
+            web = "W"
+            "2" = "II"
+            "#,
+        )
+        .unwrap();
+        let bar = &config.bar;
+        assert_eq!(bar.workspace_label(1, Some("web")), "W");
+        assert_eq!(bar.workspace_label(2, None), "II");
+        assert_eq!(bar.workspace_label(2, Some("web")), "W");
+        assert_eq!(bar.workspace_label(3, Some("chat")), "chat");
+        assert_eq!(bar.workspace_label(4, None), "4");
+    }
 }
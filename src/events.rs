@@ -1,6 +1,12 @@
+use futures::stream::{self, Stream};
 use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+/// Default number of events buffered per subscriber
+pub const DEFAULT_EVENT_CAPACITY: usize = 100;
 
 /// Event types that can be broadcast throughout the application
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +25,25 @@ pub enum Event {
     WorkspacesUpdated {
         workspaces: Vec<WorkspaceInfo>,
     },
+    /// A window on this workspace started requesting attention
+    WorkspaceUrgent {
+        id: u32,
+    },
+    /// The niri IPC socket stopped or started answering
+    NiriConnectionChanged {
+        connected: bool,
+    },
+
+    // Window events
+    FocusedWindowChanged {
+        title: Option<String>,
+        app_id: Option<String>,
+    },
+
+    // Input events
+    KeyboardLayoutChanged {
+        layout: String,
+    },
 
     // System events
     VolumeChanged {
@@ -31,19 +56,46 @@ pub enum Event {
     CpuUsageChanged {
         usage: f64,
     },
+    CpuPerCoreChanged {
+        usages: Vec<f64>,
+    },
+    LoadAverageChanged {
+        one: f64,
+        five: f64,
+        fifteen: f64,
+    },
     MemoryUsageChanged {
         used: u64,
         total: u64,
         percent: f64,
+        swap_used: u64,
+        swap_total: u64,
     },
     TemperatureChanged {
         celsius: i32,
+        /// Which sensor the reading came from
+        sensor: String,
+    },
+    DiskUsageChanged {
+        mount: String,
+        used: u64,
+        total: u64,
+        percent: f64,
+    },
+    NetworkThroughputChanged {
+        rx_bytes_per_sec: f64,
+        tx_bytes_per_sec: f64,
     },
     BatteryChanged {
         percentage: f64,
         state: String,
         is_charging: bool,
     },
+    /// The battery dropped to the critical level while discharging; sent
+    /// once until it charges or rises above that level again
+    BatteryCritical {
+        percentage: f64,
+    },
 
     // Network events
     WifiStateChanged {
@@ -53,6 +105,16 @@ pub enum Event {
         ssid: String,
     },
     WifiNetworkDisconnected,
+    /// Signal strength (0-100) of the access point we're connected to
+    WifiSignalChanged {
+        ssid: String,
+        strength: u8,
+    },
+    /// A VPN started or stopped; `name` is the active VPN, if any
+    VpnStateChanged {
+        active: bool,
+        name: Option<String>,
+    },
     WifiNetworksUpdated {
         networks: Vec<WifiNetworkInfo>,
     },
@@ -87,6 +149,71 @@ pub enum Event {
     MediaVolumeChanged {
         volume: f64,
     },
+    /// `None` when the player doesn't support shuffle
+    MediaShuffleChanged {
+        shuffle: Option<bool>,
+    },
+    /// `None` when the player doesn't support repeat
+    MediaLoopStatusChanged {
+        loop_status: Option<LoopStatus>,
+    },
+    MediaCapabilitiesChanged {
+        capabilities: MediaCapabilities,
+    },
+
+    // Notification events
+    NotificationReceived {
+        id: u32,
+        app: String,
+        /// Icon name, file path or `file://` URI the app sent, if any
+        icon: Option<String>,
+        summary: String,
+        body: String,
+        actions: Vec<NotificationAction>,
+        /// Requested display time: -1 for the server default, 0 for never expire
+        timeout_ms: i32,
+    },
+    NotificationClosed {
+        id: u32,
+    },
+    DndChanged {
+        enabled: bool,
+    },
+
+    // Idle inhibition events
+    IdleInhibitChanged {
+        enabled: bool,
+    },
+
+    // Airplane mode events
+    AirplaneModeChanged {
+        enabled: bool,
+    },
+
+    // Screen capture events
+    /// `path` is None for window screenshots, which niri saves itself
+    ScreenshotTaken {
+        path: Option<String>,
+    },
+    ScreenRecordingChanged {
+        recording: bool,
+        path: Option<String>,
+    },
+
+    // System tray events
+    TrayItemUpdated {
+        item: TrayItemInfo,
+    },
+    TrayItemRemoved {
+        id: String,
+    },
+
+    // Config events
+    ConfigReloaded,
+
+    // Lifecycle events
+    /// Something (e.g. `amiya-ctl quit`) asked Amiya to exit
+    QuitRequested,
 
     // UI events
     PopupRequested {
@@ -103,6 +230,9 @@ pub struct WorkspaceInfo {
     pub name: Option<String>,
     pub is_active: bool,
     pub is_focused: bool,
+    pub is_urgent: bool,
+    /// App IDs of the windows on this workspace, one per window
+    pub windows: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,6 +249,93 @@ pub struct BluetoothDeviceInfo {
     pub name: String,
     pub connected: bool,
     pub paired: bool,
+    /// Trusted devices may connect on their own, e.g. a headset at boot
+    #[serde(default)]
+    pub trusted: bool,
+    /// BlueZ icon name describing the device type, e.g. `audio-headset`
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Audio codec in use while connected, e.g. `SBC` or `LDAC`
+    #[serde(default)]
+    pub codec: Option<String>,
+}
+
+/// How a media player repeats, as MPRIS `LoopStatus`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoopStatus {
+    None,
+    Track,
+    Playlist,
+}
+
+impl LoopStatus {
+    /// Parse the MPRIS property value
+    pub fn from_mpris(value: &str) -> Option<Self> {
+        match value {
+            "None" => Some(LoopStatus::None),
+            "Track" => Some(LoopStatus::Track),
+            "Playlist" => Some(LoopStatus::Playlist),
+            _ => None,
+        }
+    }
+
+    /// The MPRIS property value
+    pub fn as_mpris(self) -> &'static str {
+        match self {
+            LoopStatus::None => "None",
+            LoopStatus::Track => "Track",
+            LoopStatus::Playlist => "Playlist",
+        }
+    }
+
+    /// The next mode for a repeat button: off, whole playlist, one track
+    pub fn next(self) -> Self {
+        match self {
+            LoopStatus::None => LoopStatus::Playlist,
+            LoopStatus::Playlist => LoopStatus::Track,
+            LoopStatus::Track => LoopStatus::None,
+        }
+    }
+}
+
+/// Which controls the active media player supports, from its MPRIS `Can*`
+/// properties; the default, with nothing supported, means no player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MediaCapabilities {
+    pub can_control: bool,
+    pub can_go_next: bool,
+    pub can_go_previous: bool,
+    pub can_play: bool,
+    pub can_pause: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationAction {
+    pub key: String,
+    pub label: String,
+}
+
+/// A StatusNotifierItem as shown in the tray
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrayItemInfo {
+    /// Bus name and object path, e.g. ":1.42/StatusNotifierItem"
+    pub id: String,
+    pub title: String,
+    pub icon_name: Option<String>,
+    /// Extra directory to look up `icon_name` in
+    pub icon_theme_path: Option<String>,
+    pub icon_pixmap: Option<TrayIconPixmap>,
+    pub tooltip: Option<String>,
+    /// Passive items don't need the user's attention and are hidden
+    pub passive: bool,
+}
+
+/// Icon image in RGBA8 byte order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrayIconPixmap {
+    pub width: i32,
+    pub height: i32,
+    pub rgba: Vec<u8>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -127,18 +344,41 @@ pub enum PopupType {
     Wifi,
     MediaControl,
     Power,
+    Calendar,
+    Notifications,
+    Launcher,
+    /// Event log, only available with `--debug` or `events.debug_popup`
+    Debug,
+    QuickSettings,
+    /// Open windows by workspace, needs niri
+    Windows,
 }
 
 /// Event manager handles broadcasting events throughout the application
+///
+/// Events go through a bounded broadcast channel. Emitting never blocks: once
+/// a subscriber is `capacity` events behind, the oldest events it hasn't read
+/// are overwritten, and its next `recv` returns `RecvError::Lagged(n)` before
+/// resuming with the oldest event still buffered. Missed events are counted in
+/// `dropped_count`; if it keeps growing, raise `events.capacity` in the config.
 pub struct EventManager {
     sender: broadcast::Sender<Event>,
+    capacity: usize,
+    dropped: Arc<AtomicU64>,
 }
 
 impl EventManager {
     /// Create a new event manager with specified channel capacity
+    ///
+    /// A capacity of 0 is raised to 1, since the channel can't be empty.
     pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
         let (sender, _) = broadcast::channel(capacity);
-        EventManager { sender }
+        EventManager {
+            sender,
+            capacity,
+            dropped: Arc::new(AtomicU64::new(0)),
+        }
     }
 
     /// Emit an event to all subscribers
@@ -148,27 +388,116 @@ impl EventManager {
     }
 
     /// Subscribe to events
-    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
-        self.sender.subscribe()
+    pub fn subscribe(&self) -> EventReceiver {
+        EventReceiver {
+            receiver: self.sender.subscribe(),
+            dropped: self.dropped.clone(),
+        }
+    }
+
+    /// Subscribe to just the events `filter` maps to `Some`
+    ///
+    /// Like `EventReceiver::recv_or_skip`, the stream skips over lagging and
+    /// ends when the channel closes, so callers only need
+    /// `while let Some(item) = stream.next().await`:
+    ///
+    /// ```ignore
+    /// let mut volume = pin!(events.subscribe_filtered(|event| match event {
+    ///     Event::VolumeChanged { level, muted } => Some((level, muted)),
+    ///     _ => None,
+    /// }));
+    /// while let Some((level, muted)) = volume.next().await { ... }
+    /// ```
+    pub fn subscribe_filtered<T, F>(&self, filter: F) -> impl Stream<Item = T> + 'static
+    where
+        F: FnMut(Event) -> Option<T> + 'static,
+        T: 'static,
+    {
+        stream::unfold((self.subscribe(), filter), |(mut receiver, mut filter)| async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                if let Some(item) = filter(event) {
+                    return Some((item, (receiver, filter)));
+                }
+            }
+            None
+        })
     }
 
     /// Get the number of active subscribers
     pub fn subscriber_count(&self) -> usize {
         self.sender.receiver_count()
     }
+
+    /// Number of events each subscriber can fall behind before losing events
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Total number of events lagging subscribers have missed
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
 }
 
 impl Clone for EventManager {
     fn clone(&self) -> Self {
         EventManager {
             sender: self.sender.clone(),
+            capacity: self.capacity,
+            dropped: self.dropped.clone(),
         }
     }
 }
 
 impl Default for EventManager {
     fn default() -> Self {
-        Self::new(100) // Default capacity of 100 events
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+/// Receiving end of an event subscription
+///
+/// Behaves like `broadcast::Receiver`, but adds the events it missed while
+/// lagging to its manager's `dropped_count`.
+pub struct EventReceiver {
+    receiver: broadcast::Receiver<Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventReceiver {
+    /// Wait for the next event
+    pub async fn recv(&mut self) -> Result<Event, RecvError> {
+        let result = self.receiver.recv().await;
+        if let Err(RecvError::Lagged(skipped)) = &result {
+            self.dropped.fetch_add(*skipped, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Wait for the next event, skipping over lagging
+    ///
+    /// Missed events are logged and counted in `dropped_count`. Returns
+    /// `None` once the channel closes, so listeners can loop with
+    /// `while let Some(event) = receiver.recv_or_skip().await`.
+    pub async fn recv_or_skip(&mut self) -> Option<Event> {
+        loop {
+            match self.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event listener lagged, skipped {} events", skipped);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Take the next event if one is buffered
+    pub fn try_recv(&mut self) -> Result<Event, TryRecvError> {
+        let result = self.receiver.try_recv();
+        if let Err(TryRecvError::Lagged(skipped)) = &result {
+            self.dropped.fetch_add(*skipped, Ordering::Relaxed);
+        }
+        result
     }
 }
 
@@ -210,6 +539,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_loop_status() {
+        for status in [LoopStatus::None, LoopStatus::Track, LoopStatus::Playlist] {
+            assert_eq!(LoopStatus::from_mpris(status.as_mpris()), Some(status));
+        }
+        assert_eq!(LoopStatus::from_mpris("Shuffle"), None);
+
+        // Cycles back to off after three presses
+        let status = LoopStatus::None.next();
+        assert_eq!(status, LoopStatus::Playlist);
+        assert_eq!(status.next().next(), LoopStatus::None);
+    }
+
     #[test]
     fn test_multiple_subscribers() {
         let manager = EventManager::new(10);
@@ -225,6 +567,88 @@ mod tests {
         assert!(receiver2.try_recv().is_ok());
     }
 
+    #[test]
+    fn test_lagged_subscriber_recovers() {
+        let manager = EventManager::new(2);
+        let mut receiver = manager.subscribe();
+
+        for id in 1..=3 {
+            manager.emit(Event::WorkspaceChanged { id });
+        }
+
+        // The oldest event was overwritten, but the receiver keeps working
+        assert!(matches!(
+            receiver.try_recv(),
+            Err(TryRecvError::Lagged(1))
+        ));
+        assert!(matches!(
+            receiver.try_recv(),
+            Ok(Event::WorkspaceChanged { id: 2 })
+        ));
+        assert_eq!(manager.dropped_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recv_or_skip() {
+        let manager = EventManager::new(2);
+        let mut receiver = manager.subscribe();
+
+        // Skips past the overwritten event instead of returning an error
+        for id in 1..=3 {
+            manager.emit(Event::WorkspaceChanged { id });
+        }
+        assert!(matches!(
+            receiver.recv_or_skip().await,
+            Some(Event::WorkspaceChanged { id: 2 })
+        ));
+        assert_eq!(manager.dropped_count(), 1);
+
+        receiver.recv_or_skip().await;
+        drop(manager);
+        assert!(receiver.recv_or_skip().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_filtered() {
+        use futures::StreamExt;
+
+        let manager = EventManager::new(2);
+        let mut workspaces = Box::pin(manager.subscribe_filtered(|event| match event {
+            Event::WorkspaceChanged { id } => Some(id),
+            _ => None,
+        }));
+
+        // Overflow the channel; the stream should skip ahead instead of ending
+        for id in 1..=3 {
+            manager.emit(Event::WorkspaceChanged { id });
+        }
+        assert_eq!(workspaces.next().await, Some(2));
+        assert_eq!(workspaces.next().await, Some(3));
+
+        // Other events are filtered out
+        manager.emit(Event::VolumeChanged {
+            level: 10.0,
+            muted: false,
+        });
+        manager.emit(Event::WorkspaceChanged { id: 4 });
+        assert_eq!(workspaces.next().await, Some(4));
+
+        // The stream ends once every sender is gone
+        drop(manager);
+        assert_eq!(workspaces.next().await, None);
+    }
+
+    #[test]
+    fn test_zero_capacity_is_raised() {
+        let manager = EventManager::new(0);
+        assert_eq!(manager.capacity(), 1);
+
+        let mut receiver = manager.subscribe();
+        manager.emit(Event::WorkspaceChanged { id: 1 });
+        assert!(receiver.try_recv().is_ok());
+        assert_eq!(manager.dropped_count(), 0);
+    }
+
     #[test]
     fn test_event_clone() {
         let event = Event::VolumeChanged {
@@ -0,0 +1,141 @@
+use crate::app::AppState;
+use crate::backend::system::backlight::BRIGHTNESS_TRANSITION_MS;
+use crate::backend::BrightnessBackend;
+use crate::config::{Config, HotkeyAction};
+use crate::error::{AmiyaError, Result};
+use crate::events::{Event, PopupType};
+use std::sync::Arc;
+use tracing::info;
+
+/// Step used for volume hotkeys (percent)
+const HOTKEY_VOLUME_STEP: f64 = 5.0;
+
+/// Step used for brightness hotkeys (percent)
+const HOTKEY_BRIGHTNESS_STEP: f64 = 5.0;
+
+/// Normalize a key combination so that lookups ignore case and modifier order
+///
+/// `"super+shift+Up"` and `"Shift+Super+up"` both become `"shift+super+up"`.
+pub fn normalize_key(key: &str) -> String {
+    let mut parts: Vec<String> = key
+        .split('+')
+        .map(|part| part.trim().to_lowercase())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    // The last part is the key itself, everything before it is a modifier
+    if let Some(key) = parts.pop() {
+        parts.sort();
+        parts.push(key);
+    }
+
+    parts.join("+")
+}
+
+/// Find the action bound to a key combination in the config
+pub fn lookup<'a>(config: &'a Config, key: &str) -> Option<&'a HotkeyAction> {
+    let wanted = normalize_key(key);
+    config
+        .hotkeys
+        .iter()
+        .find(|(binding, _)| normalize_key(binding) == wanted)
+        .map(|(_, action)| action)
+}
+
+/// Look up a key combination in the config and run its action
+pub async fn handle_key(state: &AppState, key: &str) -> Result<HotkeyAction> {
+    let action = lookup(&state.config(), key)
+        .cloned()
+        .ok_or_else(|| AmiyaError::Config(format!("No hotkey bound to {}", key)))?;
+
+    dispatch(state, &action).await?;
+    Ok(action)
+}
+
+/// Run a hotkey action
+pub async fn dispatch(state: &AppState, action: &HotkeyAction) -> Result<()> {
+    info!("Dispatching hotkey action: {:?}", action);
+
+    match action {
+        HotkeyAction::ShowBluetooth => show_popup(state, PopupType::Bluetooth),
+        HotkeyAction::ShowWifi => show_popup(state, PopupType::Wifi),
+        HotkeyAction::ShowMediaControl => show_popup(state, PopupType::MediaControl),
+        HotkeyAction::ShowLauncher => show_popup(state, PopupType::Launcher),
+        HotkeyAction::ShowPower => show_popup(state, PopupType::Power),
+        HotkeyAction::ShowQuickSettings => show_popup(state, PopupType::QuickSettings),
+        HotkeyAction::ShowWindows => show_popup(state, PopupType::Windows),
+        HotkeyAction::IncreaseBrightness => {
+            let backlight = available_backlight(state)?;
+            backlight
+                .increase_brightness_smooth(HOTKEY_BRIGHTNESS_STEP, BRIGHTNESS_TRANSITION_MS)
+                .await
+        }
+        HotkeyAction::DecreaseBrightness => {
+            let backlight = available_backlight(state)?;
+            backlight
+                .decrease_brightness_smooth(HOTKEY_BRIGHTNESS_STEP, BRIGHTNESS_TRANSITION_MS)
+                .await
+        }
+        HotkeyAction::IncreaseVolume => {
+            let audio = state
+                .audio_control
+                .as_ref()
+                .ok_or_else(|| AmiyaError::Backend("Audio control not available".to_string()))?;
+            audio.increase_volume(HOTKEY_VOLUME_STEP).await
+        }
+        HotkeyAction::DecreaseVolume => {
+            let audio = state
+                .audio_control
+                .as_ref()
+                .ok_or_else(|| AmiyaError::Backend("Audio control not available".to_string()))?;
+            audio.decrease_volume(HOTKEY_VOLUME_STEP).await
+        }
+        HotkeyAction::Mute => {
+            let audio = state
+                .audio_control
+                .as_ref()
+                .ok_or_else(|| AmiyaError::Backend("Audio control not available".to_string()))?;
+            audio.toggle_mute().await
+        }
+    }
+}
+
+/// The brightness backend, unless none was found or no DDC/CI monitor has
+/// turned up yet
+fn available_backlight(state: &AppState) -> Result<&Arc<dyn BrightnessBackend>> {
+    state
+        .backlight_control
+        .as_ref()
+        .filter(|backlight| backlight.is_available())
+        .ok_or_else(|| AmiyaError::Backend("Backlight control not available".to_string()))
+}
+
+fn show_popup(state: &AppState, popup_type: PopupType) -> Result<()> {
+    state.events.emit(Event::PopupRequested { popup_type });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_key() {
+        assert_eq!(normalize_key("Super+B"), "super+b");
+        assert_eq!(
+            normalize_key("Super+Shift+Up"),
+            normalize_key("shift+super+up")
+        );
+        assert_eq!(normalize_key("XF86AudioMute"), "xf86audiomute");
+    }
+
+    #[test]
+    fn test_lookup_default_hotkeys() {
+        let config = Config::default();
+        assert!(matches!(
+            lookup(&config, "super+w"),
+            Some(HotkeyAction::ShowWifi)
+        ));
+        assert!(lookup(&config, "Super+Q").is_none());
+    }
+}
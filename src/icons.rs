@@ -0,0 +1,75 @@
+//! Icon lookup for app ids and icon names
+//!
+//! Windows, tray items and notifications all name their icon differently: an
+//! app id, a themed icon name, or a file path. Resolve them here so they all
+//! come from the same icon theme and fall back the same way.
+
+use crate::backend::system::{applications, DesktopEntry};
+use gtk4::gdk;
+use gtk4::gio;
+use gtk4::prelude::*;
+use gtk4::{IconTheme, Image};
+use std::path::Path;
+use std::rc::Rc;
+
+thread_local! {
+    /// Installed applications, read once for their icon names
+    static APPLICATIONS: Rc<Vec<DesktopEntry>> = Rc::new(applications::load_applications());
+}
+
+fn theme() -> Option<IconTheme> {
+    gdk::Display::default().map(|display| IconTheme::for_display(&display))
+}
+
+/// Also look up icons in `path`, for apps that ship their own
+pub fn add_search_path(path: &str) {
+    let Some(theme) = theme() else {
+        return;
+    };
+    if !theme.search_path().iter().any(|p| p.as_os_str() == path) {
+        theme.add_search_path(path);
+    }
+}
+
+/// The icon for an icon name, file path or `file://` URI, if it exists
+pub fn lookup(name: &str) -> Option<gio::Icon> {
+    if name.starts_with("file://") {
+        let file = gio::File::for_uri(name);
+        return file
+            .query_exists(gio::Cancellable::NONE)
+            .then(|| gio::FileIcon::new(&file).upcast());
+    }
+    if name.starts_with('/') {
+        return Path::new(name)
+            .exists()
+            .then(|| gio::FileIcon::new(&gio::File::for_path(name)).upcast());
+    }
+
+    theme()
+        .filter(|theme| !name.is_empty() && theme.has_icon(name))
+        .map(|_| gio::ThemedIcon::new(name).upcast())
+}
+
+/// The icon of the application with `app_id`, e.g. a niri window's
+pub fn for_app_id(app_id: &str) -> Option<gio::Icon> {
+    APPLICATIONS.with(|apps| {
+        applications::icon_names(apps, app_id)
+            .iter()
+            .find_map(|name| lookup(name))
+    })
+}
+
+/// The icon a notification asked for, or else its app's icon
+pub fn for_notification(app: &str, icon: Option<&str>) -> Option<gio::Icon> {
+    icon.and_then(lookup).or_else(|| for_app_id(app))
+}
+
+/// An image of `icon` at `size` pixels, or of the themed `fallback` icon
+pub fn image(icon: Option<gio::Icon>, fallback: &str, size: i32) -> Image {
+    let image = match icon {
+        Some(icon) => Image::from_gicon(&icon),
+        None => Image::from_icon_name(fallback),
+    };
+    image.set_pixel_size(size);
+    image
+}
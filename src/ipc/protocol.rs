@@ -22,6 +22,45 @@ pub enum Command {
     /// Power management
     Power { action: PowerAction },
 
+    /// Playback control for the active MPRIS player
+    Media { action: MediaAction },
+
+    /// Workspace and window management
+    Workspace { action: WorkspaceAction },
+
+    /// Run the action bound to a key combination in the config
+    Hotkey { key: String },
+
+    /// Turn do-not-disturb on or off
+    SetDnd { enabled: bool },
+
+    /// Keep the screen from blanking while enabled
+    SetIdleInhibit { enabled: bool },
+
+    /// Turn all radios off, or restore them
+    SetAirplaneMode { enabled: bool },
+
+    /// VPN connections managed by NetworkManager
+    Vpn { action: VpnAction },
+
+    /// Take a screenshot
+    Screenshot { mode: ScreenshotMode },
+
+    /// Start or stop a screen recording
+    ScreenRecord { action: RecordAction },
+
+    /// Blank all monitors until the next input
+    PowerOffMonitors,
+
+    /// Output scale and transform, through niri
+    Output { action: OutputAction },
+
+    /// Re-read the config file and apply it
+    Reload,
+
+    /// Exit cleanly
+    Quit,
+
     /// Get current status
     Status,
 
@@ -37,6 +76,30 @@ pub enum PopupType {
     Wifi,
     MediaControl,
     Power,
+    Calendar,
+    Notifications,
+    Launcher,
+    Debug,
+    QuickSettings,
+    Windows,
+}
+
+/// What to capture in a screenshot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScreenshotMode {
+    Full,
+    Region,
+    Window,
+}
+
+/// Screen recording actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecordAction {
+    Start,
+    Stop,
+    Toggle,
 }
 
 /// Volume actions
@@ -46,6 +109,8 @@ pub enum VolumeAction {
     Up { amount: Option<f64> },
     Down { amount: Option<f64> },
     Set { level: f64 },
+    /// Change by `percent` percent of the current level (negative lowers it)
+    SetRelative { percent: f64 },
     Mute,
     Unmute,
     ToggleMute,
@@ -58,6 +123,8 @@ pub enum BrightnessAction {
     Up { amount: Option<f64> },
     Down { amount: Option<f64> },
     Set { level: f64 },
+    /// Change by `percent` percent of the current level (negative lowers it)
+    SetRelative { percent: f64 },
 }
 
 /// Power actions
@@ -71,6 +138,47 @@ pub enum PowerAction {
     Lock,
 }
 
+/// Media playback actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MediaAction {
+    Play,
+    Pause,
+    PlayPause,
+    Next,
+    Previous,
+    Stop,
+}
+
+/// Workspace actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum WorkspaceAction {
+    MoveWindow { index: u32 },
+    MoveWindowByName { name: String },
+    MoveWindowUp,
+    MoveWindowDown,
+}
+
+/// VPN actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum VpnAction {
+    Up { name: String },
+    Down { name: String },
+    List,
+}
+
+/// Output actions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum OutputAction {
+    List,
+    Scale { name: String, scale: f64 },
+    /// Rotate or flip, by niri's name for the transform (e.g. "90")
+    Transform { name: String, transform: String },
+}
+
 /// Response from amiya to amiya-ctl
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "status", rename_all = "kebab-case")]
@@ -84,6 +192,10 @@ pub enum Response {
     Status {
         version: String,
         uptime: u64,
+        /// Active event subscribers
+        subscribers: usize,
+        /// Events lagging subscribers have missed since startup
+        dropped_events: u64,
     },
     Pong,
 }
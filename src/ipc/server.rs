@@ -1,11 +1,17 @@
 use crate::app::AppState;
+use crate::backend::niri::{NiriClient, NiriOutput, OutputTransform};
+use crate::backend::system::backlight::BRIGHTNESS_TRANSITION_MS;
 use crate::error::{AmiyaError, Result};
 use crate::events::Event;
-use crate::ipc::protocol::{BrightnessAction, Command, PopupType, PowerAction, Response, VolumeAction};
-use std::os::unix::net::{UnixListener, UnixStream};
-use std::path::PathBuf;
+use crate::ipc::protocol::{
+    BrightnessAction, Command, MediaAction, OutputAction, PopupType, PowerAction, RecordAction,
+    Response, ScreenshotMode, VolumeAction, VpnAction, WorkspaceAction,
+};
+use std::io::{BufRead, BufReader as StdBufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener as TokioUnixListener;
 use tracing::{debug, error, info, warn};
@@ -13,6 +19,9 @@ use tracing::{debug, error, info, warn};
 const DEFAULT_VOLUME_STEP: f64 = 5.0;
 const DEFAULT_BRIGHTNESS_STEP: f64 = 5.0;
 
+/// How long to wait for a running instance to answer the startup ping
+const PING_TIMEOUT: Duration = Duration::from_millis(500);
+
 pub struct IpcServer {
     socket_path: PathBuf,
     state: Arc<AppState>,
@@ -22,10 +31,20 @@ pub struct IpcServer {
 impl IpcServer {
     /// Create a new IPC server
     pub fn new(state: Arc<AppState>) -> Result<Self> {
-        let socket_path = Self::get_socket_path()?;
+        Self::with_socket_path(state, Self::get_socket_path()?)
+    }
 
-        // Remove old socket if it exists
+    /// Create an IPC server listening on a specific socket
+    pub fn with_socket_path(state: Arc<AppState>, socket_path: PathBuf) -> Result<Self> {
+        // Remove old socket if it exists, unless another instance still owns it
         if socket_path.exists() {
+            if Self::is_live(&socket_path) {
+                return Err(AmiyaError::Ipc(format!(
+                    "Amiya is already running (socket {:?} is in use)",
+                    socket_path
+                )));
+            }
+            debug!("Removing stale socket {:?}", socket_path);
             std::fs::remove_file(&socket_path)
                 .map_err(|e| AmiyaError::Ipc(format!("Failed to remove old socket: {}", e)))?;
         }
@@ -37,6 +56,30 @@ impl IpcServer {
         })
     }
 
+    /// Whether a server answers a ping on `socket_path`
+    ///
+    /// Sockets left behind by a crashed instance refuse connections.
+    fn is_live(socket_path: &Path) -> bool {
+        let ping = || -> std::io::Result<bool> {
+            let mut stream = UnixStream::connect(socket_path)?;
+            stream.set_read_timeout(Some(PING_TIMEOUT))?;
+            stream.set_write_timeout(Some(PING_TIMEOUT))?;
+
+            let request = serde_json::to_string(&Command::Ping)?;
+            stream.write_all(request.as_bytes())?;
+            stream.write_all(b"\n")?;
+
+            let mut line = String::new();
+            StdBufReader::new(stream).read_line(&mut line)?;
+            Ok(matches!(
+                serde_json::from_str::<Response>(line.trim()),
+                Ok(Response::Pong)
+            ))
+        };
+
+        ping().unwrap_or(false)
+    }
+
     /// Get the Unix socket path
     fn get_socket_path() -> Result<PathBuf> {
         let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
@@ -137,6 +180,19 @@ impl IpcServer {
             Command::Volume { action } => self.handle_volume(action).await,
             Command::Brightness { action } => self.handle_brightness(action).await,
             Command::Power { action } => self.handle_power(action).await,
+            Command::Media { action } => self.handle_media(action).await,
+            Command::Workspace { action } => self.handle_workspace(action).await,
+            Command::Hotkey { key } => self.handle_hotkey(key).await,
+            Command::SetDnd { enabled } => self.handle_set_dnd(enabled).await,
+            Command::SetIdleInhibit { enabled } => self.handle_set_idle_inhibit(enabled).await,
+            Command::SetAirplaneMode { enabled } => self.handle_set_airplane_mode(enabled).await,
+            Command::Vpn { action } => self.handle_vpn(action).await,
+            Command::Screenshot { mode } => self.handle_screenshot(mode).await,
+            Command::ScreenRecord { action } => self.handle_screen_record(action).await,
+            Command::PowerOffMonitors => self.handle_power_off_monitors().await,
+            Command::Output { action } => self.handle_output(action).await,
+            Command::Reload => self.handle_reload().await,
+            Command::Quit => self.handle_quit().await,
             Command::Status => self.handle_status().await,
             Command::Ping => Response::pong(),
         }
@@ -187,6 +243,7 @@ impl IpcServer {
                     audio.decrease_volume(step).await
                 }
                 VolumeAction::Set { level } => audio.set_volume(level).await,
+                VolumeAction::SetRelative { percent } => audio.set_volume_relative(percent).await,
                 VolumeAction::Mute => audio.set_mute(true).await,
                 VolumeAction::Unmute => audio.set_mute(false).await,
                 VolumeAction::ToggleMute => audio.toggle_mute().await,
@@ -203,17 +260,28 @@ impl IpcServer {
 
     /// Handle brightness command
     async fn handle_brightness(&self, action: BrightnessAction) -> Response {
-        if let Some(backlight) = &self.state.backlight_control {
+        let backlight = self.state.backlight_control.as_ref();
+        // DDC/CI monitors are probed in the background and may never turn up
+        if let Some(backlight) = backlight.filter(|b| b.is_available()) {
             let result = match action {
                 BrightnessAction::Up { amount } => {
                     let step = amount.unwrap_or(DEFAULT_BRIGHTNESS_STEP);
-                    backlight.increase_brightness(step).await
+                    backlight
+                        .increase_brightness_smooth(step, BRIGHTNESS_TRANSITION_MS)
+                        .await
                 }
                 BrightnessAction::Down { amount } => {
                     let step = amount.unwrap_or(DEFAULT_BRIGHTNESS_STEP);
-                    backlight.decrease_brightness(step).await
+                    backlight
+                        .decrease_brightness_smooth(step, BRIGHTNESS_TRANSITION_MS)
+                        .await
                 }
                 BrightnessAction::Set { level } => backlight.set_brightness(level).await,
+                BrightnessAction::SetRelative { percent } => {
+                    backlight
+                        .set_brightness_relative_smooth(percent, BRIGHTNESS_TRANSITION_MS)
+                        .await
+                }
             };
 
             match result {
@@ -221,7 +289,10 @@ impl IpcServer {
                 Err(e) => Response::error(format!("Failed to adjust brightness: {}", e)),
             }
         } else {
-            Response::error("Backlight control not available".to_string())
+            Response::error(
+                "Backlight control not available: no backlight device or DDC/CI monitor"
+                    .to_string(),
+            )
         }
     }
 
@@ -250,6 +321,304 @@ impl IpcServer {
         }
     }
 
+    /// Handle media command
+    async fn handle_media(&self, action: MediaAction) -> Response {
+        let Some(media) = &self.state.media_control else {
+            return Response::error("Media control not available".to_string());
+        };
+
+        let result = match action {
+            MediaAction::Play => media.play().await,
+            MediaAction::Pause => media.pause().await,
+            MediaAction::PlayPause => media.play_pause().await,
+            MediaAction::Next => media.next().await,
+            MediaAction::Previous => media.previous().await,
+            MediaAction::Stop => media.stop().await,
+        };
+
+        match result {
+            Ok(()) => Response::success_with_message(format!("Sent {:?} to the player", action)),
+            Err(e) => Response::error(format!("Failed to control media: {}", e)),
+        }
+    }
+
+    /// Run `f` against the niri client on the blocking pool
+    ///
+    /// The niri client uses a blocking socket, so it is kept off the async
+    /// workers. Without a client, or if the task dies, the error response to
+    /// send is returned instead of `f`'s result.
+    async fn with_niri<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&NiriClient) -> Result<T> + Send + 'static,
+    ) -> std::result::Result<Result<T>, Response> {
+        let Some(niri) = self.state.niri_client.clone() else {
+            return Err(Response::error("Niri client not available".to_string()));
+        };
+        tokio::task::spawn_blocking(move || f(&niri))
+            .await
+            .map_err(|e| Response::error(format!("Niri task failed: {}", e)))
+    }
+
+    /// Handle workspace command
+    async fn handle_workspace(&self, action: WorkspaceAction) -> Response {
+        let description = match &action {
+            WorkspaceAction::MoveWindow { index } => format!("Moved window to workspace {}", index),
+            WorkspaceAction::MoveWindowByName { name } => {
+                format!("Moved window to workspace {}", name)
+            }
+            WorkspaceAction::MoveWindowUp => "Moved window to workspace above".to_string(),
+            WorkspaceAction::MoveWindowDown => "Moved window to workspace below".to_string(),
+        };
+
+        let result = self
+            .with_niri(move |niri| match action {
+                WorkspaceAction::MoveWindow { index } => niri.move_window_to_workspace(index),
+                WorkspaceAction::MoveWindowByName { name } => {
+                    niri.move_window_to_workspace_by_name(name)
+                }
+                WorkspaceAction::MoveWindowUp => niri.move_window_to_workspace_up(),
+                WorkspaceAction::MoveWindowDown => niri.move_window_to_workspace_down(),
+            })
+            .await;
+
+        match result {
+            Ok(Ok(())) => Response::success_with_message(description),
+            Ok(Err(e)) => Response::error(format!("Failed to move window: {}", e)),
+            Err(response) => response,
+        }
+    }
+
+    /// Handle hotkey command
+    async fn handle_hotkey(&self, key: String) -> Response {
+        match crate::hotkeys::handle_key(&self.state, &key).await {
+            Ok(action) => Response::success_with_message(format!("{}: {:?}", key, action)),
+            Err(e) => Response::error(format!("Failed to handle hotkey {}: {}", key, e)),
+        }
+    }
+
+    /// Handle do-not-disturb command
+    async fn handle_set_dnd(&self, enabled: bool) -> Response {
+        match &self.state.notification_daemon {
+            Some(daemon) => {
+                daemon.set_dnd(enabled);
+                Response::success_with_message(format!(
+                    "Do not disturb {}",
+                    if enabled { "enabled" } else { "disabled" }
+                ))
+            }
+            None => Response::error("Notification daemon not available".to_string()),
+        }
+    }
+
+    /// Handle idle inhibit command
+    async fn handle_set_idle_inhibit(&self, enabled: bool) -> Response {
+        match &self.state.idle_inhibitor {
+            Some(inhibitor) => match inhibitor.set_enabled(enabled).await {
+                Ok(()) => Response::success_with_message(format!(
+                    "Idle inhibitor {}",
+                    if enabled { "enabled" } else { "disabled" }
+                )),
+                Err(e) => Response::error(format!("Failed to set idle inhibitor: {}", e)),
+            },
+            None => Response::error("Idle inhibitor not available".to_string()),
+        }
+    }
+
+    /// Handle airplane mode command
+    async fn handle_set_airplane_mode(&self, enabled: bool) -> Response {
+        match &self.state.airplane_mode {
+            Some(airplane) => match airplane.set_enabled(enabled).await {
+                Ok(()) => Response::success_with_message(format!(
+                    "Airplane mode {}",
+                    if enabled { "enabled" } else { "disabled" }
+                )),
+                Err(e) => Response::error(format!("Failed to set airplane mode: {}", e)),
+            },
+            None => Response::error("Airplane mode not available".to_string()),
+        }
+    }
+
+    /// Handle VPN command
+    async fn handle_vpn(&self, action: VpnAction) -> Response {
+        let Some(network) = &self.state.network_control else {
+            return Response::error("Network control not available".to_string());
+        };
+
+        match action {
+            VpnAction::Up { name } => match network.set_vpn_active(&name, true).await {
+                Ok(()) => Response::success_with_message(format!("Activating VPN {}", name)),
+                Err(e) => Response::error(format!("Failed to activate VPN: {}", e)),
+            },
+            VpnAction::Down { name } => match network.set_vpn_active(&name, false).await {
+                Ok(()) => Response::success_with_message(format!("Deactivated VPN {}", name)),
+                Err(e) => Response::error(format!("Failed to deactivate VPN: {}", e)),
+            },
+            VpnAction::List => match network.list_vpn_connections().await {
+                Ok(vpns) if vpns.is_empty() => {
+                    Response::success_with_message("No VPN connections".to_string())
+                }
+                Ok(vpns) => {
+                    let lines: Vec<String> = vpns
+                        .iter()
+                        .map(|vpn| {
+                            format!("{}{}", vpn.name, if vpn.active { " (active)" } else { "" })
+                        })
+                        .collect();
+                    Response::success_with_message(lines.join("\n"))
+                }
+                Err(e) => Response::error(format!("Failed to list VPNs: {}", e)),
+            },
+        }
+    }
+
+    /// Handle screenshot command
+    async fn handle_screenshot(&self, mode: ScreenshotMode) -> Response {
+        use crate::backend::system::capture::ScreenshotMode as BackendScreenshotMode;
+
+        let Some(capture) = &self.state.screen_capture else {
+            return Response::error("Screen capture not available".to_string());
+        };
+
+        let mode = match mode {
+            ScreenshotMode::Full => BackendScreenshotMode::Full,
+            ScreenshotMode::Region => BackendScreenshotMode::Region,
+            ScreenshotMode::Window => BackendScreenshotMode::Window,
+        };
+
+        match capture
+            .screenshot(mode, self.state.niri_client.as_deref())
+            .await
+        {
+            Ok(Some(path)) => {
+                Response::success_with_message(format!("Screenshot saved to {}", path.display()))
+            }
+            Ok(None) => Response::success_with_message("Screenshot taken by niri".to_string()),
+            Err(e) => Response::error(format!("Failed to take screenshot: {}", e)),
+        }
+    }
+
+    /// Handle monitor power off command
+    async fn handle_power_off_monitors(&self) -> Response {
+        match self.with_niri(|niri| niri.power_off_monitors()).await {
+            Ok(Ok(())) => Response::success_with_message("Monitors powered off".to_string()),
+            Ok(Err(e)) => Response::error(format!("Failed to power off monitors: {}", e)),
+            Err(response) => response,
+        }
+    }
+
+    /// Handle output command
+    async fn handle_output(&self, action: OutputAction) -> Response {
+        let (result, message) = match action {
+            OutputAction::List => {
+                return match self.with_niri(|niri| niri.get_outputs()).await {
+                    Ok(Ok(outputs)) if outputs.is_empty() => {
+                        Response::success_with_message("No outputs".to_string())
+                    }
+                    Ok(Ok(outputs)) => {
+                        let lines: Vec<String> = outputs.iter().map(Self::output_line).collect();
+                        Response::success_with_message(lines.join("\n"))
+                    }
+                    Ok(Err(e)) => Response::error(format!("Failed to list outputs: {}", e)),
+                    Err(response) => response,
+                };
+            }
+            OutputAction::Scale { name, scale } => {
+                let message = format!("Set scale of {} to {}", name, scale);
+                let result = self
+                    .with_niri(move |niri| niri.set_output_scale(&name, scale))
+                    .await;
+                (result, message)
+            }
+            OutputAction::Transform { name, transform } => {
+                let transform = match transform.parse::<OutputTransform>() {
+                    Ok(transform) => transform,
+                    Err(e) => return Response::error(e),
+                };
+                let message = format!("Set transform of {} to {}", name, transform);
+                let result = self
+                    .with_niri(move |niri| niri.set_output_transform(&name, transform))
+                    .await;
+                (result, message)
+            }
+        };
+
+        match result {
+            Ok(Ok(())) => Response::success_with_message(message),
+            Ok(Err(e)) => Response::error(format!("Failed to configure output: {}", e)),
+            Err(response) => response,
+        }
+    }
+
+    /// "name: scale 1.5, transform 90 (make model)", or "name: off"
+    fn output_line(output: &NiriOutput) -> String {
+        let state = match &output.logical {
+            Some(logical) => format!("scale {}, transform {}", logical.scale, logical.transform),
+            None => "off".to_string(),
+        };
+        let description: Vec<&str> = [&output.make, &output.model]
+            .into_iter()
+            .filter_map(|s| s.as_deref())
+            .collect();
+        if description.is_empty() {
+            format!("{}: {}", output.name, state)
+        } else {
+            format!("{}: {} ({})", output.name, state, description.join(" "))
+        }
+    }
+
+    /// Handle screen record command
+    async fn handle_screen_record(&self, action: RecordAction) -> Response {
+        let Some(capture) = &self.state.screen_capture else {
+            return Response::error("Screen capture not available".to_string());
+        };
+
+        let start = match action {
+            RecordAction::Start => true,
+            RecordAction::Stop => false,
+            RecordAction::Toggle => !capture.is_recording().await,
+        };
+
+        if start {
+            match capture.start_recording().await {
+                Ok(path) => {
+                    Response::success_with_message(format!("Recording to {}", path.display()))
+                }
+                Err(e) => Response::error(format!("Failed to start recording: {}", e)),
+            }
+        } else {
+            match capture.stop_recording().await {
+                Ok(path) => {
+                    Response::success_with_message(format!("Recording saved to {}", path.display()))
+                }
+                Err(e) => Response::error(format!("Failed to stop recording: {}", e)),
+            }
+        }
+    }
+
+    /// Handle reload command
+    async fn handle_reload(&self) -> Response {
+        info!("Reloading config");
+
+        match crate::config::Config::read() {
+            Ok(config) => {
+                self.state.reload_config(config);
+                Response::success_with_message("Config reloaded".to_string())
+            }
+            // Keep running with the current config, like the file watcher does
+            Err(e) => Response::error(format!("Failed to reload config: {:#}", e)),
+        }
+    }
+
+    /// Handle quit command
+    async fn handle_quit(&self) -> Response {
+        info!("Quit requested over IPC");
+
+        // The GTK main loop owns shutdown; the response goes out before it stops
+        self.state.events.emit(Event::QuitRequested);
+
+        Response::success_with_message("Shutting down".to_string())
+    }
+
     /// Handle status command
     async fn handle_status(&self) -> Response {
         let uptime = self
@@ -261,13 +630,18 @@ impl IpcServer {
         Response::Status {
             version: env!("CARGO_PKG_VERSION").to_string(),
             uptime,
+            subscribers: self.state.events.subscriber_count(),
+            dropped_events: self.state.events.dropped_count(),
         }
     }
 }
 
-impl Drop for IpcServer {
-    fn drop(&mut self) {
-        // Clean up socket file
+impl IpcServer {
+    /// Remove the socket file so clients see that Amiya is gone
+    ///
+    /// The accept loop holds its own reference to the server, so `Drop`
+    /// alone doesn't run when the process exits.
+    pub fn remove_socket(&self) {
         if self.socket_path.exists() {
             if let Err(e) = std::fs::remove_file(&self.socket_path) {
                 warn!("Failed to remove socket file: {}", e);
@@ -275,3 +649,10 @@ impl Drop for IpcServer {
         }
     }
 }
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        // Clean up socket file
+        self.remove_socket();
+    }
+}
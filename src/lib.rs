@@ -0,0 +1,21 @@
+//! Amiya desktop environment
+//!
+//! The library exposes the backends, event bus, configuration and IPC
+//! protocol so they can be shared by the `amiya` and `amiya-ctl` binaries
+//! and exercised from integration tests. The GTK front end (`bar`, `widgets`,
+//! `popups`, `overlays`) is public only so the `amiya` binary can build it.
+
+pub mod app;
+pub mod backend;
+pub mod config;
+pub mod error;
+pub mod events;
+pub mod ipc;
+
+pub mod bar;
+pub mod hotkeys;
+pub mod icons;
+pub mod overlays;
+pub mod popups;
+pub mod style;
+pub mod widgets;
@@ -1,35 +1,50 @@
-mod app;
-mod backend;
-mod bar;
-mod config;
-mod error;
-mod events;
-mod ipc;
-mod overlays;
-mod popups;
-mod system;
-mod widgets;
-
+use amiya::events::Event;
+use amiya::{app, bar, config, ipc, overlays, popups, style};
 use anyhow::Result;
+use clap::Parser;
+use futures::StreamExt;
 use gtk4::prelude::*;
 use gtk4::Application as GtkApplication;
-use std::sync::Arc;
-use tracing::{error, info, Level};
-use tracing_subscriber;
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, warn, Level};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
 
 const APP_ID: &str = "com.amiya.desktop";
 
+/// Amiya - desktop shell for niri
+#[derive(Parser)]
+#[command(name = "amiya", version)]
+struct Args {
+    /// Load this config file instead of ~/.config/amiya/config.toml
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Enable the event log popup (`amiya-ctl popup show debug`)
+    #[arg(long)]
+    debug: bool,
+}
+
 fn main() -> Result<()> {
+    let args = Args::parse();
+    if let Some(path) = args.config {
+        config::Config::set_path(path);
+    }
+
+    // Load configuration first, since it decides how to log; until then
+    // warnings about the config go to stderr
+    let startup_logger = tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .finish();
+    let config = tracing::subscriber::with_default(startup_logger, config::Config::load)?;
+
     // Initialize logging
-    tracing_subscriber::fmt()
-        .with_max_level(Level::INFO)
-        .init();
+    init_logging(&config.logging);
 
     info!("Starting Amiya Desktop Environment");
 
-    // Load configuration
-    let config = config::Config::load()?;
-
     // Create application state
     let amiya_app = app::Application::new(config.clone());
 
@@ -41,21 +56,23 @@ fn main() -> Result<()> {
 
     // Clone app state for the activate closure
     let app_state = amiya_app.state().clone();
+    let debug = args.debug;
     gtk_app.connect_activate(move |gtk_app| {
-        if let Err(e) = activate(gtk_app, app_state.clone()) {
+        if let Err(e) = activate(gtk_app, app_state.clone(), debug) {
             eprintln!("Error activating application: {}", e);
+            gtk_app.quit();
         }
     });
 
     // Set up shutdown handler
-    let app_state_shutdown = amiya_app.state().clone();
     gtk_app.connect_shutdown(move |_| {
         info!("GTK application shutting down");
         // Cleanup will be handled by Drop impls
     });
 
-    // Run the application
-    gtk_app.run();
+    // Run the application; arguments were handled above, so GTK doesn't
+    // get to reject ours
+    gtk_app.run_with_args::<&str>(&[]);
 
     // Graceful shutdown
     amiya_app.shutdown();
@@ -63,21 +80,86 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn activate(gtk_app: &GtkApplication, app_state: Arc<app::AppState>) -> Result<()> {
+/// Log to stdout, and to a file under `$XDG_STATE_HOME/amiya` when
+/// `logging.file` is set, filtered by `RUST_LOG` or `logging.level`
+fn init_logging(config: &config::LoggingConfig) {
+    let mut problems = Vec::new();
+
+    let default = || Targets::new().with_default(Level::INFO);
+    let filter = match std::env::var("RUST_LOG") {
+        Ok(directives) => directives.parse::<Targets>().unwrap_or_else(|e| {
+            problems.push(format!("Ignoring invalid RUST_LOG {:?}: {}", directives, e));
+            default()
+        }),
+        // Already validated when the config was loaded
+        Err(_) => config.level.parse().unwrap_or_else(|_| default()),
+    };
+
+    let file_layer = if config.file {
+        let file = config::Config::log_path().and_then(|path| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            Ok(OpenOptions::new().create(true).append(true).open(&path)?)
+        });
+        match file {
+            Ok(file) => Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(Mutex::new(file)),
+            ),
+            Err(e) => {
+                problems.push(format!("Not logging to a file: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(file_layer)
+        .with(filter)
+        .init();
+
+    for problem in problems {
+        warn!("{}", problem);
+    }
+}
+
+fn activate(gtk_app: &GtkApplication, app_state: Arc<app::AppState>, debug: bool) -> Result<()> {
     info!("Activating Amiya");
 
+    // Claim the IPC socket first so a second instance stops before opening
+    // any windows
+    let ipc_server = Arc::new(ipc::IpcServer::new(app_state.clone())?);
+
     // Initialize the bar with event manager
     let bar = bar::Bar::new(gtk_app, &app_state)?;
     bar.show();
 
+    // Load the user stylesheet on top of the built-in styles
+    if let Some(display) = gtk4::gdk::Display::default() {
+        style::install_user_stylesheet(&display, &app_state);
+    }
+
     // Initialize overlay manager for volume and brightness sliders
     let _overlay_manager = overlays::OverlayManager::new(gtk_app, &app_state);
 
+    // Count down to the configured action when the battery turns critical
+    let _critical_battery = overlays::CriticalBatteryOverlay::new(gtk_app, &app_state);
+
     // Initialize popup manager for system popups
-    let _popup_manager = popups::PopupManager::new(gtk_app, app_state.clone());
+    let popup_manager = popups::PopupManager::new(gtk_app, app_state.clone());
+    if debug {
+        popup_manager.enable_debug();
+    }
+
+    // Show desktop notifications received by the notification daemon
+    let _notification_popups = popups::NotificationPopups::new(gtk_app, app_state.clone());
 
     // Start IPC server in background
-    let ipc_server = Arc::new(ipc::IpcServer::new(app_state.clone())?);
     let ipc_server_clone = ipc_server.clone();
     tokio::spawn(async move {
         if let Err(e) = ipc_server_clone.start().await {
@@ -86,5 +168,22 @@ fn activate(gtk_app: &GtkApplication, app_state: Arc<app::AppState>) -> Result<(
     });
     info!("IPC server started");
 
+    // The accept loop never returns, so remove the socket when GTK shuts down
+    gtk_app.connect_shutdown(move |_| ipc_server.remove_socket());
+
+    // Leave the main loop on `amiya-ctl quit`; main() then runs the shutdown
+    let mut quit_requests = Box::pin(
+        app_state
+            .events
+            .subscribe_filtered(|event| matches!(event, Event::QuitRequested).then_some(())),
+    );
+    let gtk_app = gtk_app.clone();
+    gtk4::glib::spawn_future_local(async move {
+        if quit_requests.next().await.is_some() {
+            info!("Quitting");
+            gtk_app.quit();
+        }
+    });
+
     Ok(())
 }
@@ -0,0 +1,261 @@
+use crate::app::AppState;
+use crate::backend::system::power::PowerAction;
+use crate::config::{CriticalAction, ThemeConfig};
+use crate::events::Event;
+use gtk4::prelude::*;
+use gtk4::{
+    glib, Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation, ProgressBar,
+};
+use gtk4_layer_shell::{KeyboardMode, Layer, LayerShell};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Brightness the `dim` action lowers the screen to, in percent
+const DIM_BRIGHTNESS: f64 = 10.0;
+
+/// Countdown to `[power] critical_action` once the battery is critical
+///
+/// Shown even with do-not-disturb on, as it is the only chance to cancel.
+/// The Cancel button or plugging in the charger stops the countdown;
+/// otherwise the action runs when it reaches zero.
+#[derive(Clone)]
+pub struct CriticalBatteryOverlay {
+    window: ApplicationWindow,
+    title: Label,
+    countdown: Label,
+    progress: ProgressBar,
+    /// Seconds left before the action runs
+    remaining: Rc<Cell<u32>>,
+    /// Per-second tick while the countdown runs
+    timer: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl CriticalBatteryOverlay {
+    pub fn new(app: &Application, state: &Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Battery Critical")
+            .default_width(360)
+            .build();
+
+        // Initialize layer shell; left unanchored so it sits mid-screen, and
+        // only takes the keyboard once clicked
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-battery-critical");
+        window.set_keyboard_mode(KeyboardMode::OnDemand);
+        window.add_css_class("amiya-overlay");
+        window.add_css_class("critical-battery-overlay");
+
+        let container = GtkBox::new(Orientation::Vertical, 12);
+        container.set_margin_start(24);
+        container.set_margin_end(24);
+        container.set_margin_top(24);
+        container.set_margin_bottom(24);
+
+        let title = Label::new(None);
+        title.add_css_class("critical-battery-title");
+
+        let countdown = Label::new(None);
+        countdown.add_css_class("critical-battery-countdown");
+
+        let progress = ProgressBar::new();
+        progress.set_hexpand(true);
+
+        let cancel = Button::with_label("Cancel");
+        cancel.add_css_class("critical-battery-cancel");
+        cancel.set_halign(gtk4::Align::Center);
+
+        container.append(&title);
+        container.append(&countdown);
+        container.append(&progress);
+        container.append(&cancel);
+        window.set_child(Some(&container));
+
+        // Don't show by default - will be shown by events
+        window.set_visible(false);
+
+        crate::style::install_theme_css(&WidgetExt::display(&window), state, Self::theme_css);
+
+        let overlay = CriticalBatteryOverlay {
+            window,
+            title,
+            countdown,
+            progress,
+            remaining: Rc::new(Cell::new(0)),
+            timer: Rc::new(RefCell::new(None)),
+        };
+
+        let overlay_clone = overlay.clone();
+        cancel.connect_clicked(move |_| {
+            info!("Critical battery action cancelled");
+            overlay_clone.cancel();
+        });
+
+        Self::subscribe_to_events(state.clone(), overlay.clone());
+        overlay
+    }
+
+    /// Start counting down to `action`, unless a countdown already runs
+    fn start(
+        &self,
+        percentage: f64,
+        action: CriticalAction,
+        delay_secs: u32,
+        state: &Arc<AppState>,
+    ) {
+        self.title
+            .set_text(&format!("🪫 Battery critical: {:.0}%", percentage));
+        if self.timer.borrow().is_some() {
+            return;
+        }
+
+        info!(
+            "Battery critical, {:?} in {} s unless cancelled",
+            action, delay_secs
+        );
+        self.remaining.set(delay_secs);
+        self.update_countdown(action, delay_secs);
+        self.window.present();
+
+        let overlay = self.clone();
+        let state = state.clone();
+        let timer = glib::timeout_add_local(Duration::from_secs(1), move || {
+            let remaining = overlay.remaining.get().saturating_sub(1);
+            overlay.remaining.set(remaining);
+            if remaining > 0 {
+                overlay.update_countdown(action, delay_secs);
+                return glib::ControlFlow::Continue;
+            }
+
+            // The source is finished; forget it so it isn't removed twice
+            overlay.timer.borrow_mut().take();
+            overlay.window.set_visible(false);
+            Self::run(action, &state);
+            glib::ControlFlow::Break
+        });
+        *self.timer.borrow_mut() = Some(timer);
+    }
+
+    /// Stop the countdown, if any, and hide
+    fn cancel(&self) {
+        if let Some(timer) = self.timer.borrow_mut().take() {
+            timer.remove();
+        }
+        self.window.set_visible(false);
+    }
+
+    fn update_countdown(&self, action: CriticalAction, delay_secs: u32) {
+        let remaining = self.remaining.get();
+        let verb = match action {
+            CriticalAction::Dim => "Dimming the screen",
+            CriticalAction::Suspend => "Suspending",
+            CriticalAction::None => "Continuing",
+        };
+        self.countdown
+            .set_text(&format!("{} in {} s", verb, remaining));
+        self.progress
+            .set_fraction(remaining as f64 / delay_secs.max(1) as f64);
+    }
+
+    /// Take the critical battery action
+    fn run(action: CriticalAction, state: &Arc<AppState>) {
+        match action {
+            CriticalAction::None => {}
+            CriticalAction::Dim => {
+                let Some(backlight) = state.backlight_control.clone() else {
+                    warn!("Cannot dim the screen: brightness control not available");
+                    return;
+                };
+                glib::spawn_future_local(async move {
+                    // Never brighten a screen that is already dimmer
+                    let level = backlight.get_brightness().await;
+                    if matches!(level, Ok(level) if level <= DIM_BRIGHTNESS) {
+                        return;
+                    }
+                    if let Err(e) = backlight.set_brightness(DIM_BRIGHTNESS).await {
+                        warn!("Failed to dim the screen: {}", e);
+                    }
+                });
+            }
+            CriticalAction::Suspend => {
+                let Some(power) = state.power_control.clone() else {
+                    warn!("Cannot suspend: power control not available");
+                    return;
+                };
+                glib::spawn_future_local(async move {
+                    info!("Suspending on critical battery...");
+                    if let Err(e) = power.execute(PowerAction::Suspend).await {
+                        warn!("Failed to suspend: {}", e);
+                    }
+                });
+            }
+        }
+    }
+
+    fn subscribe_to_events(state: Arc<AppState>, overlay: CriticalBatteryOverlay) {
+        let mut receiver = state.events.subscribe();
+
+        glib::spawn_future_local(async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::BatteryCritical { percentage } => {
+                        let power = state.config().power;
+                        if power.critical_action != CriticalAction::None {
+                            overlay.start(
+                                percentage,
+                                power.critical_action,
+                                power.critical_delay_secs,
+                                &state,
+                            );
+                        }
+                    }
+                    Event::BatteryChanged {
+                        is_charging: true, ..
+                    } => {
+                        // Plugged in; nothing left to save power for
+                        overlay.cancel();
+                    }
+                    _ => {} // Ignore other events
+                }
+            }
+        });
+    }
+
+    /// Stylesheet for the countdown, on top of the shared overlay styles
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.critical-battery-overlay .critical-battery-title {{
+                color: {critical};
+            }}
+
+            window.critical-battery-overlay .critical-battery-countdown {{
+                font-size: 11pt;
+                font-weight: normal;
+            }}
+
+            window.critical-battery-overlay progressbar progress {{
+                background-color: {critical};
+            }}
+
+            window.critical-battery-overlay .critical-battery-cancel {{
+                background-color: alpha({foreground}, 0.1);
+                color: {foreground};
+                border-radius: 8px;
+                padding: 6px 20px;
+            }}
+
+            window.critical-battery-overlay .critical-battery-cancel:hover {{
+                background-color: alpha({accent}, 0.3);
+            }}
+        "#,
+            foreground = theme.foreground,
+            accent = theme.accent,
+            critical = theme.critical,
+        )
+    }
+}
@@ -1,3 +1,5 @@
+pub mod battery;
 pub mod slider;
 
+pub use battery::CriticalBatteryOverlay;
 pub use slider::{OverlayManager, SliderOverlay, SliderType};
@@ -1,8 +1,11 @@
 use crate::app::AppState;
+use crate::config::{OverlayConfig, OverlayPosition, ThemeConfig};
 use crate::events::Event;
 use gtk4::prelude::*;
 use gtk4::{glib, Application, ApplicationWindow, Box as GtkBox, Label, Orientation, ProgressBar};
 use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Copy)]
@@ -16,10 +19,16 @@ pub struct SliderOverlay {
     progress: ProgressBar,
     label: Label,
     slider_type: SliderType,
+    /// Pending auto-hide timer, replaced on every show
+    hide_timer: Rc<RefCell<Option<glib::SourceId>>>,
+    /// Auto-hide delay in milliseconds
+    timeout_ms: Cell<u64>,
+    /// Value shown as a full bar (above 100 when volume boost is enabled)
+    max: Cell<f64>,
 }
 
 impl SliderOverlay {
-    pub fn new(app: &Application, slider_type: SliderType) -> Self {
+    pub fn new(app: &Application, slider_type: SliderType, config: &OverlayConfig) -> Self {
         let window = ApplicationWindow::builder()
             .application(app)
             .title(match slider_type {
@@ -34,15 +43,16 @@ impl SliderOverlay {
         window.init_layer_shell();
         window.set_layer(Layer::Overlay);
         window.set_namespace("amiya-slider");
+        window.add_css_class("amiya-overlay");
+        window.add_css_class(match slider_type {
+            SliderType::Volume => "volume-overlay",
+            SliderType::Brightness => "brightness-overlay",
+        });
 
-        // Center the overlay
-        window.set_anchor(Edge::Top, false);
-        window.set_anchor(Edge::Bottom, false);
+        // Horizontally centered; vertical placement comes from the config
         window.set_anchor(Edge::Left, false);
         window.set_anchor(Edge::Right, false);
-
-        // Set margin from top
-        window.set_margin(Edge::Top, 100);
+        Self::apply_position(&window, config);
 
         // Create container
         let container = GtkBox::new(Orientation::Vertical, 12);
@@ -77,9 +87,6 @@ impl SliderOverlay {
 
         window.set_child(Some(&container));
 
-        // Apply styling
-        Self::apply_theme(&window);
-
         // Don't show by default - will be shown by events
         window.set_visible(false);
 
@@ -88,90 +95,151 @@ impl SliderOverlay {
             progress,
             label,
             slider_type,
+            hide_timer: Rc::new(RefCell::new(None)),
+            timeout_ms: Cell::new(config.timeout_ms),
+            max: Cell::new(100.0),
         }
     }
 
+    /// Apply position and timeout changes from a config reload
+    pub fn apply_config(&self, config: &OverlayConfig) {
+        Self::apply_position(&self.window, config);
+        self.timeout_ms.set(config.timeout_ms);
+    }
+
+    /// Set the value shown as a full bar
+    pub fn set_max(&self, max: f64) {
+        self.max.set(max.max(1.0));
+    }
+
+    fn apply_position(window: &ApplicationWindow, config: &OverlayConfig) {
+        let (top, bottom) = match config.position {
+            OverlayPosition::Top => (true, false),
+            OverlayPosition::Center => (false, false),
+            OverlayPosition::Bottom => (false, true),
+        };
+
+        window.set_anchor(Edge::Top, top);
+        window.set_anchor(Edge::Bottom, bottom);
+        window.set_margin(Edge::Top, if top { config.margin } else { 0 });
+        window.set_margin(Edge::Bottom, if bottom { config.margin } else { 0 });
+    }
+
     pub fn show(&self, value: f64, muted: Option<bool>) {
-        // Update the label based on muted state (for volume)
-        if let Some(is_muted) = muted {
-            if is_muted {
-                let icon = match self.slider_type {
-                    SliderType::Volume => "🔇",
-                    _ => "☀️",
-                };
-                self.label.set_text(&format!(
-                    "{} {} (Muted)",
-                    icon,
-                    match self.slider_type {
-                        SliderType::Volume => "Volume",
-                        SliderType::Brightness => "Brightness",
-                    }
-                ));
-            } else {
-                let icon = match self.slider_type {
-                    SliderType::Volume => "🔊",
-                    _ => "☀️",
-                };
-                self.label.set_text(&format!(
-                    "{} {}",
-                    icon,
-                    match self.slider_type {
-                        SliderType::Volume => "Volume",
-                        SliderType::Brightness => "Brightness",
-                    }
-                ));
-            }
+        // Update the label based on level and muted state (for volume)
+        let is_muted = muted.unwrap_or(false);
+        let name = match self.slider_type {
+            SliderType::Volume => "Volume",
+            SliderType::Brightness => "Brightness",
+        };
+        let icon = Self::icon(self.slider_type, value, is_muted);
+        if is_muted {
+            self.label.set_text(&format!("{} {} (Muted)", icon, name));
+        } else {
+            self.label.set_text(&format!("{} {}", icon, name));
         }
 
-        self.progress.set_fraction(value / 100.0);
+        // The bar spans the whole configured range; past 100% it changes
+        // color to show the signal is being amplified
+        self.progress.set_fraction((value / self.max.get()).clamp(0.0, 1.0));
+        if value > 100.0 {
+            self.progress.add_css_class("boosted");
+        } else {
+            self.progress.remove_css_class("boosted");
+        }
         self.progress.set_text(Some(&format!("{:.0}%", value)));
-        self.window.present();
+        if !self.window.is_visible() {
+            self.window.present();
+        }
+
+        // Auto-hide a while after the last change: cancel the previous timer so
+        // rapid events (e.g. a held volume key) don't stack competing hides
+        if let Some(timer) = self.hide_timer.borrow_mut().take() {
+            timer.remove();
+        }
 
-        // Auto-hide after 2 seconds
         let window = self.window.clone();
-        glib::timeout_add_seconds_local(2, move || {
+        let hide_timer = self.hide_timer.clone();
+        let timeout = std::time::Duration::from_millis(self.timeout_ms.get());
+        let timer = glib::timeout_add_local(timeout, move || {
             window.set_visible(false);
+            // The source is finished; forget it so it isn't removed twice
+            hide_timer.borrow_mut().take();
             glib::ControlFlow::Break
         });
+        *self.hide_timer.borrow_mut() = Some(timer);
     }
 
-    fn apply_theme(window: &ApplicationWindow) {
-        let provider = gtk4::CssProvider::new();
-        let css = r#"
-            window {
-                background-color: rgba(30, 30, 46, 0.95);
-                border-radius: 12px;
-                color: #cdd6f4;
+    /// Pick an icon that reflects the level: speaker waves for volume, a sun
+    /// that grows brighter for brightness
+    fn icon(slider_type: SliderType, value: f64, muted: bool) -> &'static str {
+        match slider_type {
+            SliderType::Volume => {
+                if muted || value <= 0.0 {
+                    "🔇"
+                } else if value < 34.0 {
+                    "🔈"
+                } else if value < 67.0 {
+                    "🔉"
+                } else {
+                    "🔊"
+                }
+            }
+            SliderType::Brightness => {
+                if value < 34.0 {
+                    "🔅"
+                } else if value < 67.0 {
+                    "🔆"
+                } else {
+                    "☀️"
+                }
             }
+        }
+    }
 
-            progressbar {
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Stylesheet for the overlays, in the configured theme colors
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.amiya-overlay {{
+                background-color: alpha({background}, 0.95);
+                border-radius: 12px;
+                color: {foreground};
+            }}
+
+            window.amiya-overlay progressbar {{
                 min-height: 20px;
                 border-radius: 10px;
-            }
+            }}
 
-            progressbar trough {
-                background-color: rgba(255, 255, 255, 0.1);
+            window.amiya-overlay progressbar trough {{
+                background-color: alpha({foreground}, 0.1);
                 border-radius: 10px;
-            }
+            }}
 
-            progressbar progress {
-                background-color: #89b4fa;
+            window.amiya-overlay progressbar progress {{
+                background-color: {accent};
                 border-radius: 10px;
-            }
+            }}
+
+            window.amiya-overlay progressbar.boosted progress {{
+                background-color: {boosted};
+            }}
 
-            label {
+            window.amiya-overlay label {{
                 font-size: 14pt;
                 font-weight: bold;
-            }
-        "#;
-
-        provider.load_from_string(css);
-
-        gtk4::style_context_add_provider_for_display(
-            &window.display(),
-            &provider,
-            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-        );
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+            boosted = theme.critical,
+        )
     }
 }
 
@@ -184,12 +252,21 @@ pub struct OverlayManager {
 
 impl OverlayManager {
     pub fn new(app: &Application, state: &Arc<AppState>) -> Self {
-        let volume_overlay = Arc::new(SliderOverlay::new(app, SliderType::Volume));
-        let brightness_overlay = Arc::new(SliderOverlay::new(app, SliderType::Brightness));
+        let config = state.config();
+        let volume_overlay = Arc::new(SliderOverlay::new(app, SliderType::Volume, &config.overlay));
+        let brightness_overlay = Arc::new(SliderOverlay::new(
+            app,
+            SliderType::Brightness,
+            &config.overlay,
+        ));
+        volume_overlay.set_max(config.audio.max_volume_percent as f64);
+
+        // Both overlays share one stylesheet, selected by window class
+        SliderOverlay::apply_theme(&volume_overlay.window, state);
 
         // Subscribe to events
         Self::subscribe_to_events(
-            state.events.clone(),
+            state.clone(),
             volume_overlay.clone(),
             brightness_overlay.clone(),
         );
@@ -201,28 +278,28 @@ impl OverlayManager {
     }
 
     fn subscribe_to_events(
-        events: crate::events::EventManager,
+        state: Arc<AppState>,
         volume_overlay: Arc<SliderOverlay>,
         brightness_overlay: Arc<SliderOverlay>,
     ) {
-        let mut receiver = events.subscribe();
+        let mut receiver = state.events.subscribe();
 
         glib::spawn_future_local(async move {
-            loop {
-                match receiver.recv().await {
-                    Ok(event) => match event {
-                        Event::VolumeChanged { level, muted } => {
-                            volume_overlay.show(level, Some(muted));
-                        }
-                        Event::BrightnessChanged { level } => {
-                            brightness_overlay.show(level, None);
-                        }
-                        _ => {} // Ignore other events
-                    },
-                    Err(_) => {
-                        // Channel closed, exit loop
-                        break;
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::VolumeChanged { level, muted } => {
+                        volume_overlay.show(level, Some(muted));
+                    }
+                    Event::BrightnessChanged { level } => {
+                        brightness_overlay.show(level, None);
+                    }
+                    Event::ConfigReloaded => {
+                        let config = state.config();
+                        volume_overlay.apply_config(&config.overlay);
+                        volume_overlay.set_max(config.audio.max_volume_percent as f64);
+                        brightness_overlay.apply_config(&config.overlay);
                     }
+                    _ => {} // Ignore other events
                 }
             }
         });
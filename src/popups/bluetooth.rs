@@ -1,19 +1,42 @@
+use super::PopupTask;
 use crate::app::AppState;
+use crate::config::ThemeConfig;
 use crate::events::{BluetoothDeviceInfo, Event};
 use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{
-    Application, ApplicationWindow, Box as GtkBox, Button, Label, ListBox, Orientation,
-    ScrolledWindow, Switch,
+    Application, ApplicationWindow, Box as GtkBox, Button, CheckButton, Label, ListBox,
+    MenuButton, Orientation, Popover, ScrolledWindow, Switch,
 };
-use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use gtk4_layer_shell::{Layer, LayerShell};
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+/// Glyph for a BlueZ device icon name (freedesktop icon naming)
+fn device_glyph(icon: Option<&str>) -> &'static str {
+    match icon.unwrap_or_default() {
+        "audio-headset" | "audio-headphones" => "🎧",
+        "audio-card" | "audio-speakers" => "🔊",
+        "input-keyboard" => "⌨",
+        "input-mouse" | "input-tablet" => "🖱",
+        "input-gaming" => "🎮",
+        "phone" => "📱",
+        "computer" => "💻",
+        "camera-photo" | "camera-video" => "📷",
+        "printer" => "🖨",
+        _ => "🔵",
+    }
+}
+
 pub struct BluetoothPopup {
     window: ApplicationWindow,
     device_list: ListBox,
     toggle: Switch,
+    listener: PopupTask,
+    refresh: PopupTask,
     state: Arc<AppState>,
 }
 
@@ -30,12 +53,12 @@ impl BluetoothPopup {
         window.init_layer_shell();
         window.set_layer(Layer::Overlay);
         window.set_namespace("amiya-bluetooth");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("bluetooth-popup");
 
-        // Position in top-right
-        window.set_anchor(Edge::Top, true);
-        window.set_anchor(Edge::Right, true);
-        window.set_margin(Edge::Top, 40);
-        window.set_margin(Edge::Right, 10);
+        // Position according to the popup config
+        super::apply_popup_anchor(&window, &state.config());
 
         // Create main container
         let container = GtkBox::new(Orientation::Vertical, 12);
@@ -65,8 +88,12 @@ impl BluetoothPopup {
             });
         }
 
+        // Keeps the popup open when focus moves elsewhere
+        let pin = super::pin_button();
+
         header.append(&title);
         header.append(&toggle);
+        header.append(&pin);
 
         // Device list
         let scrolled = ScrolledWindow::builder()
@@ -75,6 +102,8 @@ impl BluetoothPopup {
             .build();
 
         let device_list = ListBox::new();
+        // Enter on a row presses its Connect/Disconnect button
+        super::activate_row_action(&device_list);
         device_list.add_css_class("device-list");
 
         scrolled.set_child(Some(&device_list));
@@ -90,17 +119,14 @@ impl BluetoothPopup {
         window.set_child(Some(&container));
 
         // Apply theme
-        Self::apply_theme(&window);
+        Self::apply_theme(&window, &state);
 
-        // Close on focus loss
-        let window_clone = window.clone();
-        window.connect_is_active_notify(move |win| {
-            if !win.is_active() {
-                window_clone.close();
-            }
-        });
+        // Close on focus loss unless pinned
+        super::close_on_focus_loss(&window, Some(&pin));
 
         let popup = BluetoothPopup {
+            listener: PopupTask::new(&window),
+            refresh: PopupTask::new(&window),
             window,
             device_list: device_list.clone(),
             toggle: toggle.clone(),
@@ -140,9 +166,13 @@ impl BluetoothPopup {
         if let Some(bt) = &state.bluetooth_control {
             let bt_clone = bt.clone();
             let scan_button_clone = scan_button.clone();
+            // Pending stop of the last scan; a new scan restarts the countdown
+            // rather than being cut short by an older one
+            let stop_timer: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
             scan_button.connect_clicked(move |_| {
                 let bt = bt_clone.clone();
                 let button = scan_button_clone.clone();
+                let stop_timer = stop_timer.clone();
                 button.set_sensitive(false);
                 button.set_label("Scanning...");
 
@@ -154,13 +184,18 @@ impl BluetoothPopup {
 
                         // Stop scan after 10 seconds
                         let bt_scan = bt.clone();
-                        glib::timeout_add_seconds_local(10, move || {
+                        let timer = stop_timer.clone();
+                        let source = glib::timeout_add_seconds_local(10, move || {
+                            timer.borrow_mut().take();
                             let bt = bt_scan.clone();
                             glib::spawn_future_local(async move {
                                 let _ = bt.stop_scan().await;
                             });
                             glib::ControlFlow::Break
                         });
+                        if let Some(previous) = stop_timer.borrow_mut().replace(source) {
+                            previous.remove();
+                        }
                     }
 
                     // Re-enable button after 1 second
@@ -173,45 +208,33 @@ impl BluetoothPopup {
             });
         }
 
-        // Subscribe to Bluetooth events
-        let device_list_clone = device_list.clone();
-        let state_clone = state.clone();
-        glib::spawn_future_local(async move {
-            let mut receiver = state_clone.events.subscribe();
+        popup
+    }
+
+    /// Keep the device list in sync while the popup is open
+    fn listen(list: ListBox, state: Arc<AppState>) -> impl Future<Output = ()> {
+        let mut receiver = state.events.subscribe();
 
-            loop {
-                match receiver.recv().await {
-                    Ok(Event::BluetoothDevicesUpdated { devices }) => {
+        async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::BluetoothDevicesUpdated { devices } => {
                         debug!("Received {} Bluetooth devices", devices.len());
-                        Self::update_device_list(&device_list_clone, &devices, &state_clone);
+                        Self::update_device_list(&list, &devices, &state);
                     }
-                    Ok(Event::BluetoothStateChanged { enabled }) => {
+                    Event::BluetoothStateChanged { enabled } => {
                         debug!("Bluetooth state changed: {}", enabled);
                     }
-                    Ok(Event::BluetoothDeviceConnected { address, name }) => {
+                    Event::BluetoothDeviceConnected { address, name } => {
                         info!("Device connected: {} ({})", name, address);
                     }
-                    Ok(Event::BluetoothDeviceDisconnected { address }) => {
+                    Event::BluetoothDeviceDisconnected { address } => {
                         info!("Device disconnected: {}", address);
                     }
                     _ => {}
                 }
             }
-        });
-
-        // Initial load of devices
-        if let Some(bt) = &state.bluetooth_control {
-            let device_list_clone = device_list.clone();
-            let state_clone = state.clone();
-            let bt_clone = bt.clone();
-            glib::spawn_future_local(async move {
-                if let Ok(devices) = bt_clone.get_devices().await {
-                    Self::update_device_list(&device_list_clone, &devices, &state_clone);
-                }
-            });
         }
-
-        popup
     }
 
     fn update_device_list(
@@ -232,6 +255,9 @@ impl BluetoothPopup {
             label.add_css_class("empty-message");
             list.append(&label);
         } else {
+            // Connected devices first, then paired ones, each by name
+            let mut devices: Vec<&BluetoothDeviceInfo> = devices.iter().collect();
+            devices.sort_by_cached_key(|d| (!d.connected, !d.paired, d.name.to_lowercase()));
             for device in devices {
                 Self::add_device(list, device, state);
             }
@@ -245,19 +271,25 @@ impl BluetoothPopup {
         row.set_margin_top(8);
         row.set_margin_bottom(8);
 
+        let icon_label = Label::new(Some(device_glyph(device.icon.as_deref())));
+        icon_label.add_css_class("device-icon");
+        if let Some(icon) = &device.icon {
+            icon_label.set_tooltip_text(Some(icon));
+        }
+        row.append(&icon_label);
+
         let device_info = GtkBox::new(Orientation::Vertical, 4);
         let name_label = Label::new(Some(&device.name));
         name_label.set_halign(gtk4::Align::Start);
         name_label.add_css_class("device-name");
 
-        let status = if device.connected {
-            "Connected"
-        } else if device.paired {
-            "Paired"
-        } else {
-            "Available"
+        let status = match (&device.codec, device.connected, device.paired) {
+            (Some(codec), true, _) => format!("Connected · {}", codec),
+            (None, true, _) => "Connected".to_string(),
+            (_, false, true) => "Paired".to_string(),
+            (_, false, false) => "Available".to_string(),
         };
-        let status_label = Label::new(Some(status));
+        let status_label = Label::new(Some(&status));
         status_label.set_halign(gtk4::Align::Start);
         status_label.add_css_class("device-status");
 
@@ -271,6 +303,7 @@ impl BluetoothPopup {
             "Connect"
         });
         connect_btn.set_valign(gtk4::Align::Center);
+        connect_btn.add_css_class(super::ROW_ACTION);
 
         // Wire up connect/disconnect button
         if let Some(bt) = &state.bluetooth_control {
@@ -318,74 +351,156 @@ impl BluetoothPopup {
         }
 
         row.append(&device_info);
+
+        // Trusting only means something for paired devices
+        if device.paired {
+            let trusted_check = CheckButton::with_label("Trusted");
+            trusted_check.set_active(device.trusted);
+            trusted_check.set_valign(gtk4::Align::Center);
+            trusted_check.set_tooltip_text(Some("Let this device reconnect automatically"));
+            trusted_check.add_css_class("device-trusted");
+
+            if let Some(bt) = &state.bluetooth_control {
+                let bt_clone = bt.clone();
+                let address = device.address.clone();
+                trusted_check.connect_toggled(move |check| {
+                    let bt = bt_clone.clone();
+                    let addr = address.clone();
+                    let check = check.clone();
+                    let trusted = check.is_active();
+
+                    check.set_sensitive(false);
+                    glib::spawn_future_local(async move {
+                        if let Err(e) = bt.set_trusted(&addr, trusted).await {
+                            warn!("Failed to set trust for device {}: {}", addr, e);
+                        }
+                        check.set_sensitive(true);
+                    });
+                });
+            } else {
+                trusted_check.set_sensitive(false);
+            }
+
+            row.append(&trusted_check);
+        }
+
         row.append(&connect_btn);
 
+        if device.paired {
+            row.append(&Self::device_menu(device, state));
+        }
+
         list.append(&row);
     }
 
+    /// "⋯" menu with less common actions for a paired device
+    fn device_menu(device: &BluetoothDeviceInfo, state: &Arc<AppState>) -> MenuButton {
+        let forget_btn = Button::with_label("Forget device");
+        forget_btn.add_css_class("flat");
+
+        let popover = Popover::new();
+        popover.set_child(Some(&forget_btn));
+
+        let menu = MenuButton::new();
+        menu.set_label("⋯");
+        menu.set_valign(gtk4::Align::Center);
+        menu.set_tooltip_text(Some("More actions"));
+        menu.add_css_class("device-menu");
+        menu.set_popover(Some(&popover));
+
+        if let Some(bt) = &state.bluetooth_control {
+            let bt_clone = bt.clone();
+            let address = device.address.clone();
+            forget_btn.connect_clicked(move |_| {
+                popover.popdown();
+                let bt = bt_clone.clone();
+                let addr = address.clone();
+                // The backend re-emits the device list once the device is gone
+                glib::spawn_future_local(async move {
+                    match bt.remove_device(&addr).await {
+                        Ok(()) => info!("Forgot device: {}", addr),
+                        Err(e) => warn!("Failed to forget device {}: {}", addr, e),
+                    }
+                });
+            });
+        } else {
+            menu.set_sensitive(false);
+        }
+
+        menu
+    }
+
     pub fn show(&self) {
-        // Refresh device list when showing
+        // Pick up anchor changes from config reloads
+        super::apply_popup_anchor(&self.window, &self.state.config());
+
+        self.listener.start(Self::listen(self.device_list.clone(), self.state.clone()));
         if let Some(bt) = &self.state.bluetooth_control {
             let device_list = self.device_list.clone();
             let state = self.state.clone();
             let bt_clone = bt.clone();
 
-            glib::spawn_future_local(async move {
+            self.refresh.start(async move {
                 if let Ok(devices) = bt_clone.get_devices().await {
                     Self::update_device_list(&device_list, &devices, &state);
                 }
             });
         }
 
-        self.window.present();
+        super::present_focused(&self.window);
     }
 
     pub fn hide(&self) {
         self.window.close();
     }
 
-    fn apply_theme(window: &ApplicationWindow) {
-        let provider = gtk4::CssProvider::new();
-        let css = r#"
-            window {
-                background-color: rgba(30, 30, 46, 0.98);
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Device list styles, with muted status lines under each name
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.bluetooth-popup {{
+                background-color: alpha({background}, 0.98);
                 border-radius: 12px;
-                color: #cdd6f4;
-            }
+                color: {foreground};
+            }}
 
-            .device-list {
+            .device-list {{
                 background-color: transparent;
-            }
+            }}
 
-            .device-name {
+            .device-icon {{
+                font-size: 16pt;
+            }}
+
+            .device-name {{
                 font-weight: bold;
-            }
+            }}
 
-            .device-status {
+            .device-status {{
                 font-size: 10pt;
-                color: #a6adc8;
-            }
+                color: alpha({foreground}, 0.75);
+            }}
 
-            .empty-message {
-                color: #a6adc8;
+            .empty-message {{
+                color: alpha({foreground}, 0.75);
                 font-style: italic;
-            }
+            }}
 
-            button.scan-button {
-                background-color: #89b4fa;
-                color: #1e1e2e;
+            button.scan-button {{
+                background-color: {accent};
+                color: {background};
                 border-radius: 6px;
                 padding: 8px;
                 font-weight: bold;
-            }
-        "#;
-
-        provider.load_from_string(css);
-
-        gtk4::style_context_add_provider_for_display(
-            &window.display(),
-            &provider,
-            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-        );
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
     }
 }
@@ -0,0 +1,167 @@
+use crate::app::AppState;
+use crate::config::{Config, Position, ThemeConfig};
+use chrono::Local;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{Application, ApplicationWindow, Box as GtkBox, Calendar, Label, Orientation};
+use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use std::sync::Arc;
+
+pub struct CalendarPopup {
+    window: ApplicationWindow,
+    calendar: Calendar,
+    time_label: Label,
+    date_label: Label,
+    state: Arc<AppState>,
+}
+
+impl CalendarPopup {
+    pub fn new(app: &Application, state: Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Calendar")
+            .default_width(320)
+            .build();
+
+        // Initialize layer shell
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-calendar");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("calendar-popup");
+
+        // Position below (or above) the clock
+        Self::apply_anchor(&window, &state.config());
+
+        // Create main container
+        let container = GtkBox::new(Orientation::Vertical, 8);
+        container.set_margin_start(16);
+        container.set_margin_end(16);
+        container.set_margin_top(16);
+        container.set_margin_bottom(16);
+
+        // Current time and date
+        let time_label = Label::new(None);
+        time_label.add_css_class("calendar-time");
+
+        let date_label = Label::new(None);
+        date_label.add_css_class("calendar-date");
+
+        let calendar = Calendar::new();
+        calendar.add_css_class("calendar");
+
+        container.append(&time_label);
+        container.append(&date_label);
+        container.append(&calendar);
+
+        window.set_child(Some(&container));
+
+        // Apply theme
+        Self::apply_theme(&window, &state);
+
+        // Close on focus loss
+        super::close_on_focus_loss(&window, None);
+
+        // Keep the time current while the popup is open
+        let time_clone = time_label.clone();
+        let date_clone = date_label.clone();
+        let window_clone = window.clone();
+        glib::timeout_add_seconds_local(1, move || {
+            if window_clone.is_visible() {
+                Self::update_time(&time_clone, &date_clone);
+            }
+            glib::ControlFlow::Continue
+        });
+
+        CalendarPopup {
+            window,
+            calendar,
+            time_label,
+            date_label,
+            state,
+        }
+    }
+
+    pub fn show(&self) {
+        // Pick up bar position changes from config reloads
+        Self::apply_anchor(&self.window, &self.state.config());
+
+        // Always open on today
+        if let Ok(today) = glib::DateTime::now_local() {
+            self.calendar.select_day(&today);
+        }
+        Self::update_time(&self.time_label, &self.date_label);
+
+        super::present_focused(&self.window);
+    }
+
+    pub fn hide(&self) {
+        self.window.close();
+    }
+
+    fn update_time(time_label: &Label, date_label: &Label) {
+        let now = Local::now();
+        time_label.set_text(&now.format("%H:%M:%S").to_string());
+        date_label.set_text(&now.format("%A, %B %d, %Y").to_string());
+    }
+
+    /// Anchor to the bar's edge, horizontally centered under the clock
+    fn apply_anchor(window: &ApplicationWindow, config: &Config) {
+        let bar_at_top = config.bar.position == Position::Top;
+        let offset = config.bar.height + config.popup.margin;
+
+        window.set_anchor(Edge::Top, bar_at_top);
+        window.set_anchor(Edge::Bottom, !bar_at_top);
+        window.set_anchor(Edge::Left, false);
+        window.set_anchor(Edge::Right, false);
+        window.set_margin(Edge::Top, if bar_at_top { offset } else { 0 });
+        window.set_margin(Edge::Bottom, if bar_at_top { 0 } else { offset });
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Clock header and month grid, with today picked out in the accent
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.calendar-popup {{
+                background-color: alpha({background}, 0.98);
+                border-radius: 12px;
+                color: {foreground};
+            }}
+
+            .calendar-time {{
+                font-size: 20pt;
+                font-weight: bold;
+            }}
+
+            .calendar-date {{
+                color: alpha({foreground}, 0.75);
+            }}
+
+            calendar.calendar {{
+                background-color: transparent;
+                border: none;
+                margin-top: 8px;
+            }}
+
+            calendar.calendar > grid > label.today {{
+                color: {accent};
+                font-weight: bold;
+            }}
+
+            calendar.calendar > grid > label:selected {{
+                background-color: {accent};
+                color: {background};
+                border-radius: 6px;
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
+    }
+}
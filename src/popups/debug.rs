@@ -0,0 +1,228 @@
+use crate::app::AppState;
+use crate::config::ThemeConfig;
+use crate::events::Event;
+use chrono::Local;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{
+    Application, ApplicationWindow, Box as GtkBox, Button, Label, ListBox, Orientation,
+    ScrolledWindow,
+};
+use gtk4_layer_shell::{Layer, LayerShell};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Log entries kept before the oldest are dropped
+const MAX_ENTRIES: usize = 200;
+
+/// Longest entry shown; tray icons and notification bodies can be huge
+const MAX_ENTRY_CHARS: usize = 300;
+
+/// Live log of every event on the bus, for debugging widgets that don't update
+///
+/// Unlike the other popups it listens from the moment it's created, not just
+/// while shown, so opening it reveals what happened beforehand. The popup
+/// manager only creates it when started with `--debug` or with
+/// `events.debug_popup` set.
+pub struct DebugPopup {
+    window: ApplicationWindow,
+    list: ListBox,
+    scrolled: ScrolledWindow,
+    state: Arc<AppState>,
+}
+
+impl DebugPopup {
+    pub fn new(app: &Application, state: Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Event Log")
+            .default_width(560)
+            .default_height(500)
+            .build();
+
+        // Initialize layer shell
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-debug");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("debug-popup");
+
+        // Position according to the popup config
+        super::apply_popup_anchor(&window, &state.config());
+
+        // Create main container
+        let container = GtkBox::new(Orientation::Vertical, 12);
+        container.set_margin_start(16);
+        container.set_margin_end(16);
+        container.set_margin_top(16);
+        container.set_margin_bottom(16);
+
+        // Header with title, clear and pin buttons
+        let header = GtkBox::new(Orientation::Horizontal, 12);
+        let title = Label::new(Some("🐞 Event Log"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_hexpand(true);
+
+        let clear_button = Button::with_label("Clear");
+        clear_button.add_css_class("clear-button");
+
+        // Keeps the popup open while watching a widget elsewhere
+        let pin = super::pin_button();
+
+        header.append(&title);
+        header.append(&clear_button);
+        header.append(&pin);
+
+        // Newest entries at the bottom
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .min_content_height(400)
+            .build();
+
+        let list = ListBox::new();
+        list.add_css_class("event-log");
+        list.set_selection_mode(gtk4::SelectionMode::None);
+        scrolled.set_child(Some(&list));
+
+        container.append(&header);
+        container.append(&scrolled);
+
+        window.set_child(Some(&container));
+
+        // Apply theme
+        Self::apply_theme(&window, &state);
+
+        // Close on focus loss unless pinned
+        super::close_on_focus_loss(&window, Some(&pin));
+
+        let list_clone = list.clone();
+        clear_button.connect_clicked(move |_| {
+            while let Some(row) = list_clone.first_child() {
+                list_clone.remove(&row);
+            }
+        });
+
+        Self::subscribe_to_events(state.clone(), list.clone(), scrolled.clone());
+
+        DebugPopup {
+            window,
+            list,
+            scrolled,
+            state,
+        }
+    }
+
+    pub fn show(&self) {
+        // Pick up anchor changes from config reloads
+        super::apply_popup_anchor(&self.window, &self.state.config());
+
+        super::present_focused(&self.window);
+        Self::scroll_to_end(&self.scrolled);
+        if let Some(row) = self.list.last_child() {
+            row.grab_focus();
+        }
+    }
+
+    pub fn hide(&self) {
+        self.window.close();
+    }
+
+    fn subscribe_to_events(state: Arc<AppState>, list: ListBox, scrolled: ScrolledWindow) {
+        let mut receiver = state.events.subscribe();
+
+        glib::spawn_future_local(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        Self::append(&list, &Self::describe(&event));
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        // Worth seeing here: it may be why a widget didn't update
+                        Self::append(&list, &format!("… skipped {} events", skipped));
+                    }
+                    Err(RecvError::Closed) => {
+                        // Channel closed, exit loop
+                        break;
+                    }
+                }
+                if scrolled.is_mapped() {
+                    Self::scroll_to_end(&scrolled);
+                }
+            }
+        });
+    }
+
+    /// Timestamped one-line description of `event`
+    fn describe(event: &Event) -> String {
+        let mut text = format!("{:?}", event);
+        if let Some((cut, _)) = text.char_indices().nth(MAX_ENTRY_CHARS) {
+            text.truncate(cut);
+            text.push('…');
+        }
+        format!("{}  {}", Local::now().format("%H:%M:%S%.3f"), text)
+    }
+
+    /// Add an entry at the bottom, dropping the oldest past `MAX_ENTRIES`
+    fn append(list: &ListBox, text: &str) {
+        let label = Label::new(Some(text));
+        label.add_css_class("event-log-entry");
+        label.set_halign(gtk4::Align::Start);
+        label.set_xalign(0.0);
+        label.set_wrap(true);
+        label.set_wrap_mode(gtk4::pango::WrapMode::WordChar);
+        label.set_selectable(true);
+        list.append(&label);
+
+        let mut count = 0;
+        let mut child = list.first_child();
+        while let Some(row) = child {
+            count += 1;
+            child = row.next_sibling();
+        }
+        while count > MAX_ENTRIES {
+            if let Some(row) = list.first_child() {
+                list.remove(&row);
+            }
+            count -= 1;
+        }
+    }
+
+    /// Scroll to the newest entry once the list has been laid out
+    fn scroll_to_end(scrolled: &ScrolledWindow) {
+        let scrolled = scrolled.clone();
+        glib::idle_add_local_once(move || {
+            let adjustment = scrolled.vadjustment();
+            adjustment.set_value(adjustment.upper() - adjustment.page_size());
+        });
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Stylesheet for the event log, in the configured theme colors
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.debug-popup {{
+                background-color: alpha({background}, 0.98);
+                border-radius: 12px;
+                color: {foreground};
+            }}
+
+            .event-log {{
+                background-color: transparent;
+            }}
+
+            .event-log-entry {{
+                font-family: monospace;
+                font-size: 9pt;
+                padding: 2px 4px;
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+        )
+    }
+}
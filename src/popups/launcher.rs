@@ -0,0 +1,293 @@
+use crate::app::AppState;
+use crate::config::ThemeConfig;
+use crate::backend::system::applications;
+use crate::backend::system::DesktopEntry;
+use gtk4::prelude::*;
+use gtk4::{
+    glib, Application, ApplicationWindow, Box as GtkBox, EventControllerKey, Label, ListBox,
+    ListBoxRow, Orientation, ScrolledWindow, SearchEntry,
+};
+use gtk4_layer_shell::{KeyboardMode, Layer, LayerShell};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Maximum number of results shown at once
+const MAX_RESULTS: usize = 50;
+
+pub struct LauncherPopup {
+    window: ApplicationWindow,
+    search: SearchEntry,
+    apps: Rc<RefCell<Vec<DesktopEntry>>>,
+}
+
+impl LauncherPopup {
+    pub fn new(app: &Application, state: Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Launcher")
+            .default_width(480)
+            .default_height(420)
+            .build();
+
+        // Initialize layer shell; the search entry needs keyboard input
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-launcher");
+        window.set_keyboard_mode(KeyboardMode::Exclusive);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("launcher-popup");
+
+        // Left unanchored so the compositor centers it on screen
+
+        // Create main container
+        let container = GtkBox::new(Orientation::Vertical, 12);
+        container.set_margin_start(16);
+        container.set_margin_end(16);
+        container.set_margin_top(16);
+        container.set_margin_bottom(16);
+
+        let search = SearchEntry::new();
+        search.set_placeholder_text(Some("Search applications..."));
+        search.add_css_class("launcher-search");
+
+        // Results list
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .min_content_height(340)
+            .vexpand(true)
+            .build();
+
+        let results = ListBox::new();
+        results.add_css_class("launcher-results");
+        scrolled.set_child(Some(&results));
+
+        container.append(&search);
+        container.append(&scrolled);
+
+        window.set_child(Some(&container));
+
+        // Apply theme
+        Self::apply_theme(&window, &state);
+
+        // Close on focus loss
+        super::close_on_focus_loss(&window, None);
+
+        // Close on Escape
+        super::close_on_escape(&window);
+
+        let apps = Rc::new(RefCell::new(Vec::new()));
+        let shown = Rc::new(RefCell::new(Vec::<DesktopEntry>::new()));
+
+        // Re-filter as the user types
+        let apps_clone = apps.clone();
+        let shown_clone = shown.clone();
+        let results_clone = results.clone();
+        search.connect_search_changed(move |search| {
+            Self::update_results(
+                &results_clone,
+                &shown_clone,
+                &apps_clone.borrow(),
+                &search.text(),
+            );
+        });
+
+        // Enter launches the selected (or first) result
+        let shown_clone = shown.clone();
+        let results_clone = results.clone();
+        let window_clone = window.clone();
+        let state_clone = state.clone();
+        search.connect_activate(move |_| {
+            let index = results_clone
+                .selected_row()
+                .map(|row| row.index())
+                .unwrap_or(0);
+            if let Some(entry) = shown_clone.borrow().get(index as usize) {
+                Self::launch(entry, &state_clone);
+                window_clone.close();
+            }
+        });
+
+        // Up and Down in the entry move through the results, as do the
+        // entry's own Ctrl+G and Ctrl+Shift+G
+        let keys = EventControllerKey::new();
+        keys.set_propagation_phase(gtk4::PropagationPhase::Capture);
+        let results_clone = results.clone();
+        keys.connect_key_pressed(move |_, key, _, _| {
+            let delta = match key {
+                gtk4::gdk::Key::Up => -1,
+                gtk4::gdk::Key::Down => 1,
+                _ => return glib::Propagation::Proceed,
+            };
+            Self::move_selection(&results_clone, delta);
+            glib::Propagation::Stop
+        });
+        search.add_controller(keys);
+        let results_clone = results.clone();
+        search.connect_next_match(move |_| Self::move_selection(&results_clone, 1));
+        let results_clone = results.clone();
+        search.connect_previous_match(move |_| Self::move_selection(&results_clone, -1));
+
+        // Clicking a row launches it
+        let shown_clone = shown.clone();
+        let window_clone = window.clone();
+        let state_clone = state.clone();
+        results.connect_row_activated(move |_, row| {
+            if let Some(entry) = shown_clone.borrow().get(row.index() as usize) {
+                Self::launch(entry, &state_clone);
+                window_clone.close();
+            }
+        });
+
+        LauncherPopup {
+            window,
+            search,
+            apps,
+        }
+    }
+
+    pub fn show(&self) {
+        // Reload so newly installed applications show up
+        *self.apps.borrow_mut() = applications::load_applications();
+
+        // Clearing the text refreshes the results through search-changed
+        if self.search.text().is_empty() {
+            self.search.emit_by_name::<()>("search-changed", &[]);
+        } else {
+            self.search.set_text("");
+        }
+
+        self.window.present();
+        self.search.grab_focus();
+    }
+
+    pub fn hide(&self) {
+        self.window.close();
+    }
+
+    fn update_results(
+        list: &ListBox,
+        shown: &Rc<RefCell<Vec<DesktopEntry>>>,
+        apps: &[DesktopEntry],
+        query: &str,
+    ) {
+        // Clear existing rows
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        let matches: Vec<DesktopEntry> = applications::search(apps, query)
+            .into_iter()
+            .take(MAX_RESULTS)
+            .cloned()
+            .collect();
+
+        for entry in &matches {
+            let row = GtkBox::new(Orientation::Vertical, 2);
+            row.set_margin_start(8);
+            row.set_margin_end(8);
+            row.set_margin_top(6);
+            row.set_margin_bottom(6);
+
+            let name_label = Label::new(Some(&entry.name));
+            name_label.set_halign(gtk4::Align::Start);
+            name_label.add_css_class("launcher-name");
+            row.append(&name_label);
+
+            if let Some(comment) = &entry.comment {
+                let comment_label = Label::new(Some(comment));
+                comment_label.set_halign(gtk4::Align::Start);
+                comment_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+                comment_label.add_css_class("launcher-comment");
+                row.append(&comment_label);
+            }
+
+            list.append(&row);
+        }
+
+        if matches.is_empty() {
+            let label = Label::new(Some("No matching applications"));
+            label.set_margin_top(32);
+            label.set_margin_bottom(32);
+            label.add_css_class("empty-message");
+            list.append(&label);
+        } else {
+            list.select_row(list.row_at_index(0).as_ref());
+        }
+
+        *shown.borrow_mut() = matches;
+    }
+
+    /// Select the row `delta` rows away and scroll it into view, leaving the
+    /// keyboard focus in the search entry
+    fn move_selection(list: &ListBox, delta: i32) {
+        let current = list.selected_row().map(|row| row.index()).unwrap_or(0);
+        let target: Option<ListBoxRow> = list.row_at_index((current + delta).max(0));
+        if let Some(row) = target {
+            list.select_row(Some(&row));
+            let scrolled = list
+                .ancestor(ScrolledWindow::static_type())
+                .and_downcast::<ScrolledWindow>();
+            if let (Some(scrolled), Some(bounds)) = (scrolled, row.compute_bounds(list)) {
+                let top = bounds.y() as f64;
+                scrolled
+                    .vadjustment()
+                    .clamp_page(top, top + bounds.height() as f64);
+            }
+        }
+    }
+
+    fn launch(entry: &DesktopEntry, state: &Arc<AppState>) {
+        if let Err(e) = entry.launch(state.niri_client.as_deref()) {
+            warn!("Failed to launch {}: {}", entry.name, e);
+        }
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Stylesheet for the launcher, in the configured theme colors
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.launcher-popup {{
+                background-color: alpha({background}, 0.98);
+                border-radius: 12px;
+                color: {foreground};
+            }}
+
+            .launcher-search {{
+                font-size: 13pt;
+                padding: 6px;
+            }}
+
+            .launcher-results {{
+                background-color: transparent;
+            }}
+
+            .launcher-results row:selected {{
+                background-color: alpha({accent}, 0.2);
+                border-radius: 6px;
+            }}
+
+            .launcher-name {{
+                font-weight: bold;
+            }}
+
+            .launcher-comment {{
+                font-size: 9pt;
+                color: alpha({foreground}, 0.75);
+            }}
+
+            .launcher-popup .empty-message {{
+                color: alpha({foreground}, 0.5);
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
+    }
+}
@@ -1,6 +1,9 @@
 use crate::app::AppState;
 use crate::events::{Event, EventManager, PopupType};
-use crate::popups::{BluetoothPopup, MediaControlPopup, PowerPopup, WifiPopup};
+use crate::popups::{
+    install_shared_css, BluetoothPopup, CalendarPopup, DebugPopup, LauncherPopup,
+    MediaControlPopup, NotificationCenter, PowerPopup, QuickSettingsPopup, WifiPopup, WindowsPopup,
+};
 use gtk4::glib;
 use gtk4::Application;
 use std::sync::Arc;
@@ -11,6 +14,12 @@ pub struct PopupManager {
     wifi_popup: Arc<Mutex<Option<WifiPopup>>>,
     media_control_popup: Arc<Mutex<Option<MediaControlPopup>>>,
     power_popup: Arc<Mutex<Option<PowerPopup>>>,
+    calendar_popup: Arc<Mutex<Option<CalendarPopup>>>,
+    notification_center: Arc<Mutex<Option<NotificationCenter>>>,
+    launcher_popup: Arc<Mutex<Option<LauncherPopup>>>,
+    debug_popup: Arc<Mutex<Option<DebugPopup>>>,
+    quick_settings_popup: Arc<Mutex<Option<QuickSettingsPopup>>>,
+    windows_popup: Arc<Mutex<Option<WindowsPopup>>>,
     app: Application,
     state: Arc<AppState>,
 }
@@ -22,10 +31,18 @@ impl PopupManager {
             wifi_popup: Arc::new(Mutex::new(None)),
             media_control_popup: Arc::new(Mutex::new(None)),
             power_popup: Arc::new(Mutex::new(None)),
+            calendar_popup: Arc::new(Mutex::new(None)),
+            notification_center: Arc::new(Mutex::new(None)),
+            launcher_popup: Arc::new(Mutex::new(None)),
+            debug_popup: Arc::new(Mutex::new(None)),
+            quick_settings_popup: Arc::new(Mutex::new(None)),
+            windows_popup: Arc::new(Mutex::new(None)),
             app: app.clone(),
             state: state.clone(),
         };
 
+        install_shared_css(&state);
+
         // Subscribe to events
         Self::subscribe_to_events(
             state.events.clone(),
@@ -33,102 +50,207 @@ impl PopupManager {
             manager.wifi_popup.clone(),
             manager.media_control_popup.clone(),
             manager.power_popup.clone(),
+            manager.calendar_popup.clone(),
+            manager.notification_center.clone(),
+            manager.launcher_popup.clone(),
+            manager.debug_popup.clone(),
+            manager.quick_settings_popup.clone(),
+            manager.windows_popup.clone(),
             app.clone(),
             state.clone(),
         );
 
+        // Record from startup when the event log is enabled in the config
+        if state.config().events.debug_popup {
+            manager.enable_debug();
+        }
+
         manager
     }
 
+    /// Create the event log popup now, so it records events from here on
+    /// even before it's first opened
+    pub fn enable_debug(&self) {
+        let mut popup_guard = self.debug_popup.lock().unwrap();
+        if popup_guard.is_none() {
+            *popup_guard = Some(DebugPopup::new(&self.app, self.state.clone()));
+        }
+    }
+
     fn subscribe_to_events(
         events: EventManager,
         bluetooth_popup: Arc<Mutex<Option<BluetoothPopup>>>,
         wifi_popup: Arc<Mutex<Option<WifiPopup>>>,
         media_control_popup: Arc<Mutex<Option<MediaControlPopup>>>,
         power_popup: Arc<Mutex<Option<PowerPopup>>>,
+        calendar_popup: Arc<Mutex<Option<CalendarPopup>>>,
+        notification_center: Arc<Mutex<Option<NotificationCenter>>>,
+        launcher_popup: Arc<Mutex<Option<LauncherPopup>>>,
+        debug_popup: Arc<Mutex<Option<DebugPopup>>>,
+        quick_settings_popup: Arc<Mutex<Option<QuickSettingsPopup>>>,
+        windows_popup: Arc<Mutex<Option<WindowsPopup>>>,
         app: Application,
         state: Arc<AppState>,
     ) {
         let mut receiver = events.subscribe();
 
         glib::spawn_future_local(async move {
-            loop {
-                match receiver.recv().await {
-                    Ok(event) => match event {
-                        Event::PopupRequested { popup_type } => {
-                            match popup_type {
-                                PopupType::Bluetooth => {
-                                    let mut popup_guard = bluetooth_popup.lock().unwrap();
-                                    if popup_guard.is_none() {
-                                        *popup_guard =
-                                            Some(BluetoothPopup::new(&app, state.clone()));
-                                    }
-                                    if let Some(popup) = popup_guard.as_ref() {
-                                        popup.show();
-                                    }
-                                }
-                                PopupType::Wifi => {
-                                    let mut popup_guard = wifi_popup.lock().unwrap();
-                                    if popup_guard.is_none() {
-                                        *popup_guard = Some(WifiPopup::new(&app, state.clone()));
-                                    }
-                                    if let Some(popup) = popup_guard.as_ref() {
-                                        popup.show();
-                                    }
-                                }
-                                PopupType::MediaControl => {
-                                    let mut popup_guard = media_control_popup.lock().unwrap();
-                                    if popup_guard.is_none() {
-                                        *popup_guard =
-                                            Some(MediaControlPopup::new(&app, state.clone()));
-                                    }
-                                    if let Some(popup) = popup_guard.as_ref() {
-                                        popup.show();
-                                    }
-                                }
-                                PopupType::Power => {
-                                    let mut popup_guard = power_popup.lock().unwrap();
-                                    if popup_guard.is_none() {
-                                        *popup_guard = Some(PowerPopup::new(&app, state.clone()));
-                                    }
-                                    if let Some(popup) = popup_guard.as_ref() {
-                                        popup.show();
-                                    }
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::PopupRequested { popup_type } => {
+                        match popup_type {
+                            PopupType::Bluetooth => {
+                                let mut popup_guard = bluetooth_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard = Some(BluetoothPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
                                 }
                             }
-                        }
-                        Event::PopupClosed { popup_type } => {
-                            match popup_type {
-                                PopupType::Bluetooth => {
-                                    if let Some(popup) = bluetooth_popup.lock().unwrap().as_ref() {
-                                        popup.hide();
-                                    }
-                                }
-                                PopupType::Wifi => {
-                                    if let Some(popup) = wifi_popup.lock().unwrap().as_ref() {
-                                        popup.hide();
-                                    }
-                                }
-                                PopupType::MediaControl => {
-                                    if let Some(popup) =
-                                        media_control_popup.lock().unwrap().as_ref()
-                                    {
-                                        popup.hide();
-                                    }
-                                }
-                                PopupType::Power => {
-                                    if let Some(popup) = power_popup.lock().unwrap().as_ref() {
-                                        popup.hide();
-                                    }
+                            PopupType::Wifi => {
+                                let mut popup_guard = wifi_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard = Some(WifiPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
+                                }
+                            }
+                            PopupType::MediaControl => {
+                                let mut popup_guard = media_control_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard =
+                                        Some(MediaControlPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
+                                }
+                            }
+                            PopupType::Power => {
+                                let mut popup_guard = power_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard = Some(PowerPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
+                                }
+                            }
+                            PopupType::Calendar => {
+                                let mut popup_guard = calendar_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard = Some(CalendarPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
+                                }
+                            }
+                            PopupType::Notifications => {
+                                let mut popup_guard = notification_center.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard =
+                                        Some(NotificationCenter::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
+                                }
+                            }
+                            PopupType::Launcher => {
+                                let mut popup_guard = launcher_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard = Some(LauncherPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
+                                }
+                            }
+                            PopupType::Debug => {
+                                let mut popup_guard = debug_popup.lock().unwrap();
+                                // Enabling it in the config takes effect on reload
+                                if popup_guard.is_none() && state.config().events.debug_popup {
+                                    *popup_guard = Some(DebugPopup::new(&app, state.clone()));
+                                }
+                                match popup_guard.as_ref() {
+                                    Some(popup) => popup.show(),
+                                    None => tracing::warn!(
+                                        "Event log is disabled; start with --debug or set events.debug_popup"
+                                    ),
+                                }
+                            }
+                            PopupType::QuickSettings => {
+                                let mut popup_guard = quick_settings_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard =
+                                        Some(QuickSettingsPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
+                                }
+                            }
+                            PopupType::Windows => {
+                                let mut popup_guard = windows_popup.lock().unwrap();
+                                if popup_guard.is_none() {
+                                    *popup_guard = Some(WindowsPopup::new(&app, state.clone()));
+                                }
+                                if let Some(popup) = popup_guard.as_ref() {
+                                    popup.show();
                                 }
                             }
                         }
-                        _ => {} // Ignore other events
-                    },
-                    Err(_) => {
-                        // Channel closed, exit loop
-                        break;
                     }
+                    Event::PopupClosed { popup_type } => match popup_type {
+                        PopupType::Bluetooth => {
+                            if let Some(popup) = bluetooth_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::Wifi => {
+                            if let Some(popup) = wifi_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::MediaControl => {
+                            if let Some(popup) = media_control_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::Power => {
+                            if let Some(popup) = power_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::Calendar => {
+                            if let Some(popup) = calendar_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::Notifications => {
+                            if let Some(popup) = notification_center.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::Launcher => {
+                            if let Some(popup) = launcher_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::Debug => {
+                            if let Some(popup) = debug_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::QuickSettings => {
+                            if let Some(popup) = quick_settings_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                        PopupType::Windows => {
+                            if let Some(popup) = windows_popup.lock().unwrap().as_ref() {
+                                popup.hide();
+                            }
+                        }
+                    },
+                    _ => {} // Ignore other events
                 }
             }
         });
@@ -1,20 +1,36 @@
+use super::{LiveScale, PopupTask};
 use crate::app::AppState;
+use crate::config::ThemeConfig;
+use crate::backend::system::media::{LoopStatus, MediaCapabilities};
 use crate::events::Event;
 use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{
-    Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation, Scale,
+    Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation, Scale, ToggleButton,
 };
-use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use gtk4_layer_shell::{Layer, LayerShell};
+use std::future::Future;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+/// Previous, play/pause and next, enabled per the player's capabilities
+#[derive(Clone)]
+struct PlaybackButtons {
+    prev: Button,
+    play: Button,
+    next: Button,
+}
+
 pub struct MediaControlPopup {
     window: ApplicationWindow,
     track_name: Label,
     artist_name: Label,
-    play_btn: Button,
-    volume_scale: Scale,
+    buttons: PlaybackButtons,
+    shuffle_btn: ToggleButton,
+    repeat_btn: ToggleButton,
+    volume_slider: LiveScale,
+    listener: PopupTask,
+    refresh: PopupTask,
     state: Arc<AppState>,
 }
 
@@ -31,12 +47,12 @@ impl MediaControlPopup {
         window.init_layer_shell();
         window.set_layer(Layer::Overlay);
         window.set_namespace("amiya-media");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("media-control-popup");
 
-        // Position in top-right
-        window.set_anchor(Edge::Top, true);
-        window.set_anchor(Edge::Right, true);
-        window.set_margin(Edge::Top, 40);
-        window.set_margin(Edge::Right, 10);
+        // Position according to the popup config
+        super::apply_popup_anchor(&window, &state.config());
 
         // Create main container
         let container = GtkBox::new(Orientation::Vertical, 16);
@@ -93,9 +109,22 @@ impl MediaControlPopup {
         let next_btn = Button::with_label("⏭");
         next_btn.add_css_class("control-button");
 
+        // Insensitive until the player reports it supports them
+        let shuffle_btn = ToggleButton::with_label("🔀");
+        shuffle_btn.add_css_class("mode-button");
+        shuffle_btn.set_valign(gtk4::Align::Center);
+        Self::show_shuffle(&shuffle_btn, None);
+
+        let repeat_btn = ToggleButton::with_label("🔁");
+        repeat_btn.add_css_class("mode-button");
+        repeat_btn.set_valign(gtk4::Align::Center);
+        Self::show_loop_status(&repeat_btn, None);
+
+        controls.append(&shuffle_btn);
         controls.append(&prev_btn);
         controls.append(&play_btn);
         controls.append(&next_btn);
+        controls.append(&repeat_btn);
 
         // Volume control
         let volume_box = GtkBox::new(Orientation::Horizontal, 8);
@@ -121,22 +150,25 @@ impl MediaControlPopup {
         window.set_child(Some(&container));
 
         // Apply theme
-        Self::apply_theme(&window);
+        Self::apply_theme(&window, &state);
 
         // Close on focus loss
-        let window_clone = window.clone();
-        window.connect_is_active_notify(move |win| {
-            if !win.is_active() {
-                window_clone.close();
-            }
-        });
+        super::close_on_focus_loss(&window, None);
 
         let popup = MediaControlPopup {
+            listener: PopupTask::new(&window),
+            refresh: PopupTask::new(&window),
             window,
             track_name: track_name.clone(),
             artist_name: artist_name.clone(),
-            play_btn: play_btn.clone(),
-            volume_scale: volume_scale.clone(),
+            buttons: PlaybackButtons {
+                prev: prev_btn.clone(),
+                play: play_btn.clone(),
+                next: next_btn.clone(),
+            },
+            shuffle_btn: shuffle_btn.clone(),
+            repeat_btn: repeat_btn.clone(),
+            volume_slider: LiveScale::new(&volume_scale),
             state: state.clone(),
         };
 
@@ -191,76 +223,103 @@ impl MediaControlPopup {
                 });
             });
 
-            // Volume control
+            // Shuffle button; `clicked` isn't emitted by `set_active`, so
+            // syncing the button from events doesn't loop back here
             let media_clone = media.clone();
-            volume_scale.connect_value_changed(move |scale| {
+            shuffle_btn.connect_clicked(move |button| {
                 let media = media_clone.clone();
-                let volume = scale.value() / 100.0; // Convert to 0.0-1.0 range
-
+                let button = button.clone();
                 glib::spawn_future_local(async move {
-                    if let Err(e) = media.set_volume(volume).await {
-                        debug!("Failed to set volume: {}", e);
+                    let shuffle = !media.get_shuffle().await.unwrap_or(false);
+                    if let Err(e) = media.set_shuffle(shuffle).await {
+                        warn!("Failed to set shuffle: {}", e);
+                        Self::show_shuffle(&button, media.get_shuffle().await);
                     }
                 });
             });
 
-            // Get initial volume
-            let volume_scale_clone = volume_scale.clone();
+            // Repeat button cycles off, playlist, track
             let media_clone = media.clone();
-            glib::spawn_future_local(async move {
-                let volume = media_clone.get_volume().await;
-                volume_scale_clone.set_value(volume * 100.0);
+            repeat_btn.connect_clicked(move |button| {
+                let media = media_clone.clone();
+                let button = button.clone();
+                glib::spawn_future_local(async move {
+                    let current = media.get_loop_status().await.unwrap_or(LoopStatus::None);
+                    if let Err(e) = media.set_loop_status(current.next()).await {
+                        warn!("Failed to set repeat: {}", e);
+                    }
+                    // The click toggled the button; show the actual mode
+                    Self::show_loop_status(&button, media.get_loop_status().await);
+                });
             });
 
-            // Get initial playback status
-            let play_btn_clone = play_btn.clone();
+            // Volume control
             let media_clone = media.clone();
-            glib::spawn_future_local(async move {
-                let status = media_clone.get_playback_status().await;
-                let icon = match status {
-                    crate::backend::system::media::PlaybackStatus::Playing => "⏸",
-                    _ => "▶",
-                };
-                play_btn_clone.set_label(icon);
+            popup.volume_slider.connect_input(move |value| {
+                let media = media_clone.clone();
+                let volume = value / 100.0; // Convert to 0.0-1.0 range
+
+                glib::spawn_future_local(async move {
+                    if let Err(e) = media.set_volume(volume).await {
+                        debug!("Failed to set volume: {}", e);
+                    }
+                });
             });
         }
 
-        // Subscribe to media events
-        let track_name_clone = track_name.clone();
-        let artist_name_clone = artist_name.clone();
-        let play_btn_clone = play_btn.clone();
-        let volume_scale_clone = volume_scale.clone();
-        let state_clone = state.clone();
-        glib::spawn_future_local(async move {
-            let mut receiver = state_clone.events.subscribe();
-
-            loop {
-                match receiver.recv().await {
-                    Ok(Event::MediaTrackChanged {
+        // Volume, playback status and metadata are loaded by `show()`
+        popup
+    }
+
+    /// Keep the track, playback and volume in sync while the popup is open
+    fn listen(
+        track_name: Label,
+        artist_name: Label,
+        buttons: PlaybackButtons,
+        shuffle_btn: ToggleButton,
+        repeat_btn: ToggleButton,
+        volume_slider: LiveScale,
+        state: Arc<AppState>,
+    ) -> impl Future<Output = ()> {
+        let mut receiver = state.events.subscribe();
+
+        async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::MediaTrackChanged {
                         title,
                         artist,
                         album,
-                    }) => {
+                    } => {
                         debug!("Track changed: {} - {}", artist, title);
-                        track_name_clone.set_text(&title);
+                        track_name.set_text(&title);
 
                         let artist_text = if let Some(alb) = album {
                             format!("{} • {}", artist, alb)
                         } else {
                             artist
                         };
-                        artist_name_clone.set_text(&artist_text);
+                        artist_name.set_text(&artist_text);
                     }
-                    Ok(Event::MediaPlaybackChanged { playing }) => {
+                    Event::MediaPlaybackChanged { playing } => {
                         debug!("Playback changed: {}", playing);
                         let icon = if playing { "⏸" } else { "▶" };
-                        play_btn_clone.set_label(icon);
+                        buttons.play.set_label(icon);
                     }
-                    Ok(Event::MediaVolumeChanged { volume }) => {
+                    Event::MediaVolumeChanged { volume } => {
                         debug!("Volume changed: {:.2}", volume);
-                        volume_scale_clone.set_value(volume * 100.0);
+                        volume_slider.set(volume * 100.0);
+                    }
+                    Event::MediaShuffleChanged { shuffle } => {
+                        Self::show_shuffle(&shuffle_btn, shuffle);
+                    }
+                    Event::MediaLoopStatusChanged { loop_status } => {
+                        Self::show_loop_status(&repeat_btn, loop_status);
+                    }
+                    Event::MediaCapabilitiesChanged { capabilities } => {
+                        Self::show_capabilities(&buttons, volume_slider.scale(), capabilities);
                     }
-                    Ok(Event::MediaPlayerChanged { player }) => {
+                    Event::MediaPlayerChanged { player } => {
                         if let Some(p) = player {
                             info!("Active player changed: {}", p);
                         }
@@ -268,40 +327,32 @@ impl MediaControlPopup {
                     _ => {}
                 }
             }
-        });
-
-        // Initial load of metadata
-        if let Some(media) = &state.media_control {
-            let track_name = track_name.clone();
-            let artist_name = artist_name.clone();
-            let media_clone = media.clone();
-            glib::spawn_future_local(async move {
-                if let Some(metadata) = media_clone.get_metadata().await {
-                    track_name.set_text(&metadata.title);
-
-                    let artist_text = if let Some(album) = metadata.album {
-                        format!("{} • {}", metadata.artist, album)
-                    } else {
-                        metadata.artist
-                    };
-                    artist_name.set_text(&artist_text);
-                }
-            });
         }
-
-        popup
     }
 
     pub fn show(&self) {
-        // Refresh metadata when showing
+        // Pick up anchor changes from config reloads
+        super::apply_popup_anchor(&self.window, &self.state.config());
+
+        self.listener.start(Self::listen(
+            self.track_name.clone(),
+            self.artist_name.clone(),
+            self.buttons.clone(),
+            self.shuffle_btn.clone(),
+            self.repeat_btn.clone(),
+            self.volume_slider.clone(),
+            self.state.clone(),
+        ));
         if let Some(media) = &self.state.media_control {
             let track_name = self.track_name.clone();
             let artist_name = self.artist_name.clone();
-            let play_btn = self.play_btn.clone();
-            let volume_scale = self.volume_scale.clone();
+            let buttons = self.buttons.clone();
+            let shuffle_btn = self.shuffle_btn.clone();
+            let repeat_btn = self.repeat_btn.clone();
+            let volume_slider = self.volume_slider.clone();
             let media_clone = media.clone();
 
-            glib::spawn_future_local(async move {
+            self.refresh.start(async move {
                 // Update metadata
                 if let Some(metadata) = media_clone.get_metadata().await {
                     track_name.set_text(&metadata.title);
@@ -323,81 +374,150 @@ impl MediaControlPopup {
                     crate::backend::system::media::PlaybackStatus::Playing => "⏸",
                     _ => "▶",
                 };
-                play_btn.set_label(icon);
+                buttons.play.set_label(icon);
+
+                // Grey out what the player doesn't support
+                let capabilities = media_clone.get_capabilities().await;
+                Self::show_capabilities(&buttons, volume_slider.scale(), capabilities);
+
+                // Update shuffle and repeat
+                Self::show_shuffle(&shuffle_btn, media_clone.get_shuffle().await);
+                Self::show_loop_status(&repeat_btn, media_clone.get_loop_status().await);
 
                 // Update volume
                 let volume = media_clone.get_volume().await;
-                volume_scale.set_value(volume * 100.0);
+                volume_slider.set(volume * 100.0);
             });
         }
 
-        self.window.present();
+        super::present_focused(&self.window);
     }
 
     pub fn hide(&self) {
         self.window.close();
     }
 
-    fn apply_theme(window: &ApplicationWindow) {
-        let provider = gtk4::CssProvider::new();
-        let css = r#"
-            window {
-                background-color: rgba(30, 30, 46, 0.98);
+    /// Enable only the controls the player supports
+    fn show_capabilities(
+        buttons: &PlaybackButtons,
+        volume_scale: &Scale,
+        capabilities: MediaCapabilities,
+    ) {
+        buttons.prev.set_sensitive(capabilities.can_go_previous);
+        buttons.next.set_sensitive(capabilities.can_go_next);
+        // The button toggles, so either direction working is enough
+        buttons
+            .play
+            .set_sensitive(capabilities.can_play || capabilities.can_pause);
+        volume_scale.set_sensitive(capabilities.can_control);
+    }
+
+    /// Show the player's shuffle state, `None` meaning unsupported
+    fn show_shuffle(button: &ToggleButton, shuffle: Option<bool>) {
+        button.set_sensitive(shuffle.is_some());
+        button.set_active(shuffle.unwrap_or(false));
+        button.set_tooltip_text(Some(match shuffle {
+            Some(true) => "Shuffle: on",
+            Some(false) => "Shuffle: off",
+            None => "Shuffle not supported by this player",
+        }));
+    }
+
+    /// Show the player's repeat mode, `None` meaning unsupported
+    fn show_loop_status(button: &ToggleButton, loop_status: Option<LoopStatus>) {
+        button.set_sensitive(loop_status.is_some());
+        button.set_active(matches!(
+            loop_status,
+            Some(LoopStatus::Track | LoopStatus::Playlist)
+        ));
+        button.set_label(if loop_status == Some(LoopStatus::Track) {
+            "🔂"
+        } else {
+            "🔁"
+        });
+        button.set_tooltip_text(Some(match loop_status {
+            Some(LoopStatus::None) => "Repeat: off",
+            Some(LoopStatus::Playlist) => "Repeat: playlist",
+            Some(LoopStatus::Track) => "Repeat: track",
+            None => "Repeat not supported by this player",
+        }));
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Album art, track details and the playback buttons and slider
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.media-control-popup {{
+                background-color: alpha({background}, 0.98);
                 border-radius: 12px;
-                color: #cdd6f4;
-            }
+                color: {foreground};
+            }}
 
-            .title {
+            .title {{
                 font-size: 14pt;
                 font-weight: bold;
-            }
+            }}
 
-            .album-art {
+            .album-art {{
                 font-size: 72pt;
                 margin: 16px;
-            }
+            }}
 
-            .track-name {
+            .track-name {{
                 font-size: 16pt;
                 font-weight: bold;
-            }
+            }}
 
-            .artist-name {
+            .artist-name {{
                 font-size: 12pt;
-                color: #a6adc8;
-            }
+                color: alpha({foreground}, 0.75);
+            }}
 
-            .control-button {
+            .control-button {{
                 min-width: 48px;
                 min-height: 48px;
                 font-size: 18pt;
                 border-radius: 24px;
-                background-color: rgba(137, 180, 250, 0.2);
-            }
+                background-color: alpha({accent}, 0.2);
+            }}
 
-            .control-button.play-button {
-                background-color: #89b4fa;
-                color: #1e1e2e;
-            }
+            .control-button.play-button {{
+                background-color: {accent};
+                color: {background};
+            }}
+
+            .mode-button {{
+                min-width: 32px;
+                min-height: 32px;
+                font-size: 12pt;
+                border-radius: 16px;
+                background-color: transparent;
+                color: alpha({foreground}, 0.6);
+            }}
+
+            .mode-button:checked {{
+                background-color: alpha({accent}, 0.2);
+                color: {accent};
+            }}
 
-            scale trough {
+            window.media-control-popup scale trough {{
                 min-height: 6px;
-                background-color: rgba(255, 255, 255, 0.1);
+                background-color: alpha({foreground}, 0.1);
                 border-radius: 3px;
-            }
+            }}
 
-            scale highlight {
-                background-color: #89b4fa;
+            window.media-control-popup scale highlight {{
+                background-color: {accent};
                 border-radius: 3px;
-            }
-        "#;
-
-        provider.load_from_string(css);
-
-        gtk4::style_context_add_provider_for_display(
-            &window.display(),
-            &provider,
-            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-        );
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
     }
 }
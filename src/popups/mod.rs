@@ -1,11 +1,313 @@
 pub mod bluetooth;
+pub mod calendar;
+pub mod debug;
+pub mod launcher;
 pub mod manager;
 pub mod media_control;
+pub mod notification;
+pub mod notification_center;
 pub mod power;
+pub mod quick_settings;
 pub mod wifi;
+pub mod windows;
 
 pub use bluetooth::BluetoothPopup;
+pub use calendar::CalendarPopup;
+pub use debug::DebugPopup;
+pub use launcher::LauncherPopup;
 pub use manager::PopupManager;
 pub use media_control::MediaControlPopup;
+pub use notification::NotificationPopups;
+pub use notification_center::NotificationCenter;
 pub use power::PowerPopup;
+pub use quick_settings::QuickSettingsPopup;
 pub use wifi::WifiPopup;
+pub use windows::WindowsPopup;
+
+use crate::app::AppState;
+use crate::config::{Config, PopupAnchor, Position, ThemeConfig};
+use gtk4::prelude::*;
+use gtk4::{
+    glib, ApplicationWindow, Button, EventControllerKey, ListBox, Scale, ToggleButton, Widget,
+    Window,
+};
+use gtk4_layer_shell::{Edge, KeyboardMode, LayerShell};
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a slider has to stay still before its value is applied
+const SLIDER_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Future that only runs while its popup is visible
+///
+/// Popups are built once and kept around, so an event listener spawned at
+/// construction would keep consuming the event stream while hidden, and a
+/// refresh spawned on every show would stack up behind its timers. `start`
+/// them from `show()` instead: each `PopupTask` runs at most one future, and
+/// it is stopped whenever the window is unmapped, including when it closes
+/// on focus loss.
+///
+/// A popup's `listen` subscribes to the event bus before returning its
+/// future, so no event is missed between `start` and the first poll.
+/// `show()` starts the listener first and then reloads its content, which
+/// catches up on whatever changed while the popup was hidden.
+pub(crate) struct PopupTask {
+    handle: Rc<RefCell<Option<glib::JoinHandle<()>>>>,
+}
+
+impl PopupTask {
+    pub(crate) fn new(window: &ApplicationWindow) -> Self {
+        let handle: Rc<RefCell<Option<glib::JoinHandle<()>>>> = Rc::new(RefCell::new(None));
+
+        let hide_handle = handle.clone();
+        window.connect_unmap(move |_| {
+            if let Some(handle) = hide_handle.borrow_mut().take() {
+                handle.abort();
+            }
+        });
+
+        PopupTask { handle }
+    }
+
+    /// Run `future`, cancelling the one started before if it's still running
+    pub(crate) fn start(&self, future: impl Future<Output = ()> + 'static) {
+        let previous = self
+            .handle
+            .borrow_mut()
+            .replace(glib::spawn_future_local(future));
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+}
+
+/// `Scale` that both sets a level and follows it
+///
+/// A slider that calls the backend when its value changes, and is moved by
+/// the events that call causes, would otherwise feed back into itself and
+/// jitter while dragged. Levels shown with `set` are not treated as input,
+/// and are skipped while the user's own value is still waiting to be
+/// applied. Dragging sends only the latest value, once the scale has been
+/// still for `SLIDER_DEBOUNCE`, instead of one D-Bus call per pixel.
+#[derive(Clone)]
+pub(crate) struct LiveScale {
+    scale: Scale,
+    /// Set while `set` moves the scale
+    updating: Rc<Cell<bool>>,
+    /// User input not applied yet
+    pending: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl LiveScale {
+    pub(crate) fn new(scale: &Scale) -> Self {
+        LiveScale {
+            scale: scale.clone(),
+            updating: Rc::new(Cell::new(false)),
+            pending: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    pub(crate) fn scale(&self) -> &Scale {
+        &self.scale
+    }
+
+    /// Show `value` without applying it
+    pub(crate) fn set(&self, value: f64) {
+        if self.pending.borrow().is_some() {
+            return;
+        }
+        self.updating.set(true);
+        self.scale.set_value(value);
+        self.updating.set(false);
+    }
+
+    /// Change the range without applying the value it clamps to
+    pub(crate) fn set_range(&self, min: f64, max: f64) {
+        self.updating.set(true);
+        self.scale.set_range(min, max);
+        self.updating.set(false);
+    }
+
+    /// Call `apply` with the value the user picked, once they stop dragging
+    pub(crate) fn connect_input(&self, apply: impl Fn(f64) + 'static) {
+        let updating = self.updating.clone();
+        let pending = self.pending.clone();
+        let apply = Rc::new(apply);
+        self.scale.connect_value_changed(move |scale| {
+            if updating.get() {
+                return;
+            }
+            if let Some(source) = pending.borrow_mut().take() {
+                source.remove();
+            }
+
+            let scale = scale.clone();
+            let apply = apply.clone();
+            let done = pending.clone();
+            let source = glib::timeout_add_local_once(SLIDER_DEBOUNCE, move || {
+                done.borrow_mut().take();
+                apply(scale.value());
+            });
+            *pending.borrow_mut() = Some(source);
+        });
+    }
+}
+
+/// CSS class marking the button a list row's Enter key presses
+pub(crate) const ROW_ACTION: &str = "row-action";
+
+/// Let a popup take keyboard focus, and close it on Escape
+///
+/// Tab and the arrow keys move between its widgets and list rows, and Enter
+/// or Space presses the focused button, through GTK's own focus handling.
+/// The focus is on demand rather than exclusive, so clicking another window
+/// still moves the keyboard there and closes the popup.
+pub(crate) fn enable_keyboard(window: &ApplicationWindow) {
+    window.set_keyboard_mode(KeyboardMode::OnDemand);
+    close_on_escape(window);
+}
+
+/// Show a popup set up with `enable_keyboard` and focus its first control,
+/// so the keys work without clicking it first
+pub(crate) fn present_focused(window: &ApplicationWindow) {
+    window.present();
+    if GtkWindowExt::focus(window).is_none() {
+        window.child_focus(gtk4::DirectionType::TabForward);
+    }
+}
+
+/// Close `window` when Escape is pressed
+pub(crate) fn close_on_escape(window: &ApplicationWindow) {
+    let keys = EventControllerKey::new();
+    let window_clone = window.clone();
+    keys.connect_key_pressed(move |_, key, _, _| {
+        if key == gtk4::gdk::Key::Escape {
+            window_clone.close();
+            glib::Propagation::Stop
+        } else {
+            glib::Propagation::Proceed
+        }
+    });
+    window.add_controller(keys);
+}
+
+/// Press a row's `ROW_ACTION` button when the row is activated with Enter
+pub(crate) fn activate_row_action(list: &ListBox) {
+    list.connect_row_activated(|_, row| {
+        if let Some(button) = find_row_action(row.upcast_ref()) {
+            button.emit_clicked();
+        }
+    });
+}
+
+fn find_row_action(widget: &Widget) -> Option<Button> {
+    let mut child = widget.first_child();
+    while let Some(widget) = child {
+        if widget.has_css_class(ROW_ACTION) {
+            if let Ok(button) = widget.clone().downcast::<Button>() {
+                return Some(button);
+            }
+        }
+        if let Some(button) = find_row_action(&widget) {
+            return Some(button);
+        }
+        child = widget.next_sibling();
+    }
+    None
+}
+
+/// Header button that keeps a popup open while it's toggled on
+pub(crate) fn pin_button() -> ToggleButton {
+    let pin = ToggleButton::with_label("📌");
+    pin.add_css_class("popup-pin");
+    pin.set_valign(gtk4::Align::Center);
+    pin.set_tooltip_text(Some("Keep open"));
+    pin
+}
+
+/// Install the styles shared by every popup, such as the pinned state of
+/// `pin_button`
+pub(crate) fn install_shared_css(state: &Arc<AppState>) {
+    if let Some(display) = gtk4::gdk::Display::default() {
+        crate::style::install_theme_css(&display, state, shared_css);
+    }
+}
+
+fn shared_css(theme: &ThemeConfig) -> String {
+    format!(
+        r#"
+        button.popup-pin:checked {{
+            color: {accent};
+        }}
+    "#,
+        accent = theme.accent,
+    )
+}
+
+/// Close `window` when it loses focus
+///
+/// It stays open while `pin` is toggled on, and while a window it opened (a
+/// dialog transient for it) is showing, so typing into a password prompt
+/// doesn't make the popup vanish.
+pub(crate) fn close_on_focus_loss(window: &ApplicationWindow, pin: Option<&ToggleButton>) {
+    let pin = pin.cloned();
+    window.connect_is_active_notify(move |win| {
+        if win.is_active() {
+            return;
+        }
+
+        // Focus may still be on its way to a dialog, so check once it landed
+        let win = win.clone();
+        let pin = pin.clone();
+        glib::idle_add_local_once(move || {
+            let pinned = pin.as_ref().is_some_and(|pin| pin.is_active());
+            if !win.is_active() && !pinned && !has_child_window(&win) {
+                win.close();
+            }
+        });
+    });
+}
+
+/// Whether a visible window is transient for `window`
+fn has_child_window(window: &ApplicationWindow) -> bool {
+    let window = window.upcast_ref::<Window>();
+    Window::list_toplevels()
+        .into_iter()
+        .filter_map(|toplevel| toplevel.downcast::<Window>().ok())
+        .any(|toplevel| {
+            toplevel.is_visible() && toplevel.transient_for().as_ref() == Some(window)
+        })
+}
+
+/// Anchor a popup window according to the popup config, keeping it clear of the bar
+pub(crate) fn apply_popup_anchor(window: &ApplicationWindow, config: &Config) {
+    let (top, bottom, left, right) = match config.popup.anchor {
+        PopupAnchor::TopLeft => (true, false, true, false),
+        PopupAnchor::TopRight => (true, false, false, true),
+        PopupAnchor::BottomLeft => (false, true, true, false),
+        PopupAnchor::BottomRight => (false, true, false, true),
+        PopupAnchor::Center => (false, false, false, false),
+    };
+
+    let margin = config.popup.margin;
+    let bar_offset = |edge_has_bar: bool| {
+        if edge_has_bar {
+            config.bar.height + margin
+        } else {
+            margin
+        }
+    };
+
+    for (edge, anchored, offset) in [
+        (Edge::Top, top, bar_offset(config.bar.position == Position::Top)),
+        (Edge::Bottom, bottom, bar_offset(config.bar.position == Position::Bottom)),
+        (Edge::Left, left, margin),
+        (Edge::Right, right, margin),
+    ] {
+        window.set_anchor(edge, anchored);
+        window.set_margin(edge, if anchored { offset } else { 0 });
+    }
+}
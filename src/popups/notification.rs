@@ -0,0 +1,325 @@
+use crate::app::AppState;
+use crate::config::ThemeConfig;
+use crate::backend::notifications::CloseReason;
+use crate::events::{Event, NotificationAction};
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation};
+use gtk4_layer_shell::{Layer, LayerShell};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Display time for notifications that leave the timeout to the server
+const DEFAULT_TIMEOUT_MS: u64 = 5000;
+
+/// Size of the app icon in a card's header, in logical pixels
+const ICON_SIZE: i32 = 16;
+
+/// Stack of notification cards in a single layer-shell window
+#[derive(Clone)]
+pub struct NotificationPopups {
+    window: ApplicationWindow,
+    list: GtkBox,
+    cards: Rc<RefCell<HashMap<u32, GtkBox>>>,
+    timers: Rc<RefCell<HashMap<u32, glib::SourceId>>>,
+    state: Arc<AppState>,
+}
+
+impl NotificationPopups {
+    pub fn new(app: &Application, state: Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Notifications")
+            .default_width(360)
+            .build();
+
+        // Initialize layer shell
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-notifications");
+        window.add_css_class("amiya-popup");
+        window.add_css_class("notification-popups");
+
+        // Position according to the popup config
+        super::apply_popup_anchor(&window, &state.config());
+
+        let list = GtkBox::new(Orientation::Vertical, 8);
+        window.set_child(Some(&list));
+
+        // Apply theme
+        Self::apply_theme(&window, &state);
+
+        // Don't show by default - will be shown by events
+        window.set_visible(false);
+
+        let popups = NotificationPopups {
+            window,
+            list,
+            cards: Rc::new(RefCell::new(HashMap::new())),
+            timers: Rc::new(RefCell::new(HashMap::new())),
+            state,
+        };
+
+        popups.subscribe_to_events();
+
+        popups
+    }
+
+    fn subscribe_to_events(&self) {
+        let popups = self.clone();
+        let mut receiver = self.state.events.subscribe();
+
+        glib::spawn_future_local(async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::NotificationReceived {
+                        id,
+                        app,
+                        icon,
+                        summary,
+                        body,
+                        actions,
+                        timeout_ms,
+                    } => {
+                        // Do-not-disturb still records the notification in history
+                        if !popups.is_dnd() {
+                            popups.add(
+                                id,
+                                &app,
+                                icon.as_deref(),
+                                &summary,
+                                &body,
+                                &actions,
+                                timeout_ms,
+                            );
+                        }
+                    }
+                    Event::NotificationClosed { id } => {
+                        popups.remove(id);
+                    }
+                    Event::ConfigReloaded => {
+                        super::apply_popup_anchor(&popups.window, &popups.state.config());
+                    }
+                    _ => {} // Ignore other events
+                }
+            }
+        });
+    }
+
+    fn is_dnd(&self) -> bool {
+        self.state
+            .notification_daemon
+            .as_ref()
+            .is_some_and(|daemon| daemon.is_dnd())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &self,
+        id: u32,
+        app: &str,
+        icon: Option<&str>,
+        summary: &str,
+        body: &str,
+        actions: &[NotificationAction],
+        timeout_ms: i32,
+    ) {
+        // A notification with the same id replaces the old card
+        self.remove(id);
+
+        let card = GtkBox::new(Orientation::Vertical, 4);
+        card.add_css_class("notification");
+
+        // Header: app icon and name, and close button
+        let header = GtkBox::new(Orientation::Horizontal, 8);
+        header.append(&crate::icons::image(
+            crate::icons::for_notification(app, icon),
+            "dialog-information",
+            ICON_SIZE,
+        ));
+        let app_label = Label::new(Some(app));
+        app_label.add_css_class("notification-app");
+        app_label.set_halign(gtk4::Align::Start);
+        app_label.set_hexpand(true);
+
+        let close_button = Button::with_label("✕");
+        close_button.add_css_class("notification-close");
+        let state = self.state.clone();
+        close_button.connect_clicked(move |_| {
+            Self::dismiss(&state, id, CloseReason::Dismissed);
+        });
+
+        header.append(&app_label);
+        header.append(&close_button);
+        card.append(&header);
+
+        let summary_label = Label::new(Some(summary));
+        summary_label.add_css_class("notification-summary");
+        summary_label.set_halign(gtk4::Align::Start);
+        summary_label.set_wrap(true);
+        card.append(&summary_label);
+
+        if !body.is_empty() {
+            let body_label = Label::new(Some(body));
+            body_label.add_css_class("notification-body");
+            body_label.set_halign(gtk4::Align::Start);
+            body_label.set_wrap(true);
+            body_label.set_xalign(0.0);
+            card.append(&body_label);
+        }
+
+        // Action buttons; "default" is triggered by clicking the card instead
+        let buttons: Vec<&NotificationAction> =
+            actions.iter().filter(|a| a.key != "default").collect();
+        if !buttons.is_empty() {
+            let action_box = GtkBox::new(Orientation::Horizontal, 8);
+            action_box.set_homogeneous(true);
+            for action in buttons {
+                let button = Button::with_label(&action.label);
+                button.add_css_class("notification-action");
+                let state = self.state.clone();
+                let key = action.key.clone();
+                button.connect_clicked(move |_| {
+                    Self::invoke(&state, id, &key);
+                });
+                action_box.append(&button);
+            }
+            card.append(&action_box);
+        }
+
+        // Clicking the card runs the default action or dismisses it
+        let has_default = actions.iter().any(|a| a.key == "default");
+        let click = gtk4::GestureClick::new();
+        let state = self.state.clone();
+        click.connect_released(move |_, _, _, _| {
+            if has_default {
+                Self::invoke(&state, id, "default");
+            } else {
+                Self::dismiss(&state, id, CloseReason::Dismissed);
+            }
+        });
+        card.add_controller(click);
+
+        self.list.append(&card);
+        self.cards.borrow_mut().insert(id, card);
+
+        // Expire after the requested timeout (0 means the app wants it to stay)
+        let timeout = match timeout_ms {
+            t if t < 0 => Some(DEFAULT_TIMEOUT_MS),
+            0 => None,
+            t => Some(t as u64),
+        };
+        if let Some(timeout) = timeout {
+            let state = self.state.clone();
+            let timers = self.timers.clone();
+            let timer = glib::timeout_add_local(Duration::from_millis(timeout), move || {
+                // The source is finished; forget it so it isn't removed twice
+                timers.borrow_mut().remove(&id);
+                Self::dismiss(&state, id, CloseReason::Expired);
+                glib::ControlFlow::Break
+            });
+            self.timers.borrow_mut().insert(id, timer);
+        }
+
+        self.window.present();
+    }
+
+    fn remove(&self, id: u32) {
+        if let Some(timer) = self.timers.borrow_mut().remove(&id) {
+            timer.remove();
+        }
+
+        if let Some(card) = self.cards.borrow_mut().remove(&id) {
+            self.list.remove(&card);
+        }
+
+        if self.cards.borrow().is_empty() {
+            self.window.set_visible(false);
+        }
+    }
+
+    /// Close a notification through the daemon so the sending app is told why
+    fn dismiss(state: &Arc<AppState>, id: u32, reason: CloseReason) {
+        match &state.notification_daemon {
+            Some(daemon) => {
+                let daemon = daemon.clone();
+                glib::spawn_future_local(async move {
+                    if let Err(e) = daemon.close(id, reason).await {
+                        warn!("Failed to close notification {}: {}", id, e);
+                    }
+                });
+            }
+            None => state.events.emit(Event::NotificationClosed { id }),
+        }
+    }
+
+    fn invoke(state: &Arc<AppState>, id: u32, key: &str) {
+        match &state.notification_daemon {
+            Some(daemon) => {
+                let daemon = daemon.clone();
+                let key = key.to_string();
+                glib::spawn_future_local(async move {
+                    if let Err(e) = daemon.invoke_action(id, &key).await {
+                        warn!("Failed to invoke action {} on notification {}: {}", key, id, e);
+                    }
+                });
+            }
+            None => state.events.emit(Event::NotificationClosed { id }),
+        }
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Stylesheet for the notification popups, in the configured theme colors
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.notification-popups {{
+                background-color: transparent;
+            }}
+
+            .notification {{
+                background-color: alpha({background}, 0.98);
+                border-radius: 12px;
+                color: {foreground};
+                padding: 12px 16px;
+            }}
+
+            .notification-app {{
+                font-size: 9pt;
+                color: alpha({foreground}, 0.75);
+            }}
+
+            .notification-summary {{
+                font-weight: bold;
+            }}
+
+            .notification-body {{
+                color: alpha({foreground}, 0.85);
+            }}
+
+            button.notification-close {{
+                background-color: transparent;
+                border: none;
+                padding: 0 4px;
+                color: alpha({foreground}, 0.75);
+            }}
+
+            button.notification-action {{
+                background-color: alpha({accent}, 0.2);
+                border-radius: 6px;
+                margin-top: 4px;
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
+    }
+}
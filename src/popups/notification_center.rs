@@ -0,0 +1,302 @@
+use super::PopupTask;
+use crate::app::AppState;
+use crate::config::ThemeConfig;
+use crate::backend::notifications::NotificationRecord;
+use crate::events::Event;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{
+    Application, ApplicationWindow, Box as GtkBox, Button, Label, ListBox, Orientation,
+    ScrolledWindow, Switch,
+};
+use gtk4_layer_shell::{Layer, LayerShell};
+use std::future::Future;
+use std::sync::Arc;
+
+/// Size of the app icon next to each notification, in logical pixels
+const ICON_SIZE: i32 = 16;
+
+pub struct NotificationCenter {
+    window: ApplicationWindow,
+    history_list: ListBox,
+    dnd_toggle: Switch,
+    listener: PopupTask,
+    state: Arc<AppState>,
+}
+
+impl NotificationCenter {
+    pub fn new(app: &Application, state: Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Notifications")
+            .default_width(400)
+            .default_height(500)
+            .build();
+
+        // Initialize layer shell
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-notification-center");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("notification-center-popup");
+
+        // Position according to the popup config
+        super::apply_popup_anchor(&window, &state.config());
+
+        // Create main container
+        let container = GtkBox::new(Orientation::Vertical, 12);
+        container.set_margin_start(16);
+        container.set_margin_end(16);
+        container.set_margin_top(16);
+        container.set_margin_bottom(16);
+
+        // Header with title and do-not-disturb toggle
+        let header = GtkBox::new(Orientation::Horizontal, 12);
+        let title = Label::new(Some("🔔 Notifications"));
+        title.set_halign(gtk4::Align::Start);
+        title.set_hexpand(true);
+
+        let dnd_label = Label::new(Some("Do not disturb"));
+        dnd_label.add_css_class("dnd-label");
+
+        let dnd_toggle = Switch::new();
+        dnd_toggle.set_valign(gtk4::Align::Center);
+
+        // Keeps the popup open when focus moves elsewhere
+        let pin = super::pin_button();
+
+        header.append(&title);
+        header.append(&dnd_label);
+        header.append(&dnd_toggle);
+        header.append(&pin);
+
+        // History list
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .min_content_height(300)
+            .build();
+
+        let history_list = ListBox::new();
+        history_list.add_css_class("notification-history");
+        history_list.set_selection_mode(gtk4::SelectionMode::None);
+
+        scrolled.set_child(Some(&history_list));
+
+        // Clear button
+        let clear_button = Button::with_label("Clear All");
+        clear_button.add_css_class("clear-button");
+
+        container.append(&header);
+        container.append(&scrolled);
+        container.append(&clear_button);
+
+        window.set_child(Some(&container));
+
+        // Apply theme
+        Self::apply_theme(&window, &state);
+
+        // Close on focus loss unless pinned
+        super::close_on_focus_loss(&window, Some(&pin));
+
+        if let Some(daemon) = &state.notification_daemon {
+            // Wire up do-not-disturb toggle
+            let daemon_clone = daemon.clone();
+            dnd_toggle.connect_state_set(move |_, enabled| {
+                daemon_clone.set_dnd(enabled);
+                glib::Propagation::Proceed
+            });
+
+            // Wire up clear button
+            let daemon_clone = daemon.clone();
+            let list_clone = history_list.clone();
+            clear_button.connect_clicked(move |_| {
+                let daemon = daemon_clone.clone();
+                let list = list_clone.clone();
+                glib::spawn_future_local(async move {
+                    daemon.clear_history().await;
+                    Self::update_history_list(&list, &[]);
+                });
+            });
+        } else {
+            dnd_toggle.set_sensitive(false);
+            clear_button.set_sensitive(false);
+        }
+
+        NotificationCenter {
+            listener: PopupTask::new(&window),
+            window,
+            history_list,
+            dnd_toggle,
+            state,
+        }
+    }
+
+    /// Keep the list and toggle in sync while the popup is open
+    fn listen(
+        list: ListBox,
+        toggle: Switch,
+        window: ApplicationWindow,
+        state: Arc<AppState>,
+    ) -> impl Future<Output = ()> {
+        let mut receiver = state.events.subscribe();
+
+        async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::NotificationReceived { .. } => {
+                        Self::refresh(&list, &state);
+                    }
+                    Event::DndChanged { enabled } => {
+                        toggle.set_active(enabled);
+                    }
+                    Event::ConfigReloaded => {
+                        super::apply_popup_anchor(&window, &state.config());
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    pub fn show(&self) {
+        // Pick up anchor changes from config reloads
+        super::apply_popup_anchor(&self.window, &self.state.config());
+
+        self.listener.start(Self::listen(
+            self.history_list.clone(),
+            self.dnd_toggle.clone(),
+            self.window.clone(),
+            self.state.clone(),
+        ));
+        if let Some(daemon) = &self.state.notification_daemon {
+            self.dnd_toggle.set_active(daemon.is_dnd());
+        }
+        Self::refresh(&self.history_list, &self.state);
+
+        super::present_focused(&self.window);
+    }
+
+    pub fn hide(&self) {
+        self.window.close();
+    }
+
+    fn refresh(list: &ListBox, state: &Arc<AppState>) {
+        match &state.notification_daemon {
+            Some(daemon) => {
+                let daemon = daemon.clone();
+                let list = list.clone();
+                glib::spawn_future_local(async move {
+                    let history = daemon.history().await;
+                    Self::update_history_list(&list, &history);
+                });
+            }
+            None => Self::update_history_list(list, &[]),
+        }
+    }
+
+    fn update_history_list(list: &ListBox, history: &[NotificationRecord]) {
+        // Clear existing rows
+        while let Some(row) = list.first_child() {
+            list.remove(&row);
+        }
+
+        if history.is_empty() {
+            let label = Label::new(Some("No notifications"));
+            label.set_margin_top(32);
+            label.set_margin_bottom(32);
+            label.add_css_class("empty-message");
+            list.append(&label);
+            return;
+        }
+
+        for record in history {
+            let row = GtkBox::new(Orientation::Vertical, 4);
+            row.set_margin_start(8);
+            row.set_margin_end(8);
+            row.set_margin_top(8);
+            row.set_margin_bottom(8);
+
+            // App icon and name, and time received
+            let header = GtkBox::new(Orientation::Horizontal, 8);
+            header.append(&crate::icons::image(
+                crate::icons::for_notification(&record.app, record.icon.as_deref()),
+                "dialog-information",
+                ICON_SIZE,
+            ));
+            let app_label = Label::new(Some(&record.app));
+            app_label.add_css_class("notification-app");
+            app_label.set_halign(gtk4::Align::Start);
+            app_label.set_hexpand(true);
+
+            let time_label = Label::new(Some(&record.timestamp.format("%H:%M").to_string()));
+            time_label.add_css_class("notification-time");
+            time_label.set_tooltip_text(Some(
+                &record.timestamp.format("%A, %B %d, %Y %H:%M:%S").to_string(),
+            ));
+
+            header.append(&app_label);
+            header.append(&time_label);
+            row.append(&header);
+
+            let summary_label = Label::new(Some(&record.summary));
+            summary_label.add_css_class("notification-summary");
+            summary_label.set_halign(gtk4::Align::Start);
+            summary_label.set_wrap(true);
+            row.append(&summary_label);
+
+            if !record.body.is_empty() {
+                let body_label = Label::new(Some(&record.body));
+                body_label.add_css_class("notification-body");
+                body_label.set_halign(gtk4::Align::Start);
+                body_label.set_wrap(true);
+                body_label.set_xalign(0.0);
+                row.append(&body_label);
+            }
+
+            list.append(&row);
+        }
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Stylesheet for the notification center, in the configured theme colors
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.notification-center-popup {{
+                background-color: alpha({background}, 0.98);
+                border-radius: 12px;
+                color: {foreground};
+            }}
+
+            .notification-history {{
+                background-color: transparent;
+            }}
+
+            .notification-center-popup .notification-app,
+            .notification-center-popup .notification-time,
+            .dnd-label {{
+                font-size: 9pt;
+                color: alpha({foreground}, 0.75);
+            }}
+
+            .notification-center-popup .notification-summary {{
+                font-weight: bold;
+            }}
+
+            .notification-center-popup .notification-body {{
+                color: alpha({foreground}, 0.85);
+            }}
+
+            .notification-center-popup .empty-message {{
+                color: alpha({foreground}, 0.5);
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+        )
+    }
+}
@@ -1,4 +1,5 @@
 use crate::app::AppState;
+use crate::config::ThemeConfig;
 use crate::backend::system::power::PowerAction;
 use gtk4::glib;
 use gtk4::prelude::*;
@@ -6,9 +7,13 @@ use gtk4::{
     Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation, Separator,
 };
 use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use std::cell::Cell;
+use std::rc::Rc;
 use std::sync::Arc;
 use tracing::{info, warn};
 
+const LOGOUT_LABEL: &str = "🚪 Log Out";
+
 pub struct PowerPopup {
     window: ApplicationWindow,
     state: Arc<AppState>,
@@ -27,6 +32,9 @@ impl PowerPopup {
         window.init_layer_shell();
         window.set_layer(Layer::Overlay);
         window.set_namespace("amiya-power");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("power-popup");
 
         // Position in center
         window.set_anchor(Edge::Top, false);
@@ -65,6 +73,12 @@ impl PowerPopup {
         hibernate_button.add_css_class("power-button");
         hibernate_button.add_css_class("hibernate-button");
 
+        // Log out button, ends the niri session
+        let logout_button = Button::with_label(LOGOUT_LABEL);
+        logout_button.add_css_class("power-button");
+        logout_button.add_css_class("logout-button");
+        logout_button.set_sensitive(state.niri_client.is_some());
+
         let separator2 = Separator::new(Orientation::Horizontal);
 
         // Reboot button
@@ -81,6 +95,7 @@ impl PowerPopup {
         container.append(&lock_button);
         container.append(&suspend_button);
         container.append(&hibernate_button);
+        container.append(&logout_button);
         container.append(&separator2);
         container.append(&reboot_button);
         container.append(&shutdown_button);
@@ -93,15 +108,10 @@ impl PowerPopup {
         window.set_child(Some(&container));
 
         // Apply theme
-        Self::apply_theme(&window);
+        Self::apply_theme(&window, &state);
 
         // Close on focus loss
-        let window_clone = window.clone();
-        window.connect_is_active_notify(move |win| {
-            if !win.is_active() {
-                window_clone.close();
-            }
-        });
+        super::close_on_focus_loss(&window, None);
 
         let popup = PowerPopup {
             window: window.clone(),
@@ -186,6 +196,37 @@ impl PowerPopup {
             });
         }
 
+        // Log out asks for a second click first, since it closes every window
+        if let Some(niri) = &state.niri_client {
+            let confirming = Rc::new(Cell::new(false));
+
+            let niri = niri.clone();
+            let confirm = confirming.clone();
+            let window_clone = window.clone();
+            logout_button.connect_clicked(move |button| {
+                if !confirm.get() {
+                    confirm.set(true);
+                    button.set_label("🚪 Click again to log out");
+                    button.add_css_class("confirm");
+                    return;
+                }
+
+                info!("Logging out...");
+                if let Err(e) = niri.quit() {
+                    warn!("Failed to log out: {}", e);
+                }
+                window_clone.close();
+            });
+
+            // Start unconfirmed the next time the menu opens
+            let button = logout_button.clone();
+            window.connect_unmap(move |_| {
+                confirming.set(false);
+                button.set_label(LOGOUT_LABEL);
+                button.remove_css_class("confirm");
+            });
+        }
+
         // Cancel button closes the popup
         let window_clone = window.clone();
         cancel_button.connect_clicked(move |_| {
@@ -196,7 +237,7 @@ impl PowerPopup {
     }
 
     pub fn show(&self) {
-        self.window.present();
+        super::present_focused(&self.window);
     }
 
     pub fn hide(&self) {
@@ -211,86 +252,98 @@ impl PowerPopup {
         }
     }
 
-    fn apply_theme(window: &ApplicationWindow) {
-        let provider = gtk4::CssProvider::new();
-        let css = r#"
-        window {
-            background-color: #1e1e2e;
-            color: #cdd6f4;
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Stylesheet for the power menu, in the configured theme colors
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+        window.power-popup {{
+            background-color: {background};
+            color: {foreground};
             border-radius: 12px;
-        }
+        }}
 
-        .power-menu-header {
+        .power-menu-header {{
             font-size: 18pt;
             font-weight: bold;
             margin-bottom: 8px;
-        }
+        }}
 
-        .power-button {
+        .power-button {{
             min-height: 48px;
             font-size: 14pt;
             border-radius: 8px;
             border: 2px solid transparent;
-            background-color: #313244;
-            color: #cdd6f4;
+            background-color: alpha({foreground}, 0.1);
+            color: {foreground};
             padding: 8px 16px;
-        }
+        }}
 
-        .power-button:hover {
-            background-color: #45475a;
-            border-color: #89b4fa;
-        }
+        .power-button:hover {{
+            background-color: alpha({foreground}, 0.15);
+            border-color: {accent};
+        }}
 
-        .power-button:active {
-            background-color: #585b70;
-        }
+        .power-button:active {{
+            background-color: alpha({foreground}, 0.25);
+        }}
 
-        .lock-button:hover {
-            border-color: #89dceb;
-        }
+        .lock-button:hover {{
+            border-color: {accent};
+        }}
 
-        .suspend-button:hover {
-            border-color: #89b4fa;
-        }
+        .suspend-button:hover {{
+            border-color: {accent};
+        }}
 
-        .hibernate-button:hover {
-            border-color: #b4befe;
-        }
+        .hibernate-button:hover {{
+            border-color: {accent};
+        }}
 
-        .reboot-button:hover {
-            border-color: #f9e2af;
-        }
+        .logout-button:hover {{
+            border-color: {warning};
+        }}
 
-        .shutdown-button:hover {
-            border-color: #f38ba8;
-        }
+        .logout-button.confirm {{
+            border-color: {critical};
+            background-color: alpha({critical}, 0.15);
+        }}
+
+        .reboot-button:hover {{
+            border-color: {warning};
+        }}
 
-        .cancel-button {
+        .shutdown-button:hover {{
+            border-color: {critical};
+        }}
+
+        .cancel-button {{
             min-height: 40px;
             font-size: 12pt;
             border-radius: 8px;
-            background-color: #45475a;
-            color: #cdd6f4;
+            background-color: alpha({foreground}, 0.15);
+            color: {foreground};
             margin-top: 8px;
-        }
+        }}
 
-        .cancel-button:hover {
-            background-color: #585b70;
-        }
+        .cancel-button:hover {{
+            background-color: alpha({foreground}, 0.25);
+        }}
 
-        separator {
-            background-color: #45475a;
+        window.power-popup separator {{
+            background-color: alpha({foreground}, 0.15);
             min-height: 1px;
             margin: 8px 0;
-        }
-        "#;
-
-        provider.load_from_string(css);
-
-        gtk4::style_context_add_provider_for_display(
-            &window.display(),
-            &provider,
-            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-        );
+        }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+            warning = theme.warning,
+            critical = theme.critical,
+        )
     }
 }
@@ -0,0 +1,470 @@
+use super::{LiveScale, PopupTask};
+use crate::app::AppState;
+use crate::config::{QuickToggle, ThemeConfig};
+use crate::error::{AmiyaError, Result};
+use crate::events::Event;
+use gtk4::glib;
+use gtk4::prelude::*;
+use gtk4::{
+    Application, ApplicationWindow, Box as GtkBox, Button, Grid, Label, Orientation, Scale,
+    ToggleButton, Widget,
+};
+use gtk4_layer_shell::{Layer, LayerShell};
+use std::cell::RefCell;
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Toggles per row of the grid
+const GRID_COLUMNS: i32 = 2;
+
+/// The toggles in the grid, in the order they were built from the config
+type Toggles = Rc<RefCell<Vec<(QuickToggle, ToggleButton)>>>;
+
+/// Volume slider and the mute button in front of it
+#[derive(Clone)]
+struct VolumeRow {
+    slider: LiveScale,
+    mute: Button,
+}
+
+impl VolumeRow {
+    fn show(&self, level: f64, muted: bool) {
+        self.slider.set(level);
+        self.mute.set_label(if muted { "🔇" } else { "🔊" });
+        self.mute
+            .set_tooltip_text(Some(if muted { "Unmute" } else { "Mute" }));
+    }
+}
+
+/// One panel with the common toggles and the volume and brightness sliders
+///
+/// Which toggles appear, and in what order, comes from
+/// `popup.quick_settings`. Toggles whose backend is unavailable are shown
+/// greyed out; sliders are left out entirely. The sliders start from the
+/// current levels each time the popup opens and follow changes made with
+/// keys or other tools while it's open.
+pub struct QuickSettingsPopup {
+    window: ApplicationWindow,
+    grid: Grid,
+    toggles: Toggles,
+    volume: Option<VolumeRow>,
+    brightness: Option<LiveScale>,
+    listener: PopupTask,
+    refresh: PopupTask,
+    state: Arc<AppState>,
+}
+
+impl QuickSettingsPopup {
+    pub fn new(app: &Application, state: Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Quick Settings")
+            .default_width(360)
+            .build();
+
+        // Initialize layer shell
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-quick-settings");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("quick-settings-popup");
+
+        // Position according to the popup config
+        super::apply_popup_anchor(&window, &state.config());
+
+        // Create main container
+        let container = GtkBox::new(Orientation::Vertical, 16);
+        container.set_margin_start(16);
+        container.set_margin_end(16);
+        container.set_margin_top(16);
+        container.set_margin_bottom(16);
+
+        let grid = Grid::new();
+        grid.set_row_spacing(8);
+        grid.set_column_spacing(8);
+        grid.set_column_homogeneous(true);
+        container.append(&grid);
+
+        // Sliders apply only user input, so following outside changes
+        // doesn't feed back into the backend
+        let volume = state.audio_control.as_ref().map(|audio| {
+            let mute = Button::with_label("🔊");
+            mute.add_css_class("mute-button");
+            let max = state.config().audio.max_volume_percent as f64;
+            let slider = Self::slider(&container, &mute, max);
+
+            let audio_clone = audio.clone();
+            mute.connect_clicked(move |_| {
+                let audio = audio_clone.clone();
+                glib::spawn_future_local(async move {
+                    if let Err(e) = audio.toggle_mute().await {
+                        warn!("Failed to toggle mute: {}", e);
+                    }
+                    // Event will be emitted by the audio control
+                });
+            });
+
+            let audio = audio.clone();
+            slider.connect_input(move |value| {
+                let audio = audio.clone();
+                glib::spawn_future_local(async move {
+                    if let Err(e) = audio.set_volume(value).await {
+                        warn!("Failed to set volume: {}", e);
+                    }
+                });
+            });
+            VolumeRow { slider, mute }
+        });
+
+        let brightness = state.backlight_control.as_ref().map(|backlight| {
+            let slider = Self::slider(&container, &Label::new(Some("☀")), 100.0);
+            let backlight = backlight.clone();
+            slider.connect_input(move |value| {
+                let backlight = backlight.clone();
+                glib::spawn_future_local(async move {
+                    if let Err(e) = backlight.set_brightness(value).await {
+                        warn!("Failed to set brightness: {}", e);
+                    }
+                });
+            });
+            slider
+        });
+
+        window.set_child(Some(&container));
+
+        // Apply theme
+        Self::apply_theme(&window, &state);
+
+        // Close on focus loss
+        super::close_on_focus_loss(&window, None);
+
+        let popup = QuickSettingsPopup {
+            listener: PopupTask::new(&window),
+            refresh: PopupTask::new(&window),
+            window,
+            grid,
+            toggles: Rc::new(RefCell::new(Vec::new())),
+            volume,
+            brightness,
+            state,
+        };
+        popup.build_toggles();
+        popup
+    }
+
+    pub fn show(&self) {
+        let config = self.state.config();
+
+        // Pick up anchor and toggle changes from config reloads
+        super::apply_popup_anchor(&self.window, &config);
+        let current: Vec<QuickToggle> = self.toggles.borrow().iter().map(|(t, _)| *t).collect();
+        if current != config.popup.quick_settings {
+            self.build_toggles();
+        }
+        if let Some(volume) = &self.volume {
+            volume
+                .slider
+                .set_range(0.0, config.audio.max_volume_percent as f64);
+        }
+
+        self.listener.start(Self::listen(
+            self.toggles.clone(),
+            self.volume.clone(),
+            self.brightness.clone(),
+            self.state.clone(),
+        ));
+
+        let toggles = self.toggles.clone();
+        let volume = self.volume.clone();
+        let brightness = self.brightness.clone();
+        let state = self.state.clone();
+        self.refresh.start(async move {
+            let kinds: Vec<QuickToggle> = toggles.borrow().iter().map(|(t, _)| *t).collect();
+            for toggle in kinds {
+                let enabled = Self::read(&state, toggle).await;
+                Self::show_toggle(&toggles, toggle, enabled);
+            }
+
+            // Start the sliders from the current levels
+            if let (Some(row), Some(audio)) = (&volume, &state.audio_control) {
+                if let Ok(level) = audio.get_volume().await {
+                    let muted = audio.get_mute().await.unwrap_or(false);
+                    row.show(level, muted);
+                }
+            }
+            if let (Some(slider), Some(backlight)) = (&brightness, &state.backlight_control) {
+                // Hidden while no DDC/CI monitor has been found
+                let level = backlight.get_brightness().await;
+                if let Some(row) = slider.scale().parent() {
+                    row.set_visible(level.is_ok());
+                }
+                if let Ok(level) = level {
+                    slider.set(level);
+                }
+            }
+        });
+
+        super::present_focused(&self.window);
+    }
+
+    pub fn hide(&self) {
+        self.window.close();
+    }
+
+    /// Fill the grid with the toggles listed in the config
+    fn build_toggles(&self) {
+        while let Some(child) = self.grid.first_child() {
+            self.grid.remove(&child);
+        }
+
+        let mut toggles = self.toggles.borrow_mut();
+        toggles.clear();
+
+        let config = self.state.config();
+        for (index, toggle) in config.popup.quick_settings.iter().copied().enumerate() {
+            let (icon, name) = Self::label(toggle);
+            let content = GtkBox::new(Orientation::Horizontal, 8);
+            content.append(&Label::new(Some(icon)));
+            let name_label = Label::new(Some(name));
+            name_label.set_halign(gtk4::Align::Start);
+            content.append(&name_label);
+
+            let button = ToggleButton::new();
+            button.set_child(Some(&content));
+            button.add_css_class("quick-toggle");
+
+            if Self::is_available(&self.state, toggle) {
+                let state = self.state.clone();
+                // The click has already flipped the button to the new state
+                button.connect_clicked(move |button| {
+                    let state = state.clone();
+                    let button = button.clone();
+                    let enabled = button.is_active();
+                    glib::spawn_future_local(async move {
+                        if let Err(e) = Self::set(&state, toggle, enabled).await {
+                            warn!("Failed to switch {}: {}", Self::label(toggle).1, e);
+                            button.set_active(!enabled);
+                        }
+                        // Event will be emitted by the backend
+                    });
+                });
+            } else {
+                button.set_sensitive(false);
+                button.set_tooltip_text(Some(&format!("{} not available", name)));
+            }
+
+            let index = index as i32;
+            self.grid
+                .attach(&button, index % GRID_COLUMNS, index / GRID_COLUMNS, 1, 1);
+            toggles.push((toggle, button));
+        }
+    }
+
+    /// Add a slider from 0 to `max` to `container`, after `icon` and with its
+    /// value in percent next to it
+    fn slider(container: &GtkBox, icon: &impl IsA<Widget>, max: f64) -> LiveScale {
+        let row = GtkBox::new(Orientation::Horizontal, 8);
+        row.append(icon);
+
+        let scale = Scale::with_range(Orientation::Horizontal, 0.0, max, 1.0);
+        scale.set_draw_value(false);
+        scale.set_hexpand(true);
+        row.append(&scale);
+
+        // Wide enough for "100%" so the scale doesn't jump while dragging
+        let value = Label::new(Some("0%"));
+        value.add_css_class("slider-value");
+        value.set_width_chars(4);
+        value.set_xalign(1.0);
+        row.append(&value);
+        scale.connect_value_changed(move |scale| {
+            value.set_text(&format!("{:.0}%", scale.value()));
+        });
+
+        container.append(&row);
+        LiveScale::new(&scale)
+    }
+
+    /// Keep the toggles and sliders in sync while the popup is open
+    fn listen(
+        toggles: Toggles,
+        volume: Option<VolumeRow>,
+        brightness: Option<LiveScale>,
+        state: Arc<AppState>,
+    ) -> impl Future<Output = ()> {
+        let mut receiver = state.events.subscribe();
+
+        async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::VolumeChanged { level, muted } => {
+                        if let Some(row) = &volume {
+                            row.show(level, muted);
+                        }
+                    }
+                    Event::BrightnessChanged { level } => {
+                        if let Some(slider) = &brightness {
+                            slider.set(level);
+                        }
+                    }
+                    event => {
+                        if let Some((toggle, enabled)) = Self::toggle_event(&event) {
+                            Self::show_toggle(&toggles, toggle, enabled);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn show_toggle(toggles: &Toggles, toggle: QuickToggle, enabled: bool) {
+        for (kind, button) in toggles.borrow().iter() {
+            if *kind == toggle {
+                button.set_active(enabled);
+            }
+        }
+    }
+
+    fn label(toggle: QuickToggle) -> (&'static str, &'static str) {
+        match toggle {
+            QuickToggle::Wifi => ("📶", "WiFi"),
+            QuickToggle::Bluetooth => ("🔷", "Bluetooth"),
+            QuickToggle::Dnd => ("🔕", "Do Not Disturb"),
+            QuickToggle::Airplane => ("✈", "Airplane Mode"),
+            QuickToggle::IdleInhibitor => ("☕", "Stay Awake"),
+        }
+    }
+
+    fn is_available(state: &AppState, toggle: QuickToggle) -> bool {
+        match toggle {
+            QuickToggle::Wifi => state.network_control.is_some(),
+            QuickToggle::Bluetooth => state.bluetooth_control.is_some(),
+            QuickToggle::Dnd => state.notification_daemon.is_some(),
+            QuickToggle::Airplane => state.airplane_mode.is_some(),
+            QuickToggle::IdleInhibitor => state.idle_inhibitor.is_some(),
+        }
+    }
+
+    /// Current state of `toggle`, off if it can't be read
+    async fn read(state: &AppState, toggle: QuickToggle) -> bool {
+        match toggle {
+            QuickToggle::Wifi => match &state.network_control {
+                Some(network) => network.is_wifi_enabled().await.unwrap_or(false),
+                None => false,
+            },
+            QuickToggle::Bluetooth => match &state.bluetooth_control {
+                Some(bluetooth) => bluetooth.is_powered().await.unwrap_or(false),
+                None => false,
+            },
+            QuickToggle::Dnd => state
+                .notification_daemon
+                .as_ref()
+                .is_some_and(|daemon| daemon.is_dnd()),
+            QuickToggle::Airplane => match &state.airplane_mode {
+                Some(airplane) => airplane.is_enabled().await,
+                None => false,
+            },
+            QuickToggle::IdleInhibitor => state
+                .idle_inhibitor
+                .as_ref()
+                .is_some_and(|inhibitor| inhibitor.is_enabled()),
+        }
+    }
+
+    async fn set(state: &AppState, toggle: QuickToggle, enabled: bool) -> Result<()> {
+        let unavailable =
+            || AmiyaError::Backend(format!("{} not available", Self::label(toggle).1));
+        match toggle {
+            QuickToggle::Wifi => {
+                let network = state.network_control.as_ref().ok_or_else(unavailable)?;
+                network.set_wifi_enabled(enabled).await
+            }
+            QuickToggle::Bluetooth => {
+                let bluetooth = state.bluetooth_control.as_ref().ok_or_else(unavailable)?;
+                bluetooth.set_powered(enabled).await
+            }
+            QuickToggle::Dnd => {
+                let daemon = state.notification_daemon.as_ref().ok_or_else(unavailable)?;
+                daemon.set_dnd(enabled);
+                Ok(())
+            }
+            QuickToggle::Airplane => {
+                let airplane = state.airplane_mode.as_ref().ok_or_else(unavailable)?;
+                airplane.set_enabled(enabled).await
+            }
+            QuickToggle::IdleInhibitor => {
+                let inhibitor = state.idle_inhibitor.as_ref().ok_or_else(unavailable)?;
+                inhibitor.set_enabled(enabled).await
+            }
+        }
+    }
+
+    /// The toggle an event reports a new state for
+    fn toggle_event(event: &Event) -> Option<(QuickToggle, bool)> {
+        match *event {
+            Event::WifiStateChanged { enabled } => Some((QuickToggle::Wifi, enabled)),
+            Event::BluetoothStateChanged { enabled } => Some((QuickToggle::Bluetooth, enabled)),
+            Event::DndChanged { enabled } => Some((QuickToggle::Dnd, enabled)),
+            Event::AirplaneModeChanged { enabled } => Some((QuickToggle::Airplane, enabled)),
+            Event::IdleInhibitChanged { enabled } => Some((QuickToggle::IdleInhibitor, enabled)),
+            _ => None,
+        }
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Toggle grid and the volume and brightness sliders
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.quick-settings-popup {{
+                background-color: alpha({background}, 0.98);
+                border-radius: 12px;
+                color: {foreground};
+            }}
+
+            .quick-toggle {{
+                padding: 12px;
+                border-radius: 12px;
+                background-color: alpha({foreground}, 0.08);
+                color: {foreground};
+            }}
+
+            .quick-toggle:checked {{
+                background-color: {accent};
+                color: {background};
+            }}
+
+            .mute-button {{
+                background-color: transparent;
+                border: none;
+                padding: 0 4px;
+            }}
+
+            .slider-value {{
+                font-size: 9pt;
+                color: alpha({foreground}, 0.75);
+            }}
+
+            window.quick-settings-popup scale trough {{
+                min-height: 6px;
+                background-color: alpha({foreground}, 0.1);
+                border-radius: 3px;
+            }}
+
+            window.quick-settings-popup scale highlight {{
+                background-color: {accent};
+                border-radius: 3px;
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
+    }
+}
@@ -1,19 +1,25 @@
+use super::PopupTask;
 use crate::app::AppState;
+use crate::config::ThemeConfig;
 use crate::events::{Event, WifiNetworkInfo};
 use gtk4::glib;
 use gtk4::prelude::*;
 use gtk4::{
-    Application, ApplicationWindow, Box as GtkBox, Button, Label, ListBox, Orientation,
+    Application, ApplicationWindow, Box as GtkBox, Button, Expander, Label, ListBox, Orientation,
     ScrolledWindow, Switch,
 };
-use gtk4_layer_shell::{Edge, Layer, LayerShell};
+use gtk4_layer_shell::{Layer, LayerShell};
+use std::future::Future;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
 pub struct WifiPopup {
     window: ApplicationWindow,
     network_list: ListBox,
+    saved_list: ListBox,
     toggle: Switch,
+    listener: PopupTask,
+    refresh: PopupTask,
     state: Arc<AppState>,
 }
 
@@ -30,12 +36,12 @@ impl WifiPopup {
         window.init_layer_shell();
         window.set_layer(Layer::Overlay);
         window.set_namespace("amiya-wifi");
+        super::enable_keyboard(&window);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("wifi-popup");
 
-        // Position in top-right
-        window.set_anchor(Edge::Top, true);
-        window.set_anchor(Edge::Right, true);
-        window.set_margin(Edge::Top, 40);
-        window.set_margin(Edge::Right, 10);
+        // Position according to the popup config
+        super::apply_popup_anchor(&window, &state.config());
 
         // Create main container
         let container = GtkBox::new(Orientation::Vertical, 12);
@@ -53,8 +59,12 @@ impl WifiPopup {
         let toggle = Switch::new();
         toggle.set_valign(gtk4::Align::Center);
 
+        // Keeps the popup open when focus moves elsewhere
+        let pin = super::pin_button();
+
         header.append(&title);
         header.append(&toggle);
+        header.append(&pin);
 
         // Network list
         let scrolled = ScrolledWindow::builder()
@@ -63,34 +73,42 @@ impl WifiPopup {
             .build();
 
         let network_list = ListBox::new();
+        // Enter on a row presses its Connect/Disconnect button
+        super::activate_row_action(&network_list);
         network_list.add_css_class("network-list");
 
         scrolled.set_child(Some(&network_list));
 
+        // Saved profiles, with a button to forget each
+        let saved_list = ListBox::new();
+        saved_list.add_css_class("network-list");
+        let saved_section = Expander::new(Some("Known Networks"));
+        saved_section.add_css_class("known-networks");
+        saved_section.set_child(Some(&saved_list));
+
         // Refresh button
         let refresh_button = Button::with_label("Refresh Networks");
         refresh_button.add_css_class("refresh-button");
 
         container.append(&header);
         container.append(&scrolled);
+        container.append(&saved_section);
         container.append(&refresh_button);
 
         window.set_child(Some(&container));
 
         // Apply theme
-        Self::apply_theme(&window);
+        Self::apply_theme(&window, &state);
 
-        // Close on focus loss
-        let window_clone = window.clone();
-        window.connect_is_active_notify(move |win| {
-            if !win.is_active() {
-                window_clone.close();
-            }
-        });
+        // Close on focus loss unless pinned
+        super::close_on_focus_loss(&window, Some(&pin));
 
         let popup = WifiPopup {
+            listener: PopupTask::new(&window),
+            refresh: PopupTask::new(&window),
             window,
             network_list: network_list.clone(),
+            saved_list: saved_list.clone(),
             toggle: toggle.clone(),
             state: state.clone(),
         };
@@ -136,24 +154,31 @@ impl WifiPopup {
 
                 glib::spawn_future_local(async move {
                     // Start scan
-                    if let Err(e) = nm.scan().await {
-                        warn!("Failed to start WiFi scan: {}", e);
-                    } else {
-                        info!("WiFi scan started");
+                    match nm.scan().await {
+                        Err(e) => warn!("Failed to start WiFi scan: {}", e),
+                        // Recent results are still fresh, list them right away
+                        Ok(false) => {
+                            if let Err(e) = nm.get_networks().await {
+                                warn!("Failed to get networks: {}", e);
+                            }
+                        }
+                        Ok(true) => {
+                            info!("WiFi scan started");
 
-                        // Wait a moment, then get networks
-                        glib::timeout_add_seconds_local(2, {
-                            let nm = nm.clone();
-                            move || {
+                            // Wait a moment, then get networks
+                            glib::timeout_add_seconds_local(2, {
                                 let nm = nm.clone();
-                                glib::spawn_future_local(async move {
-                                    if let Err(e) = nm.get_networks().await {
-                                        warn!("Failed to get networks: {}", e);
-                                    }
-                                });
-                                glib::ControlFlow::Break
-                            }
-                        });
+                                move || {
+                                    let nm = nm.clone();
+                                    glib::spawn_future_local(async move {
+                                        if let Err(e) = nm.get_networks().await {
+                                            warn!("Failed to get networks: {}", e);
+                                        }
+                                    });
+                                    glib::ControlFlow::Break
+                                }
+                            });
+                        }
                     }
 
                     // Re-enable button
@@ -166,62 +191,33 @@ impl WifiPopup {
             });
         }
 
-        // Subscribe to WiFi events
-        let network_list_clone = network_list.clone();
-        let state_clone = state.clone();
-        glib::spawn_future_local(async move {
-            let mut receiver = state_clone.events.subscribe();
+        popup
+    }
 
-            loop {
-                match receiver.recv().await {
-                    Ok(Event::WifiNetworksUpdated { networks }) => {
+    /// Keep the network list in sync while the popup is open
+    fn listen(list: ListBox, state: Arc<AppState>) -> impl Future<Output = ()> {
+        let mut receiver = state.events.subscribe();
+
+        async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::WifiNetworksUpdated { networks } => {
                         debug!("Received {} WiFi networks", networks.len());
-                        Self::update_network_list(&network_list_clone, &networks, &state_clone);
+                        Self::update_network_list(&list, &networks, &state);
                     }
-                    Ok(Event::WifiStateChanged { enabled }) => {
+                    Event::WifiStateChanged { enabled } => {
                         debug!("WiFi state changed: {}", enabled);
                     }
-                    Ok(Event::WifiNetworkConnected { ssid }) => {
+                    Event::WifiNetworkConnected { ssid } => {
                         info!("Connected to network: {}", ssid);
                     }
-                    Ok(Event::WifiNetworkDisconnected) => {
+                    Event::WifiNetworkDisconnected => {
                         info!("Disconnected from network");
                     }
                     _ => {}
                 }
             }
-        });
-
-        // Initial load of networks
-        if let Some(nm) = &state.network_control {
-            let network_list_clone = network_list.clone();
-            let state_clone = state.clone();
-            let nm_clone = nm.clone();
-            glib::spawn_future_local(async move {
-                // Trigger a scan to get fresh data
-                let _ = nm_clone.scan().await;
-
-                // Wait a moment for scan to complete
-                glib::timeout_add_seconds_local(2, {
-                    let nm = nm_clone.clone();
-                    let list = network_list_clone.clone();
-                    let state = state_clone.clone();
-                    move || {
-                        let nm = nm.clone();
-                        let list = list.clone();
-                        let state = state.clone();
-                        glib::spawn_future_local(async move {
-                            if let Ok(networks) = nm.get_networks().await {
-                                Self::update_network_list(&list, &networks, &state);
-                            }
-                        });
-                        glib::ControlFlow::Break
-                    }
-                });
-            });
         }
-
-        popup
     }
 
     fn update_network_list(
@@ -256,8 +252,8 @@ impl WifiPopup {
         row.set_margin_bottom(8);
 
         // Signal strength bars
-        let signal_bars = Self::signal_to_bars(network.signal_strength);
-        let signal_label = Label::new(Some(&signal_bars));
+        let signal_bars = crate::widgets::signal_bars(network.signal_strength);
+        let signal_label = Label::new(Some(signal_bars));
         signal_label.add_css_class("signal-strength");
 
         // Network info
@@ -293,6 +289,7 @@ impl WifiPopup {
             "Connect"
         });
         connect_btn.set_valign(gtk4::Align::Center);
+        connect_btn.add_css_class(super::ROW_ACTION);
 
         // Wire up connect/disconnect button
         if let Some(nm) = &state.network_control {
@@ -312,7 +309,8 @@ impl WifiPopup {
                     let result = if is_connected {
                         nm.disconnect().await
                     } else {
-                        // TODO: Show password dialog for secured networks
+                        // Saved profiles keep their password
+                        // TODO: Show password dialog for new secured networks
                         nm.connect(&ssid, None).await
                     };
 
@@ -347,100 +345,166 @@ impl WifiPopup {
         list.append(&row);
     }
 
-    fn signal_to_bars(strength: u8) -> String {
-        match strength {
-            0..=25 => "▂___".to_string(),
-            26..=50 => "▂▄__".to_string(),
-            51..=75 => "▂▄▆_".to_string(),
-            _ => "▂▄▆█".to_string(),
+    /// Scan if the last results are stale, then fill the list
+    fn refresh_networks(list: ListBox, state: Arc<AppState>) -> impl Future<Output = ()> {
+        async move {
+            let Some(nm) = state.network_control.clone() else {
+                return;
+            };
+
+            // Give a fresh scan a moment to complete; cached results are
+            // listed right away
+            if let Ok(true) = nm.scan().await {
+                glib::timeout_future_seconds(2).await;
+            }
+
+            if let Ok(networks) = nm.get_networks().await {
+                Self::update_network_list(&list, &networks, &state);
+            }
         }
     }
 
-    pub fn show(&self) {
-        // Refresh network list when showing
-        if let Some(nm) = &self.state.network_control {
-            let network_list = self.network_list.clone();
-            let state = self.state.clone();
-            let nm_clone = nm.clone();
+    /// Fill the known networks section from NetworkManager's saved profiles
+    fn refresh_saved(list: &ListBox, state: &Arc<AppState>) {
+        let Some(nm) = state.network_control.clone() else {
+            return;
+        };
+        let list = list.clone();
+        let state = state.clone();
 
-            glib::spawn_future_local(async move {
-                // Trigger scan
-                let _ = nm_clone.scan().await;
+        glib::spawn_future_local(async move {
+            let saved = match nm.list_saved_connections().await {
+                Ok(saved) => saved,
+                Err(e) => {
+                    warn!("Failed to list saved networks: {}", e);
+                    return;
+                }
+            };
+
+            while let Some(row) = list.first_child() {
+                list.remove(&row);
+            }
 
-                // Wait for scan to complete, then get networks
-                glib::timeout_add_seconds_local(2, {
-                    let nm = nm_clone.clone();
-                    let list = network_list.clone();
+            if saved.is_empty() {
+                let label = Label::new(Some("No saved networks"));
+                label.set_margin_top(8);
+                label.set_margin_bottom(8);
+                label.add_css_class("empty-message");
+                list.append(&label);
+                return;
+            }
+
+            for connection in saved {
+                let row = GtkBox::new(Orientation::Horizontal, 12);
+                row.set_margin_start(8);
+                row.set_margin_end(8);
+                row.set_margin_top(4);
+                row.set_margin_bottom(4);
+
+                let name_label = Label::new(Some(&connection.name));
+                name_label.set_halign(gtk4::Align::Start);
+                name_label.set_hexpand(true);
+                name_label.add_css_class("network-name");
+
+                let forget_btn = Button::with_label("Forget");
+                forget_btn.set_valign(gtk4::Align::Center);
+
+                let nm = nm.clone();
+                let forget_list = list.clone();
+                let state = state.clone();
+                forget_btn.connect_clicked(move |button| {
+                    button.set_sensitive(false);
+                    let nm = nm.clone();
+                    let list = forget_list.clone();
                     let state = state.clone();
-                    move || {
-                        let nm = nm.clone();
-                        let list = list.clone();
-                        let state = state.clone();
-                        glib::spawn_future_local(async move {
-                            if let Ok(networks) = nm.get_networks().await {
-                                Self::update_network_list(&list, &networks, &state);
+                    let connection = connection.clone();
+                    let button = button.clone();
+                    glib::spawn_future_local(async move {
+                        match nm.forget_connection(&connection.uuid).await {
+                            Ok(()) => Self::refresh_saved(&list, &state),
+                            Err(e) => {
+                                warn!("Failed to forget network {}: {}", connection.name, e);
+                                button.set_sensitive(true);
                             }
-                        });
-                        glib::ControlFlow::Break
-                    }
+                        }
+                    });
                 });
-            });
-        }
 
-        self.window.present();
+                row.append(&name_label);
+                row.append(&forget_btn);
+                list.append(&row);
+            }
+        });
+    }
+
+    pub fn show(&self) {
+        // Pick up anchor changes from config reloads
+        super::apply_popup_anchor(&self.window, &self.state.config());
+
+        self.listener.start(Self::listen(self.network_list.clone(), self.state.clone()));
+        self.refresh.start(Self::refresh_networks(self.network_list.clone(), self.state.clone()));
+        Self::refresh_saved(&self.saved_list, &self.state);
+
+        super::present_focused(&self.window);
     }
 
     pub fn hide(&self) {
         self.window.close();
     }
 
-    fn apply_theme(window: &ApplicationWindow) {
-        let provider = gtk4::CssProvider::new();
-        let css = r#"
-            window {
-                background-color: rgba(30, 30, 46, 0.98);
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Network list styles, with signal strength in the accent and known
+    /// networks dimmed
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.wifi-popup {{
+                background-color: alpha({background}, 0.98);
                 border-radius: 12px;
-                color: #cdd6f4;
-            }
+                color: {foreground};
+            }}
 
-            .network-list {
+            .network-list {{
                 background-color: transparent;
-            }
+            }}
 
-            .network-name {
+            .network-name {{
                 font-weight: bold;
-            }
+            }}
 
-            .network-status {
+            .network-status {{
                 font-size: 10pt;
-                color: #a6adc8;
-            }
+                color: alpha({foreground}, 0.75);
+            }}
 
-            .signal-strength {
+            .signal-strength {{
                 font-family: monospace;
-                color: #89b4fa;
-            }
+                color: {accent};
+            }}
+
+            .known-networks {{
+                color: alpha({foreground}, 0.75);
+            }}
 
-            .empty-message {
-                color: #a6adc8;
+            .empty-message {{
+                color: alpha({foreground}, 0.75);
                 font-style: italic;
-            }
+            }}
 
-            button.refresh-button {
-                background-color: #89b4fa;
-                color: #1e1e2e;
+            button.refresh-button {{
+                background-color: {accent};
+                color: {background};
                 border-radius: 6px;
                 padding: 8px;
                 font-weight: bold;
-            }
-        "#;
-
-        provider.load_from_string(css);
-
-        gtk4::style_context_add_provider_for_display(
-            &window.display(),
-            &provider,
-            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
-        );
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
     }
 }
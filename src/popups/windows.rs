@@ -0,0 +1,371 @@
+use crate::app::AppState;
+use crate::backend::niri::{NiriWindow, NiriWorkspace};
+use crate::config::ThemeConfig;
+use gtk4::prelude::*;
+use gtk4::{
+    Application, ApplicationWindow, Box as GtkBox, Label, ListBox, ListBoxRow, Orientation,
+    ScrolledWindow,
+};
+use gtk4_layer_shell::{KeyboardMode, Layer, LayerShell};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Size of each window's app icon, in logical pixels
+const ICON_SIZE: i32 = 32;
+
+/// Open windows grouped by workspace; activating one focuses it in niri
+///
+/// The list is fetched from niri each time the popup opens. Arrow keys move
+/// through the windows, starting at the focused one, and Enter focuses the
+/// selected window.
+pub struct WindowsPopup {
+    window: ApplicationWindow,
+    list: ListBox,
+    /// Window id for each row of `list`; workspace headers have none
+    rows: Rc<RefCell<Vec<Option<u64>>>>,
+    state: Arc<AppState>,
+}
+
+impl WindowsPopup {
+    pub fn new(app: &Application, state: Arc<AppState>) -> Self {
+        let window = ApplicationWindow::builder()
+            .application(app)
+            .title("Windows")
+            .default_width(480)
+            .default_height(420)
+            .build();
+
+        // Initialize layer shell; takes the keyboard so arrows work at once
+        window.init_layer_shell();
+        window.set_layer(Layer::Overlay);
+        window.set_namespace("amiya-windows");
+        window.set_keyboard_mode(KeyboardMode::Exclusive);
+        window.add_css_class("amiya-popup");
+        window.add_css_class("windows-popup");
+
+        // Left unanchored so the compositor centers it on screen
+
+        // Create main container
+        let container = GtkBox::new(Orientation::Vertical, 12);
+        container.set_margin_start(16);
+        container.set_margin_end(16);
+        container.set_margin_top(16);
+        container.set_margin_bottom(16);
+
+        let title = Label::new(Some("🪟 Windows"));
+        title.add_css_class("windows-title");
+        title.set_halign(gtk4::Align::Start);
+
+        let scrolled = ScrolledWindow::builder()
+            .hscrollbar_policy(gtk4::PolicyType::Never)
+            .min_content_height(340)
+            .vexpand(true)
+            .build();
+
+        let list = ListBox::new();
+        list.add_css_class("window-list");
+        scrolled.set_child(Some(&list));
+
+        container.append(&title);
+        container.append(&scrolled);
+
+        window.set_child(Some(&container));
+
+        // Apply theme
+        Self::apply_theme(&window, &state);
+
+        // Close on focus loss
+        super::close_on_focus_loss(&window, None);
+
+        // Close on Escape
+        super::close_on_escape(&window);
+
+        let rows = Rc::new(RefCell::new(Vec::new()));
+
+        // Clicking a row, or Enter on it, focuses that window
+        let rows_clone = rows.clone();
+        let window_clone = window.clone();
+        let state_clone = state.clone();
+        list.connect_row_activated(move |_, row| {
+            let id = rows_clone.borrow().get(row.index() as usize).copied();
+            if let Some(Some(id)) = id {
+                Self::focus(id, &state_clone);
+                window_clone.close();
+            }
+        });
+
+        WindowsPopup {
+            window,
+            list,
+            rows,
+            state,
+        }
+    }
+
+    pub fn show(&self) {
+        let Some(niri) = &self.state.niri_client else {
+            warn!("Window switcher needs niri");
+            return;
+        };
+
+        let windows = niri.get_windows().unwrap_or_else(|e| {
+            warn!("Failed to get windows: {}", e);
+            Vec::new()
+        });
+        let workspaces = niri.get_workspaces().unwrap_or_else(|e| {
+            warn!("Failed to get workspaces: {}", e);
+            Vec::new()
+        });
+        let focused = self.update_list(&workspaces, windows);
+
+        self.window.present();
+
+        // Start from the focused window, or the first one
+        let first = self.rows.borrow().iter().position(|id| id.is_some());
+        if let Some(row) = focused
+            .or(first)
+            .and_then(|i| self.list.row_at_index(i as i32))
+        {
+            self.list.select_row(Some(&row));
+            row.grab_focus();
+        }
+    }
+
+    pub fn hide(&self) {
+        self.window.close();
+    }
+
+    /// Rebuild the list, returning the row of the focused window
+    fn update_list(&self, workspaces: &[NiriWorkspace], windows: Vec<NiriWindow>) -> Option<usize> {
+        // Clear existing rows
+        while let Some(row) = self.list.first_child() {
+            self.list.remove(&row);
+        }
+
+        let mut rows = Vec::new();
+        let mut focused_row = None;
+        for (heading, group) in Self::group_by_workspace(workspaces, windows) {
+            let header = ListBoxRow::new();
+            header.set_selectable(false);
+            header.set_activatable(false);
+            header.set_focusable(false);
+            let label = Label::new(Some(&heading));
+            label.add_css_class("window-list-workspace");
+            label.set_halign(gtk4::Align::Start);
+            header.set_child(Some(&label));
+            self.list.append(&header);
+            rows.push(None);
+
+            for window in group {
+                if window.is_focused {
+                    focused_row = Some(rows.len());
+                }
+                self.list.append(&Self::window_row(&window));
+                rows.push(Some(window.id));
+            }
+        }
+
+        if rows.is_empty() {
+            let label = Label::new(Some("No open windows"));
+            label.set_margin_top(32);
+            label.set_margin_bottom(32);
+            label.add_css_class("empty-message");
+            self.list.append(&label);
+        }
+
+        *self.rows.borrow_mut() = rows;
+        focused_row
+    }
+
+    /// Windows under a heading for each workspace, in workspace order;
+    /// windows niri doesn't place on a known workspace come last
+    fn group_by_workspace(
+        workspaces: &[NiriWorkspace],
+        mut windows: Vec<NiriWindow>,
+    ) -> Vec<(String, Vec<NiriWindow>)> {
+        let mut workspaces: Vec<&NiriWorkspace> = workspaces.iter().collect();
+        workspaces.sort_by_key(|ws| (ws.idx, ws.id));
+
+        let mut groups = Vec::new();
+        for ws in workspaces {
+            let (on_workspace, rest): (Vec<_>, Vec<_>) = windows
+                .into_iter()
+                .partition(|w| w.workspace_id == Some(ws.id));
+            windows = rest;
+            if on_workspace.is_empty() {
+                continue;
+            }
+            let heading = match &ws.name {
+                Some(name) => format!("Workspace {}: {}", ws.idx, name),
+                None => format!("Workspace {}", ws.idx),
+            };
+            groups.push((heading, on_workspace));
+        }
+        if !windows.is_empty() {
+            groups.push(("Other".to_string(), windows));
+        }
+        groups
+    }
+
+    fn window_row(window: &NiriWindow) -> GtkBox {
+        let row = GtkBox::new(Orientation::Horizontal, 10);
+        row.set_margin_start(8);
+        row.set_margin_end(8);
+        row.set_margin_top(6);
+        row.set_margin_bottom(6);
+
+        let icon = window.app_id.as_deref().and_then(crate::icons::for_app_id);
+        row.append(&crate::icons::image(
+            icon,
+            "application-x-executable",
+            ICON_SIZE,
+        ));
+
+        let text = GtkBox::new(Orientation::Vertical, 2);
+        text.set_valign(gtk4::Align::Center);
+        row.append(&text);
+
+        let title = window.title.as_deref().unwrap_or("Untitled");
+        let title_label = Label::new(Some(title));
+        title_label.set_halign(gtk4::Align::Start);
+        title_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        title_label.add_css_class("window-list-title");
+        text.append(&title_label);
+
+        if let Some(app_id) = &window.app_id {
+            let app_label = Label::new(Some(app_id));
+            app_label.set_halign(gtk4::Align::Start);
+            app_label.add_css_class("window-list-app");
+            text.append(&app_label);
+        }
+
+        if window.is_focused {
+            row.add_css_class("focused");
+        }
+        row
+    }
+
+    fn focus(id: u64, state: &Arc<AppState>) {
+        let Some(niri) = &state.niri_client else {
+            return;
+        };
+        match niri.focus_window(id) {
+            Ok(()) => info!("Focused window {}", id),
+            Err(e) => warn!("Failed to focus window {}: {}", id, e),
+        }
+    }
+
+    fn apply_theme(window: &ApplicationWindow, state: &Arc<AppState>) {
+        crate::style::install_theme_css(&WidgetExt::display(window), state, Self::theme_css);
+    }
+
+    /// Stylesheet for the window switcher, in the configured theme colors
+    fn theme_css(theme: &ThemeConfig) -> String {
+        format!(
+            r#"
+            window.windows-popup {{
+                background-color: alpha({background}, 0.98);
+                border-radius: 12px;
+                color: {foreground};
+            }}
+
+            .windows-title {{
+                font-size: 13pt;
+                font-weight: bold;
+            }}
+
+            .window-list {{
+                background-color: transparent;
+            }}
+
+            .window-list row:selected {{
+                background-color: alpha({accent}, 0.2);
+                border-radius: 6px;
+            }}
+
+            .window-list-workspace {{
+                font-size: 9pt;
+                font-weight: bold;
+                color: {accent};
+                margin-top: 8px;
+            }}
+
+            .window-list .focused .window-list-title {{
+                color: {accent};
+            }}
+
+            .window-list-app {{
+                font-size: 9pt;
+                color: alpha({foreground}, 0.75);
+            }}
+
+            .windows-popup .empty-message {{
+                color: alpha({foreground}, 0.5);
+            }}
+        "#,
+            background = theme.background,
+            foreground = theme.foreground,
+            accent = theme.accent,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: u64, idx: u32, name: Option<&str>) -> NiriWorkspace {
+        NiriWorkspace {
+            id,
+            name: name.map(str::to_string),
+            idx,
+            is_active: false,
+            is_focused: false,
+            is_urgent: false,
+        }
+    }
+
+    fn window(id: u64, workspace_id: Option<u64>) -> NiriWindow {
+        NiriWindow {
+            id,
+            title: None,
+            app_id: None,
+            workspace_id,
+            is_focused: false,
+            is_urgent: false,
+        }
+    }
+
+    #[test]
+    fn test_group_by_workspace() {
+        let workspaces = [
+            workspace(20, 2, Some("chat")),
+            workspace(10, 1, None),
+            workspace(30, 3, None),
+        ];
+        let windows = vec![
+            window(1, Some(20)),
+            window(2, None),
+            window(3, Some(10)),
+            window(4, Some(99)),
+            window(5, Some(20)),
+        ];
+
+        let groups = WindowsPopup::group_by_workspace(&workspaces, windows);
+        let groups: Vec<(&str, Vec<u64>)> = groups
+            .iter()
+            .map(|(heading, windows)| (heading.as_str(), windows.iter().map(|w| w.id).collect()))
+            .collect();
+        // Empty workspaces get no heading
+        assert_eq!(
+            groups,
+            vec![
+                ("Workspace 1", vec![3]),
+                ("Workspace 2: chat", vec![1, 5]),
+                ("Other", vec![2, 4]),
+            ]
+        );
+    }
+}
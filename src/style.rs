@@ -0,0 +1,106 @@
+use crate::app::AppState;
+use crate::config::{Config, ThemeConfig};
+use crate::events::Event;
+use futures::StreamExt;
+use gtk4::gdk::Display;
+use gtk4::glib;
+use gtk4::CssProvider;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Install the user stylesheet from `theme.style_path`
+///
+/// It is added at user priority so it overrides the built-in styles of the bar,
+/// popups and overlays, and is reloaded when the config changes.
+pub fn install_user_stylesheet(display: &Display, state: &Arc<AppState>) {
+    let provider = CssProvider::new();
+    provider.connect_parsing_error(|_, section, error| {
+        warn!("User stylesheet error at {}: {}", section, error);
+    });
+
+    gtk4::style_context_add_provider_for_display(
+        display,
+        &provider,
+        gtk4::STYLE_PROVIDER_PRIORITY_USER,
+    );
+
+    load_user_stylesheet(&provider, &state.config().theme);
+
+    let state = state.clone();
+    let mut reloads = Box::pin(
+        state
+            .events
+            .subscribe_filtered(|event| matches!(event, Event::ConfigReloaded).then_some(())),
+    );
+    glib::spawn_future_local(async move {
+        while reloads.next().await.is_some() {
+            load_user_stylesheet(&provider, &state.config().theme);
+        }
+    });
+}
+
+/// Install CSS generated from the theme config, and rebuild it whenever the
+/// config is reloaded
+///
+/// Popups and overlays use this so they follow `theme.background`,
+/// `foreground` and `accent` the way the bar does.
+pub fn install_theme_css(
+    display: &Display,
+    state: &Arc<AppState>,
+    css: fn(&ThemeConfig) -> String,
+) {
+    let provider = CssProvider::new();
+    provider.load_from_string(&css(&state.config().theme));
+    gtk4::style_context_add_provider_for_display(
+        display,
+        &provider,
+        gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
+    );
+
+    let state = state.clone();
+    let mut reloads = Box::pin(
+        state
+            .events
+            .subscribe_filtered(|event| matches!(event, Event::ConfigReloaded).then_some(())),
+    );
+    glib::spawn_future_local(async move {
+        while reloads.next().await.is_some() {
+            provider.load_from_string(&css(&state.config().theme));
+        }
+    });
+}
+
+/// Load the stylesheet named in the theme config, or clear it if none is set
+fn load_user_stylesheet(provider: &CssProvider, theme: &ThemeConfig) {
+    let Some(path) = theme.style_path.as_deref().map(resolve_path) else {
+        provider.load_from_string("");
+        return;
+    };
+
+    if !path.is_file() {
+        warn!("User stylesheet {} not found", path.display());
+        provider.load_from_string("");
+        return;
+    }
+
+    info!("Loading user stylesheet from {}", path.display());
+    provider.load_from_path(&path);
+}
+
+/// Expand `~/` and resolve relative paths against the config directory
+fn resolve_path(path: &std::path::Path) -> PathBuf {
+    if let Ok(rest) = path.strip_prefix("~") {
+        if let Ok(home) = std::env::var("HOME") {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+
+    if path.is_relative() {
+        if let Some(dir) = Config::path().ok().as_deref().and_then(|p| p.parent()) {
+            return dir.join(path);
+        }
+    }
+
+    path.to_path_buf()
+}
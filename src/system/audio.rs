@@ -1,36 +0,0 @@
-use anyhow::Result;
-
-pub struct AudioControl;
-
-impl AudioControl {
-    pub fn new() -> Self {
-        AudioControl
-    }
-
-    pub fn get_volume(&self) -> Result<f64> {
-        // This would use PulseAudio/PipeWire via D-Bus or native API
-        // For now, return a mock value
-        Ok(75.0)
-    }
-
-    pub fn set_volume(&self, volume: f64) -> Result<()> {
-        // Set volume via PulseAudio/PipeWire
-        tracing::info!("Setting volume to {}", volume);
-        Ok(())
-    }
-
-    pub fn get_mute(&self) -> Result<bool> {
-        Ok(false)
-    }
-
-    pub fn toggle_mute(&self) -> Result<()> {
-        tracing::info!("Toggling mute");
-        Ok(())
-    }
-}
-
-impl Default for AudioControl {
-    fn default() -> Self {
-        Self::new()
-    }
-}
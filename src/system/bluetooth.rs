@@ -1,60 +0,0 @@
-use anyhow::Result;
-
-#[derive(Debug, Clone)]
-pub struct BluetoothDevice {
-    pub name: String,
-    pub address: String,
-    pub connected: bool,
-    pub paired: bool,
-}
-
-pub struct BluetoothControl;
-
-impl BluetoothControl {
-    pub fn new() -> Self {
-        BluetoothControl
-    }
-
-    pub fn is_enabled(&self) -> Result<bool> {
-        // Query BlueZ via D-Bus
-        Ok(true)
-    }
-
-    pub fn set_enabled(&self, enabled: bool) -> Result<()> {
-        tracing::info!("Setting bluetooth enabled: {}", enabled);
-        Ok(())
-    }
-
-    pub fn list_devices(&self) -> Result<Vec<BluetoothDevice>> {
-        // Query BlueZ via D-Bus for paired/available devices
-        Ok(vec![
-            BluetoothDevice {
-                name: "Headphones".to_string(),
-                address: "AA:BB:CC:DD:EE:FF".to_string(),
-                connected: true,
-                paired: true,
-            },
-        ])
-    }
-
-    pub fn connect(&self, address: &str) -> Result<()> {
-        tracing::info!("Connecting to bluetooth device: {}", address);
-        Ok(())
-    }
-
-    pub fn disconnect(&self, address: &str) -> Result<()> {
-        tracing::info!("Disconnecting bluetooth device: {}", address);
-        Ok(())
-    }
-
-    pub fn scan(&self) -> Result<()> {
-        tracing::info!("Scanning for bluetooth devices");
-        Ok(())
-    }
-}
-
-impl Default for BluetoothControl {
-    fn default() -> Self {
-        Self::new()
-    }
-}
@@ -1,9 +0,0 @@
-pub mod audio;
-pub mod backlight;
-pub mod bluetooth;
-pub mod network;
-
-pub use audio::AudioControl;
-pub use backlight::BacklightControl;
-pub use bluetooth::BluetoothControl;
-pub use network::NetworkControl;
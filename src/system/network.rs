@@ -1,69 +0,0 @@
-use anyhow::Result;
-
-#[derive(Debug, Clone)]
-pub struct WifiNetwork {
-    pub ssid: String,
-    pub signal_strength: u8,
-    pub secured: bool,
-    pub connected: bool,
-}
-
-pub struct NetworkControl;
-
-impl NetworkControl {
-    pub fn new() -> Self {
-        NetworkControl
-    }
-
-    pub fn is_wifi_enabled(&self) -> Result<bool> {
-        // Query NetworkManager via D-Bus
-        Ok(true)
-    }
-
-    pub fn set_wifi_enabled(&self, enabled: bool) -> Result<()> {
-        tracing::info!("Setting WiFi enabled: {}", enabled);
-        Ok(())
-    }
-
-    pub fn list_networks(&self) -> Result<Vec<WifiNetwork>> {
-        // Query NetworkManager via D-Bus for available networks
-        Ok(vec![
-            WifiNetwork {
-                ssid: "Home Network".to_string(),
-                signal_strength: 95,
-                secured: true,
-                connected: true,
-            },
-            WifiNetwork {
-                ssid: "Office WiFi".to_string(),
-                signal_strength: 75,
-                secured: true,
-                connected: false,
-            },
-        ])
-    }
-
-    pub fn connect(&self, ssid: &str, password: Option<&str>) -> Result<()> {
-        tracing::info!("Connecting to network: {}", ssid);
-        if let Some(pwd) = password {
-            tracing::info!("Using password (length: {})", pwd.len());
-        }
-        Ok(())
-    }
-
-    pub fn disconnect(&self) -> Result<()> {
-        tracing::info!("Disconnecting from network");
-        Ok(())
-    }
-
-    pub fn scan(&self) -> Result<()> {
-        tracing::info!("Scanning for WiFi networks");
-        Ok(())
-    }
-}
-
-impl Default for NetworkControl {
-    fn default() -> Self {
-        Self::new()
-    }
-}
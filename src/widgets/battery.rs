@@ -11,11 +11,13 @@ pub struct Battery {
 impl Battery {
     pub fn new(state: &Arc<AppState>) -> Self {
         let container = GtkBox::new(Orientation::Horizontal, 4);
+        container.add_css_class("battery");
 
         // Battery icon and percentage
         let battery_label = Label::new(Some("🔋 ---%"));
         battery_label.add_css_class("battery-label");
         container.append(&battery_label);
+        super::connect_click_actions(&container, state, "battery");
 
         // Subscribe to events
         Self::subscribe_to_events(state.events.clone(), battery_label.clone());
@@ -70,36 +72,30 @@ impl Battery {
 
         // Spawn event listener
         glib::spawn_future_local(async move {
-            loop {
-                match receiver.recv().await {
-                    Ok(event) => match event {
-                        Event::BatteryChanged {
-                            percentage,
-                            state,
-                            is_charging,
-                        } => {
-                            let text = Self::format_battery_text(percentage, &state, is_charging);
-                            battery_label.set_text(&text);
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::BatteryChanged {
+                        percentage,
+                        state,
+                        is_charging,
+                    } => {
+                        let text = Self::format_battery_text(percentage, &state, is_charging);
+                        battery_label.set_text(&text);
 
-                            // Add CSS class based on battery level for styling
-                            battery_label.remove_css_class("battery-low");
-                            battery_label.remove_css_class("battery-critical");
-                            battery_label.remove_css_class("battery-charging");
+                        // Add CSS class based on battery level for styling
+                        battery_label.remove_css_class("battery-low");
+                        battery_label.remove_css_class("battery-critical");
+                        battery_label.remove_css_class("battery-charging");
 
-                            if is_charging {
-                                battery_label.add_css_class("battery-charging");
-                            } else if percentage < 15.0 {
-                                battery_label.add_css_class("battery-critical");
-                            } else if percentage < 30.0 {
-                                battery_label.add_css_class("battery-low");
-                            }
+                        if is_charging {
+                            battery_label.add_css_class("battery-charging");
+                        } else if percentage < 15.0 {
+                            battery_label.add_css_class("battery-critical");
+                        } else if percentage < 30.0 {
+                            battery_label.add_css_class("battery-low");
                         }
-                        _ => {} // Ignore other events
-                    },
-                    Err(_) => {
-                        // Channel closed, exit loop
-                        break;
                     }
+                    _ => {} // Ignore other events
                 }
             }
         });
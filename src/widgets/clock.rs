@@ -1,38 +1,97 @@
 use crate::app::AppState;
+use crate::config::BarConfig;
+use crate::events::{Event, PopupType};
 use chrono::Local;
 use gtk4::prelude::*;
-use gtk4::{glib, Label};
+use gtk4::{glib, Button, Label};
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
+/// strftime formats for the clock text and its tooltip
+#[derive(Clone)]
+struct ClockFormats {
+    label: String,
+    tooltip: String,
+}
+
+impl ClockFormats {
+    fn from_config(config: &BarConfig) -> Self {
+        ClockFormats {
+            label: config.clock_format.clone(),
+            tooltip: config.clock_format_tooltip.clone(),
+        }
+    }
+}
+
 pub struct Clock {
-    label: Label,
+    button: Button,
 }
 
 impl Clock {
-    pub fn new(_state: &Arc<AppState>) -> Self {
+    pub fn new(state: &Arc<AppState>) -> Self {
+        let button = Button::new();
         let label = Label::new(None);
+        button.set_child(Some(&label));
+        button.add_css_class("clock-button");
         label.add_css_class("clock-label");
 
+        // Open the calendar on click
+        let events = state.events.clone();
+        button.connect_clicked(move |_| {
+            events.emit(Event::PopupRequested {
+                popup_type: PopupType::Calendar,
+            });
+        });
+
+        let formats = Rc::new(RefCell::new(ClockFormats::from_config(&state.config().bar)));
+
         // Update clock immediately
-        Self::update_time(&label);
+        Self::update_time(&label, &formats.borrow());
 
         // Update every second
         let label_clone = label.clone();
+        let formats_clone = formats.clone();
         glib::timeout_add_seconds_local(1, move || {
-            Self::update_time(&label_clone);
+            Self::update_time(&label_clone, &formats_clone.borrow());
             glib::ControlFlow::Continue
         });
 
-        Clock { label }
+        // Subscribe to events
+        Self::subscribe_to_events(state.clone(), label.clone(), formats);
+
+        Clock { button }
     }
 
-    fn update_time(label: &Label) {
+    fn update_time(label: &Label, formats: &ClockFormats) {
         let now = Local::now();
-        let time_str = now.format("%a %b %d  %H:%M:%S").to_string();
+        let time_str = now.format(&formats.label).to_string();
         label.set_text(&time_str);
+
+        if formats.tooltip.is_empty() {
+            label.set_tooltip_text(None);
+        } else {
+            label.set_tooltip_text(Some(&now.format(&formats.tooltip).to_string()));
+        }
     }
 
-    pub fn widget(&self) -> Label {
-        self.label.clone()
+    pub fn widget(&self) -> Button {
+        self.button.clone()
+    }
+
+    fn subscribe_to_events(state: Arc<AppState>, label: Label, formats: Rc<RefCell<ClockFormats>>) {
+        let mut receiver = state.events.subscribe();
+
+        glib::spawn_future_local(async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::ConfigReloaded => {
+                        *formats.borrow_mut() = ClockFormats::from_config(&state.config().bar);
+                        Self::update_time(&label, &formats.borrow());
+                    }
+                    _ => {} // Ignore other events
+                }
+            }
+        });
     }
 }
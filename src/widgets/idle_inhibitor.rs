@@ -0,0 +1,70 @@
+use crate::app::AppState;
+use crate::events::Event;
+use futures::StreamExt;
+use gtk4::prelude::*;
+use gtk4::{glib, Button, Label};
+use std::sync::Arc;
+
+/// Bar toggle that keeps the screen from blanking while active
+pub struct IdleInhibitorToggle {
+    button: Button,
+}
+
+impl IdleInhibitorToggle {
+    pub fn new(state: &Arc<AppState>) -> Self {
+        let button = Button::new();
+        let label = Label::new(Some("☕"));
+        button.set_child(Some(&label));
+        button.add_css_class("idle-inhibitor-button");
+        Self::update_state(&button, false);
+
+        // Toggle the inhibitor lock on click
+        let inhibitor = state.idle_inhibitor.clone();
+        button.connect_clicked(move |_| {
+            if let Some(inhibitor) = &inhibitor {
+                let inhibitor = inhibitor.clone();
+                glib::spawn_future_local(async move {
+                    if let Err(e) = inhibitor.toggle().await {
+                        tracing::warn!("Failed to toggle idle inhibitor: {}", e);
+                    }
+                    // Event will be emitted by the inhibitor
+                });
+            } else {
+                tracing::info!("Cannot toggle idle inhibitor (no logind)");
+            }
+        });
+
+        // Subscribe to events
+        Self::subscribe_to_events(state.events.clone(), button.clone());
+
+        IdleInhibitorToggle { button }
+    }
+
+    pub fn widget(&self) -> Button {
+        self.button.clone()
+    }
+
+    fn update_state(button: &Button, enabled: bool) {
+        if enabled {
+            button.add_css_class("active");
+            button.set_tooltip_text(Some("Idle inhibitor on: screen stays awake"));
+        } else {
+            button.remove_css_class("active");
+            button.set_tooltip_text(Some("Idle inhibitor off"));
+        }
+    }
+
+    fn subscribe_to_events(events: crate::events::EventManager, button: Button) {
+        let mut changes = Box::pin(events.subscribe_filtered(|event| match event {
+            Event::IdleInhibitChanged { enabled } => Some(enabled),
+            _ => None,
+        }));
+
+        // Spawn event listener
+        glib::spawn_future_local(async move {
+            while let Some(enabled) = changes.next().await {
+                Self::update_state(&button, enabled);
+            }
+        });
+    }
+}
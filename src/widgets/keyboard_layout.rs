@@ -0,0 +1,79 @@
+use crate::app::AppState;
+use crate::backend::niri::LayoutSwitchTarget;
+use crate::events::Event;
+use futures::StreamExt;
+use gtk4::prelude::*;
+use gtk4::{glib, Button, Label};
+use std::sync::Arc;
+
+pub struct KeyboardLayout {
+    button: Button,
+}
+
+impl KeyboardLayout {
+    pub fn new(state: &Arc<AppState>) -> Self {
+        let button = Button::new();
+        let label = Label::new(Some("⌨ --"));
+        button.set_child(Some(&label));
+        button.add_css_class("keyboard-layout-button");
+
+        // Cycle to the next layout on click
+        let niri_client = state.niri_client.clone();
+        button.connect_clicked(move |_| {
+            if let Some(client) = &niri_client {
+                if let Err(e) = client.switch_layout(LayoutSwitchTarget::Next) {
+                    tracing::warn!("Failed to switch keyboard layout: {}", e);
+                }
+                // Event will be emitted by niri polling
+            } else {
+                tracing::info!("Cannot switch keyboard layout (no niri)");
+            }
+        });
+
+        // Subscribe to events
+        Self::subscribe_to_events(state.events.clone(), button.clone(), label);
+
+        KeyboardLayout { button }
+    }
+
+    pub fn widget(&self) -> Button {
+        self.button.clone()
+    }
+
+    /// Shorten a layout name for display, e.g. "English (US)" -> "US", "German" -> "DE"
+    fn short_name(layout: &str) -> String {
+        if let (Some(start), Some(end)) = (layout.rfind('('), layout.rfind(')')) {
+            if start < end {
+                let inner = layout[start + 1..end].trim();
+                if !inner.is_empty() {
+                    return inner.to_uppercase();
+                }
+            }
+        }
+
+        // Map common layout names to their ISO code
+        match layout.split_whitespace().next().unwrap_or("") {
+            "German" => "DE".to_string(),
+            "French" => "FR".to_string(),
+            "Spanish" => "ES".to_string(),
+            "Russian" => "RU".to_string(),
+            "Japanese" => "JP".to_string(),
+            other => other.chars().take(2).collect::<String>().to_uppercase(),
+        }
+    }
+
+    fn subscribe_to_events(events: crate::events::EventManager, button: Button, label: Label) {
+        let mut layouts = Box::pin(events.subscribe_filtered(|event| match event {
+            Event::KeyboardLayoutChanged { layout } => Some(layout),
+            _ => None,
+        }));
+
+        // Spawn event listener
+        glib::spawn_future_local(async move {
+            while let Some(layout) = layouts.next().await {
+                label.set_text(&format!("⌨ {}", Self::short_name(&layout)));
+                button.set_tooltip_text(Some(&layout));
+            }
+        });
+    }
+}
@@ -0,0 +1,158 @@
+use crate::app::AppState;
+use crate::events::{Event, PopupType};
+use gtk4::prelude::*;
+use gtk4::{glib, Box as GtkBox, Button, GestureClick, Label, Orientation};
+use std::sync::Arc;
+
+/// Longest "Artist - Title" shown before truncating; the tooltip has it all
+const MAX_TEXT_CHARS: usize = 40;
+
+/// Currently playing track, hidden while no media player is running
+///
+/// Clicking opens the media popup; middle-clicking plays or pauses.
+pub struct MediaWidget {
+    container: GtkBox,
+}
+
+impl MediaWidget {
+    pub fn new(state: &Arc<AppState>) -> Self {
+        // The bar sets the container's visibility from the config, so the
+        // button inside is what hides without a player
+        let container = GtkBox::new(Orientation::Horizontal, 0);
+        container.add_css_class("media");
+
+        let button = Button::new();
+        button.add_css_class("media-button");
+        button.set_visible(false);
+
+        let content = GtkBox::new(Orientation::Horizontal, 6);
+        let status_label = Label::new(Some("⏸"));
+        status_label.add_css_class("media-status");
+        let track_label = Label::new(None);
+        track_label.add_css_class("media-track");
+        content.append(&status_label);
+        content.append(&track_label);
+        button.set_child(Some(&content));
+        container.append(&button);
+
+        // Open the media popup on click
+        let events = state.events.clone();
+        button.connect_clicked(move |_| {
+            events.emit(Event::PopupRequested {
+                popup_type: PopupType::MediaControl,
+            });
+        });
+
+        // Play or pause on middle click
+        let middle_click = GestureClick::new();
+        middle_click.set_button(gtk4::gdk::BUTTON_MIDDLE);
+        let media = state.media_control.clone();
+        middle_click.connect_released(move |_, _, _, _| {
+            let Some(media) = media.clone() else {
+                return;
+            };
+            glib::spawn_future_local(async move {
+                if let Err(e) = media.play_pause().await {
+                    tracing::warn!("Failed to toggle playback: {}", e);
+                }
+                // Event will be emitted by the media control
+            });
+        });
+        button.add_controller(middle_click);
+
+        // Subscribe to events
+        Self::subscribe_to_events(
+            state.events.clone(),
+            button.clone(),
+            status_label.clone(),
+            track_label.clone(),
+        );
+
+        // Show what is already playing
+        if let Some(media) = &state.media_control {
+            let media = media.clone();
+            glib::spawn_future_local(async move {
+                if media.get_active_player().await.is_none() {
+                    return;
+                }
+                if let Some(track) = media.get_metadata().await {
+                    Self::update_track(&button, &track_label, &track.artist, &track.title);
+                }
+                let status = media.get_playback_status().await;
+                Self::update_status(
+                    &status_label,
+                    status == crate::backend::system::media::PlaybackStatus::Playing,
+                );
+                button.set_visible(true);
+            });
+        }
+
+        MediaWidget { container }
+    }
+
+    pub fn widget(&self) -> GtkBox {
+        self.container.clone()
+    }
+
+    /// "Artist - Title", or just the title when the artist is unknown
+    fn track_text(artist: &str, title: &str) -> String {
+        if artist.is_empty() || artist == "Unknown" {
+            title.to_string()
+        } else {
+            format!("{} - {}", artist, title)
+        }
+    }
+
+    /// Truncate `text` to `MAX_TEXT_CHARS` characters, appending an ellipsis
+    /// if shortened
+    fn truncate(text: &str) -> String {
+        if text.chars().count() <= MAX_TEXT_CHARS {
+            text.to_string()
+        } else {
+            let truncated: String = text.chars().take(MAX_TEXT_CHARS - 1).collect();
+            format!("{}…", truncated)
+        }
+    }
+
+    fn update_track(button: &Button, label: &Label, artist: &str, title: &str) {
+        let text = Self::track_text(artist, title);
+        label.set_text(&Self::truncate(&text));
+        button.set_tooltip_text(Some(&text));
+    }
+
+    fn update_status(label: &Label, playing: bool) {
+        label.set_text(if playing { "▶" } else { "⏸" });
+        if playing {
+            label.add_css_class("playing");
+        } else {
+            label.remove_css_class("playing");
+        }
+    }
+
+    fn subscribe_to_events(
+        events: crate::events::EventManager,
+        button: Button,
+        status_label: Label,
+        track_label: Label,
+    ) {
+        let mut receiver = events.subscribe();
+
+        // Spawn event listener
+        glib::spawn_future_local(async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::MediaTrackChanged { title, artist, .. } => {
+                        Self::update_track(&button, &track_label, &artist, &title);
+                    }
+                    Event::MediaPlaybackChanged { playing } => {
+                        Self::update_status(&status_label, playing);
+                    }
+                    Event::MediaPlayerChanged { player } => {
+                        button.set_visible(player.is_some());
+                    }
+                    _ => {} // Ignore other events
+                }
+            }
+        });
+    }
+}
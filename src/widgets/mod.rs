@@ -1,4 +1,61 @@
 pub mod battery;
 pub mod clock;
+pub mod idle_inhibitor;
+pub mod keyboard_layout;
+pub mod media;
+pub mod network_speed;
+pub mod sparkline;
 pub mod system_info;
+pub mod tray;
+pub mod window_title;
 pub mod workspaces;
+
+use crate::app::AppState;
+use gtk4::prelude::*;
+use gtk4::{glib, GestureClick};
+use std::sync::Arc;
+
+/// Signal strength (0-100) drawn as four bars
+pub fn signal_bars(strength: u8) -> &'static str {
+    match strength {
+        0..=25 => "▂___",
+        26..=50 => "▂▄__",
+        51..=75 => "▂▄▆_",
+        _ => "▂▄▆█",
+    }
+}
+
+/// Run the `bar.click_actions` configured for widget `name` when it is
+/// clicked with any mouse button
+pub fn connect_click_actions(
+    widget: &impl IsA<gtk4::Widget>,
+    state: &Arc<AppState>,
+    name: &'static str,
+) {
+    let click = GestureClick::new();
+    // Listen to every button, not just the primary one
+    click.set_button(0);
+
+    let state = state.clone();
+    click.connect_released(move |gesture, _, _, _| {
+        let config = state.config();
+        let Some(action) = config
+            .bar
+            .click_actions
+            .get(name)
+            .and_then(|actions| actions.for_button(gesture.current_button()))
+            .cloned()
+        else {
+            return;
+        };
+
+        let state = state.clone();
+        glib::spawn_future_local(async move {
+            if let Err(e) = crate::hotkeys::dispatch(&state, &action).await {
+                tracing::warn!("Failed to run click action for {}: {}", name, e);
+            }
+        });
+    });
+
+    widget.add_controller(click);
+}
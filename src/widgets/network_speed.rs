@@ -0,0 +1,65 @@
+use crate::app::AppState;
+use crate::events::Event;
+use futures::StreamExt;
+use gtk4::prelude::*;
+use gtk4::{glib, Label};
+use std::sync::Arc;
+
+pub struct NetworkSpeed {
+    label: Label,
+}
+
+impl NetworkSpeed {
+    pub fn new(state: &Arc<AppState>) -> Self {
+        let label = Label::new(Some("↓ -- ↑ --"));
+        label.add_css_class("network-speed-label");
+
+        // Subscribe to events
+        Self::subscribe_to_events(state.events.clone(), label.clone());
+
+        NetworkSpeed { label }
+    }
+
+    pub fn widget(&self) -> Label {
+        self.label.clone()
+    }
+
+    /// Format a byte rate with a binary unit, e.g. 1258291 -> "1.2 MB/s"
+    fn format_rate(bytes_per_sec: f64) -> String {
+        const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+
+        let mut value = bytes_per_sec.max(0.0);
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 || value >= 100.0 {
+            format!("{:.0} {}", value, UNITS[unit])
+        } else {
+            format!("{:.1} {}", value, UNITS[unit])
+        }
+    }
+
+    fn subscribe_to_events(events: crate::events::EventManager, label: Label) {
+        let mut rates = Box::pin(events.subscribe_filtered(|event| match event {
+            Event::NetworkThroughputChanged {
+                rx_bytes_per_sec,
+                tx_bytes_per_sec,
+            } => Some((rx_bytes_per_sec, tx_bytes_per_sec)),
+            _ => None,
+        }));
+
+        // Spawn event listener
+        glib::spawn_future_local(async move {
+            while let Some((rx_bytes_per_sec, tx_bytes_per_sec)) = rates.next().await {
+                label.set_text(&format!(
+                    "↓ {} ↑ {}",
+                    Self::format_rate(rx_bytes_per_sec),
+                    Self::format_rate(tx_bytes_per_sec)
+                ));
+            }
+        });
+    }
+}
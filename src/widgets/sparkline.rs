@@ -0,0 +1,128 @@
+use crate::app::AppState;
+use crate::events::Event;
+use futures::StreamExt;
+use gtk4::prelude::*;
+use gtk4::{glib, DrawingArea};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Metric plotted by a `Sparkline`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Cpu,
+    Memory,
+}
+
+impl Metric {
+    fn label(self) -> &'static str {
+        match self {
+            Metric::Cpu => "CPU",
+            Metric::Memory => "Memory",
+        }
+    }
+
+    /// Usage percentage carried by `event`, if it reports this metric
+    fn value(self, event: Event) -> Option<f64> {
+        match (self, event) {
+            (Metric::Cpu, Event::CpuUsageChanged { usage }) => Some(usage),
+            (Metric::Memory, Event::MemoryUsageChanged { percent, .. }) => Some(percent),
+            _ => None,
+        }
+    }
+}
+
+/// Small line graph of the recent history of a usage percentage
+pub struct Sparkline {
+    area: DrawingArea,
+}
+
+impl Sparkline {
+    pub fn new(state: &Arc<AppState>, metric: Metric) -> Self {
+        let area = DrawingArea::new();
+        area.add_css_class("sparkline");
+        area.add_css_class(match metric {
+            Metric::Cpu => "cpu",
+            Metric::Memory => "memory",
+        });
+        area.set_content_width(60);
+        area.set_vexpand(true);
+        area.set_tooltip_text(Some(&format!("{}: --%", metric.label())));
+
+        let history: Rc<RefCell<VecDeque<f64>>> = Rc::new(RefCell::new(VecDeque::new()));
+
+        let samples = history.clone();
+        area.set_draw_func(move |area, cr, width, height| {
+            let samples = samples.borrow();
+            if samples.len() < 2 {
+                return;
+            }
+
+            // Stroke with the widget's CSS color so themes apply
+            let color = area.color();
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                color.alpha() as f64,
+            );
+            cr.set_line_width(1.5);
+
+            let (width, height) = (width as f64, height as f64);
+            let step = width / (samples.len() - 1) as f64;
+            for (i, value) in samples.iter().enumerate() {
+                let x = i as f64 * step;
+                // Keep the line inside the widget at 0% and 100%
+                let y = 1.0 + (height - 2.0) * (1.0 - value.clamp(0.0, 100.0) / 100.0);
+                if i == 0 {
+                    cr.move_to(x, y);
+                } else {
+                    cr.line_to(x, y);
+                }
+            }
+            if let Err(e) = cr.stroke() {
+                tracing::debug!("Failed to draw sparkline: {}", e);
+            }
+        });
+
+        Self::subscribe_to_events(state.clone(), area.clone(), metric, history);
+
+        Sparkline { area }
+    }
+
+    pub fn widget(&self) -> DrawingArea {
+        self.area.clone()
+    }
+
+    fn subscribe_to_events(
+        state: Arc<AppState>,
+        area: DrawingArea,
+        metric: Metric,
+        history: Rc<RefCell<VecDeque<f64>>>,
+    ) {
+        let mut values = Box::pin(
+            state
+                .events
+                .subscribe_filtered(move |event| metric.value(event)),
+        );
+
+        // Spawn event listener
+        glib::spawn_future_local(async move {
+            while let Some(value) = values.next().await {
+                // Read each time so a reload resizes the history
+                let length = state.config().bar.sparkline_history;
+                {
+                    let mut history = history.borrow_mut();
+                    history.push_back(value);
+                    while history.len() > length {
+                        history.pop_front();
+                    }
+                }
+
+                area.set_tooltip_text(Some(&format!("{}: {:.1}%", metric.label(), value)));
+                area.queue_draw();
+            }
+        });
+    }
+}
@@ -1,9 +1,26 @@
 use crate::app::AppState;
-use crate::events::Event;
+use crate::config::BarConfig;
+use crate::events::{Event, PopupType};
+use futures::StreamExt;
 use gtk4::prelude::*;
 use gtk4::{glib, Box as GtkBox, Label, Orientation};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
 
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// WiFi signal strength (percent) at or below which the label is flagged
+const WEAK_SIGNAL: u8 = 25;
+
+/// Latest usage reading for a mount point
+struct DiskUsage {
+    used: u64,
+    total: u64,
+    percent: f64,
+}
+
 pub struct SystemInfo {
     container: GtkBox,
 }
@@ -11,40 +28,146 @@ pub struct SystemInfo {
 impl SystemInfo {
     pub fn new(state: &Arc<AppState>) -> Self {
         let container = GtkBox::new(Orientation::Horizontal, 12);
+        container.add_css_class("system-info");
 
         // CPU usage
         let cpu_label = Label::new(Some("CPU: ---%"));
         cpu_label.add_css_class("system-info-label");
+        cpu_label.add_css_class("cpu");
+        super::connect_click_actions(&cpu_label, state, "cpu");
         container.append(&cpu_label);
 
+        // Per-core CPU usage
+        let cores_label = Label::new(None);
+        cores_label.add_css_class("system-info-label");
+        cores_label.add_css_class("cpu-cores");
+        container.append(&cores_label);
+
+        // Load average
+        let load_label = Label::new(Some("LOAD: --"));
+        load_label.add_css_class("system-info-label");
+        load_label.add_css_class("load-average");
+        container.append(&load_label);
+
         // Memory usage
         let mem_label = Label::new(Some("MEM: ---%"));
         mem_label.add_css_class("system-info-label");
+        mem_label.add_css_class("memory");
+        super::connect_click_actions(&mem_label, state, "memory");
         container.append(&mem_label);
 
         // Temperature
-        let temp_label = Label::new(Some("TEMP: --°C"));
+        let temp_label = Label::new(Some(&format!(
+            "TEMP: --{}",
+            state.config().bar.temperature_unit.symbol()
+        )));
         temp_label.add_css_class("system-info-label");
+        temp_label.add_css_class("temperature");
+        super::connect_click_actions(&temp_label, state, "temperature");
         container.append(&temp_label);
 
+        // Disk usage
+        let disk_label = Label::new(Some("DISK: --%"));
+        disk_label.add_css_class("system-info-label");
+        disk_label.add_css_class("disk");
+        super::connect_click_actions(&disk_label, state, "disk");
+        container.append(&disk_label);
+
+        // Volume and mute state, clicking toggles mute
+        let volume_label = Label::new(Some("🔊 --%"));
+        volume_label.add_css_class("system-info-label");
+        volume_label.add_css_class("volume");
+        volume_label.set_cursor_from_name(Some("pointer"));
+        volume_label.set_visible(state.audio_control.is_some());
+        super::connect_click_actions(&volume_label, state, "volume");
+        container.append(&volume_label);
+        Self::subscribe_to_volume(state, volume_label.clone());
+
         // WiFi status
         let wifi_label = Label::new(Some("📶 WiFi"));
         wifi_label.add_css_class("system-info-label");
+        wifi_label.add_css_class("wifi");
+        wifi_label.set_tooltip_text(Some("WiFi networks"));
+        wifi_label.set_cursor_from_name(Some("pointer"));
+        super::connect_click_actions(&wifi_label, state, "wifi");
         container.append(&wifi_label);
 
+        // VPN indicator, clicking takes the VPN down
+        let vpn_label = Label::new(Some("🔒 VPN"));
+        vpn_label.add_css_class("system-info-label");
+        vpn_label.add_css_class("vpn");
+        vpn_label.set_cursor_from_name(Some("pointer"));
+        vpn_label.set_visible(false);
+        container.append(&vpn_label);
+        Self::subscribe_to_vpn(state, vpn_label);
+
         // Bluetooth status
         let bt_label = Label::new(Some("🔵 BT"));
         bt_label.add_css_class("system-info-label");
+        bt_label.add_css_class("bluetooth");
+        bt_label.set_tooltip_text(Some("Bluetooth devices"));
+        bt_label.set_cursor_from_name(Some("pointer"));
+        super::connect_click_actions(&bt_label, state, "bluetooth");
         container.append(&bt_label);
 
+        // Do-not-disturb indicator, clicking opens the notification center
+        let dnd_label = Label::new(Some("🔕"));
+        dnd_label.add_css_class("system-info-label");
+        dnd_label.add_css_class("dnd");
+        dnd_label.set_tooltip_text(Some("Do not disturb"));
+        dnd_label.set_visible(
+            state
+                .notification_daemon
+                .as_ref()
+                .is_some_and(|daemon| daemon.is_dnd()),
+        );
+        let click = gtk4::GestureClick::new();
+        let events = state.events.clone();
+        click.connect_released(move |_, _, _, _| {
+            events.emit(Event::PopupRequested {
+                popup_type: PopupType::Notifications,
+            });
+        });
+        dnd_label.add_controller(click);
+        container.append(&dnd_label);
+
+        // Airplane mode indicator, clicking turns the radios back on
+        let airplane_label = Label::new(Some("✈"));
+        airplane_label.add_css_class("system-info-label");
+        airplane_label.add_css_class("airplane");
+        airplane_label.set_tooltip_text(Some("Airplane mode (click to turn off)"));
+        airplane_label.set_cursor_from_name(Some("pointer"));
+        airplane_label.set_visible(false);
+        if let Some(airplane) = &state.airplane_mode {
+            let click = gtk4::GestureClick::new();
+            let airplane = airplane.clone();
+            click.connect_released(move |_, _, _, _| {
+                let airplane = airplane.clone();
+                glib::spawn_future_local(async move {
+                    if let Err(e) = airplane.set_enabled(false).await {
+                        tracing::warn!("Failed to turn off airplane mode: {}", e);
+                    }
+                });
+            });
+            airplane_label.add_controller(click);
+        }
+        container.append(&airplane_label);
+        Self::subscribe_to_airplane_mode(state, airplane_label);
+
+        Self::apply_config(&state.config().bar, &cores_label, &load_label, &disk_label);
+
         // Subscribe to events
         Self::subscribe_to_events(
-            state.events.clone(),
+            state.clone(),
             cpu_label.clone(),
+            cores_label.clone(),
+            load_label.clone(),
             mem_label.clone(),
             temp_label.clone(),
+            disk_label.clone(),
             wifi_label.clone(),
             bt_label.clone(),
+            dnd_label.clone(),
         );
 
         SystemInfo { container }
@@ -54,64 +177,325 @@ impl SystemInfo {
         self.container.clone()
     }
 
+    /// Show or hide the optional labels
+    fn apply_config(
+        config: &BarConfig,
+        cores_label: &Label,
+        load_label: &Label,
+        disk_label: &Label,
+    ) {
+        cores_label.set_visible(config.show_cpu_cores);
+        load_label.set_visible(config.show_load_average);
+        disk_label.set_visible(!config.disk_mounts.is_empty());
+    }
+
+    /// Format disk usage in configured mount order, e.g. "DISK: 42%" or "DISK: / 42% /home 60%"
+    fn disk_text(config: &BarConfig, usage: &HashMap<String, DiskUsage>) -> String {
+        let format_percent = |mount: &String| {
+            usage
+                .get(mount)
+                .map(|disk| format!("{:.0}%", disk.percent))
+                .unwrap_or_else(|| "--%".to_string())
+        };
+
+        match config.disk_mounts.as_slice() {
+            [mount] => format!("DISK: {}", format_percent(mount)),
+            mounts => {
+                let parts: Vec<String> = mounts
+                    .iter()
+                    .map(|mount| format!("{} {}", mount, format_percent(mount)))
+                    .collect();
+                format!("DISK: {}", parts.join(" "))
+            }
+        }
+    }
+
+    /// Render per-core usage as a row of block characters, one per core
+    fn core_bars(usages: &[f64]) -> String {
+        const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+        usages
+            .iter()
+            .map(|usage| {
+                let level = (usage.clamp(0.0, 100.0) / 100.0 * 7.0).round() as usize;
+                BLOCKS[level]
+            })
+            .collect()
+    }
+
+    /// Used/total space per mount for the disk tooltip
+    fn disk_tooltip(config: &BarConfig, usage: &HashMap<String, DiskUsage>) -> String {
+        config
+            .disk_mounts
+            .iter()
+            .filter_map(|mount| {
+                usage.get(mount).map(|disk| {
+                    format!(
+                        "{}: {:.1} / {:.1} GiB",
+                        mount,
+                        disk.used as f64 / GIB,
+                        disk.total as f64 / GIB
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// e.g. "Memory: 6.7 / 15.5 GiB" plus a swap line when swap is configured
+    fn memory_tooltip(used: u64, total: u64, swap_used: u64, swap_total: u64) -> String {
+        let mut tooltip = format!(
+            "Memory: {:.1} / {:.1} GiB",
+            used as f64 / GIB,
+            total as f64 / GIB
+        );
+        if swap_total > 0 {
+            tooltip.push_str(&format!(
+                "\nSwap: {:.1} / {:.1} GiB",
+                swap_used as f64 / GIB,
+                swap_total as f64 / GIB
+            ));
+        }
+        tooltip
+    }
+
+    /// e.g. "🔊 45%", or "🔇 45%" while muted
+    fn volume_text(level: f64, muted: bool) -> String {
+        format!("{} {:.0}%", if muted { "🔇" } else { "🔊" }, level)
+    }
+
+    fn update_volume(label: &Label, level: f64, muted: bool) {
+        label.set_text(&Self::volume_text(level, muted));
+        label.set_tooltip_text(Some(if muted {
+            "Muted (click to unmute)"
+        } else {
+            "Click to mute"
+        }));
+        if muted {
+            label.add_css_class("muted");
+        } else {
+            label.remove_css_class("muted");
+        }
+    }
+
+    /// Show the current volume, then follow `VolumeChanged` events
+    fn subscribe_to_volume(state: &Arc<AppState>, label: Label) {
+        let mut changes = Box::pin(state.events.subscribe_filtered(|event| match event {
+            Event::VolumeChanged { level, muted } => Some((level, muted)),
+            _ => None,
+        }));
+
+        let audio = state.audio_control.clone();
+        glib::spawn_future_local(async move {
+            if let Some(audio) = audio {
+                match (audio.get_volume().await, audio.get_mute().await) {
+                    (Ok(level), Ok(muted)) => Self::update_volume(&label, level, muted),
+                    (Err(e), _) | (_, Err(e)) => {
+                        tracing::debug!("Failed to read initial volume: {}", e)
+                    }
+                }
+            }
+
+            while let Some((level, muted)) = changes.next().await {
+                Self::update_volume(&label, level, muted);
+            }
+        });
+    }
+
+    fn subscribe_to_vpn(state: &Arc<AppState>, label: Label) {
+        let Some(network) = state.network_control.clone() else {
+            return;
+        };
+
+        // Name of the VPN currently shown, for the click handler
+        let shown: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+
+        let click = gtk4::GestureClick::new();
+        let network_clone = network.clone();
+        let shown_clone = shown.clone();
+        click.connect_released(move |_, _, _, _| {
+            let Some(name) = shown_clone.borrow().clone() else {
+                return;
+            };
+            let network = network_clone.clone();
+            glib::spawn_future_local(async move {
+                if let Err(e) = network.set_vpn_active(&name, false).await {
+                    tracing::warn!("Failed to deactivate VPN {}: {}", name, e);
+                }
+            });
+        });
+        label.add_controller(click);
+
+        let mut changes = Box::pin(state.events.subscribe_filtered(|event| match event {
+            Event::VpnStateChanged { name, .. } => Some(name),
+            _ => None,
+        }));
+
+        glib::spawn_future_local(async move {
+            Self::update_vpn(&label, &shown, network.active_vpn().await);
+            while let Some(name) = changes.next().await {
+                Self::update_vpn(&label, &shown, name);
+            }
+        });
+    }
+
+    fn update_vpn(label: &Label, shown: &Rc<RefCell<Option<String>>>, name: Option<String>) {
+        label.set_visible(name.is_some());
+        if let Some(name) = &name {
+            label.set_tooltip_text(Some(&format!("VPN: {} (click to disconnect)", name)));
+        }
+        *shown.borrow_mut() = name;
+    }
+
+    fn subscribe_to_airplane_mode(state: &Arc<AppState>, label: Label) {
+        let mut changes = Box::pin(state.events.subscribe_filtered(|event| match event {
+            Event::AirplaneModeChanged { enabled } => Some(enabled),
+            _ => None,
+        }));
+
+        glib::spawn_future_local(async move {
+            while let Some(enabled) = changes.next().await {
+                label.set_visible(enabled);
+            }
+        });
+    }
+
     fn subscribe_to_events(
-        events: crate::events::EventManager,
+        state: Arc<AppState>,
         cpu_label: Label,
+        cores_label: Label,
+        load_label: Label,
         mem_label: Label,
         temp_label: Label,
+        disk_label: Label,
         wifi_label: Label,
         bt_label: Label,
+        dnd_label: Label,
     ) {
-        let mut receiver = events.subscribe();
+        let mut receiver = state.events.subscribe();
+        let mut disk_usage = HashMap::new();
 
         // Spawn event listener
         glib::spawn_future_local(async move {
-            loop {
-                match receiver.recv().await {
-                    Ok(event) => match event {
-                        Event::CpuUsageChanged { usage } => {
-                            cpu_label.set_text(&format!("CPU: {:.1}%", usage));
-                        }
-                        Event::MemoryUsageChanged { percent, .. } => {
-                            mem_label.set_text(&format!("MEM: {:.1}%", percent));
-                        }
-                        Event::TemperatureChanged { celsius } => {
-                            temp_label.set_text(&format!("TEMP: {}°C", celsius));
-                        }
-                        Event::WifiStateChanged { enabled } => {
-                            let text = if enabled {
-                                "📶 WiFi"
-                            } else {
-                                "📶 WiFi (Off)"
-                            };
-                            wifi_label.set_text(text);
-                        }
-                        Event::WifiNetworkConnected { ssid } => {
-                            wifi_label.set_text(&format!("📶 {}", ssid));
-                        }
-                        Event::WifiNetworkDisconnected => {
-                            wifi_label.set_text("📶 WiFi");
-                        }
-                        Event::BluetoothStateChanged { enabled } => {
-                            let text = if enabled {
-                                "🔵 BT"
-                            } else {
-                                "🔵 BT (Off)"
-                            };
-                            bt_label.set_text(text);
-                        }
-                        Event::BluetoothDeviceConnected { name, .. } => {
-                            bt_label.set_text(&format!("🔵 {}", name));
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::CpuUsageChanged { usage } => {
+                        cpu_label.set_text(&format!("CPU: {:.1}%", usage));
+                    }
+                    Event::CpuPerCoreChanged { usages } => {
+                        cores_label.set_text(&Self::core_bars(&usages));
+                        let tooltip = usages
+                            .iter()
+                            .enumerate()
+                            .map(|(i, usage)| format!("Core {}: {:.1}%", i, usage))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        cores_label.set_tooltip_text(Some(&tooltip));
+                    }
+                    Event::LoadAverageChanged { one, five, fifteen } => {
+                        load_label
+                            .set_text(&format!("LOAD: {:.2} {:.2} {:.2}", one, five, fifteen));
+                    }
+                    Event::DiskUsageChanged {
+                        mount,
+                        used,
+                        total,
+                        percent,
+                    } => {
+                        disk_usage.insert(
+                            mount,
+                            DiskUsage {
+                                used,
+                                total,
+                                percent,
+                            },
+                        );
+                        let config = state.config();
+                        disk_label.set_text(&Self::disk_text(&config.bar, &disk_usage));
+                        disk_label
+                            .set_tooltip_text(Some(&Self::disk_tooltip(&config.bar, &disk_usage)));
+                    }
+                    Event::ConfigReloaded => {
+                        let config = state.config();
+                        Self::apply_config(&config.bar, &cores_label, &load_label, &disk_label);
+                        disk_label.set_text(&Self::disk_text(&config.bar, &disk_usage));
+                    }
+                    Event::MemoryUsageChanged {
+                        used,
+                        total,
+                        percent,
+                        swap_used,
+                        swap_total,
+                    } => {
+                        mem_label.set_text(&format!("MEM: {:.1}%", percent));
+                        mem_label.set_tooltip_text(Some(&Self::memory_tooltip(
+                            used, total, swap_used, swap_total,
+                        )));
+                    }
+                    Event::TemperatureChanged { celsius, sensor } => {
+                        let config = state.config();
+                        let unit = config.bar.temperature_unit;
+                        temp_label.set_text(&format!(
+                            "TEMP: {}{}",
+                            unit.convert(celsius),
+                            unit.symbol()
+                        ));
+                        temp_label.set_tooltip_text(Some(&format!("Sensor: {}", sensor)));
+
+                        // Add CSS class based on temperature for styling
+                        temp_label.remove_css_class("temp-warning");
+                        temp_label.remove_css_class("temp-critical");
+
+                        if celsius >= config.bar.temperature_critical {
+                            temp_label.add_css_class("temp-critical");
+                        } else if celsius >= config.bar.temperature_warning {
+                            temp_label.add_css_class("temp-warning");
                         }
-                        Event::BluetoothDeviceDisconnected { .. } => {
-                            bt_label.set_text("🔵 BT");
+                    }
+                    Event::WifiStateChanged { enabled } => {
+                        let text = if enabled {
+                            "📶 WiFi"
+                        } else {
+                            "📶 WiFi (Off)"
+                        };
+                        wifi_label.set_text(text);
+                    }
+                    Event::WifiNetworkConnected { ssid } => {
+                        wifi_label.set_text(&format!("📶 {}", ssid));
+                    }
+                    Event::WifiSignalChanged { ssid, strength } => {
+                        wifi_label.set_text(&format!(
+                            "📶 {} {}",
+                            super::signal_bars(strength),
+                            ssid
+                        ));
+                        wifi_label
+                            .set_tooltip_text(Some(&format!("{}: {}% signal", ssid, strength)));
+                        if strength <= WEAK_SIGNAL {
+                            wifi_label.add_css_class("weak-signal");
+                        } else {
+                            wifi_label.remove_css_class("weak-signal");
                         }
-                        _ => {} // Ignore other events
-                    },
-                    Err(_) => {
-                        // Channel closed, exit loop
-                        break;
                     }
+                    Event::WifiNetworkDisconnected => {
+                        wifi_label.set_text("📶 WiFi");
+                        wifi_label.set_tooltip_text(Some("WiFi networks"));
+                        wifi_label.remove_css_class("weak-signal");
+                    }
+                    Event::BluetoothStateChanged { enabled } => {
+                        let text = if enabled { "🔵 BT" } else { "🔵 BT (Off)" };
+                        bt_label.set_text(text);
+                    }
+                    Event::BluetoothDeviceConnected { name, .. } => {
+                        bt_label.set_text(&format!("🔵 {}", name));
+                    }
+                    Event::BluetoothDeviceDisconnected { .. } => {
+                        bt_label.set_text("🔵 BT");
+                    }
+                    Event::DndChanged { enabled } => {
+                        dnd_label.set_visible(enabled);
+                    }
+                    _ => {} // Ignore other events
                 }
             }
         });
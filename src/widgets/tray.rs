@@ -0,0 +1,179 @@
+use crate::app::AppState;
+use crate::backend::TrayHost;
+use crate::events::{Event, TrayItemInfo};
+use gtk4::prelude::*;
+use gtk4::{gdk, glib, Box as GtkBox, Button, Image, Orientation};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Icon size in pixels
+const ICON_SIZE: i32 = 18;
+
+/// System tray showing StatusNotifierItems
+pub struct Tray {
+    container: GtkBox,
+}
+
+impl Tray {
+    pub fn new(state: &Arc<AppState>) -> Self {
+        let container = GtkBox::new(Orientation::Horizontal, 4);
+        container.add_css_class("tray");
+
+        if let Some(host) = &state.tray_host {
+            let buttons = Rc::new(RefCell::new(HashMap::new()));
+
+            // Items registered before the bar was built
+            let container_clone = container.clone();
+            let buttons_clone = buttons.clone();
+            let host_clone = host.clone();
+            glib::spawn_future_local(async move {
+                for item in host_clone.items().await {
+                    Self::update_item(&container_clone, &buttons_clone, &host_clone, &item);
+                }
+            });
+
+            Self::subscribe_to_events(state.clone(), host.clone(), container.clone(), buttons);
+        }
+
+        Tray { container }
+    }
+
+    pub fn widget(&self) -> GtkBox {
+        self.container.clone()
+    }
+
+    /// Add a button for a new item or refresh an existing one
+    fn update_item(
+        container: &GtkBox,
+        buttons: &Rc<RefCell<HashMap<String, Button>>>,
+        host: &Arc<TrayHost>,
+        item: &TrayItemInfo,
+    ) {
+        let button = buttons
+            .borrow_mut()
+            .entry(item.id.clone())
+            .or_insert_with(|| {
+                let button = Self::create_button(host, &item.id);
+                container.append(&button);
+                button
+            })
+            .clone();
+
+        button.set_child(Some(&Self::icon(item)));
+        let tooltip = item.tooltip.as_deref().unwrap_or(&item.title);
+        button.set_tooltip_text((!tooltip.is_empty()).then_some(tooltip));
+        button.set_visible(!item.passive);
+    }
+
+    fn create_button(host: &Arc<TrayHost>, id: &str) -> Button {
+        let button = Button::new();
+        button.add_css_class("tray-item");
+
+        // Left click activates, middle click is the secondary action, right
+        // click asks the item for its menu
+        let click = gtk4::GestureClick::new();
+        click.set_button(0);
+        let host_clone = host.clone();
+        let id_clone = id.to_string();
+        click.connect_released(move |gesture, _, _, _| {
+            let host = host_clone.clone();
+            let id = id_clone.clone();
+            let button = gesture.current_button();
+            glib::spawn_future_local(async move {
+                // Wayland doesn't expose global coordinates
+                let result = match button {
+                    gdk::BUTTON_PRIMARY => host.activate(&id, 0, 0).await,
+                    gdk::BUTTON_MIDDLE => host.secondary_activate(&id, 0, 0).await,
+                    gdk::BUTTON_SECONDARY => host.context_menu(&id, 0, 0).await,
+                    _ => Ok(()),
+                };
+                if let Err(e) = result {
+                    warn!("Tray item click failed: {}", e);
+                }
+            });
+        });
+        button.add_controller(click);
+
+        let scroll = gtk4::EventControllerScroll::new(
+            gtk4::EventControllerScrollFlags::BOTH_AXES
+                | gtk4::EventControllerScrollFlags::DISCRETE,
+        );
+        let host_clone = host.clone();
+        let id_clone = id.to_string();
+        scroll.connect_scroll(move |_, dx, dy| {
+            let host = host_clone.clone();
+            let id = id_clone.clone();
+            let (delta, horizontal) = if dy != 0.0 {
+                (dy as i32, false)
+            } else {
+                (dx as i32, true)
+            };
+            glib::spawn_future_local(async move {
+                if let Err(e) = host.scroll(&id, delta, horizontal).await {
+                    warn!("Tray item scroll failed: {}", e);
+                }
+            });
+            glib::Propagation::Stop
+        });
+        button.add_controller(scroll);
+
+        button
+    }
+
+    /// Prefer the themed icon, falling back to the pixmap the item sent
+    fn icon(item: &TrayItemInfo) -> Image {
+        if let Some(theme_path) = &item.icon_theme_path {
+            crate::icons::add_search_path(theme_path);
+        }
+        if let Some(icon) = item.icon_name.as_deref().and_then(crate::icons::lookup) {
+            let image = Image::from_gicon(&icon);
+            image.set_pixel_size(ICON_SIZE);
+            return image;
+        }
+
+        if let Some(pixmap) = &item.icon_pixmap {
+            let bytes = glib::Bytes::from(&pixmap.rgba);
+            let texture = gdk::MemoryTexture::new(
+                pixmap.width,
+                pixmap.height,
+                gdk::MemoryFormat::R8g8b8a8,
+                &bytes,
+                pixmap.width as usize * 4,
+            );
+            let image = Image::from_paintable(Some(&texture));
+            image.set_pixel_size(ICON_SIZE);
+            return image;
+        }
+
+        crate::icons::image(None, "image-missing", ICON_SIZE)
+    }
+
+    fn subscribe_to_events(
+        state: Arc<AppState>,
+        host: Arc<TrayHost>,
+        container: GtkBox,
+        buttons: Rc<RefCell<HashMap<String, Button>>>,
+    ) {
+        let mut receiver = state.events.subscribe();
+
+        // Spawn event listener
+        glib::spawn_future_local(async move {
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::TrayItemUpdated { item } => {
+                        Self::update_item(&container, &buttons, &host, &item);
+                    }
+                    Event::TrayItemRemoved { id } => {
+                        if let Some(button) = buttons.borrow_mut().remove(&id) {
+                            container.remove(&button);
+                        }
+                    }
+                    _ => {} // Ignore other events
+                }
+            }
+        });
+    }
+}
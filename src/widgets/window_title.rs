@@ -0,0 +1,89 @@
+use crate::app::AppState;
+use crate::events::Event;
+use futures::StreamExt;
+use gtk4::prelude::*;
+use gtk4::{glib, Box as GtkBox, Image, Label, Orientation};
+use std::sync::Arc;
+
+/// Size of the app icon next to the title, in logical pixels
+const ICON_SIZE: i32 = 16;
+
+pub struct WindowTitle {
+    container: GtkBox,
+}
+
+impl WindowTitle {
+    pub fn new(state: &Arc<AppState>) -> Self {
+        let container = GtkBox::new(Orientation::Horizontal, 6);
+        container.add_css_class("window-title");
+
+        // Icon of the focused window's app, hidden when there is none
+        let icon = Image::new();
+        icon.add_css_class("window-title-icon");
+        icon.set_pixel_size(ICON_SIZE);
+        icon.set_visible(false);
+
+        let label = Label::new(None);
+        label.add_css_class("window-title-label");
+
+        container.append(&icon);
+        container.append(&label);
+
+        // Subscribe to events
+        Self::subscribe_to_events(
+            state.events.clone(),
+            icon,
+            label,
+            state.config().bar.window_title_max_length,
+        );
+
+        WindowTitle { container }
+    }
+
+    pub fn widget(&self) -> GtkBox {
+        self.container.clone()
+    }
+
+    /// Truncate a title to `max_length` characters, appending an ellipsis if shortened
+    fn truncate(title: &str, max_length: usize) -> String {
+        if title.chars().count() <= max_length {
+            title.to_string()
+        } else {
+            let truncated: String = title.chars().take(max_length.saturating_sub(1)).collect();
+            format!("{}…", truncated)
+        }
+    }
+
+    fn subscribe_to_events(
+        events: crate::events::EventManager,
+        icon: Image,
+        label: Label,
+        max_length: usize,
+    ) {
+        let mut windows = Box::pin(events.subscribe_filtered(|event| match event {
+            Event::FocusedWindowChanged { title, app_id } => Some((title, app_id)),
+            _ => None,
+        }));
+
+        // Spawn event listener
+        glib::spawn_future_local(async move {
+            while let Some((title, app_id)) = windows.next().await {
+                match app_id.as_deref().and_then(crate::icons::for_app_id) {
+                    Some(app_icon) => {
+                        icon.set_from_gicon(&app_icon);
+                        icon.set_visible(true);
+                    }
+                    None => {
+                        icon.clear();
+                        icon.set_visible(false);
+                    }
+                }
+
+                // Fall back to the app ID for windows without a title
+                let text = title.or(app_id).unwrap_or_default();
+                label.set_text(&Self::truncate(&text, max_length));
+                label.set_tooltip_text(if text.is_empty() { None } else { Some(&text) });
+            }
+        });
+    }
+}
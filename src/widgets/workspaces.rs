@@ -1,66 +1,168 @@
 use crate::app::AppState;
 use crate::events::Event;
 use gtk4::prelude::*;
-use gtk4::{glib, Box as GtkBox, Button, Label, Orientation};
+use gtk4::{
+    glib, Box as GtkBox, Button, EventControllerScroll, EventControllerScrollFlags, Label,
+    Orientation,
+};
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Window dots shown before falling back to a count
+const MAX_WINDOW_DOTS: usize = 4;
+
+/// Minimum time between scroll-triggered switches, so a fast flick or
+/// touchpad momentum doesn't skip across several workspaces
+const SCROLL_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// A workspace button and the labels inside it
+struct WorkspaceButton {
+    button: Button,
+    label: Label,
+    windows_label: Label,
+    /// Workspace name reported by niri, if any
+    name: Option<String>,
+}
+
+impl WorkspaceButton {
+    fn new(state: &Arc<AppState>, id: u32, text: &str) -> Self {
+        let button = Button::new();
+        let content = GtkBox::new(Orientation::Horizontal, 2);
+        let label = Label::new(Some(text));
+        content.append(&label);
+
+        // One dot per open window, filled in from WorkspacesUpdated
+        let windows_label = Label::new(None);
+        windows_label.add_css_class("workspace-windows");
+        windows_label.set_visible(false);
+        content.append(&windows_label);
+
+        button.set_child(Some(&content));
+        button.add_css_class("workspace-button");
+        button.add_css_class("empty");
+
+        // Store workspace ID in button data
+        button.set_data("workspace_id", id);
+
+        // Buttons stay insensitive while niri is unreachable, so there's
+        // nothing to pretend to switch when there's no client
+        button.set_sensitive(state.niri_client.is_some());
+
+        // Clone for click handler
+        let niri_client = state.niri_client.clone();
+        button.connect_clicked(move |btn| {
+            let workspace_id = btn.data::<u32>("workspace_id").unwrap();
+            let Some(client) = &niri_client else {
+                return;
+            };
+
+            match client.focus_workspace(workspace_id) {
+                Ok(()) => {
+                    tracing::info!("Switched to workspace {}", workspace_id);
+                    // Event will be emitted by niri polling
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to switch workspace: {}", e);
+                }
+            }
+        });
+
+        WorkspaceButton {
+            button,
+            label,
+            windows_label,
+            name: None,
+        }
+    }
+
+    /// Show one dot per window (with a count past `MAX_WINDOW_DOTS`) and list
+    /// the apps in the tooltip
+    fn set_windows(&self, id: u32, windows: &[String], show: bool) {
+        let text = match windows.len() {
+            0 => String::new(),
+            n if n <= MAX_WINDOW_DOTS => "•".repeat(n),
+            n => format!("{}+{}", "•".repeat(MAX_WINDOW_DOTS), n - MAX_WINDOW_DOTS),
+        };
+        self.windows_label.set_text(&text);
+        self.windows_label.set_visible(show && !windows.is_empty());
+
+        let (state, previous) = if windows.is_empty() {
+            ("empty", "occupied")
+        } else {
+            ("occupied", "empty")
+        };
+        self.button.remove_css_class(previous);
+        self.button.add_css_class(state);
+
+        if windows.is_empty() {
+            self.button.set_tooltip_text(None);
+        } else {
+            let apps: Vec<&str> = windows
+                .iter()
+                .map(|app_id| if app_id.is_empty() { "unknown" } else { app_id.as_str() })
+                .collect();
+            self.button
+                .set_tooltip_text(Some(&format!("Workspace {}: {}", id, apps.join(", "))));
+        }
+    }
+}
 
 pub struct Workspaces {
     container: GtkBox,
 }
 
+/// Show or hide the disconnected indicator and enable or disable switching
+fn set_connected(
+    container: &GtkBox,
+    indicator: &Label,
+    buttons: &HashMap<u32, WorkspaceButton>,
+    connected: bool,
+) {
+    indicator.set_visible(!connected);
+    if connected {
+        container.remove_css_class("disconnected");
+    } else {
+        container.add_css_class("disconnected");
+    }
+    for workspace in buttons.values() {
+        workspace.button.set_sensitive(connected);
+    }
+}
+
 impl Workspaces {
     pub fn new(state: &Arc<AppState>) -> Self {
         let container = GtkBox::new(Orientation::Horizontal, 4);
+        container.add_css_class("workspaces");
 
-        // Create workspace buttons (1-9 for now)
-        let mut buttons = HashMap::new();
+        // Shown in front of the buttons while niri can't be reached
+        let indicator = Label::new(Some("⚠ niri"));
+        indicator.add_css_class("workspaces-disconnected");
+        indicator.set_tooltip_text(Some(
+            "niri is not running or its IPC socket is unreachable; workspace switching is disabled",
+        ));
+        container.append(&indicator);
 
+        // Start with workspaces 1-9 until niri reports the real ones
+        let config = state.config();
+        let mut buttons = HashMap::new();
         for i in 1..=9 {
-            let button = Button::new();
-            let label = Label::new(Some(&i.to_string()));
-            button.set_child(Some(&label));
-            button.add_css_class("workspace-button");
-
+            let workspace = WorkspaceButton::new(state, i, &config.bar.workspace_label(i, None));
             if i == 1 {
-                button.add_css_class("active");
+                workspace.button.add_css_class("active");
             }
+            container.append(&workspace.button);
+            buttons.insert(i, workspace);
+        }
 
-            // Store workspace ID in button data
-            button.set_data("workspace_id", i);
-
-            // Clone for click handler
-            let events = state.events.clone();
-            let niri_client = state.niri_client.clone();
-            button.connect_clicked(move |btn| {
-                let workspace_id = btn.data::<u32>("workspace_id").unwrap();
-
-                // Try to switch workspace via niri
-                if let Some(client) = &niri_client {
-                    match client.focus_workspace(workspace_id) {
-                        Ok(()) => {
-                            tracing::info!("Switched to workspace {}", workspace_id);
-                            // Event will be emitted by niri polling
-                        }
-                        Err(e) => {
-                            tracing::warn!("Failed to switch workspace: {}", e);
-                            // Emit event anyway for UI feedback
-                            events.emit(Event::WorkspaceChanged { id: workspace_id });
-                        }
-                    }
-                } else {
-                    // No niri client, just emit event for mock behavior
-                    tracing::info!("Switching to workspace {} (mock - no niri)", workspace_id);
-                    events.emit(Event::WorkspaceChanged { id: workspace_id });
-                }
-            });
+        set_connected(&container, &indicator, &buttons, state.niri_client.is_some());
 
-            buttons.insert(i, button.clone());
-            container.append(&button);
-        }
+        Self::connect_scroll(&container, state);
 
         // Subscribe to workspace events
-        Self::subscribe_to_events(state.events.clone(), buttons);
+        Self::subscribe_to_events(state.clone(), container.clone(), indicator, buttons);
 
         Workspaces { container }
     }
@@ -69,46 +171,164 @@ impl Workspaces {
         self.container.clone()
     }
 
+    /// Switch to the next/previous workspace when scrolling over the widget
+    fn connect_scroll(container: &GtkBox, state: &Arc<AppState>) {
+        let scroll = EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+        let state = state.clone();
+        // Smooth (touchpad) scrolling arrives in fractions of a notch
+        let pending = Rc::new(Cell::new(0.0));
+        let last_switch = Rc::new(Cell::new(None::<Instant>));
+
+        scroll.connect_scroll(move |scroll, _dx, dy| {
+            let Some(client) = &state.niri_client else {
+                return glib::Propagation::Proceed;
+            };
+            let disconnected = scroll
+                .widget()
+                .is_some_and(|widget| widget.has_css_class("disconnected"));
+            if disconnected {
+                return glib::Propagation::Proceed;
+            }
+
+            let delta = pending.get() + dy;
+            if delta.abs() < 1.0 {
+                pending.set(delta);
+                return glib::Propagation::Stop;
+            }
+            pending.set(0.0);
+
+            if last_switch.get().is_some_and(|t| t.elapsed() < SCROLL_DEBOUNCE) {
+                return glib::Propagation::Stop;
+            }
+            last_switch.set(Some(Instant::now()));
+
+            let next = (delta > 0.0) != state.config().bar.workspace_scroll_reverse;
+            let result = if next {
+                client.focus_workspace_down()
+            } else {
+                client.focus_workspace_up()
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to switch workspace: {}", e);
+            }
+            // Event will be emitted by niri polling
+            glib::Propagation::Stop
+        });
+
+        container.add_controller(scroll);
+    }
+
+    /// Follow niri's workspace list, adding and removing buttons as
+    /// workspaces come and go
+    fn sync_buttons(
+        state: &Arc<AppState>,
+        container: &GtkBox,
+        buttons: &mut HashMap<u32, WorkspaceButton>,
+        ids: &[u32],
+    ) {
+        buttons.retain(|id, workspace| {
+            let keep = ids.contains(id);
+            if !keep {
+                container.remove(&workspace.button);
+            }
+            keep
+        });
+
+        // Re-append everything so the order matches niri's
+        for &id in ids {
+            let workspace = buttons
+                .entry(id)
+                .or_insert_with(|| WorkspaceButton::new(state, id, &id.to_string()));
+            if workspace.button.parent().is_some() {
+                container.remove(&workspace.button);
+            }
+            container.append(&workspace.button);
+        }
+    }
+
     fn subscribe_to_events(
-        events: crate::events::EventManager,
-        buttons: HashMap<u32, Button>,
+        state: Arc<AppState>,
+        container: GtkBox,
+        indicator: Label,
+        mut buttons: HashMap<u32, WorkspaceButton>,
     ) {
-        let mut receiver = events.subscribe();
+        let mut receiver = state.events.subscribe();
+        let mut config = state.config().bar;
+        let mut order: Vec<u32> = (1..=9).collect();
 
         glib::spawn_future_local(async move {
-            loop {
-                match receiver.recv().await {
-                    Ok(event) => match event {
-                        Event::WorkspaceChanged { id } => {
-                            // Remove active class from all buttons
-                            for button in buttons.values() {
-                                button.remove_css_class("active");
+            while let Some(event) = receiver.recv_or_skip().await {
+                match event {
+                    Event::WorkspaceChanged { id } => {
+                        // Remove active class from all buttons
+                        for workspace in buttons.values() {
+                            workspace.button.remove_css_class("active");
+                        }
+
+                        // Add active class to the current workspace
+                        if let Some(workspace) = buttons.get(&id) {
+                            workspace.button.add_css_class("active");
+                        }
+                    }
+                    Event::WorkspacesUpdated { workspaces } => {
+                        // Workspaces on other outputs can share an index
+                        let mut ids: Vec<u32> = Vec::new();
+                        for workspace in &workspaces {
+                            if !ids.contains(&workspace.id) {
+                                ids.push(workspace.id);
                             }
+                        }
+                        if !ids.is_empty() && ids != order {
+                            Self::sync_buttons(&state, &container, &mut buttons, &ids);
+                            order = ids;
+                            // New buttons start out sensitive
+                            let connected = !indicator.is_visible();
+                            set_connected(&container, &indicator, &buttons, connected);
+                        }
 
-                            // Add active class to the current workspace
-                            if let Some(button) = buttons.get(&id) {
+                        // Update labels, active/urgent states and the windows on each
+                        for workspace in workspaces {
+                            let Some(entry) = buttons.get_mut(&workspace.id) else {
+                                continue;
+                            };
+                            entry.name = workspace.name.clone();
+                            entry.label.set_text(
+                                &config.workspace_label(workspace.id, entry.name.as_deref()),
+                            );
+                            entry.set_windows(
+                                workspace.id,
+                                &workspace.windows,
+                                config.workspace_show_windows,
+                            );
+
+                            let button = &entry.button;
+                            if workspace.is_active {
                                 button.add_css_class("active");
+                            } else {
+                                button.remove_css_class("active");
                             }
-                        }
-                        Event::WorkspacesUpdated { workspaces } => {
-                            // Update button visibility based on available workspaces
-                            // For now, we'll just update active states
-                            for workspace in workspaces {
-                                if let Some(button) = buttons.get(&workspace.id) {
-                                    if workspace.is_active {
-                                        button.add_css_class("active");
-                                    } else {
-                                        button.remove_css_class("active");
-                                    }
-                                }
+                            if workspace.is_urgent {
+                                button.add_css_class("urgent");
+                            } else {
+                                button.remove_css_class("urgent");
                             }
                         }
-                        _ => {} // Ignore other events
-                    },
-                    Err(_) => {
-                        // Channel closed, exit loop
-                        break;
                     }
+                    Event::ConfigReloaded => {
+                        config = state.config().bar;
+                        for (id, workspace) in &buttons {
+                            let text = config.workspace_label(*id, workspace.name.as_deref());
+                            workspace.label.set_text(&text);
+                            workspace.windows_label.set_visible(
+                                config.workspace_show_windows
+                                    && !workspace.windows_label.text().is_empty(),
+                            );
+                        }
+                    }
+                    Event::NiriConnectionChanged { connected } => {
+                        set_connected(&container, &indicator, &buttons, connected);
+                    }
+                    _ => {} // Ignore other events
                 }
             }
         });
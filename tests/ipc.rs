@@ -0,0 +1,333 @@
+//! End-to-end tests for the IPC protocol: a real `IpcServer` on a temporary
+//! socket, driven by raw JSON lines the way `amiya-ctl` sends them.
+
+use amiya::app::AppState;
+use amiya::backend::fake::{FakeBrightness, FakeVolume};
+use amiya::backend::{BrightnessBackend, VolumeBackend};
+use amiya::config::Config;
+use amiya::events::Event;
+use amiya::ipc::{IpcServer, PopupType};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Start a server with no backends on a socket unique to this test
+async fn start_server(name: &str) -> (Arc<AppState>, PathBuf) {
+    start_server_with(name, AppState::without_backends(Config::default())).await
+}
+
+async fn start_server_with(name: &str, state: AppState) -> (Arc<AppState>, PathBuf) {
+    let socket_path =
+        std::env::temp_dir().join(format!("amiya-test-{}-{}.sock", std::process::id(), name));
+    let state = Arc::new(state);
+    let server = Arc::new(IpcServer::with_socket_path(state.clone(), socket_path.clone()).unwrap());
+    tokio::spawn(server.start());
+
+    // The socket appears once the listener is bound
+    for _ in 0..100 {
+        if socket_path.exists() {
+            return (state, socket_path);
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("IPC server did not bind {:?}", socket_path);
+}
+
+/// Send one line and return the parsed response
+async fn send(socket_path: &Path, line: &str) -> Value {
+    let mut stream = UnixStream::connect(socket_path).await.unwrap();
+    stream.write_all(line.as_bytes()).await.unwrap();
+    stream.write_all(b"\n").await.unwrap();
+
+    let mut response = String::new();
+    BufReader::new(stream)
+        .read_line(&mut response)
+        .await
+        .unwrap();
+    serde_json::from_str(&response).unwrap()
+}
+
+fn assert_success(response: &Value) {
+    assert_eq!(
+        response["status"], "success",
+        "unexpected response: {}",
+        response
+    );
+}
+
+fn assert_error(response: &Value, contains: &str) {
+    assert_eq!(
+        response["status"], "error",
+        "unexpected response: {}",
+        response
+    );
+    let message = response["message"].as_str().unwrap();
+    assert!(
+        message.contains(contains),
+        "{:?} does not mention {:?}",
+        message,
+        contains
+    );
+}
+
+#[tokio::test]
+async fn test_ping_and_status() {
+    let (_state, socket) = start_server("status").await;
+
+    let response = send(&socket, r#"{"type":"ping"}"#).await;
+    assert_eq!(response["status"], "pong");
+
+    let response = send(&socket, r#"{"type":"status"}"#).await;
+    assert_eq!(response["status"], "status");
+    assert_eq!(response["version"], env!("CARGO_PKG_VERSION"));
+    assert!(response["uptime"].is_u64());
+    assert!(response["subscribers"].is_u64());
+    assert_eq!(response["dropped_events"], 0);
+}
+
+#[tokio::test]
+async fn test_popup_commands_emit_events() {
+    let (state, socket) = start_server("popups").await;
+    let mut events = state.events.subscribe();
+
+    for command in ["show-popup", "toggle-popup"] {
+        let line = format!(r#"{{"type":"{}","popup":"wifi"}}"#, command);
+        assert_success(&send(&socket, &line).await);
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            Event::PopupRequested {
+                popup_type: PopupType::Wifi
+            }
+        ));
+    }
+
+    let response = send(&socket, r#"{"type":"hide-popup","popup":"media-control"}"#).await;
+    assert_success(&response);
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        Event::PopupClosed {
+            popup_type: PopupType::MediaControl
+        }
+    ));
+}
+
+#[tokio::test]
+async fn test_hotkey() {
+    let (_state, socket) = start_server("hotkey").await;
+
+    // Bound in the default config to a popup, which needs no backend
+    let response = send(&socket, r#"{"type":"hotkey","key":"Super+B"}"#).await;
+    assert_success(&response);
+
+    let response = send(&socket, r#"{"type":"hotkey","key":"Super+Shift+F12"}"#).await;
+    assert_error(&response, "No hotkey bound");
+}
+
+#[tokio::test]
+async fn test_commands_without_backends() {
+    let (_state, socket) = start_server("backends").await;
+
+    let cases = [
+        (
+            r#"{"type":"volume","action":{"action":"up","amount":null}}"#,
+            "Audio",
+        ),
+        (
+            r#"{"type":"volume","action":{"action":"toggle-mute"}}"#,
+            "Audio",
+        ),
+        (
+            r#"{"type":"brightness","action":{"action":"set","level":40.0}}"#,
+            "Backlight",
+        ),
+        (r#"{"type":"power","action":"lock"}"#, "Power"),
+        (r#"{"type":"media","action":"play-pause"}"#, "Media"),
+        (
+            r#"{"type":"workspace","action":{"action":"move-window","index":2}}"#,
+            "Niri",
+        ),
+        (
+            r#"{"type":"workspace","action":{"action":"move-window-up"}}"#,
+            "Niri",
+        ),
+        (
+            r#"{"type":"set-dnd","enabled":true}"#,
+            "Notification daemon",
+        ),
+        (
+            r#"{"type":"set-idle-inhibit","enabled":false}"#,
+            "Idle inhibitor",
+        ),
+        (
+            r#"{"type":"set-airplane-mode","enabled":true}"#,
+            "Airplane mode",
+        ),
+        (r#"{"type":"vpn","action":{"action":"list"}}"#, "Network control"),
+        (r#"{"type":"screenshot","mode":"region"}"#, "Screen capture"),
+        (
+            r#"{"type":"screen-record","action":"toggle"}"#,
+            "Screen capture",
+        ),
+        (r#"{"type":"power-off-monitors"}"#, "Niri"),
+        (r#"{"type":"output","action":{"action":"list"}}"#, "Niri"),
+        (
+            r#"{"type":"output","action":{"action":"transform","name":"eDP-1","transform":"90"}}"#,
+            "Niri",
+        ),
+    ];
+
+    for (line, backend) in cases {
+        let response = send(&socket, line).await;
+        assert_error(&response, &format!("{} ", backend));
+        assert_error(&response, "not available");
+    }
+}
+
+#[tokio::test]
+async fn test_volume_with_fake_backend() {
+    let mut state = AppState::without_backends(Config::default());
+    let volume = Arc::new(FakeVolume::with_events(50.0, state.events.clone()));
+    state.audio_control = Some(volume.clone());
+    let (state, socket) = start_server_with("volume", state).await;
+    let mut events = state.events.subscribe();
+
+    let response = send(
+        &socket,
+        r#"{"type":"volume","action":{"action":"up","amount":10.0}}"#,
+    )
+    .await;
+    assert_success(&response);
+    assert_eq!(volume.get_volume().await.unwrap(), 60.0);
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        Event::VolumeChanged { level, muted: false } if level == 60.0
+    ));
+
+    let response = send(
+        &socket,
+        r#"{"type":"volume","action":{"action":"toggle-mute"}}"#,
+    )
+    .await;
+    assert_success(&response);
+    assert!(volume.get_mute().await.unwrap());
+
+    let response = send(
+        &socket,
+        r#"{"type":"volume","action":{"action":"set-relative","percent":-50.0}}"#,
+    )
+    .await;
+    assert_success(&response);
+    assert_eq!(volume.get_volume().await.unwrap(), 30.0);
+}
+
+#[tokio::test]
+async fn test_brightness_without_device() {
+    // A DDC/CI backend that never found a monitor is as good as none
+    let mut state = AppState::without_backends(Config::default());
+    state.backlight_control = Some(Arc::new(FakeBrightness::unavailable()));
+    let (_state, socket) = start_server_with("brightness-unavailable", state).await;
+
+    let response = send(
+        &socket,
+        r#"{"type":"brightness","action":{"action":"set","level":40.0}}"#,
+    )
+    .await;
+    assert_error(&response, "Backlight control not available");
+
+    let mut state = AppState::without_backends(Config::default());
+    let backlight = Arc::new(FakeBrightness::new(80.0));
+    state.backlight_control = Some(backlight.clone());
+    let (_state, socket) = start_server_with("brightness", state).await;
+
+    let response = send(
+        &socket,
+        r#"{"type":"brightness","action":{"action":"set","level":40.0}}"#,
+    )
+    .await;
+    assert_success(&response);
+    assert_eq!(backlight.get_brightness().await.unwrap(), 40.0);
+}
+
+#[tokio::test]
+async fn test_reload() {
+    let (state, socket) = start_server("reload").await;
+    let mut events = state.events.subscribe();
+
+    // The path is set once per process; no other test reads the file
+    let config_file =
+        std::env::temp_dir().join(format!("amiya-test-{}-config.toml", std::process::id()));
+    Config::set_path(config_file.clone());
+
+    std::fs::write(&config_file, "[bar]\nheight = 42\n").unwrap();
+    assert_success(&send(&socket, r#"{"type":"reload"}"#).await);
+    assert_eq!(state.config().bar.height, 42);
+    assert!(matches!(events.recv().await.unwrap(), Event::ConfigReloaded));
+
+    // A broken file is reported and the running config is kept
+    std::fs::write(&config_file, "[bar\n").unwrap();
+    let response = send(&socket, r#"{"type":"reload"}"#).await;
+    assert_error(&response, "Failed to reload config");
+    assert_eq!(state.config().bar.height, 42);
+
+    std::fs::remove_file(&config_file).unwrap();
+}
+
+#[tokio::test]
+async fn test_quit_emits_event() {
+    let (state, socket) = start_server("quit").await;
+    let mut events = state.events.subscribe();
+
+    assert_success(&send(&socket, r#"{"type":"quit"}"#).await);
+    assert!(matches!(events.recv().await.unwrap(), Event::QuitRequested));
+}
+
+// Multi-threaded so the running server can answer the blocking startup ping
+#[tokio::test(flavor = "multi_thread")]
+async fn test_refuses_live_socket() {
+    let (state, socket) = start_server("live").await;
+
+    let result = IpcServer::with_socket_path(state.clone(), socket.clone());
+    let error = result.err().expect("second server should not start").to_string();
+    assert!(error.contains("already running"), "{}", error);
+
+    // The first server is untouched
+    assert_eq!(send(&socket, r#"{"type":"ping"}"#).await["status"], "pong");
+}
+
+#[tokio::test]
+async fn test_replaces_stale_socket() {
+    let socket_path = std::env::temp_dir().join(format!(
+        "amiya-test-{}-stale.sock",
+        std::process::id()
+    ));
+    // Left behind by a crashed instance: nothing is listening
+    drop(std::os::unix::net::UnixListener::bind(&socket_path).unwrap());
+    assert!(socket_path.exists());
+
+    let state = Arc::new(AppState::without_backends(Config::default()));
+    let server = IpcServer::with_socket_path(state, socket_path.clone()).unwrap();
+    assert!(!socket_path.exists());
+    drop(server);
+}
+
+#[tokio::test]
+async fn test_malformed_and_unknown_commands() {
+    let (_state, socket) = start_server("malformed").await;
+
+    for line in [
+        "not json",
+        "{}",
+        r#"{"type":"frobnicate"}"#,
+        r#"{"type":"show-popup","popup":"nonexistent"}"#,
+        r#"{"type":"volume","action":{"action":"set"}}"#,
+    ] {
+        let response = send(&socket, line).await;
+        assert_error(&response, "Invalid command");
+    }
+
+    // The server keeps serving after bad input
+    assert_eq!(send(&socket, r#"{"type":"ping"}"#).await["status"], "pong");
+}